@@ -0,0 +1,169 @@
+use anyhow::Result;
+use std::{env, fs, path::PathBuf};
+
+use crate::state::write_atomic;
+use crate::version_manager::home_dir;
+
+/// 生成的补全脚本的第一行（zsh 为第二行），`ver completions --update` 据此只更新 ver 自己写入的文件
+const MARKER: &str = "# ver completion script, generated by `ver completions`";
+
+/// 支持补全的 shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// 所有支持的 shell
+    pub fn all() -> [Shell; 3] {
+        [Shell::Bash, Shell::Zsh, Shell::Fish]
+    }
+
+    /// 从名称解析 shell
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(anyhow::anyhow!("不支持的 shell: {}（可选 bash、zsh、fish）", name)),
+        }
+    }
+
+    /// 从 SHELL 环境变量识别当前使用的 shell
+    pub fn detect() -> Result<Self> {
+        let shell = env::var("SHELL").unwrap_or_default();
+        let name = shell.rsplit('/').next().unwrap_or_default();
+        Self::from_name(name).map_err(|_| anyhow::anyhow!("无法从 SHELL={} 识别 shell，请指定 bash、zsh 或 fish", shell))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+
+    /// 安装补全脚本的位置
+    ///
+    /// bash-completion 按需加载 `$XDG_DATA_HOME/bash-completion/completions` 中的脚本，
+    /// fish 自动加载 `$XDG_CONFIG_HOME/fish/completions`；zsh 没有统一的用户目录，使用 `~/.zfunc`，
+    /// 需要在 fpath 中。
+    pub fn install_path(self) -> Result<PathBuf> {
+        let xdg = |name: &str, default: &str| -> Result<PathBuf> {
+            match env::var_os(name).filter(|value| !value.is_empty()) {
+                Some(dir) => Ok(PathBuf::from(dir)),
+                None => Ok(home_dir(None)?.join(default)),
+            }
+        };
+        Ok(match self {
+            Shell::Bash => xdg("XDG_DATA_HOME", ".local/share")?.join("bash-completion").join("completions").join("ver"),
+            Shell::Zsh => match env::var_os("ZDOTDIR").filter(|value| !value.is_empty()) {
+                Some(dir) => PathBuf::from(dir),
+                None => home_dir(None)?,
+            }.join(".zfunc").join("_ver"),
+            Shell::Fish => xdg("XDG_CONFIG_HOME", ".config")?.join("fish").join("completions").join("ver.fish"),
+        })
+    }
+}
+
+/// 生成补全脚本
+///
+/// 子命令和选项由 clap_complete 根据命令行定义生成。版本号候选由 `ver completions versions`
+/// 读取本地数据和缓存输出，供编辑器插件和自定义补全使用。
+///
+/// # 参数
+///
+/// * `shell` - 目标 shell
+/// * `command` - ver 的命令行定义
+///
+/// # 返回
+///
+/// 返回脚本内容。
+pub fn script(shell: Shell, command: &mut clap::Command) -> String {
+    let generator = match shell {
+        Shell::Bash => clap_complete::Shell::Bash,
+        Shell::Zsh => clap_complete::Shell::Zsh,
+        Shell::Fish => clap_complete::Shell::Fish,
+    };
+    let mut buf = Vec::new();
+    clap_complete::generate(generator, command, "ver", &mut buf);
+    let generated = String::from_utf8_lossy(&buf);
+    // zsh 要求 `#compdef` 在第一行，标记放在第二行
+    match generated.strip_prefix("#compdef ver\n") {
+        Some(rest) => format!("#compdef ver\n{}\n{}", MARKER, rest),
+        None => format!("{}\n{}", MARKER, generated),
+    }
+}
+
+/// 安装补全脚本
+///
+/// # 参数
+///
+/// * `shell` - 目标 shell
+/// * `command` - ver 的命令行定义
+///
+/// # 返回
+///
+/// 成功时返回写入的文件路径，失败时返回错误。
+pub fn install(shell: Shell, command: &mut clap::Command) -> Result<PathBuf> {
+    let path = shell.install_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(&path, script(shell, command))?;
+    Ok(path)
+}
+
+/// 重新生成 `ver completions --install` 安装过的补全脚本
+///
+/// `ver self-update` 替换可执行文件后运行，新版本的子命令和选项可能有变化。
+/// 只更新带有 ver 标记的文件，用户自己放在这些位置的脚本不会被覆盖。
+///
+/// # 参数
+///
+/// * `command` - ver 的命令行定义
+///
+/// # 返回
+///
+/// 成功时返回更新的 shell 和文件路径，失败时返回错误。
+pub fn update(command: &mut clap::Command) -> Result<Vec<(Shell, PathBuf)>> {
+    let mut updated = Vec::new();
+    for shell in Shell::all() {
+        let path = shell.install_path()?;
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if content.lines().take(2).any(|line| line == MARKER) {
+            write_atomic(&path, script(shell, command))?;
+            updated.push((shell, path));
+        }
+    }
+    Ok(updated)
+}
+
+/// zsh 的 fpath 是否可能已包含 `~/.zfunc`，根据 .zshrc 中是否提到它判断
+pub fn zsh_fpath_configured() -> bool {
+    let dir = env::var_os("ZDOTDIR").filter(|value| !value.is_empty()).map(PathBuf::from).or_else(|| home_dir(None).ok());
+    dir.and_then(|dir| fs::read_to_string(dir.join(".zshrc")).ok())
+        .is_some_and(|zshrc| zshrc.contains(".zfunc"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, Command};
+
+    #[test]
+    fn script_carries_marker_and_keeps_compdef_first() {
+        let mut command = Command::new("ver").subcommand(Command::new("install").arg(Arg::new("version")));
+        for shell in Shell::all() {
+            let script = script(shell, &mut command);
+            assert!(script.lines().take(2).any(|line| line == MARKER), "{}", shell.name());
+            assert!(script.contains("install"), "{}", shell.name());
+        }
+        assert!(script(Shell::Zsh, &mut command).starts_with("#compdef ver\n"));
+    }
+}
@@ -0,0 +1,760 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::state::{write_atomic, SCHEMA_VERSION};
+use crate::version_file;
+use crate::version_manager::VersionType;
+
+/// 用户配置
+///
+/// 保存在 ~/.version-manager/config.json 中，通过 `ver config` 命令读写。
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    /// 文件格式版本，见 [`state::SCHEMA_VERSION`](crate::state::SCHEMA_VERSION)
+    #[serde(default)]
+    pub schema: u32,
+    /// Go相关配置
+    #[serde(default)]
+    pub go: GoConfig,
+    /// Rust相关配置
+    #[serde(default)]
+    pub rust: RustConfig,
+    /// Python相关配置
+    #[serde(default)]
+    pub python: PythonConfig,
+    /// 安装相关配置
+    #[serde(default)]
+    pub install: InstallConfig,
+    /// 团队策略相关配置
+    #[serde(default)]
+    pub policy: PolicyConfig,
+    /// 版本文件查找相关配置
+    #[serde(default)]
+    pub resolve: ResolveConfig,
+    /// `ver maintain` 相关配置
+    #[serde(default)]
+    pub maintain: MaintainConfig,
+    /// 下载缓存相关配置
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// 管理员统一提供版本时的配置
+    #[serde(default)]
+    pub managed: ManagedConfig,
+    /// `ver self-update` 相关配置
+    #[serde(default)]
+    pub update: UpdateConfig,
+    /// 版本补充信息（停止维护日期、发布说明、安全公告）的来源
+    #[serde(default)]
+    pub metadata: MetadataConfig,
+    /// ver 管理的工具，为空时管理所有工具；其他工具留给 rustup、nvm 等版本管理器，ver 不为它们创建全局链接
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub manage: Vec<String>,
+    /// 每个工具的下载镜像列表，按工具标识分组
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mirrors: BTreeMap<String, Vec<String>>,
+    /// 每个工具激活时注入的默认环境变量，按工具标识分组
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, BTreeMap<String, String>>,
+    /// 安装新版本后在该版本环境中运行的命令，按工具标识分组，例如 `npm i -g pnpm@9`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub on_install: BTreeMap<String, Vec<String>>,
+    /// 自定义项目版本文件，文件名到解析规则（工具标识或 `lines`），见 [`version_file::from_rule`](crate::version_file::from_rule)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub version_files: BTreeMap<String, String>,
+    /// 确认以 root 使用属于普通用户的主目录（或反过来），不再警告
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_root: Option<bool>,
+}
+
+/// Go相关配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GoConfig {
+    /// 激活Go版本时导出的GOPROXY
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// 激活Go版本时导出的GOSUMDB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sumdb: Option<String>,
+}
+
+/// Rust安装来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RustSource {
+    /// 下载包含所有组件的 rust-<ver> 安装包
+    Standalone,
+    /// 按渠道清单逐个下载组件安装包
+    Components,
+}
+
+/// Rust相关配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RustConfig {
+    /// 安装来源：standalone（默认）或 components
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// 使用 components 安装来源时下载的组件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<String>,
+}
+
+/// Windows 上的Python安装来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PythonWindowsSource {
+    /// nuget.org 上的 python/pythonx86 包，包含 pip 和 venv
+    Nuget,
+    /// python.org 的嵌入式 zip 包，体积小但不带 pip
+    Embed,
+}
+
+/// Python相关配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PythonConfig {
+    /// Windows 上的安装来源：nuget（默认）或 embed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub windows_source: Option<String>,
+    /// nuget 包的下载地址前缀，默认 https://www.nuget.org/api/v2/package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nuget_url: Option<String>,
+}
+
+/// 安装相关配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct InstallConfig {
+    /// Apple Silicon 上缺少 arm64 版本时，是否改为安装 x64 版本并通过 Rosetta 2 运行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rosetta_fallback: Option<bool>,
+    /// 按文件发布版本内容的增量源，有已安装的相邻版本时只下载变化的文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_url: Option<String>,
+}
+
+/// 团队策略相关配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    /// 策略文件路径，环境变量 VER_POLICY_FILE 优先
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+/// 版本文件查找相关配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ResolveConfig {
+    /// 向上查找版本文件时跳过的目录名，只在上层目录是有版本文件的项目时生效
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_dirs: Vec<String>,
+    /// 向上查找的最大层数，0 表示只查找当前目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// 是否读取 NODE_VERSION、GOTOOLCHAIN、PYENV_VERSION、RUSTUP_TOOLCHAIN 等兼容环境变量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compat_env: Option<bool>,
+}
+
+/// `ver maintain` 相关配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MaintainConfig {
+    /// 自动删除超过该天数未使用且没有引用的版本，只删除有安装记录和使用记录的版本；未设置时只报告
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prune_days: Option<i64>,
+}
+
+/// 下载缓存相关配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// 多个用户共用的安装包缓存目录（例如 /var/cache/ver），环境变量 VER_SHARED_CACHE 优先
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_dir: Option<String>,
+}
+
+/// 管理员统一提供版本时的配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ManagedConfig {
+    /// 统一提供的只读版本目录，环境变量 VER_MANAGED_VERSIONS_DIR 优先
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub versions_dir: Option<String>,
+}
+
+/// `ver self-update` 相关配置
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    /// 发布 ver 的地址（GitHub Releases 格式），用于内部镜像，环境变量 VER_RELEASE_URL 优先
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_url: Option<String>,
+}
+
+/// 版本补充信息的来源
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MetadataConfig {
+    /// endoflife.date 格式的接口地址，用于内部镜像，默认为 https://endoflife.date/api
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eol_url: Option<String>,
+    /// 安全公告源地址，内容为 `[{"tool", "id", "affected", "url"}]` 格式的JSON，`affected` 使用策略文件的版本范围语法
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security_feed: Option<String>,
+    /// 设为 false 时不访问任何补充来源，只显示版本索引中的信息
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+/// 未配置 resolve.ignore_dirs 时跳过的目录
+pub const DEFAULT_RESOLVE_IGNORE_DIRS: &[&str] = &["node_modules", "target", ".git"];
+
+/// 未配置 rust.components 时安装的组件
+pub const DEFAULT_RUST_COMPONENTS: &[&str] = &["rustc", "cargo", "rust-std"];
+
+/// 未配置 python.nuget_url 时的 nuget 包下载地址前缀
+pub const DEFAULT_NUGET_URL: &str = "https://www.nuget.org/api/v2/package";
+
+impl Config {
+    /// 从文件读取配置
+    ///
+    /// 文件不存在时返回默认配置。
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 配置文件路径
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回配置，失败时返回错误。
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self {
+                schema: SCHEMA_VERSION,
+                ..Self::default()
+            });
+        }
+
+        let content = fs::read_to_string(path)?;
+        let mut config: Self = serde_json::from_str(&content)
+            .with_context(|| format!("无法解析配置文件 {}，请修正其中的JSON或删除该文件后重试", path.display()))?;
+        // 启动时已经完成迁移，保存时按当前格式写回
+        config.schema = SCHEMA_VERSION;
+        Ok(config)
+    }
+
+    /// 保存配置到文件
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 配置文件路径
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        write_atomic(path, content)?;
+        Ok(())
+    }
+
+    /// 读取配置项
+    ///
+    /// # 参数
+    ///
+    /// * `key` - 配置项名称，例如 `go.proxy` 或 `env.python.PIP_INDEX_URL`
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回配置值（未设置时为None），配置项不存在时返回错误。
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        if let Some((tool, var)) = Self::parse_env_key(key)? {
+            return Ok(self.env.get(tool.id()).and_then(|vars| vars.get(var)).cloned());
+        }
+        
+        if let Some(tool) = Self::parse_mirrors_key(key)? {
+            return Ok(self.mirrors.get(tool.id()).map(|urls| urls.join(",")));
+        }
+        
+        if let Some(tool) = Self::parse_tool_key(key, "on_install.")? {
+            return Ok(self.on_install.get(tool.id()).map(|commands| commands.join("; ")));
+        }
+        
+        if let Some(file_name) = Self::parse_version_file_key(key)? {
+            return Ok(self.version_files.get(file_name).cloned());
+        }
+        
+        match key {
+            "go.proxy" => Ok(self.go.proxy.clone()),
+            "go.sumdb" => Ok(self.go.sumdb.clone()),
+            "rust.source" => Ok(self.rust.source.clone()),
+            "rust.components" => Ok(Some(self.rust.components.join(",")).filter(|v| !v.is_empty())),
+            "python.windows_source" => Ok(self.python.windows_source.clone()),
+            "python.nuget_url" => Ok(self.python.nuget_url.clone()),
+            "install.rosetta_fallback" => Ok(self.install.rosetta_fallback.map(|v| v.to_string())),
+            "install.delta_url" => Ok(self.install.delta_url.clone()),
+            "policy.file" => Ok(self.policy.file.clone()),
+            "resolve.ignore_dirs" => Ok(Some(self.resolve.ignore_dirs.join(",")).filter(|v| !v.is_empty())),
+            "manage" => Ok(Some(self.manage.join(",")).filter(|v| !v.is_empty())),
+            "resolve.max_depth" => Ok(self.resolve.max_depth.map(|v| v.to_string())),
+            "resolve.compat_env" => Ok(self.resolve.compat_env.map(|v| v.to_string())),
+            "maintain.prune_days" => Ok(self.maintain.prune_days.map(|v| v.to_string())),
+            "cache.shared_dir" => Ok(self.cache.shared_dir.clone()),
+            "managed.versions_dir" => Ok(self.managed.versions_dir.clone()),
+            "update.release_url" => Ok(self.update.release_url.clone()),
+            "metadata.eol_url" => Ok(self.metadata.eol_url.clone()),
+            "metadata.security_feed" => Ok(self.metadata.security_feed.clone()),
+            "metadata.enabled" => Ok(self.metadata.enabled.map(|v| v.to_string())),
+            "allow_root" => Ok(self.allow_root.map(|v| v.to_string())),
+            _ => Err(anyhow::anyhow!("未知的配置项: {}", key)),
+        }
+    }
+
+    /// 设置配置项
+    ///
+    /// # 参数
+    ///
+    /// * `key` - 配置项名称，例如 `go.proxy`、`mirrors.node` 或 `env.node.NPM_CONFIG_REGISTRY`
+    /// * `value` - 配置值，为None时清除该配置项
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，配置项不存在时返回错误。
+    pub fn set(&mut self, key: &str, value: Option<String>) -> Result<()> {
+        if let Some((tool, var)) = Self::parse_env_key(key)? {
+            match value {
+                Some(value) => {
+                    self.env.entry(tool.id().to_string()).or_default().insert(var.to_string(), value);
+                }
+                None => {
+                    if let Some(vars) = self.env.get_mut(tool.id()) {
+                        vars.remove(var);
+                        if vars.is_empty() {
+                            self.env.remove(tool.id());
+                        }
+                    }
+                }
+            }
+            return Ok(());
+        }
+        
+        if let Some(tool) = Self::parse_mirrors_key(key)? {
+            let urls: Vec<String> = value.iter()
+                .flat_map(|value| value.split(','))
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .collect();
+            if urls.is_empty() {
+                self.mirrors.remove(tool.id());
+            } else {
+                self.mirrors.insert(tool.id().to_string(), urls);
+            }
+            return Ok(());
+        }
+        
+        // 多条命令用 `;` 分隔，依次运行
+        if let Some(tool) = Self::parse_tool_key(key, "on_install.")? {
+            let commands: Vec<String> = value.iter()
+                .flat_map(|value| value.split(';'))
+                .map(|command| command.trim().to_string())
+                .filter(|command| !command.is_empty())
+                .collect();
+            if commands.is_empty() {
+                self.on_install.remove(tool.id());
+            } else {
+                self.on_install.insert(tool.id().to_string(), commands);
+            }
+            return Ok(());
+        }
+        
+        // 写入前校验解析规则，避免版本解析时静默忽略
+        if let Some(file_name) = Self::parse_version_file_key(key)? {
+            match value {
+                Some(rule) => {
+                    version_file::from_rule(file_name, &rule)?;
+                    self.version_files.insert(file_name.to_string(), rule);
+                }
+                None => {
+                    self.version_files.remove(file_name);
+                }
+            }
+            return Ok(());
+        }
+        
+        match key {
+            "go.proxy" => self.go.proxy = value,
+            "go.sumdb" => self.go.sumdb = value,
+            "rust.source" => {
+                if let Some(source) = &value
+                    && source != "standalone" && source != "components"
+                {
+                    return Err(anyhow::anyhow!("rust.source 只能是 standalone 或 components: {}", source));
+                }
+                self.rust.source = value;
+            }
+            "install.rosetta_fallback" => {
+                self.install.rosetta_fallback = match value.as_deref() {
+                    None => None,
+                    Some("true") => Some(true),
+                    Some("false") => Some(false),
+                    Some(other) => return Err(anyhow::anyhow!("install.rosetta_fallback 只能是 true 或 false: {}", other)),
+                };
+            }
+            "install.delta_url" => self.install.delta_url = value,
+            "policy.file" => self.policy.file = value,
+            "cache.shared_dir" => self.cache.shared_dir = value,
+            "managed.versions_dir" => self.managed.versions_dir = value,
+            "update.release_url" => self.update.release_url = value,
+            "metadata.eol_url" => self.metadata.eol_url = value,
+            "metadata.security_feed" => self.metadata.security_feed = value,
+            "metadata.enabled" => {
+                self.metadata.enabled = match value.as_deref() {
+                    None => None,
+                    Some("true") => Some(true),
+                    Some("false") => Some(false),
+                    Some(other) => return Err(anyhow::anyhow!("metadata.enabled 只能是 true 或 false: {}", other)),
+                };
+            }
+            "manage" => {
+                let mut tools = Vec::new();
+                for tool in value.iter().flat_map(|value| value.split(',')).map(str::trim).filter(|tool| !tool.is_empty()) {
+                    let version_type = VersionType::from_id(tool)
+                        .ok_or_else(|| anyhow::anyhow!("manage 中的工具无法识别: {}，应为 node、rust、python 或 go", tool))?;
+                    if !tools.contains(&version_type.id().to_string()) {
+                        tools.push(version_type.id().to_string());
+                    }
+                }
+                self.manage = tools;
+            }
+            "resolve.ignore_dirs" => {
+                self.resolve.ignore_dirs = value.iter()
+                    .flat_map(|value| value.split(','))
+                    .map(|dir| dir.trim().to_string())
+                    .filter(|dir| !dir.is_empty())
+                    .collect();
+            }
+            "resolve.max_depth" => {
+                self.resolve.max_depth = match value {
+                    Some(value) => Some(value.parse()
+                        .map_err(|_| anyhow::anyhow!("resolve.max_depth 必须是非负整数: {}", value))?),
+                    None => None,
+                };
+            }
+            "resolve.compat_env" => {
+                self.resolve.compat_env = match value.as_deref() {
+                    None => None,
+                    Some("true") => Some(true),
+                    Some("false") => Some(false),
+                    Some(other) => return Err(anyhow::anyhow!("resolve.compat_env 只能是 true 或 false: {}", other)),
+                };
+            }
+            "allow_root" => {
+                self.allow_root = match value.as_deref() {
+                    None => None,
+                    Some("true") => Some(true),
+                    Some("false") => Some(false),
+                    Some(other) => return Err(anyhow::anyhow!("allow_root 只能是 true 或 false: {}", other)),
+                };
+            }
+            "maintain.prune_days" => {
+                self.maintain.prune_days = match value {
+                    Some(value) => Some(value.parse().ok().filter(|days: &i64| *days > 0)
+                        .ok_or_else(|| anyhow::anyhow!("maintain.prune_days 必须是正整数: {}", value))?),
+                    None => None,
+                };
+            }
+            "python.windows_source" => {
+                if let Some(source) = &value
+                    && source != "nuget" && source != "embed"
+                {
+                    return Err(anyhow::anyhow!("python.windows_source 只能是 nuget 或 embed: {}", source));
+                }
+                self.python.windows_source = value;
+            }
+            "python.nuget_url" => self.python.nuget_url = value.map(|url| url.trim_end_matches('/').to_string()),
+            "rust.components" => {
+                self.rust.components = value.iter()
+                    .flat_map(|value| value.split(','))
+                    .map(|component| component.trim().to_string())
+                    .filter(|component| !component.is_empty())
+                    .collect();
+            }
+            _ => return Err(anyhow::anyhow!("未知的配置项: {}", key)),
+        }
+        Ok(())
+    }
+
+    /// 列出所有已设置的配置项
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = Vec::new();
+        if let Some(proxy) = &self.go.proxy {
+            entries.push(("go.proxy".to_string(), proxy.clone()));
+        }
+        if let Some(sumdb) = &self.go.sumdb {
+            entries.push(("go.sumdb".to_string(), sumdb.clone()));
+        }
+        if let Some(source) = &self.rust.source {
+            entries.push(("rust.source".to_string(), source.clone()));
+        }
+        if !self.rust.components.is_empty() {
+            entries.push(("rust.components".to_string(), self.rust.components.join(",")));
+        }
+        if let Some(source) = &self.python.windows_source {
+            entries.push(("python.windows_source".to_string(), source.clone()));
+        }
+        if let Some(url) = &self.python.nuget_url {
+            entries.push(("python.nuget_url".to_string(), url.clone()));
+        }
+        if let Some(fallback) = self.install.rosetta_fallback {
+            entries.push(("install.rosetta_fallback".to_string(), fallback.to_string()));
+        }
+        if let Some(url) = &self.install.delta_url {
+            entries.push(("install.delta_url".to_string(), url.clone()));
+        }
+        if let Some(file) = &self.policy.file {
+            entries.push(("policy.file".to_string(), file.clone()));
+        }
+        if !self.resolve.ignore_dirs.is_empty() {
+            entries.push(("resolve.ignore_dirs".to_string(), self.resolve.ignore_dirs.join(",")));
+        }
+        if !self.manage.is_empty() {
+            entries.push(("manage".to_string(), self.manage.join(",")));
+        }
+        if let Some(depth) = self.resolve.max_depth {
+            entries.push(("resolve.max_depth".to_string(), depth.to_string()));
+        }
+        if let Some(compat_env) = self.resolve.compat_env {
+            entries.push(("resolve.compat_env".to_string(), compat_env.to_string()));
+        }
+        if let Some(days) = self.maintain.prune_days {
+            entries.push(("maintain.prune_days".to_string(), days.to_string()));
+        }
+        if let Some(dir) = &self.cache.shared_dir {
+            entries.push(("cache.shared_dir".to_string(), dir.clone()));
+        }
+        if let Some(dir) = &self.managed.versions_dir {
+            entries.push(("managed.versions_dir".to_string(), dir.clone()));
+        }
+        if let Some(url) = &self.update.release_url {
+            entries.push(("update.release_url".to_string(), url.clone()));
+        }
+        if let Some(url) = &self.metadata.eol_url {
+            entries.push(("metadata.eol_url".to_string(), url.clone()));
+        }
+        if let Some(url) = &self.metadata.security_feed {
+            entries.push(("metadata.security_feed".to_string(), url.clone()));
+        }
+        if let Some(enabled) = self.metadata.enabled {
+            entries.push(("metadata.enabled".to_string(), enabled.to_string()));
+        }
+        if let Some(allow_root) = self.allow_root {
+            entries.push(("allow_root".to_string(), allow_root.to_string()));
+        }
+        for (tool, urls) in &self.mirrors {
+            entries.push((format!("mirrors.{}", tool), urls.join(",")));
+        }
+        for (tool, vars) in &self.env {
+            for (var, value) in vars {
+                entries.push((format!("env.{}.{}", tool, var), value.clone()));
+            }
+        }
+        for (tool, commands) in &self.on_install {
+            entries.push((format!("on_install.{}", tool), commands.join("; ")));
+        }
+        for (file_name, rule) in &self.version_files {
+            entries.push((format!("version_files.{}", file_name), rule.clone()));
+        }
+        entries
+    }
+
+    /// 获取某个工具配置的默认环境变量
+    ///
+    /// `go.proxy`/`go.sumdb` 会转换为 GOPROXY/GOSUMDB，`env.<tool>.*` 中的同名变量优先。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回按变量名排序的环境变量列表。
+    pub fn tool_env(&self, version_type: VersionType) -> Vec<(String, String)> {
+        let mut vars = BTreeMap::new();
+        if version_type == VersionType::Go {
+            if let Some(proxy) = &self.go.proxy {
+                vars.insert("GOPROXY".to_string(), proxy.clone());
+            }
+            if let Some(sumdb) = &self.go.sumdb {
+                vars.insert("GOSUMDB".to_string(), sumdb.clone());
+            }
+        }
+        if let Some(tool_vars) = self.env.get(version_type.id()) {
+            vars.extend(tool_vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        vars.into_iter().collect()
+    }
+
+    /// 获取Rust安装来源
+    pub fn rust_source(&self) -> RustSource {
+        match self.rust.source.as_deref() {
+            Some("components") => RustSource::Components,
+            _ => RustSource::Standalone,
+        }
+    }
+
+    /// 获取使用 components 安装来源时要下载的组件
+    pub fn rust_components(&self) -> Vec<String> {
+        if self.rust.components.is_empty() {
+            DEFAULT_RUST_COMPONENTS.iter().map(|c| c.to_string()).collect()
+        } else {
+            self.rust.components.clone()
+        }
+    }
+
+    /// 获取 Windows 上的Python安装来源
+    pub fn python_windows_source(&self) -> PythonWindowsSource {
+        match self.python.windows_source.as_deref() {
+            Some("embed") => PythonWindowsSource::Embed,
+            _ => PythonWindowsSource::Nuget,
+        }
+    }
+
+    /// 获取 nuget 包的下载地址前缀
+    pub fn python_nuget_url(&self) -> &str {
+        self.python.nuget_url.as_deref().unwrap_or(DEFAULT_NUGET_URL)
+    }
+
+    /// 是否允许在 Apple Silicon 上回退到 x64 版本
+    pub fn rosetta_fallback(&self) -> bool {
+        self.install.rosetta_fallback.unwrap_or(false)
+    }
+
+    /// ver 是否管理某个工具（配置项 `manage`，未配置时管理所有工具）
+    pub fn manages(&self, version_type: VersionType) -> bool {
+        self.manage.is_empty() || self.manage.iter().any(|tool| tool == version_type.id())
+    }
+
+    /// 获取增量源的地址，未配置时返回None
+    pub fn delta_url(&self) -> Option<String> {
+        self.install.delta_url.as_ref().map(|url| url.trim_end_matches('/').to_string())
+    }
+
+    /// 获取向上查找版本文件时跳过的目录名
+    pub fn resolve_ignore_dirs(&self) -> Vec<String> {
+        if self.resolve.ignore_dirs.is_empty() {
+            DEFAULT_RESOLVE_IGNORE_DIRS.iter().map(|dir| dir.to_string()).collect()
+        } else {
+            self.resolve.ignore_dirs.clone()
+        }
+    }
+
+    /// 是否把其他工具的版本环境变量作为版本解析的输入
+    pub fn compat_env(&self) -> bool {
+        self.resolve.compat_env.unwrap_or(false)
+    }
+
+    /// 是否已确认以 root 运行
+    ///
+    /// 环境变量 VER_ALLOW_ROOT 为 1 或 true 时优先于配置项 `allow_root`。
+    pub fn allow_root(&self) -> bool {
+        match std::env::var("VER_ALLOW_ROOT") {
+            Ok(value) if !value.is_empty() => matches!(value.as_str(), "1" | "true"),
+            _ => self.allow_root.unwrap_or(false),
+        }
+    }
+
+    /// 获取团队策略文件路径
+    ///
+    /// 环境变量 VER_POLICY_FILE 优先于配置项 `policy.file`。
+    pub fn policy_file(&self) -> Option<std::path::PathBuf> {
+        std::env::var_os("VER_POLICY_FILE")
+            .filter(|path| !path.is_empty())
+            .map(std::path::PathBuf::from)
+            .or_else(|| self.policy.file.as_ref().map(std::path::PathBuf::from))
+    }
+
+    /// 获取共享安装包缓存目录
+    ///
+    /// 环境变量 VER_SHARED_CACHE 优先于配置项 `cache.shared_dir`。
+    pub fn shared_cache_dir(&self) -> Option<std::path::PathBuf> {
+        std::env::var_os("VER_SHARED_CACHE")
+            .filter(|path| !path.is_empty())
+            .map(std::path::PathBuf::from)
+            .or_else(|| self.cache.shared_dir.as_ref().map(std::path::PathBuf::from))
+    }
+
+    /// 获取管理员统一提供的版本目录，未配置时返回None
+    ///
+    /// 环境变量 VER_MANAGED_VERSIONS_DIR 优先于配置项 `managed.versions_dir`，
+    /// 管理员可以在系统级的 shell 配置中设置它。
+    pub fn managed_versions_dir(&self) -> Option<std::path::PathBuf> {
+        std::env::var_os("VER_MANAGED_VERSIONS_DIR")
+            .filter(|path| !path.is_empty())
+            .map(std::path::PathBuf::from)
+            .or_else(|| self.managed.versions_dir.as_ref().map(std::path::PathBuf::from))
+    }
+
+    /// 获取发布 ver 的地址
+    ///
+    /// 环境变量 VER_RELEASE_URL 优先于配置项 `update.release_url`。
+    pub fn release_url(&self) -> String {
+        std::env::var("VER_RELEASE_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .or_else(|| self.update.release_url.clone())
+            .unwrap_or_else(|| crate::release::DEFAULT_RELEASE_URL.to_string())
+    }
+
+    /// 是否从补充来源获取版本信息
+    pub fn metadata_enabled(&self) -> bool {
+        self.metadata.enabled.unwrap_or(true)
+    }
+
+    /// 获取 endoflife.date 格式的接口地址
+    pub fn eol_url(&self) -> &str {
+        self.metadata.eol_url.as_deref().unwrap_or(crate::metadata::DEFAULT_EOL_URL)
+    }
+
+    /// 获取某个工具配置的镜像列表
+    pub fn mirrors(&self, version_type: VersionType) -> &[String] {
+        self.mirrors.get(version_type.id()).map(|urls| urls.as_slice()).unwrap_or(&[])
+    }
+
+    /// 解析 `mirrors.<tool>` 形式的配置项名称
+    fn parse_mirrors_key(key: &str) -> Result<Option<VersionType>> {
+        Self::parse_tool_key(key, "mirrors.")
+    }
+
+    /// 解析 `<prefix><tool>` 形式的配置项名称，例如 `on_install.node`
+    fn parse_tool_key(key: &str, prefix: &str) -> Result<Option<VersionType>> {
+        let Some(tool) = key.strip_prefix(prefix) else {
+            return Ok(None);
+        };
+        VersionType::from_id(tool)
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("不支持的工具类型: {}", tool))
+    }
+
+    /// 获取某个工具安装新版本后运行的命令
+    pub fn on_install(&self, version_type: VersionType) -> &[String] {
+        self.on_install.get(version_type.id()).map(|commands| commands.as_slice()).unwrap_or(&[])
+    }
+
+    /// 解析 `version_files.<文件名>` 形式的配置项名称，例如 `version_files..nvmrc`
+    fn parse_version_file_key(key: &str) -> Result<Option<&str>> {
+        let Some(file_name) = key.strip_prefix("version_files.") else {
+            return Ok(None);
+        };
+        if file_name.is_empty() || file_name.contains(['/', '\\']) {
+            return Err(anyhow::anyhow!("版本文件名不能为空或包含路径分隔符: {}", file_name));
+        }
+        Ok(Some(file_name))
+    }
+
+    /// 解析 `env.<tool>.<VAR>` 形式的配置项名称
+    fn parse_env_key(key: &str) -> Result<Option<(VersionType, &str)>> {
+        let Some(rest) = key.strip_prefix("env.") else {
+            return Ok(None);
+        };
+        let Some((tool, var)) = rest.split_once('.') else {
+            return Err(anyhow::anyhow!("环境变量配置项格式应为 env.<tool>.<VAR>: {}", key));
+        };
+        let tool = VersionType::from_id(tool)
+            .ok_or_else(|| anyhow::anyhow!("不支持的工具类型: {}", tool))?;
+        if var.is_empty() || !var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(anyhow::anyhow!("无效的环境变量名: {}", var));
+        }
+        Ok(Some((tool, var)))
+    }
+}
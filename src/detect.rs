@@ -0,0 +1,424 @@
+use std::{fs, path::Path};
+
+use crate::version_file::{VerTomlFile, VersionFileFormat, VER_TOML};
+use crate::version_manager::VersionType;
+
+/// 从项目文件中找到的一条版本线索
+#[derive(Debug, Clone)]
+pub struct Evidence {
+    pub tool: VersionType,
+    /// 版本范围，语法与团队策略相同（见 [`Policy::range_matches`](crate::policy::Policy::range_matches)）
+    pub range: String,
+    /// 线索来源，例如 `package.json engines.node`
+    pub source: String,
+}
+
+/// 扫描项目目录中的版本线索
+///
+/// 线索按可信程度从高到低排列：`.ver.toml`、版本文件和 volta 等明确的固定版本、CI 配置中的版本、
+/// 项目清单中的版本范围（engines、requires-python、rust-version、go 指令），
+/// 最后是锁文件和 packageManager 隐含的最低 Node.js 版本。无法识别的写法（例如 `lts/*`）会被跳过。
+///
+/// # 参数
+///
+/// * `dir` - 项目根目录
+///
+/// # 返回
+///
+/// 返回所有工具的线索。
+pub fn scan(dir: &Path) -> Vec<Evidence> {
+    let mut evidence = Vec::new();
+    let mut push = |tool: VersionType, spec: &str, source: String| {
+        if let Some(range) = normalize(spec) {
+            evidence.push(Evidence { tool, range, source });
+        }
+    };
+
+    // 明确的固定版本
+    if let Some(content) = read(dir, VER_TOML) {
+        for tool in VersionType::all() {
+            if let Some(version) = VerTomlFile.parse(&content, tool) {
+                push(tool, &version, VER_TOML.to_string());
+            }
+        }
+    }
+    if let Some(content) = read(dir, ".tool-versions") {
+        for line in content.lines().map(str::trim).filter(|line| !line.starts_with('#')) {
+            let mut fields = line.split_whitespace();
+            let (Some(name), Some(version)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            if let Some(tool) = asdf_tool(name) {
+                push(tool, version, ".tool-versions".to_string());
+            }
+        }
+    }
+    for tool in VersionType::all() {
+        if let Some(content) = read(dir, tool.version_file_name()) {
+            push(tool, content.lines().next().unwrap_or_default(), tool.version_file_name().to_string());
+        }
+    }
+    if let Some(content) = read(dir, ".nvmrc") {
+        push(VersionType::Node, content.lines().next().unwrap_or_default(), ".nvmrc".to_string());
+    }
+    if let Some(content) = read(dir, "runtime.txt")
+        && let Some(version) = content.trim().strip_prefix("python-")
+    {
+        push(VersionType::Python, version, "runtime.txt".to_string());
+    }
+    if let Some(content) = read(dir, "rust-toolchain.toml") {
+        if let Some(channel) = toml_value(&content, "toolchain", "channel") {
+            push(VersionType::Rust, &channel, "rust-toolchain.toml".to_string());
+        }
+    } else if let Some(content) = read(dir, "rust-toolchain") {
+        // 旧格式的 rust-toolchain 可能是只有渠道名的纯文本，也可能是 TOML
+        let channel = toml_value(&content, "toolchain", "channel").unwrap_or_else(|| content.trim().to_string());
+        push(VersionType::Rust, &channel, "rust-toolchain".to_string());
+    }
+    let package_json = read(dir, "package.json").and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+    if let Some(node) = package_json.as_ref().and_then(|package| package["volta"]["node"].as_str()) {
+        push(VersionType::Node, node, "package.json volta.node".to_string());
+    }
+    let go_mod = read(dir, "go.mod");
+    if let Some(toolchain) = go_mod.as_deref().and_then(|content| directive(content, "toolchain")) {
+        push(VersionType::Go, toolchain.trim_start_matches("go"), "go.mod toolchain".to_string());
+    }
+
+    // CI 配置
+    for (file, content) in ci_files(dir) {
+        for (tool, versions) in ci_versions(&content) {
+            push(tool, &versions.join(" || "), file.clone());
+        }
+    }
+
+    // 项目清单中的版本范围
+    if let Some(range) = package_json.as_ref().and_then(|package| package["engines"]["node"].as_str()) {
+        push(VersionType::Node, range, "package.json engines.node".to_string());
+    }
+    if let Some(content) = read(dir, "pyproject.toml") {
+        if let Some(range) = toml_value(&content, "project", "requires-python") {
+            push(VersionType::Python, &range, "pyproject.toml requires-python".to_string());
+        }
+        if let Some(range) = toml_value(&content, "tool.poetry.dependencies", "python") {
+            push(VersionType::Python, &range, "pyproject.toml tool.poetry.dependencies.python".to_string());
+        }
+    }
+    if let Some(version) = read(dir, "Cargo.toml").and_then(|content| toml_value(&content, "package", "rust-version")) {
+        push(VersionType::Rust, &format!(">={}", version), "Cargo.toml rust-version".to_string());
+    }
+    if let Some(version) = go_mod.as_deref().and_then(|content| directive(content, "go")) {
+        // go 指令是最低版本，固定到同一个次版本系列
+        let minor = version.split('.').take(2).collect::<Vec<_>>().join(".");
+        push(VersionType::Go, &minor, "go.mod go directive".to_string());
+    }
+
+    // 锁文件和包管理器要求的最低 Node.js 版本
+    if let Some(package_manager) = package_json.as_ref().and_then(|package| package["packageManager"].as_str()) {
+        let (name, version) = package_manager.split_once('@').unwrap_or((package_manager, ""));
+        let major = version.split('.').next().unwrap_or_default();
+        let floor = match (name, major) {
+            ("pnpm", "9" | "10") | ("yarn", "4") => ">=18.12",
+            ("pnpm", "8") => ">=16.14",
+            // packageManager 由 corepack 读取，corepack 从 Node.js 16.9 开始内置
+            _ => ">=16.9",
+        };
+        push(VersionType::Node, floor, format!("package.json packageManager {}", package_manager));
+    }
+    if let Some(content) = read(dir, "pnpm-lock.yaml")
+        && let Some(lockfile_version) = content.lines().find_map(|line| line.strip_prefix("lockfileVersion:"))
+    {
+        let lockfile_version = lockfile_version.trim().trim_matches(['\'', '"']);
+        let floor = match lockfile_version.split('.').next().unwrap_or_default() {
+            "9" => Some(">=18.12"),
+            "6" => Some(">=16.14"),
+            _ => None,
+        };
+        if let Some(floor) = floor {
+            push(VersionType::Node, floor, format!("pnpm-lock.yaml lockfileVersion {}", lockfile_version));
+        }
+    }
+    if let Some(content) = read(dir, "yarn.lock")
+        && content.contains("__metadata:")
+        && content.lines().skip_while(|line| !line.starts_with("__metadata:")).nth(1).is_some_and(|line| line.trim() == "version: 8")
+    {
+        push(VersionType::Node, ">=18.12", "yarn.lock (Yarn 4 metadata)".to_string());
+    }
+    if let Some(lockfile_version) = read(dir, "package-lock.json")
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|lock| lock["lockfileVersion"].as_u64())
+        .filter(|version| *version >= 2)
+    {
+        // lockfileVersion 2 和 3 由 npm 7 及以上写入，npm 7 随 Node.js 15 发布
+        push(VersionType::Node, ">=15", format!("package-lock.json lockfileVersion {}", lockfile_version));
+    }
+
+    evidence
+}
+
+/// 把各种生态的版本写法转换为团队策略的范围语法
+///
+/// 支持 npm 的 `^`、`~`、`x` 通配符和 `a - b` 区间，PEP 440 的 `~=`、`==3.11.*` 和逗号分隔的条件。
+/// `!=` 条件被忽略。
+///
+/// # 参数
+///
+/// * `spec` - 版本写法，例如 `^18.17.0`、`>=3.10,<3.13` 或 `v20`
+///
+/// # 返回
+///
+/// 返回范围，无法识别或不限制版本时返回None。
+pub fn normalize(spec: &str) -> Option<String> {
+    let spec = spec.trim().trim_matches(['"', '\'']);
+    let groups = spec.split("||")
+        .map(normalize_group)
+        .collect::<Option<Vec<_>>>()?;
+    (!groups.is_empty()).then(|| groups.join(" || "))
+}
+
+/// 转换 `||` 分隔的一组条件
+fn normalize_group(group: &str) -> Option<String> {
+    if let Some((low, high)) = group.split_once(" - ") {
+        return Some(format!(">={} <={}", numeric(low.trim().trim_start_matches('v'))?, numeric(high.trim().trim_start_matches('v'))?));
+    }
+
+    // `>= 18` 这样运算符后带空格的写法
+    let group = ["~=", "==", ">=", "<=", "^", "~", ">", "<"].iter()
+        .fold(group.to_string(), |group, op| group.replace(&format!("{} ", op), op));
+    let mut comparators = Vec::new();
+    for token in group.split([',', ' ']).filter(|token| !token.is_empty()) {
+        let (op, bound) = ["~=", "==", ">=", "<=", "!=", "^", "~", ">", "<", "="].iter()
+            .find_map(|op| token.strip_prefix(op).map(|bound| (*op, bound)))
+            .unwrap_or(("", token));
+        let mut bound = bound.trim().trim_start_matches('v');
+        while let Some(stripped) = [".*", ".x", ".X"].iter().find_map(|wildcard| bound.strip_suffix(wildcard)) {
+            bound = stripped;
+        }
+        if matches!(bound, "" | "*" | "x" | "X") || op == "!=" {
+            continue;
+        }
+        let parts: Vec<u64> = numeric(bound)?.split('.').filter_map(|part| part.parse().ok()).collect();
+        match op {
+            "^" => {
+                let upper = match parts.as_slice() {
+                    [0, minor, ..] => format!("0.{}", minor + 1),
+                    [major, ..] => (major + 1).to_string(),
+                    [] => return None,
+                };
+                comparators.push(format!(">={} <{}", bound, upper));
+            }
+            "~" | "~=" => {
+                // npm 的 ~ 允许补丁版本变化；PEP 440 的 ~= 允许最后一段变化
+                let keep = if op == "~" { parts.len().min(2) } else { parts.len().saturating_sub(1).max(1) };
+                let mut upper = parts[..keep].to_vec();
+                if let Some(last) = upper.last_mut() {
+                    *last += 1;
+                }
+                let upper: Vec<String> = upper.iter().map(u64::to_string).collect();
+                comparators.push(format!(">={} <{}", bound, upper.join(".")));
+            }
+            "==" | "=" | "" => comparators.push(bound.to_string()),
+            _ => comparators.push(format!("{}{}", op, bound)),
+        }
+    }
+    (!comparators.is_empty()).then(|| comparators.join(" "))
+}
+
+/// 版本号的每一段都是数字时原样返回
+fn numeric(version: &str) -> Option<&str> {
+    (!version.is_empty() && version.split('.').all(|part| part.parse::<u64>().is_ok())).then_some(version)
+}
+
+/// asdf 的插件名对应的工具
+fn asdf_tool(name: &str) -> Option<VersionType> {
+    match name {
+        "nodejs" | "node" => Some(VersionType::Node),
+        "golang" | "go" => Some(VersionType::Go),
+        other => VersionType::from_id(other),
+    }
+}
+
+/// 读取项目目录中的文件，不存在或无法读取时返回None
+fn read(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(name)).ok()
+}
+
+/// 读取 TOML 文件中某个表的字符串值
+///
+/// 只按行匹配 `key = "value"`，足以读取清单中的版本字段，不需要完整的 TOML 解析器。
+fn toml_value(content: &str, table: &str, key: &str) -> Option<String> {
+    let mut current = String::new();
+    for line in content.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            current = header.trim().to_string();
+            continue;
+        }
+        if current != table {
+            continue;
+        }
+        if let Some((name, value)) = line.split_once('=')
+            && name.trim() == key
+        {
+            let value = value.split('#').next().unwrap_or_default().trim().trim_matches(['"', '\'']);
+            return (!value.is_empty()).then(|| value.to_string());
+        }
+    }
+    None
+}
+
+/// 读取 go.mod 中的指令，例如 `go 1.22.1` 或 `toolchain go1.22.1`
+fn directive<'a>(content: &'a str, name: &str) -> Option<&'a str> {
+    content.lines()
+        .filter_map(|line| line.trim().strip_prefix(name))
+        .find(|rest| rest.starts_with(char::is_whitespace))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// 常见 CI 服务的配置文件，返回相对路径和内容
+fn ci_files(dir: &Path) -> Vec<(String, String)> {
+    let mut files = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir.join(".github").join("workflows")) {
+        let mut names: Vec<String> = entries.flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".yml") || name.ends_with(".yaml"))
+            .collect();
+        names.sort();
+        files.extend(names.into_iter().map(|name| format!(".github/workflows/{}", name)));
+    }
+    files.extend([".gitlab-ci.yml", ".circleci/config.yml"].map(str::to_string));
+    files.into_iter()
+        .filter_map(|file| read(dir, &file).map(|content| (file, content)))
+        .collect()
+}
+
+/// 从 CI 配置中读取每个工具的版本
+///
+/// 识别 setup-node 等 action 的 `node-version`、`python-version`、`go-version`，
+/// `dtolnay/rust-toolchain@<版本>`，以及 `node:20-alpine` 这类容器镜像的标签。矩阵中的多个版本都会返回。
+fn ci_versions(content: &str) -> Vec<(VersionType, Vec<String>)> {
+    let mut found: Vec<(VersionType, Vec<String>)> = Vec::new();
+    let mut add = |tool: VersionType, value: &str| {
+        let value = value.split(" #").next().unwrap_or_default().trim();
+        let values: Vec<String> = value.trim_start_matches('[').trim_end_matches(']')
+            .split(',')
+            .map(|value| value.trim().trim_matches(['"', '\'']).to_string())
+            .filter(|value| !value.is_empty() && !value.contains('$'))
+            .collect();
+        if values.is_empty() {
+            return;
+        }
+        match found.iter_mut().find(|(existing, _)| *existing == tool) {
+            Some((_, versions)) => versions.extend(values.into_iter().filter(|value| !versions.contains(value)).collect::<Vec<_>>()),
+            None => found.push((tool, values)),
+        }
+    };
+
+    for line in content.lines() {
+        let line = line.trim().trim_start_matches("- ").trim();
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "node-version" => add(VersionType::Node, value),
+            "python-version" => add(VersionType::Python, value),
+            "go-version" => add(VersionType::Go, value),
+            "uses" => {
+                if let Some(version) = value.trim().strip_prefix("dtolnay/rust-toolchain@") {
+                    add(VersionType::Rust, version);
+                }
+            }
+            "image" => {
+                let image = value.trim().trim_matches(['"', '\'']).rsplit('/').next().unwrap_or_default();
+                let Some((name, tag)) = image.split_once(':') else {
+                    continue;
+                };
+                let tool = match name {
+                    "node" => VersionType::Node,
+                    "python" => VersionType::Python,
+                    "golang" | "go" => VersionType::Go,
+                    "rust" => VersionType::Rust,
+                    _ => continue,
+                };
+                add(tool, tag.split('-').next().unwrap_or_default());
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_converts_ecosystem_ranges() {
+        let cases = [
+            ("^18.17.0", Some(">=18.17.0 <19")),
+            ("^0.3.1", Some(">=0.3.1 <0.4")),
+            ("~1.2.3", Some(">=1.2.3 <1.3")),
+            ("~=3.10", Some(">=3.10 <4")),
+            ("~=3.10.2", Some(">=3.10.2 <3.11")),
+            (">=3.10,<3.13", Some(">=3.10 <3.13")),
+            (">=3.8,!=3.9.0", Some(">=3.8")),
+            ("==3.11.*", Some("3.11")),
+            (">= 18", Some(">=18")),
+            ("v20", Some("20")),
+            ("'20'", Some("20")),
+            ("18.x", Some("18")),
+            ("16 || >=18", Some("16 || >=18")),
+            ("1.2 - v1.4", Some(">=1.2 <=1.4")),
+            ("*", None),
+            ("lts/*", None),
+            ("16 || lts/*", None),
+            ("", None),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(normalize(spec).as_deref(), expected, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn normalize_group_handles_a_single_group() {
+        let cases = [
+            ("1.2.3 - 2", Some(">=1.2.3 <=2")),
+            ("1.x - 2", None),
+            ("^x", None),
+            ("> 1 < 3", Some(">1 <3")),
+            ("=1.2.3", Some("1.2.3")),
+            ("stable", None),
+        ];
+        for (group, expected) in cases {
+            assert_eq!(normalize_group(group).as_deref(), expected, "{}", group);
+        }
+    }
+
+    #[test]
+    fn ci_versions_reads_actions_matrices_and_images() {
+        let workflow = r#"
+jobs:
+  test:
+    container:
+      image: node:20-alpine
+    strategy:
+      matrix:
+        node-version: [18, 20, '22'] # LTS
+    steps:
+      - uses: dtolnay/rust-toolchain@1.78.0
+      - uses: actions/setup-python@v5
+        with:
+          python-version: "3.12"
+      - uses: actions/setup-go@v5
+        with:
+          go-version: ${{ matrix.go }}
+"#;
+        let strings = |versions: &[&str]| versions.iter().map(|version| version.to_string()).collect::<Vec<_>>();
+        assert_eq!(ci_versions(workflow), vec![
+            (VersionType::Node, strings(&["20", "18", "22"])),
+            (VersionType::Rust, strings(&["1.78.0"])),
+            (VersionType::Python, strings(&["3.12"])),
+        ]);
+        assert_eq!(ci_versions("image: registry.example.com/golang:1.22-bookworm"), vec![(VersionType::Go, strings(&["1.22"]))]);
+        assert!(ci_versions("image: postgres:16").is_empty());
+    }
+}
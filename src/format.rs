@@ -0,0 +1,146 @@
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+/// 是否使用 `--plain` 输出：不依赖颜色区分状态，状态用 `[current]` 这样的文字标记
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// 开启或关闭 `--plain` 输出
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+/// 是否使用 `--plain` 输出
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// 版本状态的文字标记
+///
+/// 默认输出为 `(LTS)` 这样的括号形式，`--plain` 时为小写的 `[lts]`，便于屏幕阅读器朗读和在日志中搜索。
+pub fn marker(label: &str) -> String {
+    if is_plain() {
+        format!("[{}]", label.to_lowercase())
+    } else {
+        format!("({})", label)
+    }
+}
+
+/// 数字格式使用的区域设置
+///
+/// 按 `LC_ALL`、`LC_NUMERIC`、`LANG` 的顺序取第一个非空的值，只区分小数点和千位分隔符。
+struct NumberLocale {
+    decimal: char,
+    grouping: char,
+}
+
+impl NumberLocale {
+    fn current() -> Self {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        let language = locale.split(['_', '.', '@']).next().unwrap_or_default();
+        match language {
+            "fr" | "ru" | "uk" | "cs" | "pl" | "sv" | "fi" | "nb" => NumberLocale { decimal: ',', grouping: ' ' },
+            "de" | "es" | "it" | "pt" | "nl" | "tr" | "da" | "id" => NumberLocale { decimal: ',', grouping: '.' },
+            _ => NumberLocale { decimal: '.', grouping: ',' },
+        }
+    }
+}
+
+/// 按区域设置给整数加上千位分隔符，例如 `12,345`
+pub fn number(value: u64) -> String {
+    let grouping = NumberLocale::current().grouping;
+    let digits = value.to_string();
+    let mut output = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            output.push(grouping);
+        }
+        output.push(digit);
+    }
+    output
+}
+
+/// 把字节数格式化为易读的单位，例如 `1.5 MB`，小数点按区域设置
+pub fn size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", number(bytes), UNITS[0])
+    } else {
+        let decimal = NumberLocale::current().decimal;
+        format!("{:.1} {}", size, UNITS[unit]).replacen('.', &decimal.to_string(), 1)
+    }
+}
+
+/// 耗时，例如 `850ms`、`12.3s`、`2m 05s`，小数点按区域设置
+pub fn duration(duration: std::time::Duration) -> String {
+    let millis = duration.as_millis();
+    match millis {
+        0..=999 => format!("{}ms", millis),
+        1000..=59999 => {
+            let decimal = NumberLocale::current().decimal;
+            format!("{:.1}s", duration.as_secs_f64()).replacen('.', &decimal.to_string(), 1)
+        }
+        _ => format!("{}m {:02}s", millis / 60000, millis / 1000 % 60),
+    }
+}
+
+/// 距今的相对时间，例如 `3 weeks ago`；将来的时间视为刚刚
+pub fn relative(time: chrono::DateTime<chrono::Utc>) -> String {
+    let secs = (chrono::Utc::now() - time).num_seconds().max(0) as u64;
+    let (count, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86399 => (secs / 3600, "hour"),
+        86400..=1209599 => (secs / 86400, "day"),
+        1209600..=5183999 => (secs / 604800, "week"),
+        5184000..=31535999 => (secs / 2592000, "month"),
+        _ => (secs / 31536000, "year"),
+    };
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// 文件修改时间等系统时间距今的相对时间
+pub fn age(time: SystemTime) -> String {
+    relative(time.into())
+}
+
+/// 解析 RFC 3339 时间或 `YYYY-MM-DD` 日期
+fn parse_time(time: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(time) {
+        return Some(time.with_timezone(&chrono::Utc));
+    }
+    chrono::NaiveDate::parse_from_str(time, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+/// 同时显示绝对日期和相对时间，例如 `2024-05-01 (3 weeks ago)`
+///
+/// # 参数
+///
+/// * `time` - RFC 3339 时间（状态文件中的记录）或 `YYYY-MM-DD` 日期（版本索引中的发布日期）
+///
+/// # 返回
+///
+/// 本地时区的日期和相对时间；无法解析时原样返回。
+pub fn date(time: &str) -> String {
+    match parse_time(time) {
+        Some(parsed) => format!(
+            "{} ({})",
+            parsed.with_timezone(&chrono::Local).format("%Y-%m-%d"),
+            relative(parsed)
+        ),
+        None => time.to_string(),
+    }
+}
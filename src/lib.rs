@@ -0,0 +1,22 @@
+//! ver：Node.js、Rust、Go、Python 等工具的版本管理
+//!
+//! 命令行程序在 main.rs 中，这里导出版本管理的各个模块，集成测试通过 `VersionManager` 直接驱动安装、切换、删除等流程。
+
+pub mod completions;
+pub mod config;
+pub mod detect;
+pub mod format;
+pub mod local;
+pub mod metadata;
+pub mod nix;
+pub mod output;
+pub mod policy;
+pub mod prereqs;
+pub mod release;
+pub mod resolve_cache;
+pub mod schedule;
+pub mod state;
+pub mod sync;
+pub mod template;
+pub mod version_file;
+pub mod version_manager;
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::version_manager::{InstallReceipt, VersionManager, VersionSource, VersionType};
+
+/// 只读取本地状态的版本管理器视图
+///
+/// `current`、`installed`、`aliases`、`which`、`env` 这类命令只拿到这个视图，而不是整个 `VersionManager`。
+/// 视图只转发同步、只读本地文件的方法，不提供任何会下载索引、解析域名或修改状态的方法，
+/// 这些命令因此在编译期就保证不会访问网络：需要联网的功能只能通过 `VersionManager` 上的 async 方法使用。
+///
+/// 新增本地命令需要的方法时，先确认它以及它调用的方法都不访问网络再转发到这里。
+#[derive(Clone, Copy)]
+pub struct Local<'a> {
+    manager: &'a VersionManager,
+}
+
+impl<'a> Local<'a> {
+    pub fn new(manager: &'a VersionManager) -> Self {
+        Local { manager }
+    }
+
+    /// 当前全局版本
+    pub fn current_version(&self, version_type: VersionType) -> Option<String> {
+        self.manager.get_current_version(version_type)
+    }
+
+    /// 已安装的版本，当前版本带有 ` (current)` 后缀
+    pub fn installed_versions(&self, version_type: VersionType) -> Result<Vec<String>> {
+        self.manager.list_installed_versions(version_type)
+    }
+
+    pub fn is_installed(&self, version: &str, version_type: VersionType) -> bool {
+        self.manager.is_installed(version, version_type)
+    }
+
+    pub fn is_locked(&self, version: &str, version_type: VersionType) -> bool {
+        self.manager.is_locked(version, version_type)
+    }
+
+    pub fn installed_arch_label(&self, version: &str, version_type: VersionType) -> Result<Option<String>> {
+        self.manager.installed_arch_label(version, version_type)
+    }
+
+    pub fn install_receipt(&self, version: &str, version_type: VersionType) -> Result<Option<InstallReceipt>> {
+        self.manager.read_install_receipt(version, version_type)
+    }
+
+    pub fn alias(&self, alias: &str, version_type: VersionType) -> Result<Option<String>> {
+        self.manager.get_alias(alias, version_type)
+    }
+
+    pub fn aliases(&self, version_type: VersionType) -> Result<Vec<(String, String)>> {
+        self.manager.list_aliases(version_type)
+    }
+
+    pub fn system_aliases(&self, version_type: VersionType) -> Result<Vec<(String, String)>> {
+        self.manager.list_system_aliases(version_type)
+    }
+
+    /// 在目录中生效的版本，见 `VersionManager::resolve_version`
+    pub fn resolve_version(&self, version_type: VersionType, dir: &Path) -> Result<Option<(String, VersionSource)>> {
+        self.manager.resolve_version(version_type, dir)
+    }
+
+    pub fn resolve_version_traced(&self, version_type: VersionType, dir: &Path, trace: &mut Vec<String>) -> Result<Option<(String, VersionSource)>> {
+        self.manager.resolve_version_traced(version_type, dir, trace)
+    }
+
+    pub fn executable_path(&self, version: &str, version_type: VersionType) -> PathBuf {
+        self.manager.get_executable_path(version, version_type)
+    }
+
+    pub fn injected_env(&self, version: &str, version_type: VersionType) -> (Vec<PathBuf>, Vec<(String, String)>) {
+        self.manager.injected_env(version, version_type)
+    }
+
+    pub fn version_env(&self, version: &str, version_type: VersionType) -> Vec<(String, String)> {
+        self.manager.version_env(version, version_type)
+    }
+
+    pub fn tool_env(&self, version_type: VersionType) -> Vec<(String, String)> {
+        self.manager.tool_env(version_type)
+    }
+}
@@ -1,127 +1,273 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use colored::*;
-mod version_manager;
-use version_manager::{VersionManager, VersionType};
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use ver::{completions, format, local, metadata, output, prereqs, release, schedule, state, sync, template, version_file, version_manager};
+use local::Local;
+use version_manager::{ExecOptions, ManagerOptions, MigrateOptions, OwnershipMismatch, PathOwner, ProgressMode, RustPart, VersionError, VersionManager, VersionType};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    /// Emit machine-readable JSON output (errors are reported as JSON objects)
+    #[clap(long, global = true)]
+    json: bool,
+    
+    /// Progress output for downloads and installs: bar (default), json (newline-delimited events on stderr) or plain
+    #[clap(long, global = true, value_name = "FORMAT")]
+    progress: Option<String>,
+    
+    /// Screen reader and log friendly output: no colors or animations, states shown as text markers like [current]
+    #[clap(long, global = true)]
+    plain: bool,
+    
+    /// Allow installing, removing and modifying versions in a centrally provisioned (read-only) versions directory
+    #[clap(long, global = true)]
+    admin: bool,
+    
+    /// Acknowledge running as root with a data directory owned by another user (or the reverse); config `allow_root`
+    #[clap(long, global = true)]
+    allow_root: bool,
+    
+    /// Data directory to use instead of ~/.version-manager (for hermetic integration tests)
+    #[clap(long, global = true, hide = true)]
+    root: Option<PathBuf>,
+    
+    /// Endpoint replacing a network source, as <tool>=<url>, delta=<url>, metadata=<url> or release=<url>;
+    /// once any is given, sources without an endpoint are not contacted (for tests against a local mock server)
+    #[clap(long = "endpoint", global = true, hide = true, value_name = "SOURCE=URL")]
+    endpoints: Vec<String>,
+    
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Debug, Subcommand)]
 enum Commands {
-    /// List available versions (Node.js or Rust)
+    /// List available versions
     #[clap(alias = "ls")]
     List {
-        /// Show only LTS versions
-        #[clap(long)]
-        lts: bool,
+        #[clap(flatten)]
+        args: ListArgs,
         
-        /// Version type (node or rust)
-        #[clap(short, long, default_value = "node")]
-        type_: String,
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// Install a specific version (Node.js or Rust)
+    /// Install a specific version
     #[clap(alias = "i")]
     Install {
-        /// Version to install (e.g., 16.13.0, latest, lts)
-        version: String,
+        #[clap(flatten)]
+        args: InstallArgs,
         
-        /// Version type (node or rust)
-        #[clap(short, long, default_value = "node")]
-        type_: String,
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// Use a specific version (Node.js or Rust)
+    /// Use a specific version
     #[clap(alias = "u")]
     Use {
-        /// Version to use (e.g., 16.13.0, latest, lts)
-        version: String,
+        #[clap(flatten)]
+        args: UseArgs,
         
-        /// Version type (node or rust)
-        #[clap(short, long, default_value = "node")]
-        type_: String,
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// List installed versions (Node.js or Rust)
+    /// List installed versions
     Installed {
-        /// Version type (node or rust)
-        #[clap(short, long, default_value = "node")]
-        type_: String,
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// Remove a specific version (Node.js or Rust)
+    /// Remove a specific version
     #[clap(alias = "rm")]
     Remove {
-        /// Version to remove
+        #[clap(flatten)]
+        args: RemoveArgs,
+        
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
+    },
+    
+    /// Duplicate an installed version under a new name, e.g. for testing patches (copy-on-write where supported)
+    Clone {
+        /// Installed version to duplicate
         version: String,
         
-        /// Version type (node or rust)
+        /// Name of the new version
+        name: String,
+        
+        /// Version type (node, rust, python or go)
         #[clap(short, long, default_value = "node")]
         type_: String,
     },
     
-    /// Show current version (Node.js or Rust)
-    Current {
-        /// Version type (node or rust)
+    /// Install a version from an arbitrary URL, e.g. a one-off build on an internal mirror (checksum required)
+    ImportUrl {
+        /// Version to install the archive as
+        version: String,
+        
+        /// URL of a prebuilt .tar.gz or .zip archive containing bin/<executable>
+        url: String,
+        
+        /// Expected SHA-256 of the archive; the download is rejected if it does not match
+        #[clap(long)]
+        sha256: String,
+        
+        /// Version type (node, rust, python or go)
         #[clap(short, long, default_value = "node")]
         type_: String,
     },
     
-    /// Create an alias for a version (Node.js or Rust)
+    /// Show current version
+    Current {
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
+    },
+    
+    /// Create an alias for a version
     Alias {
-        /// Alias name
-        name: String,
-        
-        /// Version to alias
-        version: String,
+        #[clap(flatten)]
+        args: AliasArgs,
         
-        /// Version type (node or rust)
-        #[clap(short, long, default_value = "node")]
-        type_: String,
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// List all aliases (Node.js or Rust)
+    /// List all aliases
     Aliases {
-        /// Version type (node or rust)
-        #[clap(short, long, default_value = "node")]
-        type_: String,
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
+    },
+    
+    /// Show release date, support status, security advisories and release notes of a version
+    Info {
+        #[clap(flatten)]
+        args: InfoArgs,
+        
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// Set local version for current directory (Node.js or Rust)
+    /// Set local version for current directory
     Local {
-        /// Version to set locally
-        version: String,
+        #[clap(flatten)]
+        args: LocalArgs,
         
-        /// Version type (node or rust)
-        #[clap(short, long, default_value = "node")]
-        type_: String,
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// Execute a command with a specific version (Node.js or Rust)
+    /// Execute a command with a specific version
     Exec {
-        /// Version to use
-        version: String,
+        #[clap(flatten)]
+        args: ExecArgs,
         
-        /// Version type (node or rust)
-        #[clap(short, long, default_value = "node")]
-        type_: String,
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
+    },
+    
+    /// Scaffold a project with version pins filled in from the global versions (or the latest LTS/stable)
+    New {
+        /// Template to use (node, rust, python, go, fullstack, or a .ver.toml saved in ~/.version-manager/templates)
+        #[clap(required_unless_present = "list")]
+        template: Option<String>,
         
-        /// Command and arguments to execute
-        #[clap(last = true)]
-        args: Vec<String>,
+        /// Project directory (created if missing)
+        #[clap(default_value = ".")]
+        dir: PathBuf,
+        
+        /// Pin the latest LTS/stable release even when a global version is set
+        #[clap(long)]
+        latest: bool,
+        
+        /// List the available templates
+        #[clap(long)]
+        list: bool,
+    },
+    
+    /// Pin tool versions for the project in .ver.toml, or propose pins from the project files with --detect
+    Pin {
+        /// Tool to pin (node, rust, python or go)
+        #[clap(required_unless_present = "detect", requires = "version")]
+        tool: Option<String>,
+        
+        /// Exact version to pin
+        version: Option<String>,
+        
+        /// Propose pins from version files, package.json engines, lockfiles and CI config
+        #[clap(long, conflicts_with = "tool")]
+        detect: bool,
+        
+        /// Project directory
+        #[clap(long, default_value = ".")]
+        dir: PathBuf,
+        
+        /// Write the proposed pins without asking
+        #[clap(short, long)]
+        yes: bool,
+        
+        /// Only show the proposed pins
+        #[clap(long)]
+        dry_run: bool,
     },
     
     /// Clean cache and temporary files
     Clean,
     
+    /// Inspect or selectively delete cached downloads and indexes
+    #[clap(subcommand)]
+    Cache(CacheCommands),
+    
+    /// Generate files for bootstrapping ver elsewhere
+    #[clap(subcommand)]
+    Generate(GenerateCommands),
+    
     /// Update ver itself
     SelfUpdate,
     
+    /// Remove installed versions that are unreferenced and unused
+    Gc {
+        /// Only report what would be removed and how much space it frees
+        #[clap(long)]
+        dry_run: bool,
+        
+        /// Treat versions not used for this many days as unused
+        #[clap(long, default_value = "90")]
+        days: i64,
+        
+        /// Remove without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+    
+    /// Interactive first-run setup: shell hooks, imports and project versions
+    Setup {
+        /// Accept every suggestion without prompting
+        #[clap(short, long)]
+        yes: bool,
+    },
+    
+    /// Register Node.js, Python and Go installed by Homebrew or the system package manager as `system-<version>` versions
+    Adopt {
+        /// Only show what would be adopted
+        #[clap(long)]
+        dry_run: bool,
+    },
+    
     /// Migrate from other version managers (nvm, rustup)
     Migrate {
         /// Source to migrate from (nvm, n, rustup)
@@ -130,324 +276,2790 @@ enum Commands {
         /// Version type (node or rust)
         #[clap(short, long, default_value = "node")]
         type_: String,
+        
+        /// Skip files or directories matching this pattern (`*` and `?` wildcards, repeatable), e.g. `share/doc` or `*.md`
+        #[clap(long)]
+        exclude: Vec<String>,
+        
+        /// Only migrate these versions (comma-separated), e.g. `--only 18.19.0,20.11.1`
+        #[clap(long, value_delimiter = ',')]
+        only: Vec<String>,
+        
+        /// List what would be migrated without copying anything
+        #[clap(long)]
+        dry_run: bool,
     },
     
-    /// Rust version management commands (alternative syntax)
-    #[clap(subcommand)]
-    Rust(RustCommands),
+    /// Show the resolved executable path for a tool
+    Which {
+        /// Version type (node or rust)
+        #[clap(short, long, default_value = "node")]
+        type_: String,
+        
+        /// Directory to resolve the version for (defaults to the current directory)
+        #[clap(long)]
+        cwd: Option<PathBuf>,
+        
+        /// Print each resolution step (version file, global version, alias)
+        #[clap(long)]
+        explain: bool,
+    },
     
-    /// Python version management commands (alternative syntax)
+    /// Explain how the environment affects which tools run
     #[clap(subcommand)]
-    Python(PythonCommands),
+    Explain(ExplainCommands),
     
-    /// Go version management commands (alternative syntax)
-    #[clap(subcommand)]
-    Go(GoCommands),
-}
-
-#[derive(Debug, Subcommand)]
-enum RustCommands {
-    /// List available Rust versions
-    #[clap(alias = "ls")]
-    List {
-        /// Show only stable versions
+    /// Check that ver and other version managers (rustup, nvm, ...) each own only their tools (config `manage`)
+    Doctor {
+        /// Remove ver's links to tools that ver does not manage
+        #[clap(long)]
+        fix: bool,
+        
+        /// Give files in ver's data directory that are owned by another user (e.g. root after `sudo ver`) back to its owner
         #[clap(long)]
-        stable: bool,
+        fix_ownership: bool,
     },
     
-    /// Install a specific Rust version
-    #[clap(alias = "i")]
-    Install {
-        /// Version to install (e.g., 1.85.0, latest, stable)
-        version: String,
+    /// Show the effective version of every tool for a directory and where it comes from
+    Status {
+        /// Directory to resolve versions for (defaults to the current directory)
+        #[clap(long)]
+        cwd: Option<PathBuf>,
+        
+        /// List installs that are running or finished recently instead
+        #[clap(long)]
+        operations: bool,
     },
     
-    /// Use a specific Rust version
-    #[clap(alias = "u")]
-    Use {
-        /// Version to use (e.g., 1.85.0, latest, stable)
-        version: String,
+    /// Show resolved tool paths for the current directory (for editor plugins)
+    IdeInfo {
+        /// Directory to resolve versions for (defaults to the current directory)
+        #[clap(long)]
+        cwd: Option<PathBuf>,
     },
     
-    /// List installed Rust versions
-    Installed,
-    
-    /// Remove a specific Rust version
-    #[clap(alias = "rm")]
-    Remove {
-        /// Version to remove
-        version: String,
+    /// Print the environment variables ver injects for tools (for `eval "$(ver env)"`)
+    Env {
+        /// Version type (node, rust, python or go); all tools when omitted
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// Show current Rust version
-    Current,
-    
-    /// Create an alias for a Rust version
-    Alias {
-        /// Alias name
-        name: String,
+    /// Use a version in this terminal only (`eval "$(ver shell 20)"`), leaving the global version untouched
+    Shell {
+        /// Version or alias to use in this terminal
+        #[clap(required_unless_present = "unset")]
+        version: Option<String>,
         
-        /// Version to alias
-        version: String,
+        /// Go back to the version from version files or the global default
+        #[clap(long, conflicts_with = "version")]
+        unset: bool,
+        
+        /// Version type (node, rust, python or go); defaults to the tool pinned by the project's version file, otherwise node
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// List all Rust aliases
-    Aliases,
-    
-    /// Set local Rust version for current directory
-    Local {
-        /// Version to set locally
-        version: String,
+    /// Show tools whose resolved version is older than the latest release
+    Outdated {
+        /// Version type (node, rust, python or go); all tools when omitted
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// Execute a command with a specific Rust version
-    Exec {
-        /// Version to use
-        version: String,
+    /// Print Dockerfile ADD lines with exact URLs and checksums for the toolchains the project pins
+    Freeze {
+        /// Target architecture of the Linux image (x64, arm64, arm or x86); defaults to this machine
+        #[clap(long)]
+        arch: Option<String>,
         
-        /// Command and arguments to execute
-        #[clap(last = true)]
-        args: Vec<String>,
+        /// Directory inside the image where the archives are added
+        #[clap(long, default_value = "/opt/ver")]
+        dest: String,
     },
     
-    /// Migrate from other Rust version managers (rustup)
-    Migrate {
-        /// Source to migrate from (rustup)
-        source: String,
-    },
-}
-
-#[derive(Debug, Subcommand)]
-enum PythonCommands {
-    /// List available Python versions
-    #[clap(alias = "ls")]
-    List {
-        /// Show only stable versions
+    /// Export the project's pinned toolchain versions for another tool
+    Export {
+        /// Output format (nix: a flake with a devShell pinning the same versions)
         #[clap(long)]
-        stable: bool,
+        format: String,
     },
     
-    /// Install a specific Python version
-    #[clap(alias = "i")]
-    Install {
-        /// Version to install (e.g., 3.12.0, latest)
-        version: String,
+    /// Print `NODE := /abs/path/node`-style variables for the resolved toolchains, for Makefiles and justfiles
+    ExportMake {
+        /// Quote the values so the output can be used in a justfile
+        #[clap(long)]
+        just: bool,
     },
     
-    /// Use a specific Python version
-    #[clap(alias = "u")]
-    Use {
-        /// Version to use (e.g., 3.12.0, latest)
-        version: String,
+    /// Show what changed between two versions: release dates, skipped releases, security fixes and size delta
+    Diff {
+        /// Version type (node, rust, python or go)
+        tool: String,
+        
+        /// First version
+        v1: String,
+        
+        /// Second version
+        v2: String,
     },
     
-    /// List installed Python versions
-    Installed,
-    
-    /// Remove a specific Python version
-    #[clap(alias = "rm")]
-    Remove {
-        /// Version to remove
-        version: String,
+    /// Summarize which versions were used recently, per project directory (local data only)
+    Report {
+        /// Number of days to include
+        #[clap(long, default_value = "30")]
+        days: u32,
+        
+        /// Version type (node, rust, python or go); all tools when omitted
+        #[clap(short, long)]
+        type_: Option<String>,
     },
     
-    /// Show current Python version
-    Current,
-    
-    /// Create an alias for a Python version
-    Alias {
-        /// Alias name
-        name: String,
+    /// Lock an installed version so it cannot be removed and is verified on use
+    Lock {
+        /// Tool (node, rust, python or go)
+        tool: String,
         
-        /// Version to alias
+        /// Version to lock
         version: String,
     },
     
-    /// List all Python aliases
-    Aliases,
-    
-    /// Set local Python version for current directory
-    Local {
-        /// Version to set locally
+    /// Unlock a previously locked version
+    Unlock {
+        /// Tool (node, rust, python or go)
+        tool: String,
+        
+        /// Version to unlock
         version: String,
     },
     
-    /// Execute a command with a specific Python version
-    Exec {
-        /// Version to use
-        version: String,
+    /// Check installed files against the checksums recorded at install or migration time
+    Verify {
+        /// Version to verify (all installed versions of the type when omitted)
+        #[clap(conflicts_with = "all")]
+        version: Option<String>,
         
-        /// Command and arguments to execute
-        #[clap(last = true)]
-        args: Vec<String>,
+        /// Version type (node, rust, python or go)
+        #[clap(short = 't', long = "type", default_value = "node")]
+        type_: String,
+        
+        /// Verify the installed versions of every tool
+        #[clap(long)]
+        all: bool,
+        
+        /// Re-download versions that fail verification from the configured mirrors
+        #[clap(long)]
+        repair: bool,
+        
+        /// Also write the JSON report to this file
+        #[clap(long)]
+        report: Option<PathBuf>,
+        
+        /// Install a scheduler entry that runs `ver verify --all --repair` and keeps the last report in verify-report.json
+        #[clap(long)]
+        schedule: bool,
+        
+        /// How often the scheduled run happens (daily or weekly)
+        #[clap(long, default_value = "daily")]
+        interval: String,
     },
     
-    /// Migrate from other Python version managers (pyenv)
-    Migrate {
-        /// Source to migrate from (pyenv)
-        source: String,
-    },
-}
-
-#[derive(Debug, Subcommand)]
-enum GoCommands {
-    /// List available Go versions
-    #[clap(alias = "ls")]
-    List {
-        /// Show only stable versions
-        #[clap(long)]
-        stable: bool,
+    /// Check for the system build tools and headers a tool needs (compiler, make, pkg-config, ...) and how to install them
+    Prereqs {
+        /// Tool (node, rust, python or go)
+        tool: String,
     },
     
-    /// Install a specific Go version
-    #[clap(alias = "i")]
-    Install {
-        /// Version to install (e.g., 1.22.0, latest)
-        version: String,
+    /// Subscribe to a release channel, e.g. `ver subscribe node lts` or `ver subscribe rust stable`
+    Subscribe {
+        /// Tool (node, rust, python or go)
+        tool: String,
+        
+        /// Channel: latest, stable, lts (Node.js) or a major version such as 20
+        channel: String,
+        
+        /// Alias to repoint to each new release installed from the channel
+        #[clap(long)]
+        alias: Option<String>,
     },
     
-    /// Use a specific Go version
-    #[clap(alias = "u")]
-    Use {
-        /// Version to use (e.g., 1.22.0, latest)
-        version: String,
+    /// Remove a release channel subscription
+    Unsubscribe {
+        /// Tool (node, rust, python or go)
+        tool: String,
+        
+        /// Channel to unsubscribe from
+        channel: String,
     },
     
-    /// List installed Go versions
-    Installed,
+    /// List release channel subscriptions
+    Subscriptions,
     
-    /// Remove a specific Go version
-    #[clap(alias = "rm")]
-    Remove {
-        /// Version to remove
-        version: String,
+    /// Install new releases
+    Upgrade {
+        /// Install the newest release of every subscribed channel
+        #[clap(long)]
+        subscribed: bool,
     },
     
-    /// Show current Go version
-    Current,
-    
-    /// Create an alias for a Go version
-    Alias {
-        /// Alias name
-        name: String,
+    /// Routine upkeep for cron/launchd: refresh indexes, upgrade subscriptions, prune and verify
+    Maintain {
+        /// Install a scheduler entry (crontab, launchd or Task Scheduler) that runs `ver maintain`
+        #[clap(long)]
+        install_schedule: bool,
         
-        /// Version to alias
-        version: String,
+        /// How often the scheduled run happens (daily or weekly)
+        #[clap(long, default_value = "daily")]
+        interval: String,
     },
     
-    /// List all Go aliases
-    Aliases,
+    /// Test and select download mirrors
+    #[clap(subcommand)]
+    Mirror(MirrorCommands),
     
-    /// Set local Go version for current directory
-    Local {
-        /// Version to set locally
-        version: String,
-    },
+    /// Share aliases, default versions, subscriptions and .ver.toml templates between machines through a git repository (experimental)
+    #[clap(subcommand)]
+    Sync(SyncCommands),
     
-    /// Execute a command with a specific Go version
-    Exec {
-        /// Version to use
-        version: String,
+    /// Print the shell completion script, or install it where the shell loads it (updated by self-update)
+    #[clap(alias = "completion", args_conflicts_with_subcommands = true)]
+    Completions {
+        #[command(subcommand)]
+        command: Option<CompletionCommands>,
         
-        /// Command and arguments to execute
-        #[clap(last = true)]
-        args: Vec<String>,
+        /// Shell (bash, zsh or fish); detected from $SHELL when omitted
+        shell: Option<String>,
+        
+        /// Write the script to the shell's completion directory instead of printing it
+        #[clap(long)]
+        install: bool,
+        
+        /// Regenerate completion scripts installed earlier with --install
+        #[clap(long, conflicts_with_all = ["shell", "install"])]
+        update: bool,
     },
     
-    /// Migrate from other Go version managers (gvm)
-    Migrate {
-        /// Source to migrate from (gvm)
-        source: String,
-    },
-}
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let mut manager = VersionManager::new()?;
+    /// View or change ver configuration
+    #[clap(subcommand)]
+    Config(ConfigCommands),
     
-    match cli.command {
-        Commands::List { lts, type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            let versions = manager.list_available_versions(lts, version_type).await?;
-            
-            // 添加版本类型标题
-            match version_type {
-                VersionType::Node => println!("{}", "Available Node.js Versions:".green().bold()),
-                VersionType::Rust => println!("{}", "Available Rust Versions:".yellow().bold()),
-                VersionType::Python => println!("{}", "Available Python Versions:".blue().bold()),
-                VersionType::Go => println!("{}", "Available Go Versions:".red().bold()),
+    /// Node.js specific commands
+    #[clap(subcommand)]
+    Node(NodeCommands),
+    
+    /// Rust version management commands (alternative syntax)
+    #[clap(subcommand)]
+    Rust(RustCommands),
+    
+    /// Python version management commands (alternative syntax)
+    #[clap(subcommand)]
+    Python(PythonCommands),
+    
+    /// Go version management commands (alternative syntax)
+    #[clap(subcommand)]
+    Go(GoCommands),
+}
+
+#[derive(Debug, Subcommand)]
+enum CacheCommands {
+    /// List cached archives and indexes with sizes and ages
+    #[clap(alias = "list")]
+    Ls,
+    
+    /// Delete cache entries by file name or version
+    Rm {
+        /// File names or versions to delete
+        #[clap(required = true)]
+        entries: Vec<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum GenerateCommands {
+    /// Print a curl | sh installer pinned to a ver release and its checksums
+    InstallScript {
+        /// Release to pin (defaults to the latest release)
+        #[clap(long)]
+        version: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ExplainCommands {
+    /// Show which PATH entries belong to ver or other version managers and which shadow ver
+    Path {
+        /// Print a corrected PATH with ver's directories first for your shell
+        #[clap(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SyncCommands {
+    /// Clone the repository; merges its state if another machine pushed one, otherwise pushes the local state
+    Init {
+        /// Git URL of the repository (an empty repository works)
+        url: String,
+    },
+    
+    /// Pull the repository, merge it with the local state and push the result
+    Push,
+    
+    /// Pull the repository and merge it into the local aliases, defaults, subscriptions and templates
+    Pull,
+}
+
+#[derive(Debug, Subcommand)]
+enum MirrorCommands {
+    /// Measure latency and availability of configured mirrors
+    Test {
+        /// Version type (node, rust, python or go); all tools when omitted
+        #[clap(short, long)]
+        type_: Option<String>,
+        
+        /// Use the fastest available mirror for the next 24 hours
+        #[clap(long)]
+        select: bool,
+    },
+    
+    /// Publish an archive's files to a delta source directory (serve it and set install.delta_url)
+    Delta {
+        /// Unmodified archive downloaded from the official mirror (its SHA-256 must match the published checksum)
+        archive: PathBuf,
+        
+        /// Version contained in the archive
+        version: String,
+        
+        /// Version type (node, rust, python or go)
+        #[clap(short, long, default_value = "node")]
+        type_: String,
+        
+        /// Platform suffix of the archive (defaults to this machine's, e.g. linux-x64)
+        #[clap(long)]
+        platform: Option<String>,
+        
+        /// Delta source directory to write the manifest and file contents to
+        #[clap(long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CompletionCommands {
+    /// Print candidate versions from local data and the cached remote index (no network)
+    Versions {
+        /// Version type (node, rust, python or go)
+        #[clap(short, long, default_value = "node")]
+        type_: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommands {
+    /// List all configured values
+    #[clap(alias = "ls")]
+    List,
+    
+    /// Show a configuration value
+    Get {
+        /// Configuration key (e.g., go.proxy)
+        key: String,
+    },
+    
+    /// Set a configuration value
+    Set {
+        /// Configuration key (e.g., go.proxy, manage, mirrors.node, env.python.PIP_INDEX_URL, on_install.node, version_files..nvmrc)
+        key: String,
+        
+        /// Value to set (comma-separated list for mirrors.<tool> and manage, a URL for install.delta_url, `;`-separated commands for on_install.<tool>, a tool id or `lines` for version_files.<file>)
+        value: String,
+    },
+    
+    /// Remove a configuration value
+    Unset {
+        /// Configuration key
+        key: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum NodeCommands {
+    #[clap(flatten)]
+    Tool(ToolCommands),
+    
+    /// Manage corepack (yarn/pnpm) shims for a Node.js version
+    #[clap(subcommand)]
+    Corepack(CorepackCommands),
+    
+    /// Download the headers node-gyp needs to build native addons for a Node.js version
+    Headers {
+        /// Node.js version or alias (defaults to the version for the current directory)
+        version: Option<String>,
+    },
+    
+    /// Show the native addon ABI (NODE_MODULE_VERSION) of Node.js versions
+    Abi {
+        /// Node.js version or alias (defaults to all installed versions)
+        version: Option<String>,
+        
+        /// List the Node.js versions that have this ABI, e.g. `--find 115`
+        #[clap(long, value_name = "ABI", conflicts_with = "version")]
+        find: Option<u32>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CorepackCommands {
+    /// Install corepack shims into a Node.js version
+    Enable {
+        /// Node.js version or alias (defaults to the version for the current directory)
+        version: Option<String>,
+    },
+    
+    /// Remove corepack shims from a Node.js version
+    Disable {
+        /// Node.js version or alias (defaults to the version for the current directory)
+        version: Option<String>,
+    },
+}
+
+/// 各工具共用的子命令
+///
+/// `ver <命令> -t <工具>` 和 `ver <工具> <命令>` 都解析到这里并由 `run_tool_command` 处理，
+/// 新增工具只需在 `VersionType` 中添加一项。
+#[derive(Debug, Subcommand)]
+enum ToolCommands {
+    /// List available versions
+    #[clap(alias = "ls")]
+    List(ListArgs),
+    
+    /// Install a specific version
+    #[clap(alias = "i")]
+    Install(InstallArgs),
+    
+    /// Use a specific version
+    #[clap(alias = "u")]
+    Use(UseArgs),
+    
+    /// List installed versions
+    Installed,
+    
+    /// Remove a specific version
+    #[clap(alias = "rm")]
+    Remove(RemoveArgs),
+    
+    /// Show current version
+    Current,
+    
+    /// Create an alias for a version
+    Alias(AliasArgs),
+    
+    /// List all aliases
+    Aliases,
+    
+    /// Show release date, support status, security advisories and release notes of a version
+    Info(InfoArgs),
+    
+    /// Set local version for current directory
+    Local(LocalArgs),
+    
+    /// Execute a command with a specific version
+    Exec(ExecArgs),
+    
+    /// Show the PATH entries and environment variables ver injects when running this tool
+    Env(EnvArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct ListArgs {
+    /// Show only LTS (Node.js) or stable releases
+    #[clap(long, alias = "stable")]
+    lts: bool,
+    
+    /// Show only versions that are installed
+    #[clap(long, conflicts_with = "not_installed")]
+    installed_only: bool,
+    
+    /// Show only versions that are not installed yet
+    #[clap(long)]
+    not_installed: bool,
+    
+    /// Show only this page of results (starting at 1) and stop reading the index there
+    #[clap(long)]
+    page: Option<usize>,
+    
+    /// Versions per page for --page
+    #[clap(long, default_value_t = 50, requires = "page")]
+    per_page: usize,
+    
+    /// Also show end-of-life dates, security advisories and release notes (from endoflife.date, GitHub and the configured security feed)
+    #[clap(short, long)]
+    long: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct InstallArgs {
+    /// Version to install (e.g., 16.13.0, 1.85.0, latest, lts)
+    version: String,
+    
+    /// Install a build for another architecture (x64, arm64, x86, arm) next to the native one
+    #[clap(long)]
+    arch: Option<String>,
+    
+    /// Run the install in a background process and print its operation ID (poll with `ver status --operations`)
+    #[clap(long)]
+    detach: bool,
+    
+    /// Operation ID assigned by `--detach`
+    #[clap(long, hide = true)]
+    operation_id: Option<String>,
+}
+
+#[derive(Debug, clap::Args)]
+struct UseArgs {
+    /// Version or alias to use (e.g., 16.13.0, 1.85.0, latest, lts)
+    version: String,
+    
+    /// Use the build installed with `install --arch`
+    #[clap(long)]
+    arch: Option<String>,
+    
+    /// Only switch this terminal (`eval "$(ver use --session 20)"`), same as `ver shell`
+    #[clap(long)]
+    session: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct RemoveArgs {
+    /// Version to remove
+    version: String,
+    
+    /// Remove even if aliases, the global default or project version files still reference it
+    #[clap(long)]
+    force: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct AliasArgs {
+    /// Alias name
+    name: String,
+    
+    /// Version to alias
+    version: String,
+    
+    /// Define a machine-level alias shared by all users (usually requires root)
+    #[clap(long)]
+    system: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct InfoArgs {
+    /// Version to describe (e.g., 20.11.1, 1.85.0)
+    version: String,
+}
+
+#[derive(Debug, clap::Args)]
+struct LocalArgs {
+    /// Version to set locally
+    version: String,
+}
+
+#[derive(Debug, clap::Args)]
+struct ExecArgs {
+    /// Version to use
+    version: String,
+    
+    /// Working directory for the command
+    #[clap(long)]
+    cwd: Option<PathBuf>,
+    
+    /// Allocate a pseudo-terminal for interactive commands (REPLs)
+    #[clap(long)]
+    tty: bool,
+    
+    /// Install a missing version without progress output, printing one `installed <tool> <version> <dir>` line to stderr
+    #[clap(long)]
+    quiet_install: bool,
+    
+    /// Fail unless the version matches the project's pin; other tools come only from project pins, never from global defaults or the system
+    #[clap(long)]
+    hermetic: bool,
+    
+    /// Run the command inside a docker/podman container of this image with the toolchain mounted
+    #[clap(long, value_name = "IMAGE", conflicts_with = "hermetic")]
+    container: Option<String>,
+    
+    /// Run a version built for another architecture (`ver install --arch`) through qemu-user
+    #[clap(long, conflicts_with = "container")]
+    emulate: bool,
+    
+    /// Command and arguments to execute
+    #[clap(last = true)]
+    args: Vec<String>,
+}
+
+#[derive(Debug, clap::Args)]
+struct EnvArgs {
+    /// Version to inspect (defaults to the version resolved for the current directory)
+    version: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum RustCommands {
+    #[clap(flatten)]
+    Tool(ToolCommands),
+    
+    /// Migrate from other Rust version managers (rustup)
+    Migrate {
+        /// Source to migrate from (rustup)
+        source: String,
+        
+        /// Skip files or directories matching this pattern (`*` and `?` wildcards, repeatable), e.g. `share/doc` or `*.md`
+        #[clap(long)]
+        exclude: Vec<String>,
+        
+        /// Only migrate these versions (comma-separated), e.g. `--only 18.19.0,20.11.1`
+        #[clap(long, value_delimiter = ',')]
+        only: Vec<String>,
+        
+        /// List what would be migrated without copying anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+    
+    /// Show disk usage per toolchain by component and optionally remove parts of every toolchain
+    Du {
+        /// Remove these parts (comma-separated): docs, installer, src, targets (non-host std), registry (extracted crate sources)
+        #[clap(long, value_delimiter = ',')]
+        remove: Vec<String>,
+        
+        /// Only report what would be removed
+        #[clap(long)]
+        dry_run: bool,
+        
+        /// Remove without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+    },
+    
+    /// Pin a toolchain to a directory without adding files to it, like `rustup override`
+    #[clap(subcommand)]
+    Override(RustOverrideCommands),
+    
+    /// Run a Rust tool honoring `+toolchain` and RUSTUP_TOOLCHAIN (used by the generated shims)
+    #[clap(hide = true)]
+    Proxy {
+        /// Tool to run, e.g. cargo or rustc
+        binary: String,
+        
+        /// Arguments passed to the tool
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RustOverrideCommands {
+    /// Use a toolchain in a directory and its subdirectories, ahead of rust-toolchain files
+    Set {
+        /// Installed toolchain version or alias
+        toolchain: String,
+        
+        /// Directory to override (defaults to the current directory)
+        #[clap(long)]
+        path: Option<PathBuf>,
+    },
+    
+    /// Remove the override for a directory
+    Unset {
+        /// Directory to remove the override for (defaults to the current directory)
+        #[clap(long)]
+        path: Option<PathBuf>,
+    },
+    
+    /// List directory overrides
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+enum PythonCommands {
+    #[clap(flatten)]
+    Tool(ToolCommands),
+    
+    /// Migrate from other Python version managers (pyenv)
+    Migrate {
+        /// Source to migrate from (pyenv)
+        source: String,
+        
+        /// Skip files or directories matching this pattern (`*` and `?` wildcards, repeatable), e.g. `share/doc` or `*.md`
+        #[clap(long)]
+        exclude: Vec<String>,
+        
+        /// Only migrate these versions (comma-separated), e.g. `--only 18.19.0,20.11.1`
+        #[clap(long, value_delimiter = ',')]
+        only: Vec<String>,
+        
+        /// List what would be migrated without copying anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum GoCommands {
+    #[clap(flatten)]
+    Tool(ToolCommands),
+    
+    /// Run a command with GOOS/GOARCH set for cross-compiling, e.g. `ver go cross --goos linux --goarch arm64 -- go build ./...`
+    Cross {
+        /// Target operating system (GOOS), e.g. linux, darwin, windows
+        #[clap(long)]
+        goos: String,
+        
+        /// Target architecture (GOARCH), e.g. amd64, arm64
+        #[clap(long)]
+        goarch: String,
+        
+        /// Keep cgo enabled (CGO_ENABLED=1); by default cgo is disabled when the target differs from the host
+        #[clap(long)]
+        cgo: bool,
+        
+        /// C compiler for the target when cgo is enabled
+        #[clap(long, requires = "cgo")]
+        cc: Option<String>,
+        
+        /// Go version to use (defaults to the version for the current directory)
+        #[clap(long)]
+        go_version: Option<String>,
+        
+        /// Command and arguments to execute
+        #[clap(last = true, required = true)]
+        args: Vec<String>,
+    },
+    
+    /// Migrate from other Go version managers (gvm)
+    Migrate {
+        /// Source to migrate from (gvm)
+        source: String,
+        
+        /// Skip files or directories matching this pattern (`*` and `?` wildcards, repeatable), e.g. `share/doc` or `*.md`
+        #[clap(long)]
+        exclude: Vec<String>,
+        
+        /// Only migrate these versions (comma-separated), e.g. `--only 18.19.0,20.11.1`
+        #[clap(long, value_delimiter = ',')]
+        only: Vec<String>,
+        
+        /// List what would be migrated without copying anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let json = cli.json;
+    let options = manager_options(&cli).ok();
+    if cli.plain {
+        colored::control::set_override(false);
+        format::set_plain(true);
+    }
+    
+    if let Err(err) = run(cli).await {
+        let mut suggestions = error_suggestions(&err);
+        if let Some(version_error) = err.downcast_ref::<VersionError>()
+            && let Some(options) = options
+            && let Ok(manager) = VersionManager::with_options(options)
+        {
+            suggestions.extend(manager.did_you_mean(version_error));
+        }
+        
+        if json {
+            print_json_error(&err, &suggestions);
+        } else {
+            eprintln!("Error: {:?}", err);
+            for suggestion in suggestions {
+                eprintln!("{} {}", "hint:".yellow().bold(), suggestion);
+            }
+        }
+        // 按 shell 惯例，被 Ctrl-C 取消时以 130 退出
+        if matches!(err.downcast_ref::<VersionError>(), Some(VersionError::Cancelled)) {
+            std::process::exit(130);
+        }
+        std::process::exit(1);
+    }
+}
+
+/// 根据 --root 和 --endpoint 构造版本管理器选项
+fn manager_options(cli: &Cli) -> Result<ManagerOptions> {
+    let mut options = ManagerOptions { base_dir: cli.root.clone(), ..ManagerOptions::default() };
+    for endpoint in &cli.endpoints {
+        let Some((source, url)) = endpoint.split_once('=') else {
+            return Err(anyhow::anyhow!("地址的格式应为 <tool>=<url>: {}", endpoint));
+        };
+        let url = url.to_string();
+        match source {
+            "delta" => options.delta_endpoint = Some(url),
+            "metadata" => options.metadata_endpoint = Some(url),
+            "release" => options.release_endpoint = Some(url),
+            tool => options.endpoints.push((parse_version_type(tool)?, url)),
+        }
+    }
+    Ok(options)
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let mut manager = VersionManager::with_options(manager_options(&cli)?)?;
+    if let Some(progress) = &cli.progress {
+        manager.set_progress_mode(ProgressMode::from_name(progress)?);
+    } else if cli.plain {
+        manager.set_progress_mode(ProgressMode::Plain);
+    }
+    manager.set_admin(cli.admin);
+    if !cli.allow_root && !manager.allows_root() {
+        match manager.ownership_mismatch() {
+            Some(OwnershipMismatch::RootInUserDir { owner }) => eprintln!(
+                "{} running as root with {} owned by uid {}; files created now will be owned by root and break later runs as that user. \
+                 Use `sudo -H`, pass --allow-root or set `ver config set allow_root true` to acknowledge, and `ver doctor --fix-ownership` to repair.",
+                "Warning:".yellow().bold(), manager.base_dir().display(), owner
+            ),
+            Some(OwnershipMismatch::UserInRootDir) => eprintln!(
+                "{} {} is owned by root (ver was probably run with sudo); run `sudo ver doctor --fix-ownership` to give it back to you.",
+                "Warning:".yellow().bold(), manager.base_dir().display()
+            ),
+            None => {}
+        }
+    }
+    
+    match cli.command {
+        Commands::List { args, type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_tool_command(&mut manager, version_type, ToolCommands::List(args), cli.json).await?;
+        }
+        Commands::Install { args, type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_tool_command(&mut manager, version_type, ToolCommands::Install(args), cli.json).await?;
+        }
+        Commands::Use { args, type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_tool_command(&mut manager, version_type, ToolCommands::Use(args), cli.json).await?;
+        }
+        Commands::Installed { type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_local_command(Local::new(&manager), version_type, LocalCommand::Installed, cli.json)?;
+        }
+        Commands::Remove { args, type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_tool_command(&mut manager, version_type, ToolCommands::Remove(args), cli.json).await?;
+        }
+        Commands::Clone { version, name, type_ } => {
+            let version_type = parse_version_type(&type_)?;
+            let (cloned, total) = manager.clone_version(&version, &name, version_type)?;
+            println!("Cloned {} {} as {}", version_type, version, name.bold());
+            if cloned > 0 {
+                println!("{} of {} files share storage with {} (copy-on-write)", cloned, total, version);
+            } else {
+                println!("The filesystem does not support copy-on-write clones; {} files were copied", total);
+            }
+        }
+        Commands::ImportUrl { version, url, sha256, type_ } => {
+            let version_type = parse_version_type(&type_)?;
+            let version_dir = manager.import_url(&version, version_type, &url, &sha256).await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                    "tool": version_type.id(),
+                    "version": version,
+                    "dir": version_dir,
+                    "source": url,
+                }))?);
+            } else {
+                println!("Run `ver use {} -t {}` to switch to it", version, version_type.id());
+            }
+        }
+        Commands::Current { type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_local_command(Local::new(&manager), version_type, LocalCommand::Current, cli.json)?;
+        }
+        Commands::Alias { args, type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_tool_command(&mut manager, version_type, ToolCommands::Alias(args), cli.json).await?;
+        }
+        Commands::Aliases { type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_local_command(Local::new(&manager), version_type, LocalCommand::Aliases, cli.json)?;
+        }
+        Commands::Info { args, type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_tool_command(&mut manager, version_type, ToolCommands::Info(args), cli.json).await?;
+        }
+        Commands::Local { args, type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            run_tool_command(&mut manager, version_type, ToolCommands::Local(args), cli.json).await?;
+        }
+        Commands::Exec { args, type_ } => {
+            // 工具类型按命令的工作目录推断，而不是调用者所在的目录
+            let dir = match &args.cwd {
+                Some(cwd) => cwd.clone(),
+                None => std::env::current_dir()?,
+            };
+            let version_type = tool_type_in(&manager, type_.as_deref(), &dir, cli.json)?;
+            run_tool_command(&mut manager, version_type, ToolCommands::Exec(args), cli.json).await?;
+        }
+        Commands::New { list: true, .. } => {
+            for template in template::TEMPLATES {
+                let tools: Vec<&str> = template.tools.iter().map(|tool| tool.id()).collect();
+                println!("{:<10} {} ({})", template.name.bold(), template.description, tools.join(", "));
+            }
+            for (name, path) in template::user_templates(&manager.templates_dir()) {
+                println!("{:<10} {} ({})", name.bold(), version_file::VER_TOML, path.display());
+            }
+        }
+        Commands::New { template, dir, latest, list: false } => {
+            let name = template.as_deref().unwrap_or_default();
+            let template = match template::find(name) {
+                Ok(template) => template,
+                Err(err) => {
+                    // 不是内置模板时查找用户保存的 .ver.toml 模板
+                    let Some(source) = template::user_template(&manager.templates_dir(), name) else {
+                        return Err(err);
+                    };
+                    std::fs::create_dir_all(&dir)?;
+                    let file = dir.join(version_file::VER_TOML);
+                    if file.exists() {
+                        println!("  {} {} (already exists)", "skipped".yellow(), version_file::VER_TOML);
+                    } else {
+                        std::fs::copy(&source, &file)?;
+                        println!("  {} {}", "created".green(), version_file::VER_TOML);
+                    }
+                    return Ok(());
+                }
+            };
+            // 没有全局版本的工具同时查询最新版本
+            let lookups = template.tools.iter().map(|&version_type| {
+                let current = manager.get_current_version(version_type).filter(|_| !latest);
+                let manager = &manager;
+                async move {
+                    let version = match current {
+                        Some(version) => version,
+                        None => manager.list_available_versions(true, version_type).await?
+                            .first()
+                            .map(|release| release.version.trim_start_matches('v').to_string())
+                            .ok_or_else(|| anyhow::anyhow!("找不到最新的 {} 版本", version_type))?,
+                    };
+                    anyhow::Ok((version_type, version))
+                }
+            });
+            let pins = futures_util::future::join_all(lookups).await.into_iter().collect::<Result<Vec<_>>>()?;
+            for (version_type, version) in &pins {
+                println!("Pinning {} {}", version_type.id(), version.bold());
+            }
+            
+            std::fs::create_dir_all(&dir)?;
+            let dir_name = std::fs::canonicalize(&dir)?.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            for (path, content) in template::files(&template::package_name(&dir_name), &pins) {
+                let file = dir.join(&path);
+                if file.exists() {
+                    println!("  {} {} (already exists)", "skipped".yellow(), path);
+                    continue;
+                }
+                if let Some(parent) = file.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&file, content)?;
+                println!("  {} {}", "created".green(), path);
+            }
+            
+            let missing: Vec<String> = pins.iter()
+                .filter(|(version_type, version)| !manager.is_installed(version, *version_type))
+                .map(|(version_type, version)| format!("ver {} install {}", version_type.id(), version))
+                .collect();
+            if !missing.is_empty() {
+                println!("Install the pinned versions with: {}", missing.join(" && "));
+            }
+        }
+        Commands::Pin { tool: Some(tool), version: Some(version), dir, .. } => {
+            let version_type = parse_version_type(&tool)?;
+            let path = version_file::write_pins(&dir, &[(version_type, version.clone())])?;
+            println!("Pinned {} {} in {}", version_type.id(), version.bold(), path.display());
+        }
+        Commands::Pin { dir, yes, dry_run, .. } => {
+            let proposals = manager.detect_pins(&dir).await?;
+            if cli.json {
+                let proposals: Vec<_> = proposals.iter().map(|proposal| serde_json::json!({
+                    "tool": proposal.tool.id(),
+                    "version": proposal.version,
+                    "installed": proposal.installed,
+                    "conflict": proposal.conflict,
+                    "evidence": proposal.evidence.iter()
+                        .map(|evidence| serde_json::json!({ "source": evidence.source, "range": evidence.range }))
+                        .collect::<Vec<_>>(),
+                })).collect();
+                println!("{}", serde_json::to_string_pretty(&proposals)?);
+                return Ok(());
+            }
+            if proposals.is_empty() {
+                println!("No version hints found in {}", dir.display());
+                return Ok(());
+            }
+            
+            let mut pins = Vec::new();
+            for proposal in &proposals {
+                match &proposal.version {
+                    Some(version) => {
+                        let mut notes = Vec::new();
+                        if proposal.installed {
+                            notes.push("installed");
+                        }
+                        if proposal.conflict {
+                            notes.push("hints disagree, following the first");
+                        }
+                        let notes = if notes.is_empty() { String::new() } else { format!(" ({})", notes.join(", ")).dimmed().to_string() };
+                        println!("{} {}{}", format!("{:<7}", proposal.tool.id()).bold(), version.green(), notes);
+                        pins.push((proposal.tool, version.clone()));
+                    }
+                    None => println!("{} {}", format!("{:<7}", proposal.tool.id()).bold(), "no release matches the hints".yellow()),
+                }
+                for evidence in &proposal.evidence {
+                    println!("    {:<44} {}", evidence.source, evidence.range.dimmed());
+                }
+            }
+            
+            let file = dir.join(version_file::VER_TOML);
+            if dry_run || pins.is_empty() || !confirm(&format!("Write {} pin(s) to {}?", pins.len(), file.display()), yes)? {
+                return Ok(());
+            }
+            version_file::write_pins(&dir, &pins)?;
+            println!("Updated {}", file.display());
+        }
+        Commands::Clean => {
+            manager.clean()?;
+            println!("Cleaned cache and unnecessary files");
+        }
+        Commands::Cache(cache_command) => {
+            match cache_command {
+                CacheCommands::Ls => {
+                    let entries = manager.list_cache()?;
+                    if entries.is_empty() {
+                        println!("Cache is empty");
+                        return Ok(());
+                    }
+                    
+                    let mut total = 0;
+                    for entry in &entries {
+                        total += entry.size;
+                        println!("{:<40} {:<8} {:>10}  {:<12} {}",
+                            entry.name,
+                            entry.kind,
+                            format::size(entry.size),
+                            entry.modified.map(format::age).unwrap_or_else(|| "-".to_string()),
+                            entry.version.as_deref().unwrap_or("-"));
+                    }
+                    println!("{} entries, {} total", entries.len(), format::size(total).bold());
+                }
+                CacheCommands::Rm { entries } => {
+                    let removed = manager.remove_cache_entries(&entries)?;
+                    if removed.is_empty() {
+                        println!("No matching cache entries");
+                    } else {
+                        let freed: u64 = removed.iter().map(|entry| entry.size).sum();
+                        for entry in &removed {
+                            println!("Removed {}", entry.name);
+                        }
+                        println!("Freed {}", format::size(freed).bold());
+                    }
+                }
+            }
+        }
+        Commands::SelfUpdate => {
+            match manager.self_update().await? {
+                Some(version) => println!("Updated ver from {} to {}", env!("CARGO_PKG_VERSION"), version.bold()),
+                None => println!("ver {} is already the latest version", env!("CARGO_PKG_VERSION")),
+            }
+        }
+        Commands::Generate(GenerateCommands::InstallScript { version }) => {
+            let manifest = manager.release_manifest(version.as_deref()).await?;
+            print!("{}", release::install_script(&manifest));
+        }
+        Commands::Gc { dry_run, days, yes } => {
+            let candidates = manager.gc_report(days)?;
+            if candidates.is_empty() {
+                println!("No versions installed");
+                return Ok(());
+            }
+            
+            for candidate in &candidates {
+                let last_used = match &candidate.last_used {
+                    Some(time) => format!("last used {}", format::date(time)),
+                    None => "never used".to_string(),
+                };
+                let status = if candidate.reclaimable {
+                    "reclaimable".green().to_string()
+                } else if candidate.references.is_empty() && candidate.last_used.is_none() && candidate.installed_at.is_none() {
+                    "usage unknown (no install receipt or usage record), kept".dimmed().to_string()
+                } else if candidate.references.is_empty() {
+                    format!("used or installed within {} days", days).dimmed().to_string()
+                } else {
+                    format!("referenced by: {}", candidate.references.join(", ")).dimmed().to_string()
+                };
+                println!("{:<8} {:<16} {:>10}  {:<22} {}",
+                    candidate.version_type.id(), candidate.version, format::size(candidate.size), last_used, status);
+            }
+            
+            let reclaimable: Vec<_> = candidates.iter().filter(|candidate| candidate.reclaimable).collect();
+            let total: u64 = reclaimable.iter().map(|candidate| candidate.size).sum();
+            println!("{}", format!("Reclaimable: {} version(s), {}", reclaimable.len(), format::size(total)).bold());
+            println!("{}", "Project version files are only checked in directories recorded in the usage log.".dimmed());
+            if dry_run || reclaimable.is_empty() {
+                return Ok(());
+            }
+            
+            if !yes {
+                if !std::io::stdin().is_terminal() {
+                    return Err(anyhow::anyhow!("非交互模式下需要 --yes 才能删除版本"));
+                }
+                let answer = output::prompt(&format!("Remove {} version(s)? [y/N] ", reclaimable.len()))?;
+                if !matches!(answer.as_str(), "y" | "Y" | "yes") {
+                    return Ok(());
+                }
+            }
+            for candidate in reclaimable {
+                manager.remove_version(&candidate.version, candidate.version_type, false)?;
+            }
+        }
+        Commands::Setup { yes } => {
+            println!("{}", "Shell integration".bold());
+            let shells = manager.detect_shells()?;
+            if shells.is_empty() {
+                println!("  No supported shell (bash, zsh, fish) detected");
+            }
+            for shell in &shells {
+                if manager.shell_hook_installed(shell) {
+                    println!("  {} ({}): already configured", shell.shell, shell.file.display());
+                } else if confirm(&format!("  Add ver to {} ({})?", shell.shell, shell.file.display()), yes)? {
+                    manager.install_shell_hook(shell)?;
+                    println!("  Updated {}", shell.file.display());
+                }
+            }
+            
+            println!("{}", "Shell completions".bold());
+            for shell in shells.iter().filter_map(|shell| completions::Shell::from_name(shell.shell).ok()) {
+                let path = shell.install_path()?;
+                if path.exists() {
+                    println!("  {} ({}): already installed", shell.name(), path.display());
+                } else if confirm(&format!("  Install {} completions into {}?", shell.name(), path.display()), yes)? {
+                    completions::install(shell, &mut Cli::command())?;
+                    println!("  Installed {}", path.display());
+                    if shell == completions::Shell::Zsh && !completions::zsh_fpath_configured() {
+                        println!("  Add `fpath=(~/.zfunc $fpath)` to ~/.zshrc before `compinit` so zsh finds it");
+                    }
+                }
+            }
+            
+            println!("{}", "Other version managers".bold());
+            let managers = manager.detect_version_managers();
+            if managers.is_empty() {
+                println!("  None detected");
+            }
+            for detected in &managers {
+                let question = format!("  Import {} {} version(s) from {} ({})?",
+                    detected.versions, detected.version_type, detected.name, detected.dir.display());
+                if confirm(&question, yes)? {
+                    let count = manager.import_from_manager(detected.name).await?;
+                    println!("  Imported {} version(s) from {}", count, detected.name);
+                }
+            }
+            
+            println!("{}", "Project versions".bold());
+            let cwd = std::env::current_dir()?;
+            let mut pinned = 0;
+            for version_type in VersionType::all() {
+                let Some((version, file)) = manager.local_version_file(&cwd, version_type)? else {
+                    continue;
+                };
+                pinned += 1;
+                if manager.is_installed(&version, version_type) {
+                    println!("  {} {} ({}): installed", version_type.id(), version, file.display());
+                } else if confirm(&format!("  Install {} {} pinned by {}?", version_type, version, file.display()), yes)? {
+                    manager.install_version(&version, version_type).await?;
+                }
+            }
+            if pinned == 0 {
+                println!("  No version files found for {}", cwd.display());
+            }
+            
+            println!("Setup complete. Open a new shell to start using ver.");
+        }
+        Commands::Adopt { dry_run } => {
+            let toolchains = manager.detect_system_toolchains();
+            if toolchains.is_empty() {
+                println!("No system-installed Node.js, Python or Go found");
+                return Ok(());
+            }
+            
+            for toolchain in &toolchains {
+                let label = format!("{} {} ({}, {})", toolchain.version_type, toolchain.version.bold(), toolchain.flavor, toolchain.executable.display());
+                if dry_run {
+                    println!("Would adopt {}", label);
+                    continue;
+                }
+                match manager.adopt_system_toolchain(toolchain)? {
+                    Some(name) => println!("Adopted {} as {}", label, name.bold()),
+                    None => println!("Already adopted {}", label),
+                }
+            }
+            if !dry_run {
+                println!("Use them with `ver use system-<version>` or the `system` alias, e.g. `ver use system`");
+            }
+        }
+        Commands::Migrate { source, type_, exclude, only, dry_run } => {
+            let version_type = parse_version_type(&type_)?;
+            let count = manager.migrate_from(&source, version_type, MigrateOptions { exclude: &exclude, only: &only, dry_run }).await?;
+            if dry_run {
+                println!("Would migrate {} versions from {}", count, source);
+            } else {
+                println!("Migrated {} versions from {}", count, source);
+            }
+        }
+        Commands::Which { type_, cwd, explain } => {
+            let version_type = parse_version_type(&type_)?;
+            let local = Local::new(&manager);
+            let cwd = match cwd {
+                Some(cwd) => cwd,
+                None => std::env::current_dir()?,
+            };
+            
+            let resolved = if explain {
+                let mut trace = Vec::new();
+                let resolved = local.resolve_version_traced(version_type, &cwd, &mut trace)?;
+                for (i, step) in trace.iter().enumerate() {
+                    println!("{}. {}", i + 1, step);
+                }
+                resolved
+            } else {
+                local.resolve_version(version_type, &cwd)?
+            };
+            let Some((version, _)) = resolved else {
+                return Err(VersionError::NoVersionConfigured(version_type).into());
+            };
+            println!("{}", local.executable_path(&version, version_type).display());
+        }
+        Commands::Doctor { fix, fix_ownership } => {
+            let checks = manager.doctor(&std::env::var_os("PATH").unwrap_or_default())?;
+            let removed = if fix { manager.remove_stale_links(&checks)? } else { Vec::new() };
+            let ownership = manager.check_ownership()?;
+            let repaired = if fix_ownership { manager.repair_ownership(&ownership)? } else { 0 };
+            if cli.json {
+                let tools: Vec<_> = checks.iter().map(|check| serde_json::json!({
+                    "tool": check.version_type.id(),
+                    "managed": check.managed,
+                    "resolved": check.resolved.as_ref().map(|(path, _)| path),
+                    "owner": check.resolved.as_ref().map(|(_, owner)| match owner {
+                        PathOwner::Ver => "ver",
+                        PathOwner::Manager(name) => name,
+                        PathOwner::Other => "other",
+                    }),
+                    "problems": check.problems,
+                })).collect();
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                    "tools": tools,
+                    "removed": removed,
+                    "ownership": {
+                        "uid": ownership.uid,
+                        "gid": ownership.gid,
+                        "foreign": ownership.foreign,
+                        "repaired": repaired,
+                    },
+                }))?);
+                return Ok(());
+            }
+            
+            for check in &checks {
+                let owner = match &check.resolved {
+                    Some((path, PathOwner::Ver)) => format!("ver ({})", path.display()),
+                    Some((path, PathOwner::Manager(name))) => format!("{} ({})", name, path.display()),
+                    Some((path, PathOwner::Other)) => path.display().to_string(),
+                    None => "not found on PATH".to_string(),
+                };
+                let role = if check.managed { "managed by ver" } else { "left to other managers" };
+                let status = if check.problems.is_empty() { "ok".green() } else { "problem".red() };
+                println!("{:<8} {:<8} {:<24} {}", check.version_type.id(), status, role, owner.dimmed());
+                for problem in &check.problems {
+                    println!("    {}", problem);
+                }
+            }
+            for link in &removed {
+                println!("Removed {}", link.display());
+            }
+            if !fix && checks.iter().any(|check| !check.stale_links.is_empty()) {
+                println!("Run `ver doctor --fix` to remove ver's links to tools it does not manage");
+            }
+            if repaired > 0 {
+                println!("Changed owner of {} files to {}:{}", repaired, ownership.uid, ownership.gid);
+            } else if !ownership.foreign.is_empty() {
+                println!("{} {} files in {} are not owned by uid {}:", "Warning:".yellow().bold(),
+                    ownership.foreign.len(), manager.base_dir().display(), ownership.uid);
+                for path in ownership.foreign.iter().take(10) {
+                    println!("    {}", path.display());
+                }
+                if ownership.foreign.len() > 10 {
+                    println!("    ... and {} more", ownership.foreign.len() - 10);
+                }
+                println!("Run `sudo ver doctor --fix-ownership` to repair them");
+            }
+        }
+        Commands::Explain(ExplainCommands::Path { fix }) => {
+            let path = std::env::var_os("PATH").unwrap_or_default();
+            let entries = manager.audit_path(&path);
+            if cli.json {
+                let entries: Vec<_> = entries.iter().map(|entry| serde_json::json!({
+                    "dir": entry.dir,
+                    "owner": match entry.owner {
+                        PathOwner::Ver => "ver",
+                        PathOwner::Manager(name) => name,
+                        PathOwner::Other => "other",
+                    },
+                    "shadows": entry.shadows,
+                })).collect();
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+                return Ok(());
+            }
+            
+            for (i, entry) in entries.iter().enumerate() {
+                let owner = match entry.owner {
+                    PathOwner::Ver => format!(" {}", "[ver]".green().bold()),
+                    PathOwner::Manager(name) => format!(" {}", format!("[{}]", name).yellow()),
+                    PathOwner::Other => String::new(),
+                };
+                println!("{:>3}. {}{}", i + 1, entry.dir.display(), owner);
+                if !entry.shadows.is_empty() {
+                    println!("     {} {}", "shadows ver:".red(), entry.shadows.join(", "));
+                }
+            }
+            
+            let shadowed = entries.iter().any(|entry| !entry.shadows.is_empty());
+            if !entries.iter().any(|entry| entry.dir == manager.bin_dir()) {
+                println!("{} {} is not on PATH; run `ver setup`", "Warning:".yellow().bold(), manager.bin_dir().display());
+            } else if !shadowed {
+                println!("{}", "Nothing shadows ver's commands".green());
+            }
+            
+            if fix {
+                let dirs = manager.fixed_path(&entries);
+                let shell = std::env::var("SHELL").unwrap_or_default();
+                println!();
+                println!("Corrected PATH for {}:", if shell.ends_with("fish") { "fish" } else { "sh/bash/zsh" });
+                if shell.ends_with("fish") {
+                    let dirs: Vec<String> = dirs.iter().map(|dir| format!("\"{}\"", dir.display())).collect();
+                    println!("set -gx PATH {}", dirs.join(" "));
+                } else {
+                    println!("export PATH=\"{}\"", std::env::join_paths(&dirs)?.to_string_lossy());
+                }
+                println!("{}", "To make this permanent, move the ver lines to the end of your shell config so they run after other version managers".dimmed());
+            } else if shadowed {
+                println!("Run `ver explain path --fix` for a corrected ordering");
+            }
+        }
+        Commands::Status { operations: true, .. } => {
+            let operations = manager.list_operations()?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&operations)?);
+            } else if operations.is_empty() {
+                println!("No operations recorded");
+            } else {
+                for operation in operations {
+                    let state = match operation.state.as_str() {
+                        "succeeded" => operation.state.green(),
+                        "failed" => operation.state.red(),
+                        "cancelled" => operation.state.yellow(),
+                        _ => operation.state.cyan(),
+                    };
+                    let progress = if let Some(percent) = (operation.downloaded_bytes * 100).checked_div(operation.total_bytes) {
+                        format!("{}%", percent)
+                    } else if operation.downloaded_bytes > 0 {
+                        format::size(operation.downloaded_bytes)
+                    } else {
+                        String::new()
+                    };
+                    let progress = match &operation.phase {
+                        Some(phase) if !operation.is_finished() => format!("{} {}", phase, progress).trim_end().to_string(),
+                        Some(phase) if operation.state == "failed" => format!("during {}", phase),
+                        _ => progress,
+                    };
+                    println!("{:<28} {:<10} {} {} {} {} {}", operation.id, state, operation.kind, operation.tool, operation.version.bold(),
+                        format!("started {}", format::date(&operation.started_at)).dimmed(), progress.dimmed());
+                    if let Some(error) = &operation.error {
+                        println!("    {}", error.red());
+                    }
+                }
+            }
+        }
+        Commands::Status { cwd, .. } => {
+            let cwd = match cwd {
+                Some(cwd) => cwd,
+                None => std::env::current_dir()?,
+            };
+            let mut tools = serde_json::Map::new();
+            if !cli.json {
+                println!("{} {}", "Directory:".bold(), cwd.display());
+                if manager.is_managed() {
+                    println!("{} provisioned by the administrator (read-only)", "Versions:".bold());
+                }
+            }
+            
+            for version_type in VersionType::all() {
+                let local = manager.local_version_file(&cwd, version_type)?;
+                // 子目录的版本文件覆盖了上层目录中的版本文件
+                let overrides = match &local {
+                    Some((_, file)) => match file.parent().and_then(|dir| dir.parent()) {
+                        Some(parent) => manager.local_version_file(parent, version_type)?,
+                        None => None,
+                    },
+                    None => None,
+                };
+                let Some((version, source)) = manager.resolve_version(version_type, &cwd)? else {
+                    if cli.json {
+                        tools.insert(version_type.id().to_string(), serde_json::Value::Null);
+                    } else {
+                        println!("{:<8} {}", version_type.id(), "not configured".dimmed());
+                    }
+                    continue;
+                };
+                let installed = manager.get_executable_path(&version, version_type).exists();
+                
+                if cli.json {
+                    tools.insert(version_type.id().to_string(), serde_json::json!({
+                        "version": version,
+                        "source": source.to_string(),
+                        "file": local.as_ref().map(|(_, file)| file),
+                        "overrides": overrides.as_ref().map(|(version, file)| serde_json::json!({ "version": version, "file": file })),
+                        "installed": installed,
+                    }));
+                    continue;
+                }
+                
+                let origin = match &local {
+                    Some((_, file)) => file.display().to_string(),
+                    None => source.to_string(),
+                };
+                let mut line = format!("{:<8} {:<10} ({})", version_type.id(), version.bold(), origin);
+                if let Some((parent_version, parent_file)) = &overrides {
+                    line.push_str(&format!(" {}", format!("overrides {} from {}", parent_version, parent_file.display()).dimmed()));
+                }
+                if !installed {
+                    line.push_str(&format!(" {}", "not installed".red()));
+                }
+                println!("{}", line);
+            }
+            
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "dir": cwd, "tools": tools }))?);
+            }
+        }
+        Commands::IdeInfo { cwd } => {
+            let cwd = match cwd {
+                Some(cwd) => cwd,
+                None => std::env::current_dir()?,
+            };
+            let mut tools = serde_json::Map::new();
+            let mut settings = serde_json::Map::new();
+            
+            for version_type in VersionType::all() {
+                let Some((version, source)) = manager.resolve_version(version_type, &cwd)? else {
+                    if !cli.json {
+                        println!("{}: not configured", version_type);
+                    }
+                    continue;
+                };
+                
+                let bin_dir = manager.get_bin_dir(&version, version_type);
+                let executable = manager.get_executable_path(&version, version_type);
+                let installed = executable.exists();
+                
+                if cli.json {
+                    tools.insert(version_type.id().to_string(), serde_json::json!({
+                        "version": version,
+                        "source": source.to_string(),
+                        "installed": installed,
+                        "bin_dir": bin_dir,
+                        "executable": executable,
+                        "build": manager.read_install_receipt(&version, version_type)?.and_then(|receipt| receipt.build),
+                    }));
+                    
+                    if installed {
+                        match version_type {
+                            VersionType::Node => {
+                                settings.insert("eslint.runtime".to_string(), serde_json::json!(executable));
+                            }
+                            VersionType::Python => {
+                                settings.insert("python.defaultInterpreterPath".to_string(), serde_json::json!(executable));
+                            }
+                            VersionType::Go => {
+                                if let Some(goroot) = bin_dir.parent() {
+                                    settings.insert("go.goroot".to_string(), serde_json::json!(goroot));
+                                }
+                            }
+                            VersionType::Rust => {}
+                        }
+                    }
+                } else {
+                    println!("{}: {} ({}) -> {}{}", version_type, version, source, executable.display(),
+                        if installed { "" } else { " [not installed]" });
+                }
+            }
+            
+            if cli.json {
+                let output = serde_json::json!({
+                    "cwd": cwd,
+                    "tools": tools,
+                    "vscode": settings,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+        }
+        Commands::Shell { version, unset: _, type_ } => {
+            let version_type = tool_type(&manager, type_.as_deref(), cli.json)?;
+            let version = match version {
+                Some(version) => match manager.get_alias(&version, version_type)? {
+                    Some(aliased_version) if !manager.is_installed(&version, version_type) => Some(aliased_version),
+                    _ => Some(version),
+                },
+                None => None,
+            };
+            print_session_activation(&manager, version.as_deref(), version_type)?;
+        }
+        Commands::Env { type_ } => {
+            let version_types = match type_ {
+                Some(type_) => vec![parse_version_type(&type_)?],
+                None => VersionType::all().to_vec(),
+            };
+            
+            let local = Local::new(&manager);
+            let cwd = std::env::current_dir()?;
+            for version_type in version_types {
+                let vars = match local.resolve_version(version_type, &cwd)? {
+                    Some((version, _)) => local.version_env(&version, version_type),
+                    None => local.tool_env(version_type),
+                };
+                for (key, value) in vars {
+                    println!("export {}={}", key, version_manager::sh_quote(&value));
+                }
+            }
+        }
+        Commands::Report { days, type_ } => {
+            let version_types = match type_ {
+                Some(type_) => vec![parse_version_type(&type_)?],
+                None => VersionType::all().to_vec(),
+            };
+            let since = chrono::Utc::now() - chrono::Duration::days(days as i64);
+            let records = manager.usage_records(since)?;
+            
+            let mut report = serde_json::Map::new();
+            if !cli.json {
+                println!("{}", format!("Usage in the last {} days:", days).bold());
+            }
+            for version_type in version_types {
+                // 版本 -> (次数, 最后使用时间, 目录 -> 次数)
+                let mut versions: std::collections::BTreeMap<String, (usize, String, std::collections::BTreeMap<PathBuf, usize>)> = std::collections::BTreeMap::new();
+                for record in records.iter().filter(|record| record.tool == version_type.id()) {
+                    let entry = versions.entry(record.version.clone()).or_default();
+                    entry.0 += 1;
+                    entry.1 = entry.1.clone().max(record.time.clone());
+                    *entry.2.entry(record.dir.clone()).or_default() += 1;
+                }
+                let unused: Vec<String> = manager.list_installed_versions(version_type)?
+                    .iter()
+                    .map(|version| version.trim_end_matches(" (current)").to_string())
+                    .filter(|version| !versions.contains_key(version))
+                    .collect();
+                
+                if cli.json {
+                    let used: Vec<_> = versions.iter().map(|(version, (count, last_used, dirs))| serde_json::json!({
+                        "version": version,
+                        "count": count,
+                        "last_used": last_used,
+                        "dirs": dirs.iter().map(|(dir, count)| serde_json::json!({ "dir": dir, "count": count })).collect::<Vec<_>>(),
+                    })).collect();
+                    report.insert(version_type.id().to_string(), serde_json::json!({ "used": used, "unused": unused }));
+                    continue;
+                }
+                
+                println!("{}", version_type.to_string().bold());
+                if versions.is_empty() {
+                    println!("  {}", "no recorded usage".dimmed());
+                }
+                for (version, (count, last_used, dirs)) in &versions {
+                    println!("  {}  {} run{}, last used {}", version.bold(), format::number(*count as u64), if *count == 1 { "" } else { "s" }, format::date(last_used));
+                    for (dir, count) in dirs {
+                        println!("    {} ({})", dir.display(), count);
+                    }
+                }
+                if !unused.is_empty() {
+                    println!("  {} {}", "Installed but unused:".dimmed(), unused.join(", "));
+                }
+            }
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            }
+        }
+        Commands::Verify { version, type_, all, repair, report: report_file, schedule, interval } => {
+            if schedule {
+                let interval = schedule::Interval::from_name(&interval)?;
+                let report_file = manager.base_dir().join("verify-report.json");
+                let installed = manager.install_schedule("verify", &["verify", "--all", "--repair", "--report", &report_file.to_string_lossy()], interval)?;
+                println!("Installed {}", installed);
+                return Ok(());
+            }
+            
+            let mut targets = Vec::new();
+            let version_types = if all { VersionType::all().to_vec() } else { vec![parse_version_type(&type_)?] };
+            for &version_type in &version_types {
+                match &version {
+                    Some(version) => targets.push((version_type, version.clone())),
+                    None => targets.extend(manager.list_installed_versions(version_type)?
+                        .iter()
+                        .map(|version| (version_type, version.trim_end_matches(" (current)").to_string()))),
+                }
+            }
+            
+            // 重新安装的过程信息会混入标准输出中的JSON
+            if cli.json && repair {
+                manager.set_progress_mode(ProgressMode::Quiet);
+            }
+            let mut failed = 0;
+            let mut repaired = 0;
+            let mut report = Vec::new();
+            for (version_type, version) in &targets {
+                let (version_type, version) = (*version_type, version.as_str());
+                let result = manager.verify_version(version, version_type)?;
+                let mut status = match &result {
+                    None => "unverified",
+                    Some(result) if result.modified.is_empty() && result.missing.is_empty() => "ok",
+                    Some(_) => "failed",
+                };
+                let mut error = None;
+                if status == "failed" && repair {
+                    match manager.repair_version(version, version_type).await {
+                        Ok(()) => status = "repaired",
+                        Err(err) => {
+                            status = "repair_failed";
+                            error = Some(err.to_string());
+                        }
+                    }
+                }
+                match status {
+                    "failed" | "repair_failed" => failed += 1,
+                    "repaired" => repaired += 1,
+                    _ => {}
+                }
+                
+                report.push(serde_json::json!({
+                    "tool": version_type.id(),
+                    "version": version,
+                    "status": status,
+                    "modified": result.as_ref().map(|result| result.modified.clone()).unwrap_or_default(),
+                    "missing": result.as_ref().map(|result| result.missing.clone()).unwrap_or_default(),
+                    "error": error,
+                }));
+                if cli.json {
+                    continue;
+                }
+                let label = if all { format!("{} {}", version_type.id(), version) } else { version.to_string() };
+                match result {
+                    None => println!("{:<16} {}", label, "no checksums recorded".dimmed()),
+                    Some(_) if status == "ok" => println!("{:<16} {}", label, "ok".green()),
+                    Some(result) => {
+                        let summary = format!("{} modified, {} missing", result.modified.len(), result.missing.len());
+                        match status {
+                            "repaired" => println!("{:<16} {} ({})", label, "repaired".yellow(), summary),
+                            _ => println!("{:<16} {}", label, summary.red()),
+                        }
+                        for path in &result.modified {
+                            println!("  modified: {}", path);
+                        }
+                        for path in &result.missing {
+                            println!("  missing:  {}", path);
+                        }
+                        if let Some(error) = &error {
+                            println!("  {} {}", "repair failed:".red(), error);
+                        }
+                    }
+                }
+            }
+            
+            let summary = serde_json::json!({
+                "checked_at": chrono::Utc::now().to_rfc3339(),
+                "checked": report.len(),
+                "repaired": repaired,
+                "failed": failed,
+                "versions": report,
+            });
+            if let Some(report_file) = &report_file {
+                state::write_atomic(report_file, serde_json::to_string_pretty(&summary)?)?;
+            }
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            }
+            if failed > 0 {
+                return Err(match all {
+                    true => anyhow::anyhow!("{} 个版本校验失败", failed),
+                    false => anyhow::anyhow!("{} 个 {} 版本校验失败", failed, version_types[0]),
+                });
+            }
+        }
+        Commands::Prereqs { tool } => {
+            let version_type = parse_version_type(&tool)?;
+            let checks = prereqs::check(version_type);
+            let missing: Vec<&prereqs::Prereq> = checks.iter().filter(|check| check.found.is_none()).map(|check| check.prereq).collect();
+            let required: Vec<&str> = missing.iter().filter(|prereq| prereq.required).map(|prereq| prereq.name).collect();
+            // 识别出当前系统的包管理器时只给出它的命令，否则列出所有包管理器的命令
+            let managers: Vec<usize> = match prereqs::package_manager() {
+                Some(manager) => vec![manager],
+                None => (0..prereqs::PACKAGE_MANAGERS.len()).collect(),
+            };
+            
+            if cli.json {
+                let install: serde_json::Map<String, serde_json::Value> = (0..prereqs::PACKAGE_MANAGERS.len())
+                    .map(|manager| (prereqs::PACKAGE_MANAGERS[manager].0.to_string(), serde_json::json!(prereqs::install_commands(manager, &missing))))
+                    .collect();
+                let items: Vec<_> = checks.iter().map(|check| serde_json::json!({
+                    "name": check.prereq.name,
+                    "required": check.prereq.required,
+                    "purpose": check.prereq.purpose,
+                    "found": check.found,
+                })).collect();
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                    "tool": version_type.id(),
+                    "ok": required.is_empty(),
+                    "prereqs": items,
+                    "install": install,
+                }))?);
+            } else {
+                println!("{}", format!("Build prerequisites for {}:", version_type).bold());
+                for check in &checks {
+                    let kind = if check.prereq.required { "required" } else { "optional" };
+                    match &check.found {
+                        Some(path) => println!("  {:<8} {:<20} {}", "ok".green(), check.prereq.name, path.display().to_string().dimmed()),
+                        None if check.prereq.required => println!("  {:<8} {:<20} {} ({})", "missing".red(), check.prereq.name, check.prereq.purpose, kind),
+                        None => println!("  {:<8} {:<20} {} ({})", "missing".yellow(), check.prereq.name, check.prereq.purpose, kind),
+                    }
+                }
+                if missing.is_empty() {
+                    println!("{}", "Everything is installed".green());
+                } else {
+                    println!("Install the missing ones with:");
+                    for manager in managers {
+                        for command in prereqs::install_commands(manager, &missing) {
+                            println!("  {:<7} {}", prereqs::PACKAGE_MANAGERS[manager].0, command);
+                        }
+                    }
+                }
+            }
+            if !required.is_empty() {
+                return Err(anyhow::anyhow!("缺少 {} 必需的依赖: {}", version_type, required.join(", ")));
+            }
+        }
+        Commands::Subscribe { tool, channel, alias } => {
+            let version_type = parse_version_type(&tool)?;
+            manager.subscribe(version_type, &channel, alias.as_deref())?;
+            println!("Subscribed to {} {}", version_type, channel.bold());
+            println!("Run `ver upgrade --subscribed` to install new releases");
+        }
+        Commands::Unsubscribe { tool, channel } => {
+            let version_type = parse_version_type(&tool)?;
+            if manager.unsubscribe(version_type, &channel)? {
+                println!("Unsubscribed from {} {}", version_type, channel);
+            } else {
+                println!("Not subscribed to {} {}", version_type, channel);
+            }
+        }
+        Commands::Subscriptions => {
+            let subscriptions = manager.list_subscriptions()?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&subscriptions)?);
+            } else if subscriptions.is_empty() {
+                println!("No subscriptions");
+            } else {
+                for subscription in subscriptions {
+                    let mut details = Vec::new();
+                    if let Some(alias) = &subscription.alias {
+                        details.push(format!("alias {}", alias));
+                    }
+                    if let Some(installed) = &subscription.installed {
+                        details.push(format!("last installed {}", installed));
+                    }
+                    println!("{:<8} {:<8} {}", subscription.tool, subscription.channel, details.join(", ").dimmed());
+                }
+            }
+        }
+        Commands::Upgrade { subscribed } => {
+            if !subscribed {
+                return Err(anyhow::anyhow!("请指定要升级的内容，例如 `ver upgrade --subscribed`"));
+            }
+            let upgraded = manager.upgrade_subscribed().await?;
+            if upgraded.is_empty() {
+                println!("All subscribed channels are up to date");
+            }
+            for (version_type, version) in upgraded {
+                println!("Installed {} version {}", version_type, version.bold());
+            }
+        }
+        Commands::Maintain { install_schedule, interval } => {
+            if install_schedule {
+                let interval = schedule::Interval::from_name(&interval)?;
+                let installed = manager.install_schedule("maintain", &["maintain"], interval)?;
+                println!("Installed {}", installed);
+                return Ok(());
             }
             
-            for version in versions {
+            // 刷新远程版本索引（同时更新补全缓存）
+            let mut refreshed = Vec::new();
+            let mut refresh_failed = Vec::new();
+            let indexes = futures_util::future::join_all(
+                VersionType::all().map(|version_type| manager.list_available_versions(false, version_type))
+            ).await;
+            for (version_type, index) in VersionType::all().into_iter().zip(indexes) {
+                match index {
+                    Ok(_) => refreshed.push(version_type.id()),
+                    Err(err) => {
+                        eprintln!("{}: failed to refresh version index: {}", version_type.id(), err);
+                        refresh_failed.push(version_type.id());
+                    }
+                }
+            }
+            
+            let upgraded = manager.upgrade_subscribed().await?;
+            
+            let prune_days = manager.config().maintain.prune_days;
+            let candidates = manager.gc_report(prune_days.unwrap_or(90))?;
+            let reclaimable: Vec<_> = candidates.iter().filter(|candidate| candidate.reclaimable).collect();
+            let reclaimable_size: u64 = reclaimable.iter().map(|candidate| candidate.size).sum();
+            let mut pruned = Vec::new();
+            let mut pruned_size = 0;
+            let mut prune_failed = Vec::new();
+            if prune_days.is_some() {
+                // 无人值守时只删除有安装记录、并且有过期使用记录的版本，从未使用过的版本留给 `ver gc`
+                for candidate in reclaimable.iter().filter(|candidate| candidate.installed_at.is_some() && candidate.last_used.is_some()) {
+                    let label = format!("{} {}", candidate.version_type.id(), candidate.version);
+                    match manager.remove_version(&candidate.version, candidate.version_type, false) {
+                        Ok(()) => {
+                            pruned_size += candidate.size;
+                            pruned.push(label);
+                        }
+                        Err(err) => {
+                            eprintln!("{}: failed to remove: {}", label, err);
+                            prune_failed.push(label);
+                        }
+                    }
+                }
+            }
+            
+            let mut verified = 0;
+            let mut verify_failed = Vec::new();
+            for version_type in VersionType::all() {
+                for version in manager.list_installed_versions(version_type)? {
+                    let version = version.trim_end_matches(" (current)");
+                    match manager.verify_version(version, version_type) {
+                        Ok(Some(result)) if result.modified.is_empty() && result.missing.is_empty() => verified += 1,
+                        Ok(Some(_)) => verify_failed.push(format!("{} {}", version_type.id(), version)),
+                        Ok(None) => {}
+                        Err(err) => {
+                            eprintln!("{} {}: failed to verify: {}", version_type.id(), version, err);
+                            verify_failed.push(format!("{} {}", version_type.id(), version));
+                        }
+                    }
+                }
+            }
+            
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                    "refreshed": refreshed,
+                    "refresh_failed": refresh_failed,
+                    "upgraded": upgraded.iter().map(|(version_type, version)| format!("{} {}", version_type.id(), version)).collect::<Vec<_>>(),
+                    "reclaimable": reclaimable.iter().map(|candidate| format!("{} {}", candidate.version_type.id(), candidate.version)).collect::<Vec<_>>(),
+                    "reclaimable_bytes": reclaimable_size,
+                    "pruned": pruned,
+                    "pruned_bytes": pruned_size,
+                    "prune_failed": prune_failed,
+                    "verified": verified,
+                    "verify_failed": verify_failed,
+                }))?);
+            } else {
+                println!("{}", format!("ver maintain ({})", chrono::Local::now().format("%Y-%m-%d %H:%M")).bold());
+                println!("  indexes:       {} refreshed, {} failed", refreshed.len(), refresh_failed.len());
+                println!("  subscriptions: {} new release(s) installed", upgraded.len());
+                if prune_days.is_some() {
+                    println!("  prune:         {} version(s) removed, {} freed, {} failed", pruned.len(), format::size(pruned_size), prune_failed.len());
+                } else {
+                    println!("  prune:         {} version(s) reclaimable ({}), run `ver gc` or set maintain.prune_days",
+                        reclaimable.len(), format::size(reclaimable_size));
+                }
+                println!("  verify:        {} ok, {} failed", verified, verify_failed.len());
+                for failed in &verify_failed {
+                    println!("    {} {}", "failed:".red(), failed);
+                }
+            }
+            if !prune_failed.is_empty() {
+                return Err(anyhow::anyhow!("{} 个版本删除失败: {}", prune_failed.len(), prune_failed.join(", ")));
+            }
+            if !verify_failed.is_empty() {
+                return Err(anyhow::anyhow!("{} 个版本校验失败，运行 `ver verify` 查看详情", verify_failed.len()));
+            }
+        }
+        Commands::Lock { tool, version } => {
+            let version_type = parse_version_type(&tool)?;
+            manager.lock_version(&version, version_type)?;
+            println!("Locked {} version {}", version_type, version.bold());
+        }
+        Commands::Unlock { tool, version } => {
+            let version_type = parse_version_type(&tool)?;
+            if manager.unlock_version(&version, version_type)? {
+                println!("Unlocked {} version {}", version_type, version.bold());
+            } else {
+                println!("{} version {} is not locked", version_type, version);
+            }
+        }
+        Commands::Outdated { type_ } => {
+            let version_types = match type_ {
+                Some(type_) => vec![parse_version_type(&type_)?],
+                None => VersionType::all().to_vec(),
+            };
+            
+            let current_dir = std::env::current_dir()?;
+            let mut outdated = 0;
+            let mut failed = 0;
+            for (version_type, latest) in manager.fetch_latest_versions(&version_types).await {
+                let current = manager.resolve_version(version_type, &current_dir)?;
+                let latest = match latest {
+                    Ok(Some(latest)) => latest,
+                    Ok(None) => continue,
+                    Err(err) => {
+                        eprintln!("{}: failed to fetch remote versions: {}", version_type.id(), err);
+                        failed += 1;
+                        continue;
+                    }
+                };
+                let Some((current, _)) = current else {
+                    continue;
+                };
+                if version_manager::compare_versions(&current, &latest) == std::cmp::Ordering::Less {
+                    outdated += 1;
+                    println!("{}: {} -> {}", version_type.id(), current, latest.bold());
+                }
+            }
+            
+            if outdated == 0 && failed == 0 {
+                println!("All tools are up to date");
+            }
+        }
+        Commands::Freeze { arch, dest } => {
+            let frozen = manager.freeze(&std::env::current_dir()?, arch.as_deref()).await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&frozen)?);
+                return Ok(());
+            }
+            if frozen.is_empty() {
+                return Err(anyhow::anyhow!("当前项目没有通过版本文件指定任何工具的版本"));
+            }
+            
+            println!("# syntax=docker/dockerfile:1.6");
+            println!("# Generated by `ver freeze`; ADD --checksum requires BuildKit");
+            for toolchain in &frozen {
+                let file_name = toolchain.url.rsplit('/').next().unwrap_or_default();
+                println!("# {} {} ({})", toolchain.tool, toolchain.version, toolchain.platform);
+                println!("ADD --checksum=sha256:{} {} {}/{}", toolchain.sha256, toolchain.url, dest.trim_end_matches('/'), file_name);
+            }
+        }
+        Commands::Export { format } => {
+            let current_dir = std::env::current_dir()?;
+            match format.as_str() {
+                "nix" => print!("{}", manager.export_nix(&current_dir).await?),
+                _ => return Err(anyhow::anyhow!("不支持的导出格式: {}（可选 nix）", format)),
+            }
+        }
+        Commands::ExportMake { just } => {
+            let variables = manager.make_variables(&std::env::current_dir()?)?;
+            if cli.json {
+                let variables: serde_json::Map<String, serde_json::Value> = variables.into_iter()
+                    .map(|(name, value)| (name, serde_json::Value::String(value)))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&variables)?);
+                return Ok(());
+            }
+            
+            println!("# Generated by `ver export-make`");
+            for (name, value) in variables {
+                if just {
+                    println!("{} := {}", name, serde_json::to_string(&value)?);
+                } else {
+                    println!("{} := {}", name, value);
+                }
+            }
+        }
+        Commands::Diff { tool, v1, v2 } => {
+            let version_type = parse_version_type(&tool)?;
+            let diff = manager.version_diff(version_type, &v1, &v2).await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+                return Ok(());
+            }
+            
+            let date = |date: &Option<String>| date.as_deref().map(|date| format!(" ({})", date)).unwrap_or_default();
+            println!("{} {}{} -> {}{}", version_type, diff.from, date(&diff.from_date), diff.to.bold(), date(&diff.to_date));
+            if let (Some(from), Some(to)) = (&diff.from_date, &diff.to_date)
+                && let (Ok(from), Ok(to)) = (chrono::NaiveDate::parse_from_str(from, "%Y-%m-%d"), chrono::NaiveDate::parse_from_str(to, "%Y-%m-%d"))
+            {
+                println!("Time between releases: {} days", (to - from).num_days());
+            }
+            match diff.skipped.len() {
+                0 => println!("Releases skipped: none"),
+                count => println!("Releases skipped: {} ({})", count, diff.skipped.join(", ")),
+            }
+            match &diff.security_fixes {
+                Some(fixes) if fixes.is_empty() => println!("Security releases: none"),
+                Some(fixes) => {
+                    println!("Security releases: {}", fixes.len().to_string().red());
+                    for (version, url) in fixes {
+                        println!("  {} {}", version, url);
+                    }
+                }
+                None => println!("Security releases: not reported by the {} index", version_type),
+            }
+            if let Some((from, to)) = diff.sizes {
+                let delta = if to >= from { format!("+{}", format::size(to - from)) } else { format!("-{}", format::size(from - to)) };
+                println!("Installed size: {} -> {} ({})", format::size(from), format::size(to), delta);
+            }
+            println!("Release notes: {}", diff.release_notes);
+        }
+        Commands::Sync(sync_command) => {
+            let dir = manager.sync_dir();
+            // 上次同步时仓库中的状态，合并时据此判断哪一方修改了哪些内容
+            let (base, push) = match &sync_command {
+                SyncCommands::Init { url } => {
+                    if dir.exists() {
+                        return Err(anyhow::anyhow!("已经配置了同步仓库 {}（{}），删除该目录后才能重新初始化", sync::remote_url(&dir).unwrap_or_default(), dir.display()));
+                    }
+                    sync::clone(url, &dir)?;
+                    // 仓库中已有其他机器推送的状态时只导入，否则推送本地状态
+                    (sync::SyncState::default(), !sync::has_state(&dir))
+                }
+                SyncCommands::Push | SyncCommands::Pull if !dir.exists() => {
+                    return Err(anyhow::anyhow!("还没有配置同步仓库，先运行 `ver sync init <git-url>`"));
+                }
+                SyncCommands::Push | SyncCommands::Pull => {
+                    let base = sync::read_state(&dir)?;
+                    sync::pull(&dir)?;
+                    (base, matches!(sync_command, SyncCommands::Push))
+                }
+            };
+            let url = sync::remote_url(&dir)?;
+            
+            let merged = sync::merge(&base, &sync::read_state(&dir)?, &manager.local_sync_state()?);
+            let import = manager.import_sync_state(&merged)?;
+            if let Some(backup) = &import.backup {
+                println!("Merged aliases, defaults, subscriptions and templates from {} (previous local state saved in {})", url, backup.display());
+            }
+            for (version_type, version) in &import.missing {
+                println!("  default {} {} is not installed here; run `ver {} install {}` and `ver {} use {}`",
+                    version_type.id(), version.bold(), version_type.id(), version, version_type.id(), version);
+            }
+            if push {
+                sync::write_state(&dir, &merged)?;
+                if sync::commit(&dir)? {
+                    sync::push(&dir)?;
+                    println!("Pushed aliases, defaults, subscriptions and templates to {}", url);
+                } else {
+                    println!("{} is already up to date", url);
+                }
+            } else if import.backup.is_none() {
+                println!("Local state is already up to date with {}", url);
+            }
+        }
+        Commands::Mirror(mirror_command) => {
+            match mirror_command {
+                MirrorCommands::Test { type_, select } => {
+                    let version_types = match type_ {
+                        Some(type_) => vec![parse_version_type(&type_)?],
+                        None => VersionType::all().to_vec(),
+                    };
+                    
+                    let results = futures_util::future::join_all(
+                        version_types.iter().map(|&version_type| manager.probe_mirrors(version_type))
+                    ).await;
+                    
+                    for (version_type, probes) in version_types.into_iter().zip(results) {
+                        println!("{}", format!("{} mirrors:", version_type).bold());
+                        let probes = probes?;
+                        for probe in &probes {
+                            match (probe.latency_ms, &probe.error) {
+                                (Some(latency), _) => println!("  {} {} ms", probe.url, latency.to_string().green()),
+                                (None, Some(error)) => println!("  {} {}", probe.url, format!("unavailable ({})", error).red()),
+                                (None, None) => println!("  {} {}", probe.url, "unavailable".red()),
+                            }
+                        }
+                        
+                        if select {
+                            let fastest = probes.iter()
+                                .filter_map(|probe| probe.latency_ms.map(|latency| (latency, &probe.url)))
+                                .min();
+                            match fastest {
+                                Some((_, url)) => {
+                                    manager.select_mirror(version_type, url)?;
+                                    println!("  Selected {}", url.bold());
+                                }
+                                None => println!("  No available mirror to select"),
+                            }
+                        }
+                    }
+                }
+                MirrorCommands::Delta { archive, version, type_, platform, out } => {
+                    let version_type = parse_version_type(&type_)?;
+                    let platform = match platform {
+                        Some(platform) => platform,
+                        None => manager.platform_suffix(version_type)?,
+                    };
+                    let version = version.trim_start_matches('v');
+                    let (files, written) = manager.publish_delta(&archive, version_type, version, &platform, &out)?;
+                    println!("Published {} {} ({}): {} files, {} new file contents in {}",
+                        version_type.id(), version, platform, files, written, out.display());
+                }
+            }
+        }
+        Commands::Completions { command: Some(CompletionCommands::Versions { type_ }), .. } => {
+            let version_type = parse_version_type(&type_)?;
+            for version in manager.completion_versions(version_type)? {
+                println!("{}", version);
+            }
+        }
+        Commands::Completions { command: None, shell, install, update } => {
+            let mut command = Cli::command();
+            if update {
+                let updated = completions::update(&mut command)?;
+                if cli.json {
+                    println!("{}", serde_json::to_string_pretty(&updated.iter()
+                        .map(|(shell, path)| serde_json::json!({ "shell": shell.name(), "path": path }))
+                        .collect::<Vec<_>>())?);
+                } else if updated.is_empty() {
+                    println!("No completion scripts installed");
+                } else {
+                    for (shell, path) in &updated {
+                        println!("Updated {} completions in {}", shell.name(), path.display());
+                    }
+                }
+                return Ok(());
+            }
+            
+            let shell = match shell {
+                Some(shell) => completions::Shell::from_name(&shell)?,
+                None => completions::Shell::detect()?,
+            };
+            if !install {
+                print!("{}", completions::script(shell, &mut command));
+                return Ok(());
+            }
+            let path = completions::install(shell, &mut command)?;
+            if cli.json {
+                println!("{}", serde_json::json!({ "shell": shell.name(), "path": path }));
+                return Ok(());
+            }
+            println!("Installed {} completions in {}", shell.name(), path.display());
+            match shell {
+                completions::Shell::Zsh if !completions::zsh_fpath_configured() => {
+                    println!("Add this to ~/.zshrc before `compinit` so zsh finds it:");
+                    println!("  fpath=(~/.zfunc $fpath)");
+                }
+                _ => println!("Open a new shell to use it"),
+            }
+        }
+        Commands::Config(config_command) => {
+            match config_command {
+                ConfigCommands::List => {
+                    let entries = manager.config().entries();
+                    if entries.is_empty() {
+                        println!("No configuration values set");
+                    } else {
+                        for (key, value) in entries {
+                            println!("{} = {}", key, value);
+                        }
+                    }
+                }
+                ConfigCommands::Get { key } => {
+                    match manager.config().get(&key)? {
+                        Some(value) => println!("{}", value),
+                        None => println!("{} is not set", key),
+                    }
+                }
+                ConfigCommands::Set { key, value } => {
+                    manager.set_config(&key, Some(value.clone()))?;
+                    println!("Set {} = {}", key, value);
+                }
+                ConfigCommands::Unset { key } => {
+                    manager.set_config(&key, None)?;
+                    println!("Unset {}", key);
+                }
+            }
+        }
+        Commands::Node(node_command) => {
+            match node_command {
+                NodeCommands::Tool(command) => {
+                    run_tool_command(&mut manager, VersionType::Node, command, cli.json).await?;
+                }
+                NodeCommands::Headers { version } => {
+                    let version = match version {
+                        Some(version) => manager.get_alias(&version, VersionType::Node)?.unwrap_or(version),
+                        None => manager.resolve_version(VersionType::Node, &std::env::current_dir()?)?
+                            .map(|(version, _)| version)
+                            .ok_or(VersionError::NoVersionConfigured(VersionType::Node))?,
+                    };
+                    
+                    let headers = manager.install_node_headers(&version).await?;
+                    println!("Installed {} {} headers to {}", "Node.js".green().bold(), version.green(), headers.display());
+                    println!("npm_config_nodedir is set for this version in `ver exec`, activate scripts and `ver env`");
+                }
+                NodeCommands::Abi { version: _, find: Some(abi) } => {
+                    let versions: Vec<String> = manager.node_abi_table().await?
+                        .into_iter()
+                        .filter(|(_, version_abi)| *version_abi == abi)
+                        .map(|(version, _)| version)
+                        .collect();
+                    if cli.json {
+                        println!("{}", serde_json::json!({ "abi": abi, "versions": versions }));
+                    } else if versions.is_empty() {
+                        println!("No {} version has ABI {}", "Node.js".green().bold(), abi);
+                    } else {
+                        println!("{} versions with ABI {}:", "Node.js".green().bold(), abi.to_string().bold());
+                        for version in versions {
+                            if manager.is_installed(&version, VersionType::Node) {
+                                println!("  {} {}", version.green(), format::marker("installed").dimmed());
+                            } else {
+                                println!("  {}", version);
+                            }
+                        }
+                    }
+                }
+                NodeCommands::Abi { version, find: None } => {
+                    let versions = match version {
+                        Some(version) => vec![manager.get_alias(&version, VersionType::Node)?.unwrap_or(version)],
+                        None => manager.list_installed_versions(VersionType::Node)?
+                            .iter()
+                            .map(|version| version.trim_end_matches(" (current)").to_string())
+                            .collect(),
+                    };
+                    
+                    // 本地无法确定时从远程索引中查找，只在需要时请求一次
+                    let mut table = None;
+                    let mut rows = Vec::new();
+                    for version in versions {
+                        let mut abi = None;
+                        if manager.is_installed(&version, VersionType::Node) {
+                            abi = manager.installed_node_abi(&version);
+                        }
+                        if abi.is_none() {
+                            if table.is_none() {
+                                table = Some(manager.node_abi_table().await?);
+                            }
+                            let normalized = version.trim_start_matches('v');
+                            abi = table.iter().flatten()
+                                .find(|(candidate, _)| candidate == normalized)
+                                .map(|(_, abi)| *abi);
+                        }
+                        rows.push((version, abi));
+                    }
+                    
+                    if cli.json {
+                        let rows: Vec<_> = rows.iter()
+                            .map(|(version, abi)| serde_json::json!({ "version": version, "abi": abi }))
+                            .collect();
+                        println!("{}", serde_json::to_string_pretty(&rows)?);
+                    } else if rows.is_empty() {
+                        println!("No {} versions installed", "Node.js".green());
+                    } else {
+                        for (version, abi) in rows {
+                            match abi {
+                                Some(abi) => println!("{:<12} NODE_MODULE_VERSION {}", version.green(), abi),
+                                None => println!("{:<12} {}", version.green(), "unknown".dimmed()),
+                            }
+                        }
+                    }
+                }
+                NodeCommands::Corepack(corepack_command) => {
+                    let (version, enable) = match corepack_command {
+                        CorepackCommands::Enable { version } => (version, true),
+                        CorepackCommands::Disable { version } => (version, false),
+                    };
+                    let version = match version {
+                        Some(version) => manager.get_alias(&version, VersionType::Node)?.unwrap_or(version),
+                        None => manager.resolve_version(VersionType::Node, &std::env::current_dir()?)?
+                            .map(|(version, _)| version)
+                            .ok_or(VersionError::NoVersionConfigured(VersionType::Node))?,
+                    };
+                    
+                    let shims = manager.set_corepack_enabled(&version, enable)?;
+                    if enable {
+                        println!("Enabled corepack for {} {}", "Node.js".green().bold(), version.green());
+                    } else {
+                        println!("Disabled corepack for {} {}", "Node.js".green().bold(), version.green());
+                    }
+                    if !shims.is_empty() {
+                        println!("Corepack-managed tools: {}", shims.join(", "));
+                    }
+                }
+            }
+        }
+        Commands::Rust(rust_command) => {
+            match rust_command {
+                RustCommands::Tool(command) => {
+                    run_tool_command(&mut manager, VersionType::Rust, command, cli.json).await?;
+                }
+                RustCommands::Migrate { source, exclude, only, dry_run } => {
+                    manager.migrate_from(&source, VersionType::Rust, MigrateOptions { exclude: &exclude, only: &only, dry_run }).await?;
+                }
+                RustCommands::Du { remove, dry_run, yes } => {
+                    let parts = remove.iter()
+                        .map(|name| RustPart::from_name(name))
+                        .collect::<Result<Vec<_>>>()?;
+                    let toolchains = manager.rust_toolchain_usage()?;
+                    let cargo_home = manager.cargo_home_usage()?;
+                    
+                    // (工具链版本, 要删除的部分)
+                    let mut removals = Vec::new();
+                    for toolchain in &toolchains {
+                        let locked = if toolchain.locked { " (locked)".dimmed().to_string() } else { String::new() };
+                        println!("{} {:<28} {:>10}{}", "rust".yellow().bold(), toolchain.version.yellow(), format::size(toolchain.size), locked);
+                        for entry in &toolchain.entries {
+                            let hint = entry.part.map(|part| format!("--remove {}", part.name())).unwrap_or_default();
+                            println!("    {:<32} {:>10}  {}", entry.label, format::size(entry.size), hint.dimmed());
+                            if !toolchain.locked && entry.part.is_some_and(|part| parts.contains(&part)) {
+                                removals.push((Some(toolchain.version.as_str()), entry));
+                            }
+                        }
+                    }
+                    if let Some((path, entries)) = &cargo_home {
+                        println!("{} {}", "cargo home".bold(), path.display());
+                        for entry in entries {
+                            let hint = entry.part.map(|part| format!("--remove {}", part.name())).unwrap_or_default();
+                            println!("    {:<32} {:>10}  {}", entry.label, format::size(entry.size), hint.dimmed());
+                            if entry.part.is_some_and(|part| parts.contains(&part)) {
+                                removals.push((None, entry));
+                            }
+                        }
+                    }
+                    if toolchains.is_empty() && cargo_home.is_none() {
+                        println!("No {} toolchains installed", "Rust".yellow());
+                        return Ok(());
+                    }
+                    
+                    if parts.is_empty() {
+                        let reclaimable: u64 = toolchains.iter()
+                            .filter(|toolchain| !toolchain.locked)
+                            .flat_map(|toolchain| &toolchain.entries)
+                            .chain(cargo_home.iter().flat_map(|(_, entries)| entries))
+                            .filter(|entry| entry.part.is_some())
+                            .map(|entry| entry.size)
+                            .sum();
+                        println!("{}", format!("Reclaimable: {}", format::size(reclaimable)).bold());
+                        println!("Remove with `ver rust du --remove docs,installer,src,targets,registry`");
+                        return Ok(());
+                    }
+                    
+                    let total: u64 = removals.iter().map(|(_, entry)| entry.size).sum();
+                    println!("{}", format!("Selected for removal: {} item(s), {}", removals.len(), format::size(total)).bold());
+                    if dry_run || removals.is_empty() || !confirm(&format!("Remove {}?", format::size(total)), yes)? {
+                        return Ok(());
+                    }
+                    for (version, entry) in removals {
+                        manager.remove_rust_part(version, entry)?;
+                        println!("Removed {}", entry.path.display());
+                    }
+                }
+                RustCommands::Override(RustOverrideCommands::Set { toolchain, path }) => {
+                    let path = match path {
+                        Some(path) => path,
+                        None => std::env::current_dir()?,
+                    };
+                    let dir = manager.set_rust_override(&path, &toolchain)?;
+                    println!("Overriding {} with {} in {}", "Rust".yellow(), toolchain.bold(), dir.display());
+                }
+                RustCommands::Override(RustOverrideCommands::Unset { path }) => {
+                    let path = match path {
+                        Some(path) => path,
+                        None => std::env::current_dir()?,
+                    };
+                    if manager.unset_rust_override(&path)? {
+                        println!("Removed the {} override for {}", "Rust".yellow(), path.display());
+                    } else {
+                        println!("No {} override for {}", "Rust".yellow(), path.display());
+                    }
+                }
+                RustCommands::Override(RustOverrideCommands::List) => {
+                    let overrides = manager.list_rust_overrides()?;
+                    if cli.json {
+                        let overrides: Vec<_> = overrides.iter()
+                            .map(|(dir, toolchain)| serde_json::json!({ "path": dir, "toolchain": toolchain, "exists": dir.is_dir() }))
+                            .collect();
+                        println!("{}", serde_json::to_string_pretty(&overrides)?);
+                    } else if overrides.is_empty() {
+                        println!("No {} overrides", "Rust".yellow());
+                    } else {
+                        for (dir, toolchain) in overrides {
+                            let missing = if dir.is_dir() { String::new() } else { " (directory missing)".dimmed().to_string() };
+                            println!("{:<48} {}{}", dir.display(), toolchain.yellow(), missing);
+                        }
+                    }
+                }
+                RustCommands::Proxy { binary, args } => {
+                    let code = manager.run_rust_proxy(&binary, &args)?;
+                    std::process::exit(code);
+                }
+            }
+        }
+        Commands::Python(python_command) => {
+            match python_command {
+                PythonCommands::Tool(command) => {
+                    run_tool_command(&mut manager, VersionType::Python, command, cli.json).await?;
+                }
+                PythonCommands::Migrate { source: _, exclude, only, dry_run } => {
+                    manager.migrate_from_pyenv(MigrateOptions { exclude: &exclude, only: &only, dry_run }).await?;
+                }
+            }
+        }
+        Commands::Go(go_command) => {
+            match go_command {
+                GoCommands::Tool(command) => {
+                    run_tool_command(&mut manager, VersionType::Go, command, cli.json).await?;
+                }
+                GoCommands::Cross { goos, goarch, cgo, cc, go_version, args } => {
+                    let version = match go_version {
+                        Some(version) => manager.get_alias(&version, VersionType::Go)?.unwrap_or(version),
+                        None => manager.resolve_version(VersionType::Go, &std::env::current_dir()?)?
+                            .map(|(version, _)| version)
+                            .ok_or(VersionError::NoVersionConfigured(VersionType::Go))?,
+                    };
+                    let env = manager.go_cross_env(&version, &goos, &goarch, cgo, cc.as_deref())?;
+                    eprintln!("{}", format!("Go {} targeting {}/{}", version, goos, goarch).dimmed());
+                    manager.exec_with_version(&version, &args[0], &args[1..], VersionType::Go, ExecOptions { env: &env, ..Default::default() }).await?;
+                }
+                GoCommands::Migrate { source: _, exclude, only, dry_run } => {
+                    manager.migrate_from_gvm(MigrateOptions { exclude: &exclude, only: &only, dry_run }).await?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 执行各工具共用的子命令
+///
+/// `ver <命令> -t <工具>` 和 `ver <工具> <命令>` 共用这一个实现，保证所有工具的行为一致。
+async fn run_tool_command(manager: &mut VersionManager, version_type: VersionType, command: ToolCommands, json: bool) -> Result<()> {
+    match command {
+        ToolCommands::List(ListArgs { lts, installed_only, not_installed, page, per_page, long }) => {
+            let installed: Vec<String> = manager.list_installed_versions(version_type)?
+                .into_iter()
+                .map(|version| version.trim_end_matches(" (current)").to_string())
+                .collect();
+            let active = manager.resolve_version(version_type, &std::env::current_dir()?)?
+                .map(|(version, _)| version.trim_start_matches('v').to_string());
+            let is_installed = |version: &str| installed.iter().any(|v| v.trim_start_matches('v') == version.trim_start_matches('v'));
+            
+            let sources = if long || json { Some(manager.metadata_sources(version_type).await) } else { None };
+            let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+            let mut records = Vec::new();
+            
+            // 没有指定页码时在终端中通过分页器显示，结果边解析边输出
+            let mut pager = if page.is_none() && !json { spawn_pager() } else { None };
+            let mut stdout = std::io::stdout();
+            let out: &mut dyn Write = match pager.as_mut().and_then(|pager| pager.stdin.as_mut()) {
+                Some(stdin) => stdin,
+                None => &mut stdout,
+            };
+            
+            // 添加版本类型标题
+            let title = match version_type {
+                VersionType::Node => "Available Node.js Versions:".green().bold(),
+                VersionType::Rust => "Available Rust Versions:".yellow().bold(),
+                VersionType::Python => "Available Python Versions:".blue().bold(),
+                VersionType::Go => "Available Go Versions:".red().bold(),
+            };
+            if !json {
+                writeln!(out, "{}", title)?;
+            }
+            
+            let platform = manager.platform_suffix(version_type).ok();
+            let skip = page.map(|page| page.saturating_sub(1) * per_page).unwrap_or(0);
+            let mut shown = 0;
+            let mut more = false;
+            let mut remote = Vec::new();
+            manager.stream_available_versions(lts, version_type, |version| {
+                let installed = is_installed(&version.version);
+                if installed_only {
+                    remote.push(version.version.trim_start_matches('v').to_string());
+                }
+                if (installed_only && !installed) || (not_installed && installed) {
+                    return true;
+                }
+                if shown == skip + per_page && page.is_some() {
+                    more = true;
+                    return false;
+                }
+                shown += 1;
+                if shown <= skip {
+                    return true;
+                }
+                let record = sources.as_ref().map(|sources| metadata::merge(version_type, &version, sources, &today));
+                if json {
+                    records.extend(record);
+                    return true;
+                }
                 let version_str = match version_type {
                     VersionType::Node => {
-                        if version.lts {
-                            format!("{} (LTS)", version.version).green()
+                        if version.is_recommended() {
+                            format!("{} {}", version.version, format::marker("LTS")).green()
                         } else {
                             version.version.green()
                         }
                     },
                     VersionType::Rust => {
-                        if version.lts {
-                            format!("{} (Stable)", version.version).yellow()
+                        if version.is_recommended() {
+                            format!("{} {}", version.version, format::marker("Stable")).yellow()
                         } else {
                             version.version.yellow()
                         }
                     },
                     VersionType::Python => {
-                        if version.lts {
-                            format!("{} (Stable)", version.version).blue()
+                        if version.is_recommended() {
+                            format!("{} {}", version.version, format::marker("Stable")).blue()
                         } else {
                             version.version.blue()
                         }
                     },
                     VersionType::Go => {
-                        if version.lts {
-                            format!("{} (Stable)", version.version).red()
+                        if version.is_recommended() {
+                            format!("{} {}", version.version, format::marker("Stable")).red()
                         } else {
                             version.version.red()
                         }
                     },
                 };
-                println!("{}", version_str);
+                let released = match &version.date {
+                    Some(date) => format!(" {}", format!("released {}", format::date(date)).dimmed()),
+                    None => String::new(),
+                };
+                let state = if active.as_deref() == Some(version.version.trim_start_matches('v')) {
+                    format!(" {}", format::marker("active").bold())
+                } else if installed {
+                    format!(" {}", format::marker("installed").dimmed())
+                } else {
+                    String::new()
+                };
+                let details = record.as_ref().map(format_metadata).unwrap_or_default();
+                // 分页器已退出时停止读取索引
+                match &platform {
+                    Some(platform) if version.file_for(platform).is_none() && !installed => {
+                        writeln!(out, "{} {}{}", version_str, "(not available for this platform)".dimmed(), details).is_ok()
+                    }
+                    _ => writeln!(out, "{}{}{}{}", version_str, released, state, details).is_ok(),
+                }
+            }).await?;
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&records)?);
+                return Ok(());
+            }
+            
+            // 不在远程索引中的已安装版本（其他架构、ver adopt 登记或迁移的版本）
+            if installed_only && !more {
+                for version in installed.iter().filter(|v| !remote.contains(&v.trim_start_matches('v').to_string())) {
+                    writeln!(out, "{} {}", version, "(installed, not in the remote index)".dimmed())?;
+                }
+            }
+            if let Some(page) = page {
+                if shown <= skip {
+                    writeln!(out, "{}", format!("No versions on page {}", page).dimmed())?;
+                } else if more {
+                    writeln!(out, "{}", format!("Page {} (versions {}-{}); next: --page {}", page, skip + 1, shown, page + 1).dimmed())?;
+                }
+            }
+            
+            if let Some(mut pager) = pager {
+                drop(pager.stdin.take());
+                pager.wait()?;
+            }
+        }
+        ToolCommands::Install(InstallArgs { version, arch, detach, operation_id }) => {
+            if detach {
+                let id = VersionManager::new_operation_id();
+                let pid = spawn_detached(&id)?;
+                manager.queue_operation(&id, version_type, &version, pid)?;
+                if json {
+                    println!("{}", serde_json::json!({ "operation_id": id }));
+                } else {
+                    println!("Started operation {}", id.bold());
+                    println!("Run `ver status --operations` to follow its progress");
+                }
+                return Ok(());
+            }
+            if let Some(id) = &operation_id {
+                manager.set_operation_id(id);
+            }
+            let type_color = match version_type {
+                VersionType::Node => "Node.js".green().bold(),
+                VersionType::Rust => "Rust".yellow().bold(),
+                VersionType::Python => "Python".blue().bold(),
+                VersionType::Go => "Go".red().bold(),
+            };
+            
+            if arch.is_some() && (version == "latest" || version == "lts") {
+                anyhow::bail!("--arch 需要指定具体的版本号");
+            }
+            
+            if version == "latest" {
+                println!("Installing latest {} version...", type_color);
+                manager.install_latest(version_type).await?;
+            } else if version == "lts" && version_type == VersionType::Node {
+                println!("Installing latest LTS {} version...", type_color);
+                manager.install_latest_lts(version_type).await?;
+            } else {
+                println!("Installing {} version {}...", type_color, version.bold());
+                manager.install_version_for_arch(&version, version_type, arch.as_deref()).await?;
+                manager.refresh_completion_cache(version_type).await;
+            }
+        }
+        ToolCommands::Use(UseArgs { version, arch, session: true }) => {
+            let version = match manager.get_alias(&version, version_type)? {
+                Some(aliased_version) if !manager.is_installed(&version, version_type) => aliased_version,
+                _ => version,
+            };
+            let version = manager.installed_version_name(&version, arch.as_deref())?;
+            print_session_activation(manager, Some(&version), version_type)?;
+        }
+        ToolCommands::Use(UseArgs { version, arch, session: false }) => {
+            let type_color = match version_type {
+                VersionType::Node => "Node.js".green().bold(),
+                VersionType::Rust => "Rust".yellow().bold(),
+                VersionType::Python => "Python".blue().bold(),
+                VersionType::Go => "Go".red().bold(),
+            };
+            
+            let version = match manager.get_alias(&version, version_type)? {
+                Some(aliased_version) if !manager.is_installed(&version, version_type) => {
+                    println!("Using alias '{}' -> {} version {}", version, type_color, aliased_version);
+                    aliased_version
+                }
+                _ => version,
+            };
+            let version = manager.installed_version_name(&version, arch.as_deref())?;
+            println!("Switching to {} version {}...", type_color, version.bold());
+            manager.use_version(&version, version_type)?;
+            if version_type == VersionType::Go {
+                check_go_toolchain(manager, &version).await?;
             }
         }
-        Commands::Install { version, type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            let type_color = match version_type {
+        ToolCommands::Installed => run_local_command(Local::new(manager), version_type, LocalCommand::Installed, json)?,
+        ToolCommands::Remove(RemoveArgs { version, force }) => {
+            manager.remove_version(&version, version_type, force)?;
+            manager.refresh_completion_cache(version_type).await;
+        }
+        ToolCommands::Current => run_local_command(Local::new(manager), version_type, LocalCommand::Current, json)?,
+        ToolCommands::Alias(AliasArgs { name, version, system }) => {
+            if system {
+                let path = manager.create_system_alias(&name, &version, version_type)?;
+                println!("Created system alias '{}' -> {} version {} in {}", name, version_type, version, path.display());
+                return Ok(());
+            }
+            manager.create_alias(&name, &version, version_type)?;
+            println!("Created alias '{}' -> {} version {}", name, match version_type {
                 VersionType::Node => "Node.js".green().bold(),
                 VersionType::Rust => "Rust".yellow().bold(),
                 VersionType::Python => "Python".blue().bold(),
                 VersionType::Go => "Go".red().bold(),
-            };
+            }, version);
+        }
+        ToolCommands::Aliases => run_local_command(Local::new(manager), version_type, LocalCommand::Aliases, json)?,
+        ToolCommands::Info(InfoArgs { version }) => {
+            let record = manager.version_metadata(&version, version_type).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&record)?);
+                return Ok(());
+            }
             
-            if version == "latest" {
-                println!("Installing latest {} version...", type_color);
-                manager.install_latest(version_type).await?;
-            } else if version == "lts" && version_type == VersionType::Node {
-                println!("Installing latest LTS {} version...", type_color);
-                manager.install_latest_lts(version_type).await?;
+            let recommended = if record.recommended { format!(" {}", format::marker("recommended")) } else { String::new() };
+            println!("{}{}", format!("{} {}", version_type, record.version).bold(), recommended);
+            if let Some(date) = &record.date {
+                println!("Released:      {}", format::date(date));
+            }
+            let cycle = record.cycle.as_deref().map(|cycle| format!(" (cycle {})", cycle)).unwrap_or_default();
+            match (&record.eol, record.supported) {
+                (Some(eol), Some(false)) => println!("Support:       {}{}", format!("ended {}", eol).red(), cycle),
+                (Some(eol), _) => println!("Support:       until {}{}", eol, cycle),
+                (None, Some(false)) => println!("Support:       {}{}", "ended".red(), cycle),
+                (None, Some(true)) => println!("Support:       {}{}", "supported".green(), cycle),
+                (None, None) => println!("Support:       {}", "unknown".dimmed()),
+            }
+            if record.security_release {
+                println!("Security:      {}", "security release".yellow());
+            }
+            if record.advisories.is_empty() {
+                println!("Advisories:    {}", "none known".dimmed());
             } else {
-                println!("Installing {} version {}...", type_color, version.bold());
-                manager.install_version(&version, version_type).await?;
+                println!("Advisories:");
+                for advisory in &record.advisories {
+                    let url = advisory.url.as_deref().map(|url| format!(" {}", url.dimmed())).unwrap_or_default();
+                    println!("  {} {}{}", advisory.id.red(), advisory.affected, url);
+                }
             }
+            println!("Release notes: {}", record.release_notes);
+            println!("Sources:       {}", record.sources.join(", ").dimmed());
         }
-        Commands::Use { version, type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            let type_color = match version_type {
+        ToolCommands::Local(LocalArgs { version }) => {
+            manager.set_local_version(&version, version_type)?;
+            println!("Set local {} version to {} for the current directory", match version_type {
                 VersionType::Node => "Node.js".green().bold(),
                 VersionType::Rust => "Rust".yellow().bold(),
                 VersionType::Python => "Python".blue().bold(),
                 VersionType::Go => "Go".red().bold(),
-            };
+            }, version);
+        }
+        ToolCommands::Exec(ExecArgs { version, cwd, tty, quiet_install, hermetic, container, emulate, args }) => {
+            if args.is_empty() {
+                println!("No command specified");
+                return Ok(());
+            }
             
-            println!("Switching to {} version {}...", type_color, version.bold());
-            manager.use_version(&version, version_type)?;
+            let command = &args[0];
+            let command_args = if args.len() > 1 { &args[1..] } else { &[] };
+            
+            if quiet_install {
+                manager.set_progress_mode(ProgressMode::Quiet);
+            }
+            let options = ExecOptions { cwd: cwd.as_deref(), tty, hermetic, env: &[], emulate };
+            match container {
+                Some(image) => manager.exec_in_container(&version, command, command_args, version_type, &image, options).await?,
+                None => manager.exec_with_version(&version, command, command_args, version_type, options).await?,
+            }
         }
-        Commands::Installed { type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            let versions = manager.list_installed_versions(version_type)?;
+        ToolCommands::Env(args) => run_local_command(Local::new(manager), version_type, LocalCommand::Env(args), json)?,
+    }
+    
+    Ok(())
+}
+
+/// 只读取本地状态的工具子命令
+enum LocalCommand {
+    Installed,
+    Current,
+    Aliases,
+    Env(EnvArgs),
+}
+
+/// 执行只读取本地状态的工具子命令
+///
+/// 只拿到 `Local` 视图并且不是 async 函数，因此不会访问网络，离线或 DNS 不可用时也能立即返回。
+fn run_local_command(local: Local, version_type: VersionType, command: LocalCommand, json: bool) -> Result<()> {
+    match command {
+        LocalCommand::Installed => {
+            let versions = local.installed_versions(version_type)?;
             
             // 添加版本类型标题
             match version_type {
@@ -468,7 +3080,9 @@ async fn main() -> Result<()> {
             }
             
             for version in versions {
-                let is_current = version.contains("(current)");
+                let is_current = version.ends_with(" (current)");
+                let name = version.trim_end_matches(" (current)");
+                let version = if is_current { format!("{} {}", name, format::marker("current")) } else { name.to_string() };
                 let version_str = match version_type {
                     VersionType::Node => {
                         if is_current {
@@ -499,16 +3113,33 @@ async fn main() -> Result<()> {
                         }
                     },
                 };
-                println!("{}", version_str);
+                let mut labels = Vec::new();
+                if let Some(arch) = local.installed_arch_label(name, version_type)? {
+                    labels.push(arch);
+                }
+                if let Some(receipt) = local.install_receipt(name, version_type)? {
+                    labels.extend(receipt.build.iter().flat_map(|build| build.labels()));
+                    labels.push(format!("installed {}", format::date(&receipt.installed_at)));
+                    if let Some(bytes) = receipt.archive_bytes {
+                        labels.push(format!("{} download", format::size(bytes)));
+                    }
+                    labels.extend(receipt.flavor);
+                    if !receipt.skipped_modules.is_empty() {
+                        labels.push(format!("built without: {}", receipt.skipped_modules.join(", ")));
+                    }
+                }
+                if local.is_locked(name, version_type) {
+                    labels.push("locked".to_string());
+                }
+                if labels.is_empty() {
+                    println!("{}", version_str);
+                } else {
+                    println!("{} {}", version_str, format!("({})", labels.join(", ")).dimmed());
+                }
             }
         }
-        Commands::Remove { version, type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            manager.remove_version(&version, version_type)?;
-        }
-        Commands::Current { type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            if let Some(version) = manager.get_current_version(version_type) {
+        LocalCommand::Current => {
+            if let Some(version) = local.current_version(version_type) {
                 println!("Current {} version: {}", match version_type {
                     VersionType::Node => "Node.js".green().bold(),
                     VersionType::Rust => "Rust".yellow().bold(),
@@ -524,19 +3155,8 @@ async fn main() -> Result<()> {
                 });
             }
         }
-        Commands::Alias { name, version, type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            manager.create_alias(&name, &version, version_type)?;
-            println!("Created alias '{}' -> {} version {}", name, match version_type {
-                VersionType::Node => "Node.js".green().bold(),
-                VersionType::Rust => "Rust".yellow().bold(),
-                VersionType::Python => "Python".blue().bold(),
-                VersionType::Go => "Go".red().bold(),
-            }, version);
-        }
-        Commands::Aliases { type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            let aliases = manager.list_aliases(version_type)?;
+        LocalCommand::Aliases => {
+            let aliases = local.aliases(version_type)?;
             if aliases.is_empty() {
                 println!("No aliases defined for {}", match version_type {
                     VersionType::Node => "Node.js".green(),
@@ -551,375 +3171,267 @@ async fn main() -> Result<()> {
                     VersionType::Python => "Python".blue().bold(),
                     VersionType::Go => "Go".red().bold(),
                 });
+                let system = local.system_aliases(version_type)?;
                 for (alias, version) in aliases {
-                    println!("{} -> {}", alias, version);
+                    if system.iter().any(|(name, target)| *name == alias && *target == version) {
+                        println!("{} -> {} {}", alias, version, "(system)".dimmed());
+                    } else {
+                        println!("{} -> {}", alias, version);
+                    }
                 }
             }
         }
-        Commands::Local { version, type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            manager.set_local_version(&version, version_type)?;
-            println!("Set local {} version to {} for the current directory", match version_type {
-                VersionType::Node => "Node.js".green().bold(),
-                VersionType::Rust => "Rust".yellow().bold(),
-                VersionType::Python => "Python".blue().bold(),
-                VersionType::Go => "Go".red().bold(),
-            }, version);
-        }
-        Commands::Exec { version, type_, args } => {
-            let version_type = parse_version_type(&type_)?;
-            if args.is_empty() {
-                println!("No command specified");
+        LocalCommand::Env(EnvArgs { version }) => {
+            let (version, source) = match version {
+                Some(version) => (local.alias(&version, version_type)?.unwrap_or(version), "command line".to_string()),
+                None => local.resolve_version(version_type, &std::env::current_dir()?)?
+                    .map(|(version, source)| (version, source.to_string()))
+                    .ok_or(VersionError::NoVersionConfigured(version_type))?,
+            };
+            let (path, vars) = local.injected_env(&version, version_type);
+            
+            if json {
+                let env: serde_json::Map<String, serde_json::Value> = vars.into_iter()
+                    .map(|(key, value)| (key, serde_json::Value::String(value)))
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                    "tool": version_type.id(),
+                    "version": version,
+                    "source": source,
+                    "installed": local.is_installed(&version, version_type),
+                    "path": path,
+                    "env": env,
+                }))?);
                 return Ok(());
             }
             
-            let command = &args[0];
-            let command_args = if args.len() > 1 { &args[1..] } else { &[] };
-            
-            manager.exec_with_version(&version, command, command_args, version_type)?;
-        }
-        Commands::Clean => {
-            manager.clean()?;
-            println!("Cleaned cache and unnecessary files");
-        }
-        Commands::SelfUpdate => {
-            manager.self_update().await?;
-            println!("Updated ver to the latest version");
-        }
-        Commands::Migrate { source, type_ } => {
-            let version_type = parse_version_type(&type_)?;
-            let count = manager.migrate_from(&source, version_type).await?;
-            println!("Migrated {} versions from {}", count, source);
-        }
-        Commands::Rust(rust_command) => {
-            match rust_command {
-                RustCommands::List { stable } => {
-                    let versions = manager.list_available_rust_versions(stable).await?;
-                    if versions.is_empty() {
-                        println!("No Rust versions available");
-                    } else {
-                        println!("{}", "Available Rust Versions:".yellow().bold());
-                        for version in versions {
-                            // 检查版本是否为稳定版
-                            let is_stable = version.contains("stable") || version.contains("Stable");
-                            let version_str = if is_stable {
-                                format!("{} (Stable)", version).yellow()
-                            } else {
-                                version.yellow()
-                            };
-                            println!("{}", version_str);
-                        }
-                    }
-                }
-                RustCommands::Install { version } => {
-                    println!("Installing Rust version {}...", version.yellow().bold());
-                    manager.install_rust_version(&version).await?;
-                }
-                RustCommands::Use { version } => {
-                    // Check if version is an alias
-                    if let Some(aliased_version) = manager.get_rust_alias(&version)? {
-                        println!("Using alias '{}' -> {} version {}", 
-                            version, 
-                            "Rust".yellow().bold(), 
-                            aliased_version.yellow());
-                        manager.use_rust_version(&aliased_version)?;
-                    } else {
-                        println!("Switching to {} version {}...", 
-                            "Rust".yellow().bold(), 
-                            version.yellow());
-                        manager.use_rust_version(&version)?;
-                    }
-                }
-                RustCommands::Installed => {
-                    let versions = manager.list_installed_rust_versions()?;
-                    if versions.is_empty() {
-                        println!("No {} versions installed", "Rust".yellow());
-                    } else {
-                        println!("{}", "Installed Rust Versions:".yellow().bold());
-                        for version in versions {
-                            let is_current = version.contains("(current)");
-                            let version_str = if is_current {
-                                version.yellow().bold()
-                            } else {
-                                version.yellow()
-                            };
-                            println!("{}", version_str);
-                        }
-                    }
-                }
-                RustCommands::Remove { version } => {
-                    println!("Removing {} version {}...", 
-                        "Rust".yellow().bold(), 
-                        version.yellow());
-                    manager.remove_rust_version(&version)?;
-                }
-                RustCommands::Current => {
-                    if let Some(version) = manager.get_current_rust_version() {
-                        println!("Current {} version: {}", 
-                            "Rust".yellow().bold(), 
-                            version.yellow());
-                    } else {
-                        println!("No active {} version", "Rust".yellow());
-                    }
-                }
-                RustCommands::Alias { name, version } => {
-                    manager.create_rust_alias(&name, &version)?;
-                    println!("Created alias '{}' -> {} version {}", name, "Rust".yellow().bold(), version);
-                }
-                RustCommands::Aliases => {
-                    let aliases = manager.list_rust_aliases()?;
-                    if aliases.is_empty() {
-                        println!("No aliases defined for Rust");
-                    } else {
-                        println!("Defined aliases for Rust:");
-                        for (alias, version) in aliases {
-                            println!("{} -> {}", alias, version);
-                        }
-                    }
-                }
-                RustCommands::Local { version } => {
-                    manager.set_local_rust_version(&version)?;
-                    println!("Set local Rust version to {} for the current directory", version);
-                }
-                RustCommands::Exec { version, args } => {
-                    if args.is_empty() {
-                        println!("No command specified");
-                        return Ok(());
-                    }
-                    
-                    let command = &args[0];
-                    let command_args = if args.len() > 1 { &args[1..] } else { &[] };
-                    
-                    manager.exec_with_rust_version(&version, command, command_args)?;
-                }
-                RustCommands::Migrate { source } => {
-                    manager.migrate_from(&source, VersionType::Rust).await?;
-                }
+            println!("# {} {} ({})", version_type.id(), version, source);
+            if !local.is_installed(&version, version_type) {
+                println!("# not installed; `ver {} exec` installs it first", version_type.id());
             }
-        }
-        Commands::Python(python_command) => {
-            match python_command {
-                PythonCommands::List { stable } => {
-                    let versions = manager.list_available_python_versions(stable).await?;
-                    if versions.is_empty() {
-                        println!("No Python versions available");
-                    } else {
-                        println!("{}", "Available Python Versions:".blue().bold());
-                        for version in versions {
-                            // 检查版本是否为稳定版
-                            let is_stable = version.contains("stable") || version.contains("Stable");
-                            let version_str = if is_stable {
-                                format!("{} (Stable)", version).blue()
-                            } else {
-                                version.blue()
-                            };
-                            println!("{}", version_str);
-                        }
-                    }
-                }
-                PythonCommands::Install { version } => {
-                    println!("Installing Python version {}...", version.blue().bold());
-                    manager.install_python_version(&version).await?;
-                }
-                PythonCommands::Use { version } => {
-                    // Check if version is an alias
-                    if let Some(aliased_version) = manager.get_python_alias(&version)? {
-                        println!("Using alias '{}' -> {} version {}", 
-                            version, 
-                            "Python".blue().bold(), 
-                            aliased_version.blue());
-                        manager.use_python_version(&aliased_version)?;
-                    } else {
-                        println!("Switching to {} version {}...", 
-                            "Python".blue().bold(), 
-                            version.blue());
-                        manager.use_python_version(&version)?;
-                    }
-                }
-                PythonCommands::Installed => {
-                    let versions = manager.list_installed_python_versions()?;
-                    if versions.is_empty() {
-                        println!("No {} versions installed", "Python".blue());
-                    } else {
-                        println!("{}", "Installed Python Versions:".blue().bold());
-                        for version in versions {
-                            let is_current = version.contains("(current)");
-                            let version_str = if is_current {
-                                version.blue().bold()
-                            } else {
-                                version.blue()
-                            };
-                            println!("{}", version_str);
-                        }
-                    }
-                }
-                PythonCommands::Remove { version } => {
-                    println!("Removing {} version {}...", 
-                        "Python".blue().bold(), 
-                        version.blue());
-                    manager.remove_python_version(&version)?;
-                }
-                PythonCommands::Current => {
-                    if let Some(version) = manager.get_current_python_version() {
-                        println!("Current {} version: {}", 
-                            "Python".blue().bold(), 
-                            version.blue());
-                    } else {
-                        println!("No active {} version", "Python".blue());
-                    }
-                }
-                PythonCommands::Alias { name, version } => {
-                    manager.create_python_alias(&name, &version)?;
-                    println!("Created alias '{}' -> {} version {}", name, "Python".blue().bold(), version);
-                }
-                PythonCommands::Aliases => {
-                    let aliases = manager.list_python_aliases()?;
-                    if aliases.is_empty() {
-                        println!("No aliases defined for Python");
-                    } else {
-                        println!("Defined aliases for Python:");
-                        for (alias, version) in aliases {
-                            println!("{} -> {}", alias, version);
-                        }
-                    }
-                }
-                PythonCommands::Local { version } => {
-                    manager.set_local_python_version(&version)?;
-                    println!("Set local Python version to {} for the current directory", version);
-                }
-                PythonCommands::Exec { version, args } => {
-                    if args.is_empty() {
-                        println!("No command specified");
-                        return Ok(());
-                    }
-                    
-                    let command = &args[0];
-                    let command_args = if args.len() > 1 { &args[1..] } else { &[] };
-                    
-                    manager.exec_with_python_version(&version, command, command_args)?;
-                }
-                PythonCommands::Migrate { source: _ } => {
-                    manager.migrate_from_pyenv().await?;
-                }
+            let path: Vec<String> = path.iter().map(|dir| dir.display().to_string()).collect();
+            println!("export PATH={}:\"$PATH\"", version_manager::sh_quote(&path.join(":")));
+            for (key, value) in vars {
+                println!("export {}={}", key, version_manager::sh_quote(&value));
             }
         }
-        Commands::Go(go_command) => {
-            match go_command {
-                GoCommands::List { stable } => {
-                    let versions = manager.list_available_go_versions(stable).await?;
-                    if versions.is_empty() {
-                        println!("No Go versions available");
-                    } else {
-                        println!("{}", "Available Go Versions:".red().bold());
-                        for version in versions {
-                            // 检查版本是否为稳定版
-                            let is_stable = version.contains("stable") || version.contains("Stable");
-                            let version_str = if is_stable {
-                                format!("{} (Stable)", version).red()
-                            } else {
-                                version.red()
-                            };
-                            println!("{}", version_str);
-                        }
-                    }
-                }
-                GoCommands::Install { version } => {
-                    println!("Installing Go version {}...", version.red().bold());
-                    manager.install_go_version(&version).await?;
-                }
-                GoCommands::Use { version } => {
-                    // Check if version is an alias
-                    if let Some(aliased_version) = manager.get_go_alias(&version)? {
-                        println!("Using alias '{}' -> {} version {}", 
-                            version, 
-                            "Go".red().bold(), 
-                            aliased_version.red());
-                        manager.use_go_version(&aliased_version)?;
-                    } else {
-                        println!("Switching to {} version {}...", 
-                            "Go".red().bold(), 
-                            version.red());
-                        manager.use_go_version(&version)?;
-                    }
-                }
-                GoCommands::Installed => {
-                    let versions = manager.list_installed_go_versions()?;
-                    if versions.is_empty() {
-                        println!("No {} versions installed", "Go".red());
-                    } else {
-                        println!("{}", "Installed Go Versions:".red().bold());
-                        for version in versions {
-                            let is_current = version.contains("(current)");
-                            let version_str = if is_current {
-                                version.red().bold()
-                            } else {
-                                version.red()
-                            };
-                            println!("{}", version_str);
-                        }
-                    }
-                }
-                GoCommands::Remove { version } => {
-                    println!("Removing {} version {}...", 
-                        "Go".red().bold(), 
-                        version.red());
-                    manager.remove_go_version(&version)?;
-                }
-                GoCommands::Current => {
-                    if let Some(version) = manager.get_current_go_version() {
-                        println!("Current {} version: {}", 
-                            "Go".red().bold(), 
-                            version.red());
-                    } else {
-                        println!("No active {} version", "Go".red());
-                    }
-                }
-                GoCommands::Alias { name, version } => {
-                    manager.create_go_alias(&name, &version)?;
-                    println!("Created alias '{}' -> {} version {}", name, "Go".red().bold(), version);
-                }
-                GoCommands::Aliases => {
-                    let aliases = manager.list_go_aliases()?;
-                    if aliases.is_empty() {
-                        println!("No aliases defined for Go");
-                    } else {
-                        println!("Defined aliases for Go:");
-                        for (alias, version) in aliases {
-                            println!("{} -> {}", alias, version);
-                        }
-                    }
-                }
-                GoCommands::Local { version } => {
-                    manager.set_local_go_version(&version)?;
-                    println!("Set local Go version to {} for the current directory", version);
-                }
-                GoCommands::Exec { version, args } => {
-                    if args.is_empty() {
-                        println!("No command specified");
-                        return Ok(());
-                    }
-                    
-                    let command = &args[0];
-                    let command_args = if args.len() > 1 { &args[1..] } else { &[] };
-                    
-                    manager.exec_with_go_version(&version, command, command_args)?;
-                }
-                GoCommands::Migrate { source: _ } => {
-                    manager.migrate_from_gvm().await?;
-                }
-            }
+    }
+    
+    Ok(())
+}
+
+/// `ver list --long` 中版本后面的维护状态、安全公告和发布说明
+fn format_metadata(record: &metadata::VersionMetadata) -> String {
+    let mut details = String::new();
+    match (&record.eol, record.supported) {
+        (Some(eol), Some(false)) => details.push_str(&format!(" {}", format!("eol {}", eol).red())),
+        (Some(eol), _) => details.push_str(&format!(" {}", format!("supported until {}", eol).dimmed())),
+        (None, Some(false)) => details.push_str(&format!(" {}", "eol".red())),
+        _ => {}
+    }
+    if record.security_release {
+        details.push_str(&format!(" {}", format::marker("security").yellow()));
+    }
+    if !record.advisories.is_empty() {
+        details.push_str(&format!(" {}", format!("{} advisories", record.advisories.len()).red()));
+    }
+    details.push_str(&format!(" {}", record.release_notes.dimmed()));
+    details
+}
+
+/// 在终端中启动分页器，标准输出不是终端或使用 `--plain` 时返回None
+///
+/// 使用 $PAGER，未设置时使用 `less -FRX`（内容不足一屏时直接输出并退出）。
+fn spawn_pager() -> Option<std::process::Child> {
+    if !std::io::stdout().is_terminal() || format::is_plain() {
+        return None;
+    }
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -FRX".to_string());
+    let mut parts = pager.split_whitespace();
+    let program = parts.next().filter(|program| *program != "cat")?;
+    std::process::Command::new(program)
+        .args(parts)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .ok()
+}
+
+/// 打印只对当前终端生效的版本切换，供 shell 的 eval 执行
+///
+/// 标准输出只包含 shell 命令；直接在终端中运行时在标准错误提示需要 eval。
+fn print_session_activation(manager: &VersionManager, version: Option<&str>, version_type: VersionType) -> Result<()> {
+    let changes = manager.session_activation(version, version_type)?;
+    let fish = std::env::var("SHELL").unwrap_or_default().ends_with("fish");
+    for (key, value) in changes {
+        match (value, fish) {
+            (Some(value), true) => println!("set -gx {} {};", key, version_manager::fish_quote(&value)),
+            (Some(value), false) => println!("export {}={};", key, version_manager::sh_quote(&value)),
+            (None, true) => println!("set -e {};", key),
+            (None, false) => println!("unset {};", key),
         }
     }
+    if std::io::stdout().is_terminal() {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        eprintln!("{}", format!("Run `eval \"$(ver {})\"` to apply this to the current terminal", args.join(" ")).dimmed());
+    }
+    Ok(())
+}
 
+/// 检查当前目录的Go工作区是否要求更高的工具链
+///
+/// 与 Go 1.21+ 的 GOTOOLCHAIN=auto 行为一致：激活的版本低于 go.work/go.mod 的要求时发出警告，
+/// 在交互式终端中询问是否安装并切换到要求的版本。
+async fn check_go_toolchain(manager: &mut VersionManager, active: &str) -> Result<()> {
+    let Some(requirement) = manager.go_toolchain_requirement(&std::env::current_dir()?)? else {
+        return Ok(());
+    };
+    if version_manager::compare_versions(active, &requirement.version) != std::cmp::Ordering::Less {
+        return Ok(());
+    }
+    
+    println!("{} {} requires Go {}, but Go {} is active",
+        "Warning:".yellow().bold(),
+        requirement.file.display(),
+        requirement.version.red(),
+        active);
+    
+    if !std::io::stdin().is_terminal() {
+        println!("Run `ver go install {}` and `ver go use {}` to match it.", requirement.version, requirement.version);
+        return Ok(());
+    }
+    
+    let answer = output::prompt(&format!("Install and switch to Go {}? [y/N] ", requirement.version))?;
+    if matches!(answer.as_str(), "y" | "Y" | "yes") {
+        manager.install_version(&requirement.version, VersionType::Go).await?;
+        manager.use_version(&requirement.version, VersionType::Go)?;
+    }
     Ok(())
 }
 
+/// 在后台重新运行当前的 install 命令
+///
+/// 子进程使用相同的参数（去掉 `--detach`）并带上预先分配的操作ID，
+/// 输出被丢弃，进度和结果写入操作记录。返回子进程的进程号。
+fn spawn_detached(operation_id: &str) -> Result<u32> {
+    let exe = std::env::current_exe()?;
+    let mut command = std::process::Command::new(exe);
+    command.args(std::env::args().skip(1).filter(|arg| arg != "--detach"))
+        .arg("--operation-id")
+        .arg(operation_id)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    // 放到单独的进程组，终端中的 Ctrl-C 不会中断后台安装
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    Ok(command.spawn()?.id())
+}
+
+/// 询问是否执行某项操作，直接回车表示同意
+///
+/// # 参数
+///
+/// * `question` - 询问的内容
+/// * `assume_yes` - 是否不询问直接同意（`--yes`）
+///
+/// # 返回
+///
+/// 同意时返回true；设置了 `assume_yes` 时不询问直接返回true，标准输入不是终端时不询问直接返回false。
+fn confirm(question: &str, assume_yes: bool) -> Result<bool> {
+    if assume_yes {
+        println!("{} yes", question);
+        return Ok(true);
+    }
+    if !std::io::stdin().is_terminal() {
+        println!("{} skipped (not a terminal, use --yes to accept)", question);
+        return Ok(false);
+    }
+    
+    let answer = output::prompt(&format!("{} [Y/n] ", question))?;
+    Ok(matches!(answer.as_str(), "" | "y" | "Y" | "yes"))
+}
+
+/// 以JSON对象的形式把错误输出到标准错误
+///
+/// 包含错误代码、相关的工具、版本、下载地址和HTTP状态，以及错误原因链和建议，
+/// 供调用 ver 的脚本和编辑器插件解析。
+///
+/// # 参数
+///
+/// * `err` - 要输出的错误
+/// * `suggestions` - 解决问题的建议
+fn print_json_error(err: &anyhow::Error, suggestions: &[String]) {
+    let version_error = err.downcast_ref::<VersionError>();
+    let causes: Vec<String> = err.chain().skip(1).map(|cause| cause.to_string()).collect();
+    
+    let output = serde_json::json!({
+        "error": {
+            "code": version_error.map(|e| e.code()).unwrap_or("error"),
+            "message": err.to_string(),
+            "context": {
+                "tool": version_error.and_then(|e| e.version_type()).map(|t| t.id()),
+                "version": version_error.and_then(|e| e.version()),
+                "url": version_error.and_then(|e| e.url()),
+                "status": version_error.and_then(|e| e.http_status()),
+            },
+            "causes": causes,
+            "suggestions": suggestions,
+        }
+    });
+    eprintln!("{}", output);
+}
+
+/// 收集错误的修复建议
+///
+/// 优先使用 VersionError 自带的建议，其他错误根据错误链中的IO错误给出建议。
+fn error_suggestions(err: &anyhow::Error) -> Vec<String> {
+    if let Some(version_error) = err.downcast_ref::<VersionError>() {
+        return version_error.suggestions();
+    }
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .map(version_manager::io_error_suggestions)
+        .unwrap_or_default()
+}
+
+/// 确定通用子命令作用的工具
+///
+/// 没有指定 `--type` 时根据当前目录的项目版本文件推断，无法推断时使用 Node.js。
+fn tool_type(manager: &VersionManager, type_: Option<&str>, json: bool) -> Result<VersionType> {
+    tool_type_in(manager, type_, &std::env::current_dir()?, json)
+}
+
+/// 同 `tool_type`，但从指定目录查找版本文件
+fn tool_type_in(manager: &VersionManager, type_: Option<&str>, dir: &std::path::Path, json: bool) -> Result<VersionType> {
+    if let Some(type_) = type_ {
+        return parse_version_type(type_);
+    }
+    match manager.infer_version_type(dir)? {
+        Some((version_type, file)) => {
+            if !json && version_type != VersionType::Node {
+                eprintln!("{}", format!("Using {} (from {}); pass --type to override", version_type, file.display()).dimmed());
+            }
+            Ok(version_type)
+        }
+        None => Ok(VersionType::Node),
+    }
+}
+
 fn parse_version_type(type_: &str) -> Result<VersionType> {
-    match type_.to_lowercase().as_str() {
-        "node" => Ok(VersionType::Node),
-        "rust" => Ok(VersionType::Rust),
-        "python" => Ok(VersionType::Python),
-        "go" => Ok(VersionType::Go),
-        _ => anyhow::bail!("Unsupported version type: {}. Use 'node', 'rust', 'python', or 'go'.", type_),
+    if let Some(version_type) = VersionType::from_id(type_) {
+        return Ok(version_type);
+    }
+    // 拼写错误时提示最接近的工具名
+    let closest = VersionType::all()
+        .into_iter()
+        .map(|version_type| (version_manager::edit_distance(&type_.to_lowercase(), version_type.id()), version_type.id()))
+        .filter(|(distance, _)| *distance <= 2)
+        .min();
+    match closest {
+        Some((_, id)) => anyhow::bail!("Unsupported version type: {}. Did you mean '{}'?", type_, id),
+        None => anyhow::bail!("Unsupported version type: {}. Use 'node', 'rust', 'python', or 'go'.", type_),
     }
 }
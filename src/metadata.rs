@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::policy::Policy;
+use crate::version_manager::{RemoteRelease, VersionType};
+
+/// endoflife.date 的默认接口地址
+pub const DEFAULT_EOL_URL: &str = "https://endoflife.date/api";
+
+/// GitHub API 地址
+pub const GITHUB_API: &str = "https://api.github.com";
+
+/// 补充来源缓存的有效期（秒）
+pub const CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// 一个发布周期（例如 Node.js 20、Python 3.12）的维护信息，来自 endoflife.date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cycle {
+    pub cycle: String,
+    /// 停止维护的日期（YYYY-MM-DD）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eol: Option<String>,
+    /// 没有公布日期，只标记了是否已停止维护
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended: Option<bool>,
+}
+
+/// 安全公告源中的一条公告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Advisory {
+    /// 工具标识
+    #[serde(default)]
+    pub tool: String,
+    /// 公告编号，例如 CVE-2024-27983
+    pub id: String,
+    /// 受影响的版本范围，语法见 [`Policy::range_matches`]
+    pub affected: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// 从补充来源获取的一个工具的信息，缓存在下载缓存目录中
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Sources {
+    /// 获取时间（Unix 时间戳）
+    pub fetched_at: i64,
+    /// 获取成功的来源
+    pub available: Vec<String>,
+    pub cycles: Vec<Cycle>,
+    /// GitHub Releases 中的标签到发布页面地址
+    pub releases: BTreeMap<String, String>,
+    pub advisories: Vec<Advisory>,
+}
+
+/// 合并版本索引和补充来源后的版本信息，`ver info`、`ver list --long` 和 `--json` 输出的都是它
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionMetadata {
+    /// 工具标识
+    pub tool: &'static str,
+    pub version: String,
+    /// 发布日期
+    pub date: Option<String>,
+    /// LTS 或稳定版本
+    pub recommended: bool,
+    /// 版本索引标记为安全更新（目前只有 Node.js 的索引提供）
+    pub security_release: bool,
+    /// 发布说明地址，GitHub Releases 中有该版本时使用其页面
+    pub release_notes: String,
+    /// 所属的发布周期
+    pub cycle: Option<String>,
+    /// 停止维护的日期
+    pub eol: Option<String>,
+    /// 是否仍在维护，没有维护信息时为None
+    pub supported: Option<bool>,
+    /// 影响该版本的安全公告
+    pub advisories: Vec<Advisory>,
+    /// 提供了信息的来源
+    pub sources: Vec<String>,
+}
+
+/// 工具在 endoflife.date 中的产品名
+fn eol_product(version_type: VersionType) -> &'static str {
+    match version_type {
+        VersionType::Node => "nodejs",
+        VersionType::Rust => "rust",
+        VersionType::Python => "python",
+        VersionType::Go => "go",
+    }
+}
+
+/// 在 GitHub Releases 发布版本的仓库，Python 和 Go 只打标签，不发布 Release
+fn github_repo(version_type: VersionType) -> Option<&'static str> {
+    match version_type {
+        VersionType::Node => Some("nodejs/node"),
+        VersionType::Rust => Some("rust-lang/rust"),
+        VersionType::Python | VersionType::Go => None,
+    }
+}
+
+/// 从所有补充来源获取一个工具的信息
+///
+/// 各来源互不依赖，某个来源不可用时跳过它，不影响其他来源。
+///
+/// # 参数
+///
+/// * `version_type` - 版本类型
+/// * `eol_url` - endoflife.date 格式的接口地址
+/// * `github_api` - GitHub API 地址
+/// * `security_feed` - 安全公告源地址，未配置时为None
+///
+/// # 返回
+///
+/// 返回获取到的信息，`available` 中记录获取成功的来源。
+pub async fn fetch(version_type: VersionType, eol_url: &str, github_api: &str, security_feed: Option<&str>) -> Sources {
+    let client = reqwest::Client::new();
+    let mut sources = Sources { fetched_at: chrono::Utc::now().timestamp(), ..Sources::default() };
+
+    let url = format!("{}/{}.json", eol_url.trim_end_matches('/'), eol_product(version_type));
+    if let Ok(cycles) = fetch_cycles(&client, &url).await {
+        sources.cycles = cycles;
+        sources.available.push("endoflife.date".to_string());
+    }
+    if let Some(repo) = github_repo(version_type)
+        && let Ok(releases) = fetch_github_releases(&client, github_api, repo).await
+    {
+        sources.releases = releases;
+        sources.available.push("github".to_string());
+    }
+    if let Some(feed) = security_feed
+        && let Ok(advisories) = fetch_advisories(&client, feed, version_type).await
+    {
+        sources.advisories = advisories;
+        sources.available.push("security-feed".to_string());
+    }
+    sources
+}
+
+/// 读取 endoflife.date 的发布周期列表
+///
+/// `eol` 字段是日期或布尔值，布尔值表示没有公布日期。
+async fn fetch_cycles(client: &reqwest::Client, url: &str) -> anyhow::Result<Vec<Cycle>> {
+    let entries: Vec<serde_json::Value> = client.get(url).send().await?.error_for_status()?.json().await?;
+    Ok(entries.into_iter()
+        .filter_map(|entry| {
+            let cycle = match entry.get("cycle")? {
+                serde_json::Value::String(cycle) => cycle.clone(),
+                serde_json::Value::Number(cycle) => cycle.to_string(),
+                _ => return None,
+            };
+            let (eol, ended) = match entry.get("eol") {
+                Some(serde_json::Value::String(date)) => (Some(date.clone()), None),
+                Some(serde_json::Value::Bool(ended)) => (None, Some(*ended)),
+                _ => (None, None),
+            };
+            Some(Cycle { cycle, eol, ended })
+        })
+        .collect())
+}
+
+/// 读取仓库最近的 GitHub Releases，设置了 GITHUB_TOKEN 时用它提高访问频率限制
+async fn fetch_github_releases(client: &reqwest::Client, api: &str, repo: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut request = client.get(format!("{}/repos/{}/releases?per_page=100", api.trim_end_matches('/'), repo))
+        .header(reqwest::header::USER_AGENT, "ver")
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json");
+    if let Ok(token) = std::env::var("GITHUB_TOKEN")
+        && !token.is_empty()
+    {
+        request = request.bearer_auth(token);
+    }
+    let releases: Vec<serde_json::Value> = request.send().await?.error_for_status()?.json().await?;
+    Ok(releases.iter()
+        .filter_map(|release| Some((
+            release.get("tag_name")?.as_str()?.to_string(),
+            release.get("html_url")?.as_str()?.to_string(),
+        )))
+        .collect())
+}
+
+/// 读取安全公告源中属于该工具的公告
+async fn fetch_advisories(client: &reqwest::Client, url: &str, version_type: VersionType) -> anyhow::Result<Vec<Advisory>> {
+    let advisories: Vec<Advisory> = client.get(url).send().await?.error_for_status()?.json().await?;
+    Ok(advisories.into_iter()
+        .filter(|advisory| VersionType::from_id(&advisory.tool) == Some(version_type))
+        .collect())
+}
+
+/// 合并版本索引中的一个版本和补充来源的信息
+///
+/// # 参数
+///
+/// * `version_type` - 版本类型
+/// * `release` - 版本索引中的版本
+/// * `sources` - 补充来源的信息
+/// * `today` - 当前日期（YYYY-MM-DD），用于判断是否已停止维护
+///
+/// # 返回
+///
+/// 返回合并后的版本信息。
+pub fn merge(version_type: VersionType, release: &RemoteRelease, sources: &Sources, today: &str) -> VersionMetadata {
+    let version = release.version.trim_start_matches('v').to_string();
+    let mut used = vec!["index".to_string()];
+
+    // 周期是版本号的前缀，例如 3.12 和 3.12.1，取最长的匹配
+    let cycle = sources.cycles.iter()
+        .filter(|cycle| version == cycle.cycle || version.starts_with(&format!("{}.", cycle.cycle)))
+        .max_by_key(|cycle| cycle.cycle.len());
+    let supported = cycle.and_then(|cycle| match (&cycle.eol, cycle.ended) {
+        (Some(eol), _) => Some(eol.as_str() > today),
+        (None, Some(ended)) => Some(!ended),
+        (None, None) => None,
+    });
+    if cycle.is_some() {
+        used.push("endoflife.date".to_string());
+    }
+
+    let release_notes = match sources.releases.get(&format!("v{}", version)).or_else(|| sources.releases.get(&version)) {
+        Some(url) => {
+            used.push("github".to_string());
+            url.clone()
+        }
+        None => version_type.release_notes_url(&version),
+    };
+
+    let advisories: Vec<Advisory> = sources.advisories.iter()
+        .filter(|advisory| Policy::range_matches(&advisory.affected, &version) == Some(true))
+        .cloned()
+        .collect();
+    if sources.available.iter().any(|source| source == "security-feed") {
+        used.push("security-feed".to_string());
+    }
+
+    VersionMetadata {
+        tool: version_type.id(),
+        version,
+        date: release.date.clone(),
+        recommended: release.is_recommended(),
+        security_release: release.security,
+        release_notes,
+        cycle: cycle.map(|cycle| cycle.cycle.clone()),
+        eol: cycle.and_then(|cycle| cycle.eol.clone()),
+        supported,
+        advisories,
+        sources: used,
+    }
+}
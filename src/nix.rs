@@ -0,0 +1,108 @@
+use crate::version_manager::{FrozenToolchain, VersionType};
+
+/// flake 中一个工具的固定方式
+pub struct NixPin {
+    pub version_type: VersionType,
+    pub version: String,
+    /// 按 Nix 系统列出的预编译包，只有 Node.js 和 Go 使用
+    pub sources: Vec<(&'static str, FrozenToolchain)>,
+}
+
+/// 固定方式用到的公共部分
+///
+/// 预编译包按当前系统选择下载地址，Linux 上用 autoPatchelfHook 修正动态链接器路径。
+const PREBUILT: &str = r#"        prebuilt = { pname, version, sources }:
+          let
+            source = sources.${system} or (throw "${pname} ${version} is not available for ${system}");
+          in
+          pkgs.stdenv.mkDerivation {
+            inherit pname version;
+            src = pkgs.fetchurl { inherit (source) url sha256; };
+            nativeBuildInputs = pkgs.lib.optionals pkgs.stdenv.isLinux [ pkgs.autoPatchelfHook ];
+            buildInputs = pkgs.lib.optionals pkgs.stdenv.isLinux [ pkgs.stdenv.cc.cc.lib ];
+            dontConfigure = true;
+            dontBuild = true;
+            installPhase = "mkdir -p $out && cp -r . $out";
+          };
+"#;
+
+/// 生成 flake.nix
+///
+/// # 参数
+///
+/// * `pins` - 项目固定的工具版本
+/// * `systems` - flake 支持的 Nix 系统
+///
+/// # 返回
+///
+/// 返回 flake.nix 的内容，devShells.default 包含所有固定的工具。
+pub fn flake(pins: &[NixPin], systems: &[&str]) -> String {
+    let has_rust = pins.iter().any(|pin| pin.version_type == VersionType::Rust);
+    let has_prebuilt = pins.iter().any(|pin| !pin.sources.is_empty());
+    let systems: Vec<String> = systems.iter()
+        .map(|system| format!("\"{}\"", system))
+        .collect();
+    
+    let mut out = String::new();
+    out.push_str("# Generated by `ver export --format nix` from the versions pinned for this project\n");
+    out.push_str("{\n  description = \"Development shell with the toolchains pinned by ver\";\n\n");
+    out.push_str("  inputs = {\n");
+    out.push_str("    nixpkgs.url = \"github:NixOS/nixpkgs/nixos-unstable\";\n");
+    out.push_str("    flake-utils.url = \"github:numtide/flake-utils\";\n");
+    if has_rust {
+        out.push_str("    rust-overlay.url = \"github:oxalica/rust-overlay\";\n");
+        out.push_str("    rust-overlay.inputs.nixpkgs.follows = \"nixpkgs\";\n");
+    }
+    out.push_str("  };\n\n");
+    
+    let inputs = if has_rust { "self, nixpkgs, flake-utils, rust-overlay" } else { "self, nixpkgs, flake-utils" };
+    out.push_str(&format!("  outputs = {{ {} }}:\n", inputs));
+    out.push_str(&format!("    flake-utils.lib.eachSystem [ {} ] (system:\n", systems.join(" ")));
+    out.push_str("      let\n");
+    if has_rust {
+        out.push_str("        pkgs = import nixpkgs { inherit system; overlays = [ rust-overlay.overlays.default ]; };\n");
+    } else {
+        out.push_str("        pkgs = import nixpkgs { inherit system; };\n");
+    }
+    if has_prebuilt {
+        out.push_str(PREBUILT);
+    }
+    
+    let mut packages = Vec::new();
+    for pin in pins {
+        match pin.version_type {
+            VersionType::Node | VersionType::Go if !pin.sources.is_empty() => {
+                let (name, pname) = match pin.version_type {
+                    VersionType::Node => ("nodejs", "nodejs"),
+                    _ => ("go", "go"),
+                };
+                out.push_str(&format!("        {} = prebuilt {{\n", name));
+                out.push_str(&format!("          pname = \"{}\";\n", pname));
+                out.push_str(&format!("          version = \"{}\";\n", pin.version));
+                out.push_str("          sources = {\n");
+                for (system, toolchain) in &pin.sources {
+                    out.push_str(&format!("            {} = {{ url = \"{}\"; sha256 = \"{}\"; }};\n", system, toolchain.url, toolchain.sha256));
+                }
+                out.push_str("          };\n        };\n");
+                packages.push(name.to_string());
+            }
+            VersionType::Node | VersionType::Go => {
+                packages.push(format!("# {} {}: no prebuilt archives found", pin.version_type, pin.version));
+            }
+            VersionType::Rust => packages.push(format!("pkgs.rust-bin.stable.\"{}\".default", pin.version)),
+            VersionType::Python => {
+                // nixpkgs 只按小版本提供 Python，补丁版本由 nixpkgs 的版本决定
+                let minor: Vec<&str> = pin.version.split('.').take(2).collect();
+                packages.push(format!("pkgs.python{} # ver pins {}; nixpkgs provides the latest {}.x", minor.concat(), pin.version, minor.join(".")));
+            }
+        }
+    }
+    
+    out.push_str("      in\n      {\n");
+    out.push_str("        devShells.default = pkgs.mkShell {\n          packages = [\n");
+    for package in &packages {
+        out.push_str(&format!("            {}\n", package));
+    }
+    out.push_str("          ];\n        };\n      });\n}\n");
+    out
+}
@@ -0,0 +1,59 @@
+use indicatif::{MultiProgress, ProgressBar};
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::sync::LazyLock;
+
+/// 当前显示的所有进度条
+///
+/// 进度条在绘制时会覆盖终端最后几行，其他输出直接写入终端会把进度条拆散成多行。
+/// 所有进度条登记在这里，其他输出先暂时清除进度条，写完后再重新绘制。
+static BARS: LazyLock<MultiProgress> = LazyLock::new(MultiProgress::new);
+
+/// 登记进度条，之后通过本模块的输出不会破坏它
+///
+/// # 参数
+///
+/// * `bar` - 新建的进度条
+///
+/// # 返回
+///
+/// 返回登记后的进度条。
+pub fn add(bar: ProgressBar) -> ProgressBar {
+    BARS.add(bar)
+}
+
+/// 暂时清除进度条，执行 `f` 后重新绘制
+///
+/// 用于等待用户输入，或运行直接输出到终端的子进程。
+pub fn suspend<R>(f: impl FnOnce() -> R) -> R {
+    BARS.suspend(f)
+}
+
+/// 在标准输出写一行，不破坏进度条
+pub fn println(message: impl Display) {
+    suspend(|| println!("{}", message));
+}
+
+/// 在标准错误写一行，不破坏进度条
+pub fn eprintln(message: impl Display) {
+    suspend(|| eprintln!("{}", message));
+}
+
+/// 显示提示并读取一行输入，等待输入期间不显示进度条
+///
+/// # 参数
+///
+/// * `question` - 提示文字，不换行
+///
+/// # 返回
+///
+/// 成功时返回去掉首尾空白的输入，读写终端失败时返回错误。
+pub fn prompt(question: &str) -> io::Result<String> {
+    suspend(|| {
+        print!("{}", question);
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        Ok(answer.trim().to_string())
+    })
+}
@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::version_manager::{compare_versions, VersionType};
+
+/// 团队策略文件
+///
+/// 由平台团队统一维护，声明每个工具允许使用的版本范围，例如：
+///
+/// ```json
+/// {
+///   "mode": "deny",
+///   "tools": { "node": ">=18 <23", "python": ">=3.10 || =3.8.18" }
+/// }
+/// ```
+///
+/// 文件路径通过配置项 `policy.file` 或环境变量 VER_POLICY_FILE 指定。
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Policy {
+    /// 违反策略时的处理方式：warn（默认）或 deny
+    #[serde(default)]
+    pub mode: PolicyMode,
+    /// 每个工具允许的版本范围，按工具标识分组
+    #[serde(default)]
+    pub tools: BTreeMap<String, String>,
+}
+
+/// 违反策略时的处理方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyMode {
+    /// 打印警告后继续
+    #[default]
+    Warn,
+    /// 拒绝安装或切换
+    Deny,
+}
+
+impl Policy {
+    /// 从文件读取策略
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 策略文件路径
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回策略，文件不存在或格式错误时返回错误。
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("无法读取策略文件 {}", path.display()))?;
+        let policy: Policy = serde_json::from_str(&content)
+            .with_context(|| format!("无法解析策略文件 {}", path.display()))?;
+
+        for (tool, range) in &policy.tools {
+            if VersionType::from_id(tool).is_none() {
+                return Err(anyhow::anyhow!("策略文件 {} 中有不支持的工具类型: {}", path.display(), tool));
+            }
+            Self::parse_range(range)
+                .with_context(|| format!("策略文件 {} 中 {} 的版本范围无效", path.display(), tool))?;
+        }
+        Ok(policy)
+    }
+
+    /// 获取某个工具允许的版本范围
+    pub fn range(&self, version_type: VersionType) -> Option<&str> {
+        self.tools.get(version_type.id()).map(|range| range.as_str())
+    }
+
+    /// 检查版本是否在策略允许的范围内
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `version` - 版本号
+    ///
+    /// # 返回
+    ///
+    /// 允许或策略中没有该工具时返回true。
+    pub fn allows(&self, version_type: VersionType, version: &str) -> bool {
+        let Some(range) = self.range(version_type) else {
+            return true;
+        };
+        Self::range_matches(range, version).unwrap_or(true)
+    }
+
+    /// 检查版本是否满足版本范围，范围的语法见 [`Policy::parse_range`]
+    ///
+    /// # 返回
+    ///
+    /// 范围无效时返回None。
+    pub fn range_matches(range: &str, version: &str) -> Option<bool> {
+        let alternatives = Self::parse_range(range).ok()?;
+        Some(alternatives.iter().any(|comparators| {
+            comparators.iter().all(|(op, bound)| Self::matches(version, op, bound))
+        }))
+    }
+
+    /// 解析版本范围
+    ///
+    /// 范围由 `||` 分隔的多组条件组成，每组内空格分隔的条件需要同时满足。
+    /// 条件支持 `>=`、`<=`、`>`、`<`、`=`，省略运算符时按前缀匹配，例如 `20` 匹配 20.x。
+    fn parse_range(range: &str) -> Result<Vec<Vec<(&str, &str)>>> {
+        let mut alternatives = Vec::new();
+        for group in range.split("||") {
+            let mut comparators = Vec::new();
+            for comparator in group.split_whitespace() {
+                let (op, bound) = [">=", "<=", ">", "<", "="].iter()
+                    .find_map(|op| comparator.strip_prefix(op).map(|bound| (*op, bound)))
+                    .unwrap_or(("", comparator));
+                let bound = bound.trim_start_matches('v');
+                if bound.is_empty() || !bound.split('.').all(|part| part.parse::<u64>().is_ok()) {
+                    return Err(anyhow::anyhow!("无法识别的版本条件: {}", comparator));
+                }
+                comparators.push((op, bound));
+            }
+            if comparators.is_empty() {
+                return Err(anyhow::anyhow!("版本范围中有空的条件组: {}", range));
+            }
+            alternatives.push(comparators);
+        }
+        Ok(alternatives)
+    }
+
+    /// 判断版本是否满足单个条件
+    ///
+    /// 只比较条件中给出的部分，因此 `<23` 不包含 23.0.1，`<=22` 包含 22.5.0。
+    fn matches(version: &str, op: &str, bound: &str) -> bool {
+        let parts = bound.split('.').count();
+        let truncated = version.trim_start_matches('v')
+            .split('.')
+            .take(parts)
+            .collect::<Vec<_>>()
+            .join(".");
+        let ordering = compare_versions(&truncated, bound);
+        match op {
+            ">=" => ordering.is_ge(),
+            "<=" => ordering.is_le(),
+            ">" => ordering.is_gt(),
+            "<" => ordering.is_lt(),
+            _ => ordering.is_eq(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_matches_compares_only_the_given_parts() {
+        let cases = [
+            ("20", "20.11.1", Some(true)),
+            ("20", "21.0.0", Some(false)),
+            ("v20.11", "v20.11.1", Some(true)),
+            (">=18 <23", "22.5.0", Some(true)),
+            (">=18 <23", "23.0.1", Some(false)),
+            (">=18 <23", "17.9.0", Some(false)),
+            ("<=22", "22.5.0", Some(true)),
+            (">22", "22.5.0", Some(false)),
+            (">=3.10 <3.13", "3.12.4", Some(true)),
+            (">=3.10 <3.13", "3.9.18", Some(false)),
+            ("=1.78.0", "1.78.0", Some(true)),
+            ("16 || >=20", "18.20.0", Some(false)),
+            ("16 || >=20", "16.20.2", Some(true)),
+            ("16 || >=20", "22.1.0", Some(true)),
+            ("lts/*", "20.11.1", None),
+            ("18 ||", "18.0.0", None),
+            ("", "18.0.0", None),
+        ];
+        for (range, version, expected) in cases {
+            assert_eq!(Policy::range_matches(range, version), expected, "{} {}", range, version);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use std::{env, fs, path::{Path, PathBuf}, process::Command};
+
+use crate::version_manager::VersionType;
+
+/// 支持生成安装命令的包管理器，顺序与 [`Prereq::packages`] 一致
+pub const PACKAGE_MANAGERS: [(&str, &str); 5] = [
+    ("apt", "sudo apt-get install -y"),
+    ("dnf", "sudo dnf install -y"),
+    ("pacman", "sudo pacman -S --needed"),
+    ("apk", "sudo apk add"),
+    ("brew", "brew install"),
+];
+
+/// 检测依赖是否存在的方式
+#[derive(Debug)]
+pub enum Probe {
+    /// PATH 中有其中任意一个命令
+    Command(&'static [&'static str]),
+    /// 系统的 include 目录中有该头文件
+    Header(&'static str),
+}
+
+/// 编译或使用某个工具需要的系统依赖
+#[derive(Debug)]
+pub struct Prereq {
+    /// 显示的名称，例如 `C compiler`、`openssl/ssl.h`
+    pub name: &'static str,
+    pub probe: Probe,
+    /// 缺少时无法完成编译，否则只是部分功能不可用
+    pub required: bool,
+    /// 缺少时受影响的功能
+    pub purpose: &'static str,
+    /// 依赖的头文件对应的 Python 模块，编译后据此检查模块能否导入
+    pub module: Option<&'static str>,
+    /// 各包管理器中的包名，见 [`PACKAGE_MANAGERS`]；为空时该包管理器不提供
+    pub packages: [&'static str; 5],
+}
+
+/// 一项依赖的检查结果
+#[derive(Debug)]
+pub struct PrereqCheck {
+    pub prereq: &'static Prereq,
+    /// 找到的命令或头文件路径
+    pub found: Option<PathBuf>,
+}
+
+const C_COMPILER: Prereq = Prereq {
+    name: "C compiler",
+    probe: Probe::Command(&["cc", "gcc", "clang"]),
+    required: true,
+    purpose: "compiling C sources",
+    module: None,
+    packages: ["build-essential", "gcc", "base-devel", "build-base", ""],
+};
+
+const MAKE: Prereq = Prereq {
+    name: "make",
+    probe: Probe::Command(&["make", "gmake"]),
+    required: true,
+    purpose: "running the build",
+    module: None,
+    packages: ["make", "make", "make", "make", "make"],
+};
+
+const PKG_CONFIG: Prereq = Prereq {
+    name: "pkg-config",
+    probe: Probe::Command(&["pkg-config", "pkgconf"]),
+    required: false,
+    purpose: "locating system libraries",
+    module: None,
+    packages: ["pkg-config", "pkgconf-pkg-config", "pkgconf", "pkgconf", "pkg-config"],
+};
+
+/// 从源码编译 Python 需要的依赖，头文件缺失时对应的可选模块不可用
+const PYTHON_PREREQS: &[Prereq] = &[
+    C_COMPILER,
+    MAKE,
+    PKG_CONFIG,
+    Prereq { name: "openssl/ssl.h", probe: Probe::Header("openssl/ssl.h"), required: false, purpose: "ssl module", module: Some("ssl"), packages: ["libssl-dev", "openssl-devel", "openssl", "openssl-dev", "openssl@3"] },
+    Prereq { name: "readline/readline.h", probe: Probe::Header("readline/readline.h"), required: false, purpose: "readline module", module: Some("readline"), packages: ["libreadline-dev", "readline-devel", "readline", "readline-dev", "readline"] },
+    Prereq { name: "sqlite3.h", probe: Probe::Header("sqlite3.h"), required: false, purpose: "sqlite3 module", module: Some("sqlite3"), packages: ["libsqlite3-dev", "sqlite-devel", "sqlite", "sqlite-dev", "sqlite"] },
+    Prereq { name: "tk.h", probe: Probe::Header("tk.h"), required: false, purpose: "tkinter module", module: Some("tkinter"), packages: ["tk-dev", "tk-devel", "tk", "tk-dev", "tcl-tk"] },
+    Prereq { name: "zlib.h", probe: Probe::Header("zlib.h"), required: false, purpose: "zlib module", module: Some("zlib"), packages: ["zlib1g-dev", "zlib-devel", "zlib", "zlib-dev", "zlib"] },
+    Prereq { name: "ffi.h", probe: Probe::Header("ffi.h"), required: false, purpose: "ctypes module", module: Some("ctypes"), packages: ["libffi-dev", "libffi-devel", "libffi", "libffi-dev", "libffi"] },
+    Prereq { name: "bzlib.h", probe: Probe::Header("bzlib.h"), required: false, purpose: "bz2 module", module: Some("bz2"), packages: ["libbz2-dev", "bzip2-devel", "bzip2", "bzip2-dev", "bzip2"] },
+    Prereq { name: "lzma.h", probe: Probe::Header("lzma.h"), required: false, purpose: "lzma module", module: Some("lzma"), packages: ["liblzma-dev", "xz-devel", "xz", "xz-dev", "xz"] },
+];
+
+/// Node.js 是预编译包，node-gyp 编译原生模块时需要这些依赖
+const NODE_PREREQS: &[Prereq] = &[
+    Prereq { name: "C++ compiler", probe: Probe::Command(&["c++", "g++", "clang++"]), required: false, purpose: "native addons (node-gyp)", module: None, packages: ["build-essential", "gcc-c++", "base-devel", "build-base", ""] },
+    Prereq { name: "make", probe: Probe::Command(&["make", "gmake"]), required: false, purpose: "native addons (node-gyp)", module: None, packages: ["make", "make", "make", "make", "make"] },
+    Prereq { name: "python3", probe: Probe::Command(&["python3"]), required: false, purpose: "native addons (node-gyp)", module: None, packages: ["python3", "python3", "python", "python3", "python"] },
+];
+
+/// rustc 用系统的C编译器链接可执行文件
+const RUST_PREREQS: &[Prereq] = &[
+    Prereq { name: "C compiler", probe: Probe::Command(&["cc", "gcc", "clang"]), required: true, purpose: "linking Rust binaries", module: None, packages: ["build-essential", "gcc", "base-devel", "build-base", ""] },
+    PKG_CONFIG,
+];
+
+/// Go 的工具链是静态链接的，只有 cgo 需要C编译器
+const GO_PREREQS: &[Prereq] = &[
+    Prereq { name: "C compiler", probe: Probe::Command(&["cc", "gcc", "clang"]), required: false, purpose: "cgo", module: None, packages: ["build-essential", "gcc", "base-devel", "build-base", ""] },
+];
+
+/// 工具需要的系统依赖
+pub fn prereqs(version_type: VersionType) -> &'static [Prereq] {
+    match version_type {
+        VersionType::Python => PYTHON_PREREQS,
+        VersionType::Node => NODE_PREREQS,
+        VersionType::Rust => RUST_PREREQS,
+        VersionType::Go => GO_PREREQS,
+    }
+}
+
+/// 查找头文件的目录
+///
+/// 部分头文件位于多架构目录、tcl 版本目录或 Homebrew 的 keg 中。
+fn include_dirs() -> Vec<PathBuf> {
+    let mut include_dirs = vec![PathBuf::from("/usr/include"), PathBuf::from("/usr/local/include")];
+    for parent in ["/usr/include", "/usr/local/opt", "/opt/homebrew/opt"] {
+        if let Ok(entries) = fs::read_dir(parent) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.ends_with("-linux-gnu") || name.starts_with("tcl") {
+                    include_dirs.push(path);
+                } else if parent != "/usr/include" {
+                    include_dirs.push(path.join("include"));
+                }
+            }
+        }
+    }
+    include_dirs.push(PathBuf::from("/opt/homebrew/include"));
+    include_dirs
+}
+
+/// 检查工具需要的系统依赖
+///
+/// # 参数
+///
+/// * `version_type` - 版本类型
+///
+/// # 返回
+///
+/// 返回每项依赖的检查结果。
+pub fn check(version_type: VersionType) -> Vec<PrereqCheck> {
+    let path = env::var_os("PATH").unwrap_or_default();
+    let include_dirs = include_dirs();
+    prereqs(version_type).iter()
+        .map(|prereq| {
+            let found = match &prereq.probe {
+                Probe::Command(commands) => commands.iter()
+                    .flat_map(|command| env::split_paths(&path).map(move |dir| dir.join(command)))
+                    .find(|candidate| candidate.is_file()),
+                Probe::Header(header) => include_dirs.iter()
+                    .map(|dir| dir.join(header))
+                    .find(|candidate| candidate.exists()),
+            };
+            PrereqCheck { prereq, found }
+        })
+        .collect()
+}
+
+/// 查询文件是否由当前系统的包管理器安装
+///
+/// # 参数
+///
+/// * `path` - 要查询的文件
+///
+/// # 返回
+///
+/// 文件属于某个软件包时返回包管理器名称（例如 `apt`、`dnf`），否则返回None。
+pub fn owning_package_manager(path: &Path) -> Option<&'static str> {
+    let manager = package_manager()?;
+    let query: &[&str] = match PACKAGE_MANAGERS[manager].0 {
+        "apt" => &["dpkg", "-S"],
+        "dnf" => &["rpm", "-qf"],
+        "pacman" => &["pacman", "-Qo"],
+        "apk" => &["apk", "info", "--who-owns"],
+        _ => return None,
+    };
+    let owned = Command::new(query[0])
+        .args(&query[1..])
+        .arg(path)
+        .output()
+        .is_ok_and(|output| output.status.success());
+    owned.then_some(PACKAGE_MANAGERS[manager].0)
+}
+
+/// 当前系统使用的包管理器在 [`PACKAGE_MANAGERS`] 中的位置，无法识别时返回None
+pub fn package_manager() -> Option<usize> {
+    if cfg!(target_os = "macos") {
+        return Some(4);
+    }
+    let os_release = fs::read_to_string("/etc/os-release").ok()?;
+    let ids: Vec<&str> = os_release.lines()
+        .filter_map(|line| line.strip_prefix("ID=").or_else(|| line.strip_prefix("ID_LIKE=")))
+        .flat_map(|value| value.trim_matches('"').split_whitespace())
+        .collect();
+    if ids.iter().any(|id| matches!(*id, "debian" | "ubuntu")) {
+        Some(0)
+    } else if ids.iter().any(|id| matches!(*id, "fedora" | "rhel" | "centos")) {
+        Some(1)
+    } else if ids.contains(&"arch") {
+        Some(2)
+    } else if ids.contains(&"alpine") {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// 生成用某个包管理器安装缺失依赖的命令
+///
+/// # 参数
+///
+/// * `manager` - 包管理器在 [`PACKAGE_MANAGERS`] 中的位置
+/// * `missing` - 缺失的依赖
+///
+/// # 返回
+///
+/// 返回安装命令；macOS 上的编译器由 Xcode 命令行工具提供，需要时加上 `xcode-select --install`。
+pub fn install_commands(manager: usize, missing: &[&Prereq]) -> Vec<String> {
+    let (_, prefix) = PACKAGE_MANAGERS[manager];
+    let mut packages: Vec<&str> = Vec::new();
+    let mut xcode = false;
+    for prereq in missing {
+        match prereq.packages[manager] {
+            "" => xcode = true,
+            package if !packages.contains(&package) => packages.push(package),
+            _ => {}
+        }
+    }
+    let mut commands = Vec::new();
+    if xcode {
+        commands.push("xcode-select --install".to_string());
+    }
+    if !packages.is_empty() {
+        commands.push(format!("{} {}", prefix, packages.join(" ")));
+    }
+    commands
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+/// 未配置 update.release_url 时使用的发布地址
+pub const DEFAULT_RELEASE_URL: &str = "https://github.com/yourusername/ver/releases";
+
+/// 每个发布附带的元数据文件名
+pub const MANIFEST_FILE: &str = "ver-release.json";
+
+/// ver 的发布元数据
+///
+/// 与各平台的二进制文件一起发布，`ver self-update` 和 `ver generate install-script` 都从这里读取
+/// 下载地址和SHA-256。
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    /// 版本号，不带 `v` 前缀
+    pub version: String,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// 一个平台的二进制文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleaseAsset {
+    /// Rust 目标三元组，例如 `x86_64-unknown-linux-musl`
+    pub target: String,
+    pub url: String,
+    pub sha256: String,
+}
+
+impl ReleaseManifest {
+    /// 查找指定目标的二进制文件
+    pub fn asset_for(&self, target: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.target == target)
+    }
+}
+
+/// 发布元数据的下载地址
+///
+/// # 参数
+///
+/// * `base` - 发布地址，格式与 GitHub Releases 相同
+/// * `version` - 版本号，为None时取最新版本
+pub fn manifest_url(base: &str, version: Option<&str>) -> String {
+    let base = base.trim_end_matches('/');
+    match version {
+        Some(version) => format!("{}/download/v{}/{}", base, version.trim_start_matches('v'), MANIFEST_FILE),
+        None => format!("{}/latest/download/{}", base, MANIFEST_FILE),
+    }
+}
+
+/// 当前 ver 可执行文件对应的发布目标
+///
+/// Linux 版本使用 musl 静态链接，不依赖系统的 glibc 版本，可以直接在任何发行版和容器中运行。
+pub fn current_target() -> Option<&'static str> {
+    target_for(std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// 根据操作系统和架构名称（Rust 的命名）获取发布目标
+fn target_for(os: &str, arch: &str) -> Option<&'static str> {
+    match (os, arch) {
+        ("linux", "x86_64") => Some("x86_64-unknown-linux-musl"),
+        ("linux", "aarch64") => Some("aarch64-unknown-linux-musl"),
+        ("macos", "x86_64") => Some("x86_64-apple-darwin"),
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("windows", "x86_64") => Some("x86_64-pc-windows-msvc"),
+        _ => None,
+    }
+}
+
+/// 生成 curl | sh 安装脚本
+///
+/// 脚本中固定了该发布每个 Unix 平台的下载地址和SHA-256，下载后校验，不一致时中止。
+/// 安装目录默认为 ~/.local/bin，可以通过 VER_INSTALL_DIR 修改。
+///
+/// # 参数
+///
+/// * `manifest` - 发布元数据
+///
+/// # 返回
+///
+/// 返回脚本内容。
+pub fn install_script(manifest: &ReleaseManifest) -> String {
+    let mut cases = String::new();
+    let platforms = [
+        ("Linux", "x86_64", "linux", "x86_64"),
+        ("Linux", "aarch64", "linux", "aarch64"),
+        ("Linux", "arm64", "linux", "aarch64"),
+        ("Darwin", "x86_64", "macos", "x86_64"),
+        ("Darwin", "arm64", "macos", "aarch64"),
+    ];
+    for (uname_s, uname_m, os, arch) in platforms {
+        let Some(asset) = target_for(os, arch).and_then(|target| manifest.asset_for(target)) else {
+            continue;
+        };
+        cases.push_str(&format!(
+            "  {}:{}) url='{}'; sha256='{}' ;;\n",
+            uname_s, uname_m, asset.url, asset.sha256
+        ));
+    }
+    
+    format!(r#"#!/bin/sh
+# Installs ver {version}. Generated by `ver generate install-script`.
+set -eu
+
+case "$(uname -s):$(uname -m)" in
+{cases}  *) echo "ver {version} has no build for $(uname -s) $(uname -m)" >&2; exit 1 ;;
+esac
+
+dir="${{VER_INSTALL_DIR:-$HOME/.local/bin}}"
+tmp="$(mktemp)"
+trap 'rm -f "$tmp"' EXIT
+
+if command -v curl >/dev/null 2>&1; then
+  curl -fsSL "$url" -o "$tmp"
+else
+  wget -qO "$tmp" "$url"
+fi
+
+if command -v sha256sum >/dev/null 2>&1; then
+  actual="$(sha256sum "$tmp" | cut -d ' ' -f 1)"
+else
+  actual="$(shasum -a 256 "$tmp" | cut -d ' ' -f 1)"
+fi
+if [ "$actual" != "$sha256" ]; then
+  echo "checksum mismatch for $url: expected $sha256, got $actual" >&2
+  exit 1
+fi
+
+mkdir -p "$dir"
+chmod 755 "$tmp"
+mv "$tmp" "$dir/ver"
+trap - EXIT
+echo "Installed ver {version} to $dir/ver"
+case ":$PATH:" in
+  *":$dir:"*) ;;
+  *) echo "Add $dir to PATH, then run \`ver setup\`" ;;
+esac
+"#, version = manifest.version, cases = cases)
+}
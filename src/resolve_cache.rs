@@ -0,0 +1,350 @@
+use anyhow::Result;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::state::write_atomic;
+
+/// 缓存文件开头的标识，格式变化时修改
+const MAGIC: &[u8; 4] = b"VRC1";
+
+/// 最多保存的目录数，超出时丢弃最早的记录
+const MAX_ENTRIES: usize = 512;
+
+/// 版本文件查找结果的缓存
+///
+/// 构建过程中 shim 会被调用成百上千次，每次都从当前目录向上查找版本文件。
+/// 缓存记录每个目录的查找结果，以及查找时检查过的目录和找到的版本文件的修改时间：
+/// 在目录中新建、删除或重命名文件会改变目录的修改时间，编辑版本文件会改变文件的修改时间，
+/// 只要这些时间都没有变化，查找结果就仍然有效，不需要再逐层读取版本文件。
+///
+/// 缓存以紧凑的二进制格式保存在下载缓存目录中，读取失败或格式不符时视为空缓存。
+#[derive(Debug, Default)]
+pub struct ResolveCache {
+    /// 影响查找结果的配置（resolve.ignore_dirs、resolve.max_depth），变化时整个缓存失效
+    fingerprint: String,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    tool: String,
+    dir: PathBuf,
+    /// 找到的版本号和版本文件
+    found: Option<(String, PathBuf)>,
+    /// 查找时检查过的路径及其修改时间
+    stamps: Vec<(PathBuf, Option<(u64, u32)>)>,
+}
+
+impl ResolveCache {
+    /// 读取缓存
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 缓存文件路径
+    /// * `fingerprint` - 当前配置的指纹，与缓存中的不同时返回空缓存
+    pub fn load(path: &Path, fingerprint: &str) -> Self {
+        let empty = Self { fingerprint: fingerprint.to_string(), entries: Vec::new() };
+        let Ok(data) = fs::read(path) else {
+            return empty;
+        };
+        match Self::decode(&data) {
+            Some(cache) if cache.fingerprint == fingerprint => cache,
+            _ => empty,
+        }
+    }
+
+    /// 保存缓存
+    ///
+    /// 先写入临时文件再重命名，并发运行的 shim 不会读到写了一半的文件。
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(path, self.encode())?;
+        Ok(())
+    }
+
+    /// 查找仍然有效的缓存结果
+    ///
+    /// # 参数
+    ///
+    /// * `tool` - 工具标识
+    /// * `dir` - 开始查找的目录
+    ///
+    /// # 返回
+    ///
+    /// 缓存有效时返回找到的版本号和版本文件以及校验过的路径数，没有缓存或已失效时返回None。
+    pub fn lookup(&self, tool: &str, dir: &Path) -> Option<(Option<(String, PathBuf)>, usize)> {
+        let entry = self.entries.iter().find(|entry| entry.tool == tool && entry.dir == dir)?;
+        entry.stamps.iter()
+            .all(|(path, stamp)| modified(path) == *stamp)
+            .then(|| (entry.found.clone(), entry.stamps.len()))
+    }
+
+    /// 记录查找结果
+    ///
+    /// # 参数
+    ///
+    /// * `tool` - 工具标识
+    /// * `dir` - 开始查找的目录
+    /// * `found` - 找到的版本号和版本文件
+    /// * `checked` - 查找时检查过的目录
+    pub fn insert(&mut self, tool: &str, dir: &Path, found: Option<(String, PathBuf)>, checked: &[PathBuf]) {
+        let mut stamps: Vec<_> = checked.iter()
+            .map(|path| (path.clone(), modified(path)))
+            .collect();
+        if let Some((_, file)) = &found {
+            stamps.push((file.clone(), modified(file)));
+        }
+        self.entries.retain(|entry| !(entry.tool == tool && entry.dir == dir));
+        self.entries.push(Entry { tool: tool.to_string(), dir: dir.to_path_buf(), found, stamps });
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        put_str(&mut out, &self.fingerprint);
+        out.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for entry in &self.entries {
+            put_str(&mut out, &entry.tool);
+            put_str(&mut out, &entry.dir.to_string_lossy());
+            match &entry.found {
+                Some((version, file)) => {
+                    out.push(1);
+                    put_str(&mut out, version);
+                    put_str(&mut out, &file.to_string_lossy());
+                }
+                None => out.push(0),
+            }
+            out.extend_from_slice(&(entry.stamps.len() as u32).to_le_bytes());
+            for (path, stamp) in &entry.stamps {
+                put_str(&mut out, &path.to_string_lossy());
+                match stamp {
+                    Some((secs, nanos)) => {
+                        out.push(1);
+                        out.extend_from_slice(&secs.to_le_bytes());
+                        out.extend_from_slice(&nanos.to_le_bytes());
+                    }
+                    None => out.push(0),
+                }
+            }
+        }
+        out
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        let mut reader = Reader { data, pos: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return None;
+        }
+        let fingerprint = reader.string()?;
+        let count = reader.u32()?;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let tool = reader.string()?;
+            let dir = PathBuf::from(reader.string()?);
+            let found = match reader.u8()? {
+                1 => Some((reader.string()?, PathBuf::from(reader.string()?))),
+                _ => None,
+            };
+            let mut stamps = Vec::new();
+            for _ in 0..reader.u32()? {
+                let path = PathBuf::from(reader.string()?);
+                let stamp = match reader.u8()? {
+                    1 => Some((reader.u64()?, reader.u32()?)),
+                    _ => None,
+                };
+                stamps.push((path, stamp));
+            }
+            entries.push(Entry { tool, dir, found, stamps });
+        }
+        Some(Self { fingerprint, entries })
+    }
+}
+
+/// 路径的修改时间，路径不存在时为None
+fn modified(path: &Path) -> Option<(u64, u32)> {
+    let time = fs::metadata(path).ok()?.modified().ok()?;
+    let duration = time.duration_since(UNIX_EPOCH).ok()?;
+    Some((duration.as_secs(), duration.subsec_nanos()))
+}
+
+/// 写入带长度前缀的字符串
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// 按顺序读取缓存内容，数据不完整时返回None
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    /// 每个测试独立的临时目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ver-resolve-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 把路径的修改时间改为一小时前，之后的任何修改都会改变它
+    fn backdate(path: &Path) {
+        let time = SystemTime::now() - Duration::from_secs(3600);
+        // Windows 上修改文件时间需要写权限，目录只能以只读方式打开（目录的测试只在 Unix 上运行）
+        let file = if path.is_dir() { fs::File::open(path) } else { fs::OpenOptions::new().write(true).open(path) };
+        file.unwrap().set_modified(time).unwrap();
+    }
+
+    fn sample() -> ResolveCache {
+        let mut cache = ResolveCache { fingerprint: "ignore=node_modules;depth=".to_string(), entries: Vec::new() };
+        cache.insert("node", Path::new("/work/app"), Some(("20.11.1".to_string(), PathBuf::from("/work/app/.nvmrc"))), &[PathBuf::from("/work/app")]);
+        cache.insert("rust", Path::new("/work/app/src"), None, &[PathBuf::from("/work/app/src"), PathBuf::from("/work/app")]);
+        cache.entries[1].stamps.push((PathBuf::from("/work/目录"), Some((1_700_000_000, 123_456_789))));
+        cache
+    }
+
+    #[test]
+    fn encode_decode_round_trips() {
+        let cache = sample();
+        let decoded = ResolveCache::decode(&cache.encode()).unwrap();
+        assert_eq!(decoded.fingerprint, cache.fingerprint);
+        assert_eq!(decoded.encode(), cache.encode());
+
+        let dir = temp_dir("round-trip");
+        let path = dir.join("resolve.bin");
+        cache.save(&path).unwrap();
+        let loaded = ResolveCache::load(&path, &cache.fingerprint);
+        assert_eq!(loaded.entries.len(), 2);
+        assert_eq!(loaded.entries[1].stamps, cache.entries[1].stamps);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn truncated_or_garbage_data_loads_as_empty() {
+        let data = sample().encode();
+        for len in 0..data.len() {
+            assert!(ResolveCache::decode(&data[..len]).is_none(), "prefix of {} bytes", len);
+        }
+
+        let dir = temp_dir("garbage");
+        let path = dir.join("resolve.bin");
+        let mut huge_length = MAGIC.to_vec();
+        huge_length.extend_from_slice(&u32::MAX.to_le_bytes());
+        for garbage in [&b"not a cache"[..], &b"VRC0\0\0\0\0"[..], &huge_length, &data[..data.len() - 1]] {
+            fs::write(&path, garbage).unwrap();
+            let cache = ResolveCache::load(&path, "fp");
+            assert!(cache.entries.is_empty());
+            assert_eq!(cache.fingerprint, "fp");
+        }
+        assert!(ResolveCache::load(&dir.join("missing.bin"), "fp").entries.is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fingerprint_mismatch_discards_the_cache() {
+        let dir = temp_dir("fingerprint");
+        let path = dir.join("resolve.bin");
+        let cache = sample();
+        cache.save(&path).unwrap();
+        let loaded = ResolveCache::load(&path, "ignore=target;depth=2");
+        assert!(loaded.entries.is_empty());
+        assert_eq!(loaded.fingerprint, "ignore=target;depth=2");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn lookup_is_invalidated_when_a_version_file_changes() {
+        let dir = temp_dir("file-mtime");
+        let file = dir.join(".nvmrc");
+        fs::write(&file, "20.11.1\n").unwrap();
+        backdate(&file);
+        let mut cache = ResolveCache::default();
+        cache.insert("node", &dir, Some(("20.11.1".to_string(), file.clone())), &[]);
+        assert_eq!(cache.lookup("node", &dir), Some((Some(("20.11.1".to_string(), file.clone())), 1)));
+        assert_eq!(cache.lookup("rust", &dir), None);
+
+        fs::write(&file, "22.1.0\n").unwrap();
+        assert_eq!(cache.lookup("node", &dir), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lookup_is_invalidated_when_a_checked_directory_changes() {
+        let dir = temp_dir("dir-mtime");
+        let project = dir.join("app");
+        fs::create_dir_all(&project).unwrap();
+        backdate(&project);
+        let mut cache = ResolveCache::default();
+        cache.insert("node", &project, None, std::slice::from_ref(&project));
+        assert_eq!(cache.lookup("node", &project), Some((None, 1)));
+
+        // 新建版本文件会改变目录的修改时间
+        fs::write(project.join(".nvmrc"), "20.11.1\n").unwrap();
+        assert_eq!(cache.lookup("node", &project), None);
+
+        // 检查时不存在的路径出现后也失效
+        let missing = dir.join("missing");
+        cache.insert("node", &project, None, std::slice::from_ref(&missing));
+        assert!(cache.lookup("node", &project).is_some());
+        fs::create_dir(&missing).unwrap();
+        assert_eq!(cache.lookup("node", &project), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn insert_evicts_the_oldest_entries_beyond_the_limit() {
+        let mut cache = ResolveCache::default();
+        for i in 0..=MAX_ENTRIES {
+            cache.insert("node", &PathBuf::from(format!("/work/{}", i)), None, &[]);
+        }
+        assert_eq!(cache.entries.len(), MAX_ENTRIES);
+        assert_eq!(cache.lookup("node", Path::new("/work/0")), None);
+        assert!(cache.lookup("node", Path::new("/work/1")).is_some());
+        assert!(cache.lookup("node", &PathBuf::from(format!("/work/{}", MAX_ENTRIES))).is_some());
+
+        // 同一目录再次记录时替换原有记录，不占用新的位置
+        cache.insert("node", Path::new("/work/1"), Some(("20.11.1".to_string(), PathBuf::from("/work/1/.nvmrc"))), &[]);
+        assert_eq!(cache.entries.len(), MAX_ENTRIES);
+        assert_eq!(cache.entries.iter().filter(|entry| entry.dir == Path::new("/work/1")).count(), 1);
+    }
+}
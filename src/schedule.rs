@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use std::{
+    env,
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// 定时任务的执行间隔
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interval {
+    /// 每天凌晨3点
+    Daily,
+    /// 每周日凌晨3点
+    Weekly,
+}
+
+impl Interval {
+    /// 从名称解析执行间隔
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "daily" => Ok(Interval::Daily),
+            "weekly" => Ok(Interval::Weekly),
+            _ => Err(anyhow::anyhow!("不支持的执行间隔: {}（可选 daily、weekly）", name)),
+        }
+    }
+}
+
+/// 为 ver 子命令安装系统定时任务
+///
+/// Linux 使用当前用户的 crontab，macOS 使用 ~/Library/LaunchAgents 中的 launchd 任务，
+/// Windows 使用计划任务。同名任务已存在时会被替换，因此可以重复执行。
+///
+/// # 参数
+///
+/// * `name` - 任务名称，同时用于识别已安装的任务，例如 `maintain`
+/// * `args` - 传给 ver 的参数
+/// * `interval` - 执行间隔
+/// * `log_file` - 保存输出的日志文件（Windows 计划任务不记录输出）
+///
+/// # 返回
+///
+/// 成功时返回已安装任务的说明，失败时返回错误。
+pub fn install(name: &str, args: &[&str], interval: Interval, log_file: &Path) -> Result<String> {
+    let exe = env::current_exe().context("无法确定 ver 的可执行文件路径")?;
+    match env::consts::OS {
+        "linux" => install_crontab(name, &exe, args, interval, log_file),
+        "macos" => install_launchd(name, &exe, args, interval, log_file),
+        "windows" => install_schtasks(name, &exe, args, interval),
+        os => Err(anyhow::anyhow!("不支持在 {} 上安装定时任务", os)),
+    }
+}
+
+/// 写入 crontab，用行尾的 `# ver:<名称>` 标记识别已安装的任务
+fn install_crontab(name: &str, exe: &Path, args: &[&str], interval: Interval, log_file: &Path) -> Result<String> {
+    let marker = format!("# ver:{}", name);
+    let schedule = match interval {
+        Interval::Daily => "0 3 * * *",
+        Interval::Weekly => "0 3 * * 0",
+    };
+    // cron 会把命令中未转义的 `%` 当作换行，需要写成 `\%`
+    let quote = |value: &str| format!("'{}'", value.replace('\'', "'\\''").replace('%', "\\%"));
+    let command = std::iter::once(exe.to_string_lossy().to_string())
+        .chain(args.iter().map(|arg| arg.to_string()))
+        .map(|part| quote(&part))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let entry = format!("{} {} >> {} 2>&1 {}", schedule, command, quote(&log_file.to_string_lossy()), marker);
+
+    // 没有 crontab 时 `crontab -l` 以非零状态退出，视为空
+    let output = Command::new("crontab").arg("-l").output()
+        .map_err(|err| anyhow::anyhow!("无法运行 crontab: {}", err))?;
+    let existing = if output.status.success() { String::from_utf8_lossy(&output.stdout).to_string() } else { String::new() };
+    let mut content: String = existing.lines()
+        .filter(|line| !line.ends_with(&marker))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    content.push_str(&entry);
+    content.push('\n');
+
+    let mut child = Command::new("crontab").arg("-").stdin(Stdio::piped()).spawn()?;
+    child.stdin.take().context("无法写入 crontab")?.write_all(content.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("crontab 更新失败，退出码: {}", status));
+    }
+    Ok(format!("crontab entry: {}", entry))
+}
+
+/// 写入 launchd 任务并加载
+fn install_launchd(name: &str, exe: &Path, args: &[&str], interval: Interval, log_file: &Path) -> Result<String> {
+    let label = format!("dev.ver.{}", name);
+    let agents_dir = crate::version_manager::home_dir(None)?
+        .join("Library")
+        .join("LaunchAgents");
+    fs::create_dir_all(&agents_dir)?;
+    let plist = agents_dir.join(format!("{}.plist", label));
+
+    let escape = |value: &str| value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let program_arguments: String = std::iter::once(exe.to_string_lossy().to_string())
+        .chain(args.iter().map(|arg| arg.to_string()))
+        .map(|arg| format!("        <string>{}</string>\n", escape(&arg)))
+        .collect();
+    let weekday = match interval {
+        Interval::Daily => String::new(),
+        Interval::Weekly => "        <key>Weekday</key>\n        <integer>0</integer>\n".to_string(),
+    };
+    let content = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \x20   <key>Label</key>\n\
+         \x20   <string>{label}</string>\n\
+         \x20   <key>ProgramArguments</key>\n\
+         \x20   <array>\n{program_arguments}\x20   </array>\n\
+         \x20   <key>StartCalendarInterval</key>\n\
+         \x20   <dict>\n\
+         \x20       <key>Hour</key>\n\
+         \x20       <integer>3</integer>\n\
+         \x20       <key>Minute</key>\n\
+         \x20       <integer>0</integer>\n\
+         {weekday}\
+         \x20   </dict>\n\
+         \x20   <key>StandardOutPath</key>\n\
+         \x20   <string>{log}</string>\n\
+         \x20   <key>StandardErrorPath</key>\n\
+         \x20   <string>{log}</string>\n\
+         </dict>\n\
+         </plist>\n",
+        label = label,
+        program_arguments = program_arguments,
+        weekday = weekday,
+        log = escape(&log_file.to_string_lossy()),
+    );
+    fs::write(&plist, content)?;
+
+    // 已加载的旧任务需要先卸载，否则新配置不会生效
+    let _ = Command::new("launchctl").arg("unload").arg(&plist).output();
+    let status = Command::new("launchctl").arg("load").arg("-w").arg(&plist).status()
+        .map_err(|err| anyhow::anyhow!("无法运行 launchctl: {}", err))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("launchctl 加载 {} 失败，退出码: {}", plist.display(), status));
+    }
+    Ok(format!("launchd agent: {}", plist.display()))
+}
+
+/// 创建 Windows 计划任务
+fn install_schtasks(name: &str, exe: &Path, args: &[&str], interval: Interval) -> Result<String> {
+    let task = format!("ver-{}", name);
+    let command = std::iter::once(format!("\"{}\"", exe.display()))
+        .chain(args.iter().map(|arg| arg.to_string()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let schedule = match interval {
+        Interval::Daily => "DAILY",
+        Interval::Weekly => "WEEKLY",
+    };
+    let status = Command::new("schtasks")
+        .args(["/Create", "/F", "/SC", schedule, "/ST", "03:00", "/TN", &task, "/TR", &command])
+        .status()
+        .map_err(|err| anyhow::anyhow!("无法运行 schtasks: {}", err))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("创建计划任务 {} 失败，退出码: {}", task, status));
+    }
+    Ok(format!("scheduled task: {}", task))
+}
@@ -0,0 +1,310 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::version_manager::VersionType;
+
+/// ver 主目录中状态文件的格式版本
+///
+/// 修改别名、配置、安装记录等文件的格式或目录布局时加一，并在 [`MIGRATIONS`] 末尾加上对应的迁移。
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 记录主目录格式版本的文件
+const SCHEMA_FILE: &str = "schema-version";
+
+/// 一次格式迁移
+struct Migration {
+    /// 迁移说明，执行时显示
+    description: &'static str,
+    /// 迁移函数，参数是 ver 主目录；中途失败后会在下次运行时重新执行，需要可以重复执行
+    run: fn(&Path) -> Result<()>,
+}
+
+/// 按顺序执行的迁移，第 n 项把格式从版本 n 升级到 n + 1
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "move legacy Node.js state files and record the schema version",
+        run: migrate_v1,
+    },
+];
+
+const _: () = assert!(MIGRATIONS.len() == SCHEMA_VERSION as usize);
+
+/// 把主目录升级到当前格式
+///
+/// 每完成一个迁移就更新一次格式版本文件，中途失败时下次运行从失败的迁移继续。
+/// 主目录不存在时直接按当前格式创建。
+///
+/// # 参数
+///
+/// * `base_dir` - ver 主目录
+///
+/// # 返回
+///
+/// 成功时返回Ok(()，主目录由更新版本的 ver 创建或迁移失败时返回错误。
+pub fn migrate(base_dir: &Path) -> Result<()> {
+    let schema_file = base_dir.join(SCHEMA_FILE);
+    if !base_dir.exists() {
+        fs::create_dir_all(base_dir).context("无法创建基础目录")?;
+        write_atomic(&schema_file, SCHEMA_VERSION.to_string())?;
+        return Ok(());
+    }
+    
+    // 引入格式版本之前的主目录没有这个文件，从头执行所有迁移
+    let current = fs::read_to_string(&schema_file)
+        .ok()
+        .and_then(|content| content.trim().parse::<u32>().ok())
+        .unwrap_or(0);
+    if current > SCHEMA_VERSION {
+        anyhow::bail!(
+            "{} 由更新版本的 ver 创建（格式版本 {}，当前版本只支持到 {}），请升级 ver 后再使用",
+            base_dir.display(), current, SCHEMA_VERSION
+        );
+    }
+    
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let target = version as u32 + 1;
+        eprintln!("Upgrading {} to schema version {}: {}", base_dir.display(), target, migration.description);
+        (migration.run)(base_dir)
+            .with_context(|| format!("无法把 {} 升级到格式版本 {}", base_dir.display(), target))?;
+        write_atomic(&schema_file, target.to_string())?;
+    }
+    Ok(())
+}
+
+/// 迁移到版本 1
+///
+/// 早期只支持Node.js时，别名和当前版本文件没有工具后缀；把它们改成按工具区分的文件名，
+/// 并在别名和配置文件中记录格式版本。
+fn migrate_v1(base_dir: &Path) -> Result<()> {
+    let legacy = [
+        ("aliases.json", format!("aliases-{}.json", VersionType::Node)),
+        (".current-node", format!(".current-{}", VersionType::Node)),
+    ];
+    for (old, new) in legacy {
+        let (old, new) = (base_dir.join(old), base_dir.join(new));
+        if old.exists() && !new.exists() {
+            fs::rename(&old, &new)?;
+        }
+    }
+    
+    for entry in fs::read_dir(base_dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if name == "config.json" || (name.starts_with("aliases-") && name.ends_with(".json")) {
+            stamp_schema(&path, 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// 在JSON状态文件中写入格式版本
+fn stamp_schema(path: &Path, version: u32) -> Result<()> {
+    let Some(mut value) = read_json::<serde_json::Value>(path)? else {
+        return Ok(());
+    };
+    if let Some(object) = value.as_object_mut() {
+        object.insert("schema".to_string(), version.into());
+        write_atomic(path, serde_json::to_string_pretty(&value)?)?;
+    }
+    Ok(())
+}
+
+/// 原子地写入文件
+///
+/// 先写入同一目录下的临时文件并同步到磁盘，再重命名为目标文件。写入过程中崩溃或断电时，
+/// 目标文件要么是旧内容，要么是完整的新内容，不会被截断。已有文件的权限会保留，
+/// 目标是符号链接时（例如由 dotfiles 管理的 shell 配置）写入链接指向的文件。
+///
+/// # 参数
+///
+/// * `path` - 目标文件路径
+/// * `contents` - 文件内容
+///
+/// # 返回
+///
+/// 成功时返回Ok(()，失败时返回错误。
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(path)?,
+        _ => path.to_path_buf(),
+    };
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+    
+    let result = (|| {
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(contents.as_ref())?;
+        if let Ok(metadata) = fs::metadata(&path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+        return result;
+    }
+    
+    // 重命名本身也要落盘，否则断电后目录中可能仍是旧文件；Windows 不支持打开目录，跳过
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// 读取 ver 自己维护的JSON状态文件
+///
+/// 文件不存在时返回None。内容无法解析时（例如旧版本在写入过程中崩溃留下的截断文件），
+/// 把文件改名为 `<文件名>.corrupt` 保留下来，打印警告并返回None，调用方按文件不存在处理，
+/// 而不是让之后的每个命令都失败。
+///
+/// # 参数
+///
+/// * `path` - 状态文件路径
+///
+/// # 返回
+///
+/// 成功时返回解析结果，文件不存在或已损坏时返回None，无法读取时返回错误。
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    match serde_json::from_str(&content) {
+        Ok(value) => Ok(Some(value)),
+        Err(err) => {
+            let backup = corrupt_backup_path(path);
+            let _ = fs::rename(path, &backup);
+            eprintln!(
+                "Warning: {} is corrupted ({}); moved it to {} and continuing without it",
+                path.display(), err, backup.display()
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// 损坏文件的备份路径
+fn corrupt_backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".corrupt");
+    path.with_file_name(name)
+}
+
+/// 锁文件超过这个时间没有释放时视为持有者已异常退出
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+/// 进程间互斥锁
+///
+/// 通过独占创建锁文件实现，离开作用域时删除锁文件。
+pub struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// 获取锁，其他进程持有时等待
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 锁文件路径
+    /// * `timeout` - 最长等待时间
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回锁，等待超时或无法创建锁文件时返回错误。
+    pub fn acquire(path: &Path, timeout: Duration) -> Result<Self> {
+        let started = SystemTime::now();
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id());
+                    return Ok(Self { path: path.to_path_buf() });
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err).with_context(|| format!("无法创建锁文件 {}", path.display())),
+            }
+            
+            let stale = fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified.elapsed().unwrap_or_default() > STALE_LOCK_AGE);
+            if stale {
+                let _ = fs::remove_file(path);
+                continue;
+            }
+            if started.elapsed().unwrap_or_default() > timeout {
+                anyhow::bail!(
+                    "另一个 ver 进程正在使用 {}，请稍后重试；如果没有其他 ver 进程在运行，可以删除该文件",
+                    path.display()
+                );
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每个测试独立的临时目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ver-state-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_atomic_replaces_contents_without_leaving_temp_files() {
+        let dir = temp_dir("replace");
+        let path = dir.join("aliases.json");
+        write_atomic(&path, "{}").unwrap();
+        write_atomic(&path, "{\"lts\": \"20.11.1\"}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"lts\": \"20.11.1\"}");
+        let names: Vec<_> = fs::read_dir(&dir).unwrap().flatten().map(|entry| entry.file_name()).collect();
+        assert_eq!(names, vec!["aliases.json"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_keeps_permissions_and_follows_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("symlink");
+        let target = dir.join("dotfiles-bashrc");
+        fs::write(&target, "old").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        let link = dir.join(".bashrc");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomic(&link, "new").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o600);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn write_atomic_fails_when_the_directory_is_missing() {
+        let dir = temp_dir("missing");
+        assert!(write_atomic(&dir.join("missing").join("config.json"), "{}").is_err());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
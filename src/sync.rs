@@ -0,0 +1,294 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, fs, path::Path, process::Command};
+
+use crate::state::{self, write_atomic};
+use crate::version_manager::Subscription;
+
+/// 同步仓库中标记 ver 状态的文件，没有它的仓库视为空仓库
+pub const MARKER_FILE: &str = "ver-sync.json";
+
+/// 同步的状态：别名、默认版本、渠道订阅和项目模板
+///
+/// 同步仓库中的文件与它一一对应：`aliases/<工具>.json`、`defaults.json`、`subscriptions.json` 和 `templates/`。
+/// 已安装的版本不同步。
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SyncState {
+    /// （工具，别名）到版本
+    pub aliases: BTreeMap<(String, String), String>,
+    /// 工具到默认版本
+    pub defaults: BTreeMap<String, String>,
+    /// （工具，渠道）到订阅
+    pub subscriptions: BTreeMap<(String, String), Subscription>,
+    /// 模板文件名到内容
+    pub templates: BTreeMap<String, String>,
+}
+
+/// subscriptions.json 的格式，与 ver 主目录中的同名文件相同
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SubscriptionsFile {
+    subscriptions: Vec<Subscription>,
+}
+
+/// 读取同步仓库工作目录中的状态，没有的文件视为空
+pub fn read_state(dir: &Path) -> Result<SyncState> {
+    let mut sync_state = SyncState::default();
+    if let Ok(entries) = fs::read_dir(dir.join("aliases")) {
+        for entry in entries {
+            let path = entry?.path();
+            let Some(tool) = path.file_stem().map(|stem| stem.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let aliases: BTreeMap<String, String> = state::read_json(&path)?.unwrap_or_default();
+            sync_state.aliases.extend(aliases.into_iter().map(|(alias, version)| ((tool.clone(), alias), version)));
+        }
+    }
+    sync_state.defaults = state::read_json(&dir.join("defaults.json"))?.unwrap_or_default();
+    let subscriptions: SubscriptionsFile = state::read_json(&dir.join("subscriptions.json"))?.unwrap_or_default();
+    sync_state.subscriptions = subscriptions.subscriptions.into_iter()
+        .map(|subscription| ((subscription.tool.clone(), subscription.channel.clone()), subscription))
+        .collect();
+    sync_state.templates = read_files(&dir.join("templates"))?;
+    Ok(sync_state)
+}
+
+/// 把状态写入同步仓库的工作目录（或备份目录），替换其中原有的内容
+pub fn write_state(dir: &Path, sync_state: &SyncState) -> Result<()> {
+    let aliases_dir = dir.join("aliases");
+    if aliases_dir.exists() {
+        fs::remove_dir_all(&aliases_dir)?;
+    }
+    let mut by_tool: BTreeMap<&str, BTreeMap<&str, &str>> = BTreeMap::new();
+    for ((tool, alias), version) in &sync_state.aliases {
+        by_tool.entry(tool).or_default().insert(alias, version);
+    }
+    for (tool, aliases) in by_tool {
+        fs::create_dir_all(&aliases_dir)?;
+        write_atomic(&aliases_dir.join(format!("{}.json", tool)), serde_json::to_string_pretty(&aliases)?)?;
+    }
+    write_atomic(&dir.join("defaults.json"), serde_json::to_string_pretty(&sync_state.defaults)?)?;
+    let subscriptions = SubscriptionsFile { subscriptions: sync_state.subscriptions.values().cloned().collect() };
+    write_atomic(&dir.join("subscriptions.json"), serde_json::to_string_pretty(&subscriptions)?)?;
+    write_files(&dir.join("templates"), &sync_state.templates)?;
+    write_atomic(&dir.join(MARKER_FILE), serde_json::to_string_pretty(&serde_json::json!({ "schema": state::SCHEMA_VERSION }))?)?;
+    Ok(())
+}
+
+/// 读取目录中的文件（只读一层），目录不存在时返回空
+pub fn read_files(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(files);
+    };
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            files.insert(entry.file_name().to_string_lossy().to_string(), fs::read_to_string(entry.path())?);
+        }
+    }
+    Ok(files)
+}
+
+/// 让目录中的文件与 `files` 相同，`files` 为空时删除目录
+pub fn write_files(dir: &Path, files: &BTreeMap<String, String>) -> Result<()> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    if files.is_empty() {
+        return Ok(());
+    }
+    fs::create_dir_all(dir)?;
+    for (name, content) in files {
+        fs::write(dir.join(name), content)?;
+    }
+    Ok(())
+}
+
+/// 按键三方合并同步状态
+///
+/// `base` 是上次同步时仓库中的状态。只有一方修改（包括添加和删除）的键采用修改后的值，
+/// 两方都修改时本地优先。本机没有设置默认版本的工具不视为删除，保留仓库中的设置。
+///
+/// # 参数
+///
+/// * `base` - 上次同步时的状态
+/// * `theirs` - 仓库中最新的状态
+/// * `ours` - 本地的状态
+///
+/// # 返回
+///
+/// 返回合并后的状态。
+pub fn merge(base: &SyncState, theirs: &SyncState, ours: &SyncState) -> SyncState {
+    let mut ours_defaults = base.defaults.clone();
+    ours_defaults.extend(ours.defaults.clone());
+    SyncState {
+        aliases: merge_maps(&base.aliases, &theirs.aliases, &ours.aliases),
+        defaults: merge_maps(&base.defaults, &theirs.defaults, &ours_defaults),
+        subscriptions: merge_maps(&base.subscriptions, &theirs.subscriptions, &ours.subscriptions),
+        templates: merge_maps(&base.templates, &theirs.templates, &ours.templates),
+    }
+}
+
+/// 按键三方合并一个映射，见 `merge`
+fn merge_maps<K: Ord + Clone, V: PartialEq + Clone>(base: &BTreeMap<K, V>, theirs: &BTreeMap<K, V>, ours: &BTreeMap<K, V>) -> BTreeMap<K, V> {
+    let keys: std::collections::BTreeSet<&K> = base.keys().chain(theirs.keys()).chain(ours.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let (base, theirs, ours) = (base.get(key), theirs.get(key), ours.get(key));
+            let value = if ours == base { theirs } else { ours };
+            value.map(|value| (key.clone(), value.clone()))
+        })
+        .collect()
+}
+
+/// 在同步仓库中运行 git
+///
+/// # 参数
+///
+/// * `dir` - 仓库目录
+/// * `args` - git 的参数
+///
+/// # 返回
+///
+/// 成功时返回标准输出，git 失败时返回包含其错误输出的错误。
+pub fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|err| anyhow::anyhow!("无法运行 git: {}", err))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("git {} 失败: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 克隆同步仓库，远程仓库可以是空的
+pub fn clone(url: &str, dir: &Path) -> Result<()> {
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let output = Command::new("git")
+        .args(["clone", "--quiet", url])
+        .arg(dir)
+        .output()
+        .map_err(|err| anyhow::anyhow!("无法运行 git: {}", err))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("无法克隆 {}: {}", url, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// 仓库中是否已有其他机器推送的状态
+pub fn has_state(dir: &Path) -> bool {
+    dir.join(MARKER_FILE).exists()
+}
+
+/// 远程仓库的地址
+pub fn remote_url(dir: &Path) -> Result<String> {
+    git(dir, &["remote", "get-url", "origin"])
+}
+
+/// 当前机器的名称，写入提交说明
+fn hostname() -> String {
+    env::var("HOSTNAME").ok()
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown host".to_string())
+}
+
+/// 提交工作目录中的所有改动
+///
+/// 没有配置 git 用户时以 `ver` 的身份提交，同步仓库只由 ver 写入。
+///
+/// # 返回
+///
+/// 有改动并已提交时返回true，没有改动时返回false。
+pub fn commit(dir: &Path) -> Result<bool> {
+    git(dir, &["add", "--all"])?;
+    if git(dir, &["status", "--porcelain"])?.is_empty() {
+        return Ok(false);
+    }
+    let message = format!("Update ver state from {}", hostname());
+    let mut args = Vec::new();
+    if git(dir, &["config", "user.email"]).unwrap_or_default().is_empty() {
+        args.extend(["-c", "user.name=ver", "-c", "user.email=ver@localhost"]);
+    }
+    args.extend(["commit", "--quiet", "-m", &message]);
+    git(dir, &args)?;
+    Ok(true)
+}
+
+/// 远程仓库是否已有提交（新建的空仓库没有分支）
+fn remote_has_commits(dir: &Path) -> Result<bool> {
+    Ok(!git(dir, &["ls-remote", "--heads", "origin"])?.is_empty())
+}
+
+/// 拉取远程的提交，本地未推送的提交变基到其上
+///
+/// 有冲突时放弃变基，保持仓库不变并返回错误。
+pub fn pull(dir: &Path) -> Result<()> {
+    if !remote_has_commits(dir)? {
+        return Ok(());
+    }
+    git(dir, &["fetch", "--quiet", "origin"])?;
+    let upstream = format!("origin/{}", git(dir, &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"])
+        .ok()
+        .and_then(|head| head.strip_prefix("origin/").map(str::to_string))
+        .unwrap_or_else(|| git(dir, &["branch", "--show-current"]).unwrap_or_default()));
+    if git(dir, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_err() {
+        // 本地还没有提交（从空仓库克隆），直接使用远程的分支
+        git(dir, &["reset", "--quiet", "--hard", &upstream])?;
+        return Ok(());
+    }
+    if let Err(err) = git(dir, &["rebase", "--quiet", &upstream]) {
+        let _ = git(dir, &["rebase", "--abort"]);
+        return Err(anyhow::anyhow!("同步仓库 {} 与远程有冲突，请在该目录中手动解决后再同步: {}", dir.display(), err));
+    }
+    Ok(())
+}
+
+/// 把本地提交推送到远程
+pub fn push(dir: &Path) -> Result<()> {
+    git(dir, &["push", "--quiet", "--set-upstream", "origin", "HEAD"])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(aliases: &[(&str, &str)], defaults: &[(&str, &str)]) -> SyncState {
+        SyncState {
+            aliases: aliases.iter().map(|(alias, version)| (("node".to_string(), alias.to_string()), version.to_string())).collect(),
+            defaults: defaults.iter().map(|(tool, version)| (tool.to_string(), version.to_string())).collect(),
+            ..SyncState::default()
+        }
+    }
+
+    #[test]
+    fn merge_keeps_changes_from_both_sides() {
+        let base = state(&[("lts", "18.0.0"), ("old", "16.0.0"), ("work", "20.0.0")], &[("node", "18.0.0"), ("go", "1.21.0")]);
+        // 远程修改了 lts、删除了 old；本地新增 dev、修改了 work，并且没有 Go 的默认版本
+        let theirs = state(&[("lts", "20.0.0"), ("work", "20.0.0")], &[("node", "20.0.0"), ("go", "1.21.0")]);
+        let ours = state(&[("lts", "18.0.0"), ("old", "16.0.0"), ("work", "22.0.0"), ("dev", "23.0.0")], &[("node", "18.0.0")]);
+        assert_eq!(merge(&base, &theirs, &ours), state(
+            &[("dev", "23.0.0"), ("lts", "20.0.0"), ("work", "22.0.0")],
+            &[("go", "1.21.0"), ("node", "20.0.0")],
+        ));
+    }
+
+    #[test]
+    fn merge_prefers_local_when_both_sides_change_a_key() {
+        let base = state(&[("lts", "18.0.0")], &[]);
+        let theirs = state(&[("lts", "20.0.0")], &[]);
+        let ours = state(&[], &[]);
+        // 本地删除、远程修改时保留本地的删除
+        assert_eq!(merge(&base, &theirs, &ours), state(&[], &[]));
+        let ours = state(&[("lts", "22.0.0")], &[]);
+        assert_eq!(merge(&base, &theirs, &ours), ours);
+    }
+}
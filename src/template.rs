@@ -0,0 +1,114 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::version_manager::VersionType;
+
+/// `ver new` 的项目模板
+pub struct Template {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// 模板固定版本的工具
+    pub tools: &'static [VersionType],
+}
+
+/// 内置的项目模板
+pub const TEMPLATES: &[Template] = &[
+    Template { name: "node", description: "Node.js package", tools: &[VersionType::Node] },
+    Template { name: "rust", description: "Rust binary crate", tools: &[VersionType::Rust] },
+    Template { name: "python", description: "Python project (pyproject.toml)", tools: &[VersionType::Python] },
+    Template { name: "go", description: "Go module", tools: &[VersionType::Go] },
+    Template { name: "fullstack", description: "Node.js frontend with a Python backend", tools: &[VersionType::Node, VersionType::Python] },
+];
+
+/// 按名称查找模板
+pub fn find(name: &str) -> Result<&'static Template> {
+    TEMPLATES.iter().find(|template| template.name == name).ok_or_else(|| {
+        let names: Vec<&str> = TEMPLATES.iter().map(|template| template.name).collect();
+        anyhow::anyhow!("未知的项目模板: {}，可选 {}", name, names.join("、"))
+    })
+}
+
+/// 用户保存的 `.ver.toml` 模板，`templates_dir/<名称>.ver.toml`，可以通过 `ver sync` 在机器间同步
+pub fn user_template(templates_dir: &Path, name: &str) -> Option<PathBuf> {
+    let path = templates_dir.join(format!("{}.ver.toml", name));
+    path.is_file().then_some(path)
+}
+
+/// 列出用户保存的模板，按名称排序
+pub fn user_templates(templates_dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut templates: Vec<(String, PathBuf)> = std::fs::read_dir(templates_dir).into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().strip_suffix(".ver.toml")?.to_string();
+            Some((name, entry.path()))
+        })
+        .collect();
+    templates.sort();
+    templates
+}
+
+/// 把目录名转换为包名：小写，字母数字以外的字符换成 `-`
+pub fn package_name(dir_name: &str) -> String {
+    let name: String = dir_name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+    if name.is_empty() { "app".to_string() } else { name.to_string() }
+}
+
+/// 生成模板中的文件
+///
+/// 每个工具都写入 ver 读取的 `.<tool>-version`，并在项目清单中声明同一版本
+/// （package.json 的 engines、Cargo.toml 的 rust-version 和 rust-toolchain.toml、
+/// pyproject.toml 的 requires-python、go.mod 的 go 指令），不使用 ver 的协作者和 CI 也能得到相同的约束。
+///
+/// # 参数
+///
+/// * `name` - 包名
+/// * `pins` - 每个工具固定的版本
+///
+/// # 返回
+///
+/// 返回相对路径和文件内容的列表。
+pub fn files(name: &str, pins: &[(VersionType, String)]) -> Vec<(String, String)> {
+    let mut files = Vec::new();
+    let mut ignores = Vec::new();
+    for (version_type, version) in pins {
+        files.push((version_type.version_file_name().to_string(), format!("{}\n", version)));
+        let major = version.split('.').next().unwrap_or(version);
+        let minor = version.split('.').take(2).collect::<Vec<_>>().join(".");
+        match version_type {
+            VersionType::Node => {
+                files.push(("package.json".to_string(), format!(
+                    "{{\n  \"name\": \"{}\",\n  \"version\": \"0.1.0\",\n  \"private\": true,\n  \"engines\": {{\n    \"node\": \">={}\"\n  }}\n}}\n",
+                    name, major
+                )));
+                ignores.push("node_modules/");
+            }
+            VersionType::Rust => {
+                files.push(("rust-toolchain.toml".to_string(), format!("[toolchain]\nchannel = \"{}\"\n", version)));
+                files.push(("Cargo.toml".to_string(), format!(
+                    "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\nrust-version = \"{}\"\n\n[dependencies]\n",
+                    name, minor
+                )));
+                files.push(("src/main.rs".to_string(), "fn main() {\n    println!(\"Hello, world!\");\n}\n".to_string()));
+                ignores.push("/target");
+            }
+            VersionType::Python => {
+                files.push(("pyproject.toml".to_string(), format!(
+                    "[project]\nname = \"{}\"\nversion = \"0.1.0\"\nrequires-python = \">={}\"\ndependencies = []\n",
+                    name, minor
+                )));
+                ignores.extend([".venv/", "__pycache__/"]);
+            }
+            VersionType::Go => {
+                files.push(("go.mod".to_string(), format!("module {}\n\ngo {}\n", name, version)));
+            }
+        }
+    }
+    if !ignores.is_empty() {
+        files.push((".gitignore".to_string(), format!("{}\n", ignores.join("\n"))));
+    }
+    files
+}
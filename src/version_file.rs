@@ -0,0 +1,183 @@
+use anyhow::Result;
+use std::{fs, path::{Path, PathBuf}};
+
+use crate::config::Config;
+use crate::state::write_atomic;
+use crate::version_manager::VersionType;
+
+/// ver 自己的项目文件，在一个文件中固定多个工具的版本
+pub const VER_TOML: &str = ".ver.toml";
+
+/// 项目版本文件格式
+///
+/// 版本解析时在每一层目录中按顺序尝试所有格式，第一个给出版本的文件生效。
+pub trait VersionFileFormat {
+    /// 版本文件名，例如 `.node-version`
+    fn file_name(&self) -> &str;
+
+    /// 从文件内容中读取工具的版本
+    ///
+    /// # 参数
+    ///
+    /// * `content` - 文件内容
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 文件声明了该工具的版本时返回版本号，否则返回None。
+    fn parse(&self, content: &str, version_type: VersionType) -> Option<String>;
+}
+
+/// 整个文件内容就是某个工具的版本，例如 `.node-version` 和 `.nvmrc`
+pub struct SingleToolFile {
+    file_name: String,
+    tool: VersionType,
+}
+
+impl VersionFileFormat for SingleToolFile {
+    fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    fn parse(&self, content: &str, version_type: VersionType) -> Option<String> {
+        let version = content.trim();
+        (version_type == self.tool && !version.is_empty()).then(|| version.to_string())
+    }
+}
+
+/// 每行声明一个工具的版本，例如 `node=20` 或 `go 1.22.1`，`#` 开头的行是注释
+pub struct ToolLinesFile {
+    file_name: String,
+}
+
+impl VersionFileFormat for ToolLinesFile {
+    fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    fn parse(&self, content: &str, version_type: VersionType) -> Option<String> {
+        content.lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once(['=', ' ', '\t']))
+            .find(|(tool, _)| VersionType::from_id(tool.trim()) == Some(version_type))
+            .map(|(_, version)| version.trim().to_string())
+            .filter(|version| !version.is_empty())
+    }
+}
+
+/// `.ver.toml`：在 `[tools]` 表中声明每个工具的版本，例如 `node = "20.11.1"`
+pub struct VerTomlFile;
+
+impl VersionFileFormat for VerTomlFile {
+    fn file_name(&self) -> &str {
+        VER_TOML
+    }
+
+    fn parse(&self, content: &str, version_type: VersionType) -> Option<String> {
+        let mut in_tools = false;
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_tools = line == "[tools]";
+                continue;
+            }
+            if !in_tools {
+                continue;
+            }
+            if let Some((tool, version)) = line.split_once('=')
+                && VersionType::from_id(tool.trim().trim_matches('"')) == Some(version_type)
+            {
+                let version = version.split('#').next().unwrap_or_default().trim().trim_matches('"');
+                return (!version.is_empty()).then(|| version.to_string());
+            }
+        }
+        None
+    }
+}
+
+/// 把固定的版本写入目录中的 `.ver.toml`
+///
+/// 已有的文件中同一工具的版本会被替换，其他内容保持不变；没有 `[tools]` 表时追加到文件末尾。
+///
+/// # 参数
+///
+/// * `dir` - 项目目录
+/// * `pins` - 每个工具固定的版本
+///
+/// # 返回
+///
+/// 成功时返回写入的文件路径，失败时返回错误。
+pub fn write_pins(dir: &Path, pins: &[(VersionType, String)]) -> Result<PathBuf> {
+    let path = dir.join(VER_TOML);
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    
+    let start = match lines.iter().position(|line| line.trim() == "[tools]") {
+        Some(index) => index + 1,
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push("[tools]".to_string());
+            lines.len()
+        }
+    };
+    for (version_type, version) in pins {
+        let end = lines[start..].iter()
+            .position(|line| line.trim().starts_with('['))
+            .map_or(lines.len(), |offset| start + offset);
+        let entry = format!("{} = \"{}\"", version_type.id(), version);
+        let existing = lines[start..end].iter().position(|line| {
+            line.split_once('=').is_some_and(|(tool, _)| VersionType::from_id(tool.trim().trim_matches('"')) == Some(*version_type))
+        });
+        match existing {
+            Some(offset) => lines[start + offset] = entry,
+            None => {
+                // 插入到表中最后一项之后，保留表之间的空行
+                let insert_at = (start..end).rev()
+                    .find(|index| !lines[*index].trim().is_empty())
+                    .map_or(start, |index| index + 1);
+                lines.insert(insert_at, entry);
+            }
+        }
+    }
+    
+    write_atomic(&path, format!("{}\n", lines.join("\n")))?;
+    Ok(path)
+}
+
+/// 根据配置的解析规则创建版本文件格式
+///
+/// 规则为工具标识（整个文件是该工具的版本）或 `lines`（每行 `<工具>=<版本>`）。
+///
+/// # 参数
+///
+/// * `file_name` - 版本文件名
+/// * `rule` - 解析规则
+///
+/// # 返回
+///
+/// 成功时返回版本文件格式，规则无法识别时返回错误。
+pub fn from_rule(file_name: &str, rule: &str) -> Result<Box<dyn VersionFileFormat>> {
+    if rule == "lines" {
+        return Ok(Box::new(ToolLinesFile { file_name: file_name.to_string() }));
+    }
+    match VersionType::from_id(rule) {
+        Some(tool) => Ok(Box::new(SingleToolFile { file_name: file_name.to_string(), tool })),
+        None => Err(anyhow::anyhow!("无法识别的版本文件解析规则: {}，应为 node、rust、python、go 或 lines", rule)),
+    }
+}
+
+/// 获取版本解析使用的所有版本文件格式
+///
+/// 内置的 `.<tool>-version` 优先，然后是 `.ver.toml`，最后是配置项 `version_files.<文件名>` 中按文件名排序的自定义格式。
+/// 配置中无法识别的规则会被跳过，写入配置时已经校验过。
+pub fn formats(config: &Config) -> Vec<Box<dyn VersionFileFormat>> {
+    let mut formats: Vec<Box<dyn VersionFileFormat>> = VersionType::all()
+        .into_iter()
+        .map(|tool| Box::new(SingleToolFile { file_name: tool.version_file_name().to_string(), tool }) as Box<dyn VersionFileFormat>)
+        .collect();
+    formats.push(Box::new(VerTomlFile));
+    formats.extend(config.version_files.iter().filter_map(|(file_name, rule)| from_rule(file_name, rule).ok()));
+    formats
+}
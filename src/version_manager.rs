@@ -9,18 +9,57 @@ use std::{
     path::{Path, PathBuf},
     process::Command,
     str::FromStr,
+    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
 };
+#[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
+use crate::config::{Config, PythonWindowsSource, RustSource};
+use crate::detect::{self, Evidence};
+use crate::format;
+use crate::metadata;
+use crate::nix;
+use crate::output;
+use crate::policy::{Policy, PolicyMode};
+use crate::prereqs;
+use crate::release::{self, ReleaseManifest};
+use crate::resolve_cache::ResolveCache;
+use crate::schedule;
+use crate::state::{self, write_atomic};
+use crate::sync::SyncState;
+use crate::version_file;
+
+/// 补全缓存中保存的远程版本数量
+const COMPLETION_CACHE_SIZE: usize = 50;
+
+/// 自动选择的镜像的有效期（秒）
+const MIRROR_SELECTION_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// 修改shell配置文件时，文件被其他程序改写后最多重试的次数
+const SHELL_CONFIG_ATTEMPTS: usize = 3;
+
+/// Rust代理脚本第二行的标记，用于识别由 ver 生成的脚本
+const RUST_SHIM_MARKER: &str = "# ver rust shim";
+
 // 支持的操作系统和架构
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum OsType {
     Darwin,
     Linux,
     Windows,
 }
 
-#[derive(Debug)]
+impl OsType {
+    fn name(&self) -> &'static str {
+        match self {
+            OsType::Darwin => "darwin",
+            OsType::Linux => "linux",
+            OsType::Windows => "windows",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum ArchType {
     X64,
     Arm64,
@@ -28,6 +67,77 @@ enum ArchType {
     X86,
 }
 
+impl ArchType {
+    /// 从名称解析架构，同时接受 amd64/aarch64 等常见写法
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "x64" | "amd64" | "x86_64" => Some(ArchType::X64),
+            "arm64" | "aarch64" => Some(ArchType::Arm64),
+            "arm" | "armv7l" => Some(ArchType::Arm),
+            "x86" | "386" | "i686" => Some(ArchType::X86),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ArchType::X64 => "x64",
+            ArchType::Arm64 => "arm64",
+            ArchType::Arm => "arm",
+            ArchType::X86 => "x86",
+        }
+    }
+}
+
+// 各工具提供预编译包的平台及对应的下载文件名后缀
+const NODE_TARGETS: &[(OsType, ArchType, &str)] = &[
+    (OsType::Darwin, ArchType::X64, "darwin-x64"),
+    (OsType::Darwin, ArchType::Arm64, "darwin-arm64"),
+    (OsType::Linux, ArchType::X64, "linux-x64"),
+    (OsType::Linux, ArchType::Arm64, "linux-arm64"),
+    (OsType::Linux, ArchType::Arm, "linux-armv7l"),
+    (OsType::Windows, ArchType::X64, "win-x64"),
+    (OsType::Windows, ArchType::X86, "win-x86"),
+];
+
+const RUST_TARGETS: &[(OsType, ArchType, &str)] = &[
+    (OsType::Darwin, ArchType::X64, "x86_64-apple-darwin"),
+    (OsType::Darwin, ArchType::Arm64, "aarch64-apple-darwin"),
+    (OsType::Linux, ArchType::X64, "x86_64-unknown-linux-gnu"),
+    (OsType::Linux, ArchType::Arm64, "aarch64-unknown-linux-gnu"),
+    (OsType::Linux, ArchType::Arm, "armv7-unknown-linux-gnueabihf"),
+    (OsType::Windows, ArchType::X64, "x86_64-pc-windows-msvc"),
+    (OsType::Windows, ArchType::X86, "i686-pc-windows-msvc"),
+];
+
+const PYTHON_TARGETS: &[(OsType, ArchType, &str)] = &[
+    (OsType::Darwin, ArchType::X64, "macosx10.9.x86_64"),
+    (OsType::Darwin, ArchType::Arm64, "macos11.0.arm64"),
+    (OsType::Linux, ArchType::X64, "x86_64"),
+    (OsType::Linux, ArchType::Arm64, "aarch64"),
+    (OsType::Linux, ArchType::Arm, "armv7l"),
+    (OsType::Windows, ArchType::X64, "amd64"),
+    (OsType::Windows, ArchType::X86, "win32"),
+];
+
+/// `ver export --format nix` 生成的 flake 支持的 Nix 系统
+const NIX_SYSTEMS: &[(&str, OsType, ArchType)] = &[
+    ("x86_64-linux", OsType::Linux, ArchType::X64),
+    ("aarch64-linux", OsType::Linux, ArchType::Arm64),
+    ("x86_64-darwin", OsType::Darwin, ArchType::X64),
+    ("aarch64-darwin", OsType::Darwin, ArchType::Arm64),
+];
+
+const GO_TARGETS: &[(OsType, ArchType, &str)] = &[
+    (OsType::Darwin, ArchType::X64, "darwin-amd64"),
+    (OsType::Darwin, ArchType::Arm64, "darwin-arm64"),
+    (OsType::Linux, ArchType::X64, "linux-amd64"),
+    (OsType::Linux, ArchType::Arm64, "linux-arm64"),
+    (OsType::Linux, ArchType::Arm, "linux-armv6l"),
+    (OsType::Windows, ArchType::X64, "windows-amd64"),
+    (OsType::Windows, ArchType::X86, "windows-386"),
+];
+
 // 版本类型枚举
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VersionType {
@@ -37,6 +147,112 @@ pub enum VersionType {
     Go,
 }
 
+impl VersionType {
+    /// 所有支持的版本类型
+    pub fn all() -> [VersionType; 4] {
+        [VersionType::Node, VersionType::Rust, VersionType::Python, VersionType::Go]
+    }
+
+    /// 根据工具标识获取版本类型
+    pub fn from_id(id: &str) -> Option<VersionType> {
+        VersionType::all().into_iter().find(|t| t.id() == id.to_lowercase())
+    }
+
+    /// 命令行和机器可读输出中使用的工具标识
+    pub fn id(&self) -> &'static str {
+        match self {
+            VersionType::Node => "node",
+            VersionType::Rust => "rust",
+            VersionType::Python => "python",
+            VersionType::Go => "go",
+        }
+    }
+
+    /// 项目中声明版本的文件名
+    pub fn version_file_name(&self) -> &'static str {
+        match self {
+            VersionType::Node => ".node-version",
+            VersionType::Rust => ".rust-version",
+            VersionType::Python => ".python-version",
+            VersionType::Go => ".go-version",
+        }
+    }
+
+    /// `ver shell` 为当前终端设置的版本环境变量，优先级高于版本文件和全局版本
+    pub fn session_env_var(&self) -> &'static str {
+        match self {
+            VersionType::Node => "VER_NODE_VERSION",
+            VersionType::Rust => "VER_RUST_VERSION",
+            VersionType::Python => "VER_PYTHON_VERSION",
+            VersionType::Go => "VER_GO_VERSION",
+        }
+    }
+
+    /// 其他版本管理工具和 CI 脚本中常用的版本环境变量
+    ///
+    /// 开启 `resolve.compat_env` 后作为版本解析的输入。
+    pub fn compat_env_var(&self) -> &'static str {
+        match self {
+            VersionType::Node => "NODE_VERSION",
+            VersionType::Rust => "RUSTUP_TOOLCHAIN",
+            VersionType::Python => "PYENV_VERSION",
+            VersionType::Go => "GOTOOLCHAIN",
+        }
+    }
+
+    /// 从兼容环境变量的值中取出版本号
+    ///
+    /// PYENV_VERSION 可以是冒号分隔的多个版本，只使用第一个；`system` 表示不由 ver 管理。
+    /// GOTOOLCHAIN 的 `local`、`auto` 不指定版本，`go1.22.1+auto` 取 `+` 之前的部分。
+    /// RUSTUP_TOOLCHAIN 中的目标三元组会被去掉，例如 `1.75.0-x86_64-unknown-linux-gnu`。
+    pub fn compat_env_version(&self, value: &str) -> Option<String> {
+        let value = value.trim();
+        let version = match self {
+            VersionType::Node => value.trim_start_matches('v'),
+            VersionType::Python => value.split(':').next().unwrap_or_default(),
+            VersionType::Go => value.split('+').next().unwrap_or_default().trim_start_matches("go"),
+            VersionType::Rust => value.split_once('-')
+                .filter(|(_, rest)| rest.contains('-'))
+                .map(|(toolchain, _)| toolchain)
+                .unwrap_or(value),
+        };
+        match version {
+            "" | "system" | "local" | "auto" => None,
+            _ => Some(version.to_string()),
+        }
+    }
+
+    /// 从源码构建的说明文档地址
+    pub fn source_build_url(&self) -> &'static str {
+        match self {
+            VersionType::Node => "https://github.com/nodejs/node/blob/main/BUILDING.md",
+            VersionType::Rust => "https://github.com/rust-lang/rust/blob/master/INSTALL.md",
+            VersionType::Python => "https://devguide.python.org/getting-started/setup-building/",
+            VersionType::Go => "https://go.dev/doc/install/source",
+        }
+    }
+
+    /// 某个版本的发布说明地址
+    pub fn release_notes_url(&self, version: &str) -> String {
+        match self {
+            VersionType::Node => format!("https://nodejs.org/en/blog/release/v{}", version),
+            VersionType::Rust => format!("https://github.com/rust-lang/rust/releases/tag/{}", version),
+            VersionType::Python => format!("https://www.python.org/downloads/release/python-{}/", version.replace('.', "")),
+            VersionType::Go => format!("https://go.dev/doc/devel/release#go{}", version),
+        }
+    }
+
+    /// 官方下载源地址
+    pub fn default_mirror(&self) -> &'static str {
+        match self {
+            VersionType::Node => "https://nodejs.org/dist",
+            VersionType::Rust => "https://static.rust-lang.org/dist",
+            VersionType::Python => "https://www.python.org/ftp/python",
+            VersionType::Go => "https://golang.org/dl",
+        }
+    }
+}
+
 impl std::fmt::Display for VersionType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -48,21 +264,337 @@ impl std::fmt::Display for VersionType {
     }
 }
 
+// Node.js 官方 index.json 中的条目
 #[derive(Debug, Serialize, Deserialize)]
-pub struct NodeVersion {
-    pub version: String,
+struct NodeVersion {
+    version: String,
     #[serde(deserialize_with = "deserialize_lts")]
-    pub lts: bool,
-    pub date: String,
-    pub files: Vec<String>,
+    lts: bool,
+    date: String,
+    files: Vec<String>,
+    /// NODE_MODULE_VERSION，原生模块的ABI版本
+    #[serde(default)]
+    modules: Option<String>,
+    /// 是否为安全更新版本
+    #[serde(default)]
+    security: bool,
 }
 
-// Rust版本结构体
-#[derive(Debug, Serialize, Deserialize)]
-pub struct RustVersion {
+/// 远程版本所属的发布渠道
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    /// Node.js 长期支持版本
+    Lts,
+    /// 渠道清单中标记的当前稳定版本
+    Stable,
+    /// 普通发布版本
+    Release,
+}
+
+/// 远程版本中某个平台的下载文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseFile {
+    /// 平台后缀，与各工具下载文件名中的后缀一致（例如 `linux-x64`）
+    pub platform: String,
+    pub url: String,
+    /// 发布方提供的SHA-256校验和，索引中没有时为None
+    pub sha256: Option<String>,
+}
+
+/// 远程版本信息
+///
+/// 由各工具的后端从远程索引解析得到。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteRelease {
     pub version: String,
-    pub date: String,
-    pub stable: bool,
+    pub channel: ReleaseChannel,
+    pub date: Option<String>,
+    /// 索引中标记为安全更新（目前只有 Node.js 的索引提供）
+    #[serde(default)]
+    pub security: bool,
+    pub files: Vec<ReleaseFile>,
+}
+
+impl RemoteRelease {
+    /// 是否为推荐版本（LTS或稳定渠道）
+    pub fn is_recommended(&self) -> bool {
+        matches!(self.channel, ReleaseChannel::Lts | ReleaseChannel::Stable)
+    }
+
+    /// 查找指定平台的下载文件
+    pub fn file_for(&self, platform: &str) -> Option<&ReleaseFile> {
+        self.files.iter().find(|file| file.platform == platform)
+    }
+}
+
+/// Rust渠道清单（channel-rust-*.toml）中用到的部分
+struct RustManifest {
+    date: Option<String>,
+    version: Option<String>,
+    /// (包名, 目标三元组) -> (下载地址, SHA-256)
+    packages: HashMap<(String, String), (String, String)>,
+}
+
+impl RustManifest {
+    /// 解析清单内容
+    ///
+    /// 只处理本项目需要的键，不是完整的TOML解析器。
+    fn parse(manifest: &str) -> Self {
+        let mut date = None;
+        let mut version = None;
+        let mut packages = HashMap::new();
+        let mut section = String::new();
+        let mut url = None;
+        
+        for line in manifest.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                section = line.trim_matches(|c| c == '[' || c == ']').to_string();
+                url = None;
+                continue;
+            }
+            
+            let Some((key, value)) = line.split_once(" = ") else {
+                continue;
+            };
+            let value = value.trim_matches('"');
+            
+            if section.is_empty() && key == "date" {
+                date = Some(value.to_string());
+            } else if section == "pkg.rust" && key == "version" && version.is_none() {
+                // 形如 "1.85.0 (4d91de4e4 2025-02-17)"
+                version = value.split_whitespace().next().map(|v| v.to_string());
+            } else if let Some((package, target)) = section.strip_prefix("pkg.")
+                .and_then(|rest| rest.split_once(".target."))
+            {
+                match key {
+                    "url" => url = Some(value.to_string()),
+                    "hash" => {
+                        if let Some(url) = url.take() {
+                            packages.insert((package.to_string(), target.to_string()), (url, value.to_string()));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        
+        Self { date, version, packages }
+    }
+}
+
+/// 创建符号链接
+///
+/// Windows 上创建符号链接需要开发者模式或管理员权限，失败时复制链接目标。
+fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link)
+    }
+    #[cfg(windows)]
+    {
+        let resolved = link.parent().unwrap_or(Path::new("")).join(target);
+        let result = if resolved.is_dir() {
+            std::os::windows::fs::symlink_dir(target, link)
+        } else {
+            std::os::windows::fs::symlink_file(target, link)
+        };
+        result.or_else(|_| fs::copy(&resolved, link).map(|_| ()))
+    }
+}
+
+/// 复制文件，文件系统支持时使用写时复制克隆
+///
+/// Linux 上通过 FICLONE 在 Btrfs、XFS 等文件系统上共享数据块，macOS 上使用 APFS 的 clonefile，
+/// 克隆几乎瞬间完成且不占用额外空间。不支持克隆时（例如 ext4 或跨文件系统）退回普通复制。
+///
+/// # 返回
+///
+/// 成功时返回是否使用了克隆，失败时返回错误。
+fn clone_or_copy(from: &Path, to: &Path) -> io::Result<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+        let source = fs::File::open(from)?;
+        let dest = fs::File::create(to)?;
+        // SAFETY: 两个文件描述符在调用期间都有效
+        if unsafe { libc::ioctl(dest.as_raw_fd(), libc::FICLONE as _, source.as_raw_fd()) } == 0 {
+            dest.set_permissions(source.metadata()?.permissions())?;
+            return Ok(true);
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let from_c = std::ffi::CString::new(from.as_os_str().as_bytes())?;
+        let to_c = std::ffi::CString::new(to.as_os_str().as_bytes())?;
+        // clonefile 要求目标不存在，会同时保留权限
+        let _ = fs::remove_file(to);
+        // SAFETY: 两个路径都是以 NUL 结尾的有效C字符串
+        if unsafe { libc::clonefile(from_c.as_ptr(), to_c.as_ptr(), 0) } == 0 {
+            return Ok(true);
+        }
+    }
+    fs::copy(from, to).map(|_| false)
+}
+
+/// 转换为 Windows 扩展长度路径
+///
+/// 为绝对路径加上 `\\?\` 前缀（UNC 路径为 `\\?\UNC\`），绕过 260 字符的 MAX_PATH 限制。
+/// 其他平台或相对路径原样返回。
+fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) || !path.is_absolute() {
+        return path.to_path_buf();
+    }
+    
+    // 扩展长度路径不会再被规范化，需要统一使用反斜杠
+    let raw = path.to_string_lossy().replace('/', r"\");
+    if raw.starts_with(r"\\?\") {
+        PathBuf::from(raw)
+    } else if let Some(unc) = raw.strip_prefix(r"\\") {
+        PathBuf::from(format!(r"\\?\UNC\{}", unc))
+    } else {
+        PathBuf::from(format!(r"\\?\{}", raw))
+    }
+}
+
+/// 从分块到达的JSON数组中逐个取出顶层元素
+///
+/// 只跟踪括号深度和字符串，不解析元素内容；每个完整的元素交给 serde_json 解析。
+/// 用于在下载完整个版本索引之前就开始输出结果。
+#[derive(Default)]
+struct JsonArrayItems {
+    item: Vec<u8>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl JsonArrayItems {
+    /// 输入一块数据，返回其中完成的元素
+    fn push(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        let mut items = Vec::new();
+        for &byte in chunk {
+            if self.depth >= 2 || (self.depth == 1 && !matches!(byte, b',' | b']') && !byte.is_ascii_whitespace()) {
+                self.item.push(byte);
+            }
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match byte {
+                b'"' => self.in_string = true,
+                b'[' | b'{' => self.depth += 1,
+                b']' | b'}' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 1 && !self.item.is_empty() {
+                        items.push(std::mem::take(&mut self.item));
+                    }
+                }
+                _ => {}
+            }
+        }
+        items
+    }
+}
+
+/// 下载地址的短摘要，用于区分不同镜像下载的同名文件
+fn url_digest(url: &str) -> String {
+    use sha2::{Digest, Sha256};
+    
+    format!("{:x}", Sha256::digest(url.as_bytes()))[..12].to_string()
+}
+
+/// 把安装包文件名分成主干和扩展名，例如 `node-v20.1.0-linux-x64` 和 `.tar.gz`
+fn split_archive_name(file_name: &str) -> (&str, &str) {
+    [".tar.gz", ".tar.xz", ".zip"].iter()
+        .find_map(|extension| file_name.strip_suffix(extension).map(|stem| (stem, &file_name[stem.len()..])))
+        .unwrap_or((file_name, ""))
+}
+
+/// 删除文本中重复出现的某一行，只保留第一次出现的位置
+///
+/// 用于清理早期版本并发修改shell配置文件时重复加入的内容。
+fn dedup_line(content: &str, line: &str) -> String {
+    let line = line.trim_end();
+    let mut seen = false;
+    content
+        .split_inclusive('\n')
+        .filter(|current| {
+            if current.trim_end() != line {
+                return true;
+            }
+            !std::mem::replace(&mut seen, true)
+        })
+        .collect()
+}
+
+/// 把值加上单引号，使其可以原样用在 sh 命令中
+pub fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// 把值加上单引号，使其可以原样用在 fish 命令中
+pub fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// 按数字比较两个版本号
+///
+/// 忽略开头的 `v`，逐段比较数字部分，无法解析的段视为0。
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let a_parts: Vec<&str> = a.trim_start_matches('v').split('.').collect();
+    let b_parts: Vec<&str> = b.trim_start_matches('v').split('.').collect();
+    
+    for (a_part, b_part) in a_parts.iter().zip(b_parts.iter()) {
+        let a_num = a_part.parse::<i32>().unwrap_or(0);
+        let b_num = b_part.parse::<i32>().unwrap_or(0);
+        
+        if a_num != b_num {
+            return a_num.cmp(&b_num);
+        }
+    }
+    
+    a_parts.len().cmp(&b_parts.len())
+}
+
+/// 计算两个字符串的编辑距离（Levenshtein）
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// 从候选版本中找出与请求的版本最接近的几个
+///
+/// 同一主版本的候选优先，其次按编辑距离和版本号（从新到旧）排序；
+/// 主版本不同且编辑距离超过2的候选不会返回。
+pub fn similar_versions<'a>(requested: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let requested = requested.trim_start_matches('v');
+    let major = |version: &str| version.split('.').next().unwrap_or("").to_string();
+    let mut matches: Vec<(bool, usize, String)> = candidates.into_iter()
+        .map(|candidate| candidate.trim_start_matches('v'))
+        .filter(|candidate| *candidate != requested)
+        .map(|candidate| (major(candidate) != major(requested), edit_distance(requested, candidate), candidate.to_string()))
+        .filter(|(other_major, distance, _)| !other_major || *distance <= 2)
+        .collect();
+    matches.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)).then(compare_versions(&b.2, &a.2)));
+    matches.dedup_by(|a, b| a.2 == b.2);
+    matches.into_iter().take(3).map(|(_, _, version)| version).collect()
 }
 
 // 自定义反序列化函数来处理 lts 字段
@@ -84,352 +616,6748 @@ where
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Aliases {
-    aliases: HashMap<String, String>,
-}
-
-// 自定义错误类型
-#[derive(Debug)]
-pub enum VersionError {
-    NotInstalled(String, VersionType),
-    NotFound(String, VersionType),
-    CurrentlyActive(String, VersionType),
-    IoError(io::Error),
+// 版本解析来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VersionSource {
+    /// 当前目录下的版本文件
+    Local,
+    /// 全局当前版本
+    Global,
+    /// 兼容其他工具的环境变量，例如 NODE_VERSION
+    Environment,
+    /// `ver shell` 为当前终端设置的版本
+    Session,
+    /// `ver rust override` 为目录设置的工具链
+    Override,
 }
 
-impl std::fmt::Display for VersionError {
+impl std::fmt::Display for VersionSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            VersionError::NotInstalled(version, version_type) => 
-                write!(f, "{} 版本 {} 未安装", version_type, version),
-            VersionError::NotFound(version, version_type) => 
-                write!(f, "找不到 {} 版本 {}", version_type, version),
-            VersionError::CurrentlyActive(version, version_type) => 
-                write!(f, "无法删除当前活动的 {} 版本 {}。请先切换到其他版本。", version_type, version),
-            VersionError::IoError(err) => 
-                write!(f, "IO错误: {}", err),
+            VersionSource::Local => write!(f, "local"),
+            VersionSource::Global => write!(f, "global"),
+            VersionSource::Environment => write!(f, "environment"),
+            VersionSource::Session => write!(f, "session"),
+            VersionSource::Override => write!(f, "override"),
         }
     }
 }
 
-impl std::error::Error for VersionError {}
+// 自动选择的镜像记录
+#[derive(Debug, Serialize, Deserialize)]
+struct MirrorSelection {
+    url: String,
+    selected_at: i64,
+}
 
-impl From<io::Error> for VersionError {
-    fn from(err: io::Error) -> Self {
-        VersionError::IoError(err)
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MirrorSelections {
+    selections: HashMap<String, MirrorSelection>,
+}
+
+/// 订阅的发布渠道
+///
+/// `ver upgrade --subscribed` 会安装渠道中的最新版本，并可选地把别名指向它。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+    /// 工具标识
+    pub tool: String,
+    /// 渠道：latest、lts、stable 或主版本前缀（例如 20）
+    pub channel: String,
+    /// 安装新版本后重新指向的别名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// 最近一次通过订阅安装的版本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Subscriptions {
+    subscriptions: Vec<Subscription>,
+}
+
+/// 目录到 Rust 工具链的映射，与 rustup 的目录覆盖相同
+///
+/// 保存在 ver 主目录中，不需要在项目中放置 rust-toolchain 文件，适合无法修改的仓库。
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RustOverrides {
+    /// 规范化的目录路径到工具链
+    overrides: std::collections::BTreeMap<PathBuf, String>,
+}
+
+/// 后台或正在进行的安装操作
+///
+/// 每个操作保存在 `~/.version-manager/operations/<id>.json`，
+/// 编辑器插件可以用 `ver install --detach` 启动安装，再轮询 `ver status --operations --json` 直到完成。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    /// 操作ID
+    pub id: String,
+    /// 操作类型，目前只有 install
+    pub kind: String,
+    /// 工具标识
+    pub tool: String,
+    /// 版本号
+    pub version: String,
+    /// 执行操作的进程ID
+    pub pid: u32,
+    /// 状态：queued、running、succeeded、failed、cancelled
+    pub state: String,
+    /// 开始时间
+    pub started_at: String,
+    /// 结束时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    /// 已下载的字节数
+    #[serde(default)]
+    pub downloaded_bytes: u64,
+    /// 下载的总字节数，未知时为0
+    #[serde(default)]
+    pub total_bytes: u64,
+    /// 失败原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 正在进行的安装阶段，见 [`InstallPhase::name`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+}
+
+impl Operation {
+    /// 操作是否已经结束
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state.as_str(), "succeeded" | "failed" | "cancelled")
     }
 }
 
-/// 版本管理器结构体，用于管理不同语言的版本
+/// 镜像测速结果
+#[derive(Debug)]
+pub struct MirrorProbe {
+    pub url: String,
+    /// 响应耗时（毫秒），不可用时为None
+    pub latency_ms: Option<u128>,
+    /// 不可用时的错误信息
+    pub error: Option<String>,
+}
+
+/// 版本目录中保存安装记录的文件名
+const INSTALL_RECEIPT_FILE: &str = ".ver-receipt.json";
+
+/// 版本目录中标记版本已锁定的文件名
+const LOCK_FILE: &str = ".ver-lock.json";
+
+/// 本地使用记录文件名，每行一条JSON记录
+const USAGE_LOG_FILE: &str = "usage.log";
+
+/// 使用记录文件超过该大小时清理过期记录
+const USAGE_LOG_MAX_BYTES: u64 = 1024 * 1024;
+
+/// 清理使用记录时保留的天数
+const USAGE_LOG_RETENTION_DAYS: i64 = 90;
+
+/// `ver pin --detect` 为一个工具推荐的固定版本
+#[derive(Debug)]
+pub struct PinProposal {
+    pub tool: VersionType,
+    /// 推荐的确切版本，找不到满足线索的版本时为None
+    pub version: Option<String>,
+    /// 推荐的版本已经安装
+    pub installed: bool,
+    /// 推荐的版本不满足所有线索，只满足最可信的一条
+    pub conflict: bool,
+    /// 项目中找到的线索，按可信程度从高到低排列
+    pub evidence: Vec<Evidence>,
+}
+
+/// 安装记录
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstallReceipt {
+    /// 文件格式版本，见 [`state::SCHEMA_VERSION`]；引入格式版本之前的记录为0
+    #[serde(default)]
+    pub schema: u32,
+    /// 工具标识
+    pub tool: String,
+    pub version: String,
+    /// 下载地址
+    pub source: String,
+    /// 安装的架构（旧记录中没有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// 安装时间（RFC 3339）
+    pub installed_at: String,
+    /// 源码编译时因缺少依赖而不可用的可选模块
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_modules: Vec<String>,
+    /// 非 ver 下载的版本的来源类型，例如 `ver adopt` 登记的 homebrew、apt，
+    /// 或迁移来源 nvm、pyenv
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flavor: Option<String>,
+    /// 安装时版本目录中每个文件的SHA-256（相对路径到摘要），用于 `ver verify`
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub files: std::collections::BTreeMap<String, String>,
+    /// 下载的安装包大小（字节），按实际收到的字节数记录，镜像没有返回 Content-Length 时也有值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_bytes: Option<u64>,
+    /// 构建变体（旧记录中没有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildFlavor>,
+}
+
+/// 安装的构建变体
 ///
-/// 支持管理Node.js和Rust版本，提供版本的安装、切换、删除等功能。
-pub struct VersionManager {
-    /// 基础目录，默认为~/.version-manager
-    base_dir: PathBuf,
-    /// 存放已安装版本的目录
-    versions_dir: PathBuf,
-    /// 别名配置文件路径
-    aliases_file: PathBuf,
-    /// 下载缓存目录
-    cache_dir: PathBuf,
-    /// 可执行文件目录
-    bin_dir: PathBuf,
-    /// 当前使用的版本
-    current_version: Option<String>,
-    /// 当前使用的版本类型
-    current_version_type: VersionType,
-    /// 操作系统类型
-    os_type: OsType,
-    /// 系统架构类型
-    arch_type: ArchType,
+/// 同一版本号可能有多种互不兼容的构建，例如 Python 3.13 的 free-threaded（无GIL）构建和调试构建。
+/// 变体写在版本号的后缀中，与 pyenv 相同：`3.13.0t` 是 free-threaded 构建，`3.12.1-debug` 是调试构建，
+/// 不同变体安装在不同的版本目录中。安装记录中保存实际安装的变体，切换和运行时与版本号要求的变体核对。
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildFlavor {
+    /// Linux 上可执行文件依赖的C库：glibc 或 musl，静态链接时为None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub libc: Option<String>,
+    /// 无GIL的 free-threaded 构建
+    #[serde(default)]
+    pub free_threaded: bool,
+    /// 调试构建
+    #[serde(default)]
+    pub debug: bool,
+    /// 从源码编译，而不是官方的预编译包
+    #[serde(default)]
+    pub source_built: bool,
 }
 
-impl VersionManager {
-    /// 创建一个新的版本管理器实例
+impl BuildFlavor {
+    /// 拆分版本号中的变体后缀
     ///
-    /// 初始化必要的目录结构，检测系统环境，读取当前版本信息。
+    /// 只有 Python 有变体后缀，其他工具原样返回版本号和默认变体。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号，例如 `3.13.0t` 或 `3.12.1-debug`
+    /// * `version_type` - 版本类型
     ///
     /// # 返回
     ///
-    /// 成功时返回VersionManager实例，失败时返回错误。
-    pub fn new() -> Result<Self> {
-        let base_dir = dirs::home_dir()
-            .context("无法找到用户主目录")?
-            .join(".version-manager");
-        
-        let versions_dir = base_dir.join("versions");
-        let aliases_file = base_dir.join("aliases.json");
-        let cache_dir = base_dir.join("cache");
-        let bin_dir = base_dir.join("bin");
-        
-        // Create directories if they don't exist
-        fs::create_dir_all(&base_dir).context("无法创建基础目录")?;
-        fs::create_dir_all(&versions_dir).context("无法创建版本目录")?;
-        fs::create_dir_all(&cache_dir).context("无法创建缓存目录")?;
-        fs::create_dir_all(&bin_dir).context("无法创建bin目录")?;
-
-        // Try to read current version from file
-        let current_version = Self::read_current_version(&base_dir, VersionType::Node).ok();
-        
-        // Detect OS and architecture
-        let os_type = Self::detect_os()?;
-        let arch_type = Self::detect_arch()?;
+    /// 返回不带后缀的版本号和版本号要求的变体（只设置 free_threaded 和 debug）。
+    pub fn split(version: &str, version_type: VersionType) -> (&str, BuildFlavor) {
+        let mut flavor = BuildFlavor::default();
+        if version_type != VersionType::Python {
+            return (version, flavor);
+        }
+        let mut base = version;
+        if let Some(stripped) = base.strip_suffix("-debug") {
+            flavor.debug = true;
+            base = stripped;
+        }
+        if let Some(stripped) = base.strip_suffix('t')
+            && stripped.ends_with(|c: char| c.is_ascii_digit())
+        {
+            flavor.free_threaded = true;
+            base = stripped;
+        }
+        (base, flavor)
+    }
 
-        Ok(Self {
-            base_dir,
-            versions_dir,
-            aliases_file,
-            cache_dir,
-            bin_dir,
-            current_version,
-            current_version_type: VersionType::Node,
-            os_type,
-            arch_type,
-        })
+    /// 版本号要求的变体与实际安装的变体是否一致，只比较 free_threaded 和 debug
+    pub fn satisfies(&self, requested: &BuildFlavor) -> bool {
+        self.free_threaded == requested.free_threaded && self.debug == requested.debug
     }
 
-    /// 检测操作系统类型
-    ///
-    /// 根据系统环境变量OS来检测操作系统类型。
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回OsType枚举值，失败时返回错误。
-    fn detect_os() -> Result<OsType> {
-        let os = env::consts::OS;
-        match os {
-            "macos" | "darwin" => Ok(OsType::Darwin),
-            "linux" => Ok(OsType::Linux),
-            "windows" => Ok(OsType::Windows),
-            _ => Err(anyhow::anyhow!("不支持的操作系统: {}", os)),
+    /// 显示用的标签，例如 `glibc, free-threaded, source-built`
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self.libc.iter().cloned().collect();
+        if self.free_threaded {
+            labels.push("free-threaded".to_string());
         }
+        if self.debug {
+            labels.push("debug".to_string());
+        }
+        labels.push(if self.source_built { "source-built" } else { "prebuilt" }.to_string());
+        labels
     }
 
-    /// 检测架构类型
-    ///
-    /// 根据系统环境变量ARCH来检测架构类型。
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回ArchType枚举值，失败时返回错误。
-    fn detect_arch() -> Result<ArchType> {
-        let arch = env::consts::ARCH;
-        match arch {
-            "x86_64" => Ok(ArchType::X64),
-            "aarch64" => Ok(ArchType::Arm64),
-            "arm" => Ok(ArchType::Arm),
-            "x86" => Ok(ArchType::X86),
-            _ => Err(anyhow::anyhow!("不支持的架构: {}", arch)),
+    /// 变体的简短说明，用于错误信息
+    fn describe(&self) -> &'static str {
+        match (self.free_threaded, self.debug) {
+            (true, true) => "free-threaded debug",
+            (true, false) => "free-threaded",
+            (false, true) => "debug",
+            (false, false) => "default",
         }
     }
+}
 
-    /// 获取操作系统和架构对应的下载 URL 后缀
-    ///
-    /// 根据操作系统类型和架构类型生成下载 URL 后缀。
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回URL后缀字符串，失败时返回错误。
-    fn get_os_arch_suffix(&self) -> String {
-        match (&self.os_type, &self.arch_type) {
-            (OsType::Darwin, ArchType::X64) => "darwin-x64".to_string(),
-            (OsType::Darwin, ArchType::Arm64) => "darwin-arm64".to_string(),
-            (OsType::Linux, ArchType::X64) => "linux-x64".to_string(),
-            (OsType::Linux, ArchType::Arm64) => "linux-arm64".to_string(),
-            (OsType::Linux, ArchType::Arm) => "linux-armv7l".to_string(),
-            (OsType::Windows, ArchType::X64) => "win-x64".to_string(),
-            (OsType::Windows, ArchType::X86) => "win-x86".to_string(),
-            _ => "unknown".to_string(),
-        }
+/// 写入安装记录的安装结果
+#[derive(Debug, Default)]
+struct InstallDetails {
+    /// 源码编译时跳过的可选模块
+    skipped_modules: Vec<String>,
+    /// 下载的安装包大小（字节）
+    archive_bytes: Option<u64>,
+}
+
+/// `ver verify` 的校验结果
+#[derive(Debug, Default)]
+pub struct VerifyResult {
+    /// 内容与安装记录不一致的文件
+    pub modified: Vec<String>,
+    /// 安装记录中有但已被删除的文件
+    pub missing: Vec<String>,
+}
+
+/// 增量源中某个版本的文件清单
+///
+/// 保存在 `<增量源>/<工具>/<版本>/<平台>.json`，文件内容按SHA-256保存在 `<增量源>/blobs/<sha256>`。
+/// 安装时与已安装版本的安装记录比对，只下载内容变化的文件。
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeltaManifest {
+    /// 生成清单所用的官方安装包的SHA-256，安装前与发布方公布的校验和比对
+    #[serde(default)]
+    pub archive_sha256: Option<String>,
+    /// 解压后的相对路径到SHA-256的映射，符号链接记录为 `symlink:<目标>`，与安装记录的格式相同
+    pub files: std::collections::BTreeMap<String, String>,
+    /// 需要可执行权限的文件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub executables: Vec<String>,
+}
+
+/// 一次增量安装的统计
+pub struct DeltaStats {
+    /// 从已安装版本复制的文件数
+    pub reused: usize,
+    /// 从增量源下载的文件数
+    pub downloaded: usize,
+    /// 下载的字节数
+    pub downloaded_bytes: u64,
+}
+
+/// `ver sync` 导入前保留的本地状态备份数
+pub const SYNC_BACKUPS: usize = 5;
+
+/// 导入同步状态的结果
+pub struct SyncImport {
+    /// 导入前本地状态的备份目录，本地没有改动时为None
+    pub backup: Option<PathBuf>,
+    /// 因未安装而没有切换的默认版本
+    pub missing: Vec<(VersionType, String)>,
+}
+
+/// ver 自己写入版本目录的文件，不计入安装记录的校验
+const VER_METADATA_FILES: &[&str] = &[INSTALL_RECEIPT_FILE, LOCK_FILE, "activate", "activate.fish", "Activate.ps1"];
+
+/// 锁定版本的记录
+///
+/// 保存锁定时bin目录中每个文件的SHA-256，切换到该版本时用于校验。
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionLock {
+    /// 锁定时间（RFC 3339）
+    pub locked_at: String,
+    /// bin目录中的文件名到SHA-256的映射
+    pub files: std::collections::BTreeMap<String, String>,
+}
+
+/// 一次版本使用记录
+///
+/// 只保存在本地，用于 `ver report` 统计各项目实际使用的版本。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// 使用时间（RFC 3339）
+    pub time: String,
+    /// 工具标识
+    pub tool: String,
+    pub version: String,
+    /// 运行命令时所在的目录
+    pub dir: PathBuf,
+}
+
+/// Go工作区要求的工具链版本
+///
+/// 按 Go 1.21+ 的规则，取 `go` 指令和 `toolchain` 指令中较高的版本。
+#[derive(Debug)]
+pub struct GoToolchainRequirement {
+    /// 需要的Go版本（不带 `go` 前缀）
+    pub version: String,
+    /// 声明该要求的 go.work 或 go.mod 文件
+    pub file: PathBuf,
+}
+
+/// 执行命令时的选项
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExecOptions<'a> {
+    /// 命令的工作目录，为None时使用当前目录
+    pub cwd: Option<&'a Path>,
+    /// 是否为命令分配伪终端
+    pub tty: bool,
+    /// 只使用项目版本文件中固定的版本，不回退到全局默认版本和系统中的同名工具
+    pub hermetic: bool,
+    /// 额外设置的环境变量，优先于工具的默认环境变量
+    pub env: &'a [(String, String)],
+    /// 版本是其他架构的预编译包时通过 qemu-user 模拟运行
+    pub emulate: bool,
+}
+
+/// 安装过程中创建的临时文件和目录
+///
+/// 安装失败或被取消（Ctrl-C）时在 drop 中删除，避免留下半下载的压缩包和
+/// 会被当作已安装的版本目录。安装成功后调用 `commit` 保留这些文件。
+#[derive(Debug, Default)]
+struct InstallStaging {
+    paths: Vec<PathBuf>,
+    committed: bool,
+}
+
+impl InstallStaging {
+    /// 记录一个需要在失败时删除的路径
+    fn track(&mut self, path: &Path) {
+        self.paths.push(path.to_path_buf());
     }
 
-    /// 获取可执行文件的扩展名
-    ///
-    /// 根据操作系统类型获取可执行文件的扩展名。
-    ///
-    /// # 返回
+    /// 安装成功，保留所有文件
+    fn commit(&mut self) {
+        self.committed = true;
+    }
+
+    /// 立即删除一个已记录的路径，例如增量安装失败后改为完整下载前清空版本目录
     ///
-    /// 成功时返回扩展名字符串，失败时返回错误。
-    fn get_exe_extension(&self) -> &str {
-        match self.os_type {
-            OsType::Windows => ".exe",
-            _ => "",
+    /// 路径仍然保留在记录中，之后重新创建的内容在失败时同样会被删除。
+    fn discard(&self, path: &Path) {
+        debug_assert!(self.paths.iter().any(|tracked| tracked == path));
+        Self::remove(path);
+    }
+
+    fn remove(path: &Path) {
+        if path.is_dir() {
+            let _ = fs::remove_dir_all(path);
+        } else if path.exists() {
+            let _ = fs::remove_file(path);
         }
     }
+}
 
-    /// 读取当前版本从文件
-    ///
-    /// 从指定目录下的.current-node文件读取当前版本信息。
-    ///
-    /// # 参数
-    ///
-    /// * `base_dir` - 基础目录
-    /// * `version_type` - 版本类型
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回当前版本字符串，失败时返回错误。
-    fn read_current_version(base_dir: &PathBuf, version_type: VersionType) -> Result<String> {
-        let version_file = base_dir.join(format!(".current-{}", version_type));
-        if version_file.exists() {
-            let version = fs::read_to_string(version_file)?;
-            Ok(version.trim().to_string())
-        } else {
-            Err(anyhow::anyhow!("找不到当前版本文件"))
+impl Drop for InstallStaging {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        for path in self.paths.iter().rev() {
+            Self::remove(path);
         }
     }
+}
 
-    /// 保存当前版本到文件
-    ///
-    /// 将当前版本信息保存到指定目录下的.current-node文件。
-    ///
-    /// # 参数
-    ///
-    /// * `version` - 当前版本字符串
-    /// * `version_type` - 版本类型
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    fn save_current_version(&self, version: &str, version_type: VersionType) -> Result<()> {
-        let version_file = self.base_dir.join(format!(".current-{}", version_type));
-        fs::write(version_file, version)?;
+/// 在阻塞线程中执行的工作的取消标记
+///
+/// 解压和编译在 `spawn_blocking` 中执行，安装的 future 仍能响应 Ctrl-C。
+#[derive(Debug, Default)]
+struct CancelToken {
+    cancelled: AtomicBool,
+    /// 工作执行期间一直持有，取消时借此等待工作退出
+    running: Mutex<()>,
+}
+
+impl CancelToken {
+    /// 检查点：已取消时返回 `VersionError::Cancelled`
+    fn check(&self) -> Result<()> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return Err(VersionError::Cancelled.into());
+        }
         Ok(())
     }
+}
 
-    /// 获取当前版本
-    ///
-    /// 获取当前使用的版本信息。
-    ///
-    /// # 参数
-    ///
-    /// * `version_type` - 版本类型
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回当前版本字符串，失败时返回错误。
-    pub fn get_current_version(&self, version_type: VersionType) -> Option<&String> {
-        if self.current_version_type == version_type {
-            self.current_version.as_ref()
-        } else {
-            None
-        }
+/// future 被丢弃时取消阻塞线程中的工作，并等待它在下一个检查点退出，
+/// 这样之后 InstallStaging 删除目录时不会再有文件写入
+struct CancelOnDrop(Arc<CancelToken>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        let _running = self.0.running.lock();
     }
+}
 
-    /// 读取别名配置
-    ///
-    /// 从指定目录下的aliases.json文件读取别名配置信息。
-    ///
-    /// # 参数
-    ///
-    /// * `version_type` - 版本类型
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回别名配置信息，失败时返回错误。
-    fn read_aliases(&self, version_type: VersionType) -> Result<Aliases> {
-        let aliases_file = self.aliases_file.with_file_name(format!("aliases-{}.json", version_type));
-        if !aliases_file.exists() {
-            return Ok(Aliases {
-                aliases: HashMap::new(),
-            });
+/// 在阻塞线程中执行可取消的工作
+///
+/// # 参数
+///
+/// * `work` - 要执行的工作，应在写入文件之间调用 `CancelToken::check`
+///
+/// # 返回
+///
+/// 返回工作的结果，取消时返回 `VersionError::Cancelled`。
+async fn run_blocking<T, F>(work: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&CancelToken) -> Result<T> + Send + 'static,
+{
+    let token = Arc::new(CancelToken::default());
+    let _guard = CancelOnDrop(token.clone());
+    tokio::task::spawn_blocking(move || {
+        let _running = token.running.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        token.check()?;
+        work(&token)
+    }).await?
+}
+
+/// 长时间操作的进度输出方式
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ProgressMode {
+    /// 在终端显示进度条
+    #[default]
+    Bar,
+    /// 在标准错误输出逐行输出JSON事件，供图形界面和CI包装脚本使用
+    Json,
+    /// 不显示进度条和安装过程中的状态信息，用于 `ver exec --quiet-install`
+    Quiet,
+    /// 不显示动画，只输出逐行的状态信息，用于屏幕阅读器和日志（`--plain`）
+    Plain,
+}
+
+impl ProgressMode {
+    /// 从名称解析进度输出方式
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "bar" => Ok(ProgressMode::Bar),
+            "json" => Ok(ProgressMode::Json),
+            "plain" => Ok(ProgressMode::Plain),
+            _ => Err(anyhow::anyhow!("不支持的进度输出方式: {}（可选 bar、json、plain）", name)),
         }
+    }
+}
 
-        let content = fs::read_to_string(&aliases_file)?;
-        let aliases: Aliases = serde_json::from_str(&content)?;
-        Ok(aliases)
+/// JSON进度事件之间的最小间隔（毫秒）
+const PROGRESS_EVENT_INTERVAL_MS: u128 = 200;
+
+/// 安装过程的阶段，按执行顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallPhase {
+    /// 下载安装包（或从增量源下载文件）
+    Download,
+    /// 核对安装包的SHA-256
+    Verify,
+    /// 解压安装包
+    Extract,
+    /// 运行安装脚本、编译源码、下载头文件、写入安装记录
+    PostInstall,
+    /// 生成激活脚本
+    Shim,
+}
+
+impl InstallPhase {
+    /// 所有阶段
+    pub const ALL: [InstallPhase; 5] = [
+        InstallPhase::Download,
+        InstallPhase::Verify,
+        InstallPhase::Extract,
+        InstallPhase::PostInstall,
+        InstallPhase::Shim,
+    ];
+
+    /// 进度事件和耗时摘要中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            InstallPhase::Download => "download",
+            InstallPhase::Verify => "verify",
+            InstallPhase::Extract => "extract",
+            InstallPhase::PostInstall => "post-install",
+            InstallPhase::Shim => "shim",
+        }
     }
 
-    /// 保存别名配置
-    ///
-    /// 将别名配置信息保存到指定目录下的aliases.json文件。
-    ///
-    /// # 参数
-    ///
-    /// * `aliases` - 别名配置信息
-    /// * `version_type` - 版本类型
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    fn save_aliases(&self, aliases: &Aliases, version_type: VersionType) -> Result<()> {
-        let aliases_file = self.aliases_file.with_file_name(format!("aliases-{}.json", version_type));
-        let content = serde_json::to_string_pretty(aliases)?;
-        fs::write(&aliases_file, content)?;
-        Ok(())
+    /// 阶段的序号，从1开始
+    fn step(self) -> usize {
+        Self::ALL.iter().position(|phase| *phase == self).unwrap_or(0) + 1
     }
+}
 
-    /// 创建版本别名
-    ///
-    /// 为指定版本创建一个别名。
-    ///
-    /// # 参数
-    ///
-    /// * `alias` - 别名名称
-    /// * `version` - 版本号
-    /// * `version_type` - 版本类型
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub fn create_alias(&self, alias: &str, version: &str, version_type: VersionType) -> Result<()> {
-        // 检查版本是否已安装
-        let version_dir = self.get_version_dir(version, version_type);
-        if !version_dir.exists() {
-            return Err(anyhow::anyhow!("{}", VersionError::NotInstalled(version.to_string(), version_type)));
+/// 正在进行的安装的阶段计时
+#[derive(Debug)]
+struct PhaseTimer {
+    version_type: VersionType,
+    version: String,
+    started: std::time::Instant,
+    /// 当前阶段和它的开始时间
+    current: Option<(InstallPhase, std::time::Instant)>,
+    /// 每个阶段累计的耗时，按组件安装 Rust 时同一阶段会进入多次
+    elapsed: Vec<(InstallPhase, std::time::Duration)>,
+}
+
+impl PhaseTimer {
+    /// 结束当前阶段，返回它和本次的耗时
+    fn close(&mut self) -> Option<(InstallPhase, std::time::Duration)> {
+        let (phase, started) = self.current.take()?;
+        let duration = started.elapsed();
+        match self.elapsed.iter_mut().find(|(recorded, _)| *recorded == phase) {
+            Some((_, total)) => *total += duration,
+            None => self.elapsed.push((phase, duration)),
         }
+        Some((phase, duration))
+    }
+}
 
-        let mut aliases = self.read_aliases(version_type)?;
-        aliases.aliases.insert(alias.to_string(), version.to_string());
-        self.save_aliases(&aliases, version_type)?;
+/// 一次安装的耗时，`ver install` 结束时显示，也写入 installed 事件
+#[derive(Debug)]
+pub struct InstallTiming {
+    /// 总耗时
+    pub total: std::time::Duration,
+    /// 每个阶段的耗时，按首次进入的顺序排列，跳过的阶段（例如缓存命中时的下载）不在其中
+    pub phases: Vec<(InstallPhase, std::time::Duration)>,
+}
 
-        Ok(())
+impl InstallTiming {
+    /// 耗时摘要，例如 `Installed in 12.3s (download 8.1s, verify 0.1s, extract 2.0s, ...)`
+    pub fn summary(&self) -> String {
+        let phases: Vec<String> = self.phases.iter()
+            .map(|(phase, duration)| format!("{} {}", phase.name(), format::duration(*duration)))
+            .collect();
+        format!("Installed in {} ({})", format::duration(self.total), phases.join(", "))
     }
 
-    /// 获取别名对应的版本
-    ///
-    /// 获取指定别名对应的版本号。
-    ///
-    /// # 参数
-    ///
-    /// * `alias` - 别名名称
-    /// * `version_type` - 版本类型
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回版本号字符串，失败时返回错误。
-    pub fn get_alias(&self, alias: &str, version_type: VersionType) -> Result<Option<String>> {
-        let aliases = self.read_aliases(version_type)?;
-        Ok(aliases.aliases.get(alias).cloned())
+    /// installed 事件中的耗时字段
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "duration_ms": self.total.as_millis() as u64,
+            "phases": self.phases.iter()
+                .map(|(phase, duration)| serde_json::json!({ "phase": phase.name(), "duration_ms": duration.as_millis() as u64 }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// 迁移其他版本管理器时的选项
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MigrateOptions<'a> {
+    /// 不复制的文件或目录，支持 `*` 和 `?` 通配符，匹配相对路径或其中任意一级名称
+    pub exclude: &'a [String],
+    /// 只迁移这些版本，为空时迁移所有版本
+    pub only: &'a [String],
+    /// 只列出将要迁移的版本，不复制文件
+    pub dry_run: bool,
+}
+
+impl MigrateOptions<'_> {
+    /// 判断版本是否需要迁移（忽略 `v` 前缀）
+    pub fn includes(&self, version: &str) -> bool {
+        self.only.is_empty()
+            || self.only.iter().any(|only| only.trim_start_matches('v') == version.trim_start_matches('v'))
+    }
+}
+
+/// 某个工具中引用已安装版本的位置，用于 `ver gc` 和 `ver remove`
+struct ReferenceIndex {
+    /// 全局版本
+    current: Option<String>,
+    /// 别名及其指向的版本
+    aliases: Vec<(String, String)>,
+    /// 项目版本文件固定的版本（已解析别名）及文件路径
+    project_files: Vec<(String, PathBuf)>,
+}
+
+impl ReferenceIndex {
+    /// 列出引用指定版本的位置
+    fn references_to(&self, version: &str) -> Vec<String> {
+        let mut references = Vec::new();
+        if self.current.as_deref() == Some(version) {
+            references.push("global version".to_string());
+        }
+        for (alias, target) in &self.aliases {
+            if target == version {
+                references.push(format!("alias {}", alias));
+            }
+        }
+        for (pinned, file) in &self.project_files {
+            if pinned == version {
+                references.push(file.display().to_string());
+            }
+        }
+        references
+    }
+}
+
+/// `ver gc` 中一个已安装版本的回收信息
+#[derive(Debug)]
+pub struct GcCandidate {
+    pub version_type: VersionType,
+    pub version: String,
+    /// 占用空间（字节）
+    pub size: u64,
+    /// 最后使用时间（RFC 3339），没有使用记录时为None
+    pub last_used: Option<String>,
+    /// 安装时间（RFC 3339），没有安装记录时为None
+    pub installed_at: Option<String>,
+    /// 引用该版本的全局版本、别名、锁定和项目版本文件
+    pub references: Vec<String>,
+    /// 没有引用且近期未使用，可以删除；既没有安装记录也没有使用记录的版本无法判断，不算可回收
+    pub reclaimable: bool,
+}
+
+/// `ver rust du` 中可以单独清理的部分
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RustPart {
+    /// rust-docs 组件的 HTML 文档（share/doc/rust/html）
+    Docs,
+    /// 安装完成后留在版本目录中的安装包解压目录
+    Installer,
+    /// rust-src 组件（lib/rustlib/src）
+    Source,
+    /// 非本机平台的标准库（lib/rustlib/<target>）
+    Targets,
+    /// cargo 解压的依赖源码（registry/src 和 git/checkouts），需要时会从下载缓存重新生成
+    Registry,
+}
+
+impl RustPart {
+    /// 从名称解析
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "docs" => Ok(RustPart::Docs),
+            "installer" => Ok(RustPart::Installer),
+            "src" => Ok(RustPart::Source),
+            "targets" => Ok(RustPart::Targets),
+            "registry" => Ok(RustPart::Registry),
+            _ => Err(anyhow::anyhow!("不支持清理的部分: {}（可选 docs、installer、src、targets、registry）", name)),
+        }
+    }
+
+    /// 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            RustPart::Docs => "docs",
+            RustPart::Installer => "installer",
+            RustPart::Source => "src",
+            RustPart::Targets => "targets",
+            RustPart::Registry => "registry",
+        }
+    }
+}
+
+/// 工具链或 cargo 目录中一部分文件的占用空间
+#[derive(Debug)]
+pub struct RustUsageEntry {
+    /// 显示名称，例如 docs、std x86_64-unknown-linux-gnu、registry/cache
+    pub label: String,
+    pub path: PathBuf,
+    /// 占用空间（字节）
+    pub size: u64,
+    /// 可以用 `--remove` 清理时对应的部分
+    pub part: Option<RustPart>,
+}
+
+/// 一个Rust工具链的占用空间
+#[derive(Debug)]
+pub struct RustToolchainUsage {
+    pub version: String,
+    /// 总占用空间（字节）
+    pub size: u64,
+    pub entries: Vec<RustUsageEntry>,
+    /// 已锁定的工具链不会被清理
+    pub locked: bool,
+}
+
+/// 通过系统包管理器安装的工具链
+#[derive(Debug)]
+pub struct SystemToolchain {
+    pub version_type: VersionType,
+    pub version: String,
+    /// 检测到的可执行文件
+    pub executable: PathBuf,
+    /// 来源：homebrew、apt、dnf、pacman、apk 或 system
+    pub flavor: &'static str,
+}
+
+/// shell配置文件
+#[derive(Debug)]
+pub struct ShellConfig {
+    /// shell名称：bash、zsh 或 fish
+    pub shell: &'static str,
+    /// 配置文件路径
+    pub file: PathBuf,
+}
+
+/// 检测到的其他版本管理器
+#[derive(Debug)]
+pub struct DetectedManager {
+    /// 版本管理器名称
+    pub name: &'static str,
+    pub version_type: VersionType,
+    /// 保存版本的目录
+    pub dir: PathBuf,
+    /// 已安装的版本数量
+    pub versions: usize,
+}
+
+/// PATH 中一个目录的归属
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathOwner {
+    /// ver 的 bin 目录或 ver 安装的版本
+    Ver,
+    /// 其他版本管理器，例如 nvm 或 pyenv
+    Manager(&'static str),
+    /// 系统或用户自己的目录
+    Other,
+}
+
+/// PATH 中的一个目录
+#[derive(Debug)]
+pub struct PathEntry {
+    pub dir: PathBuf,
+    pub owner: PathOwner,
+    /// 排在 ver 的 bin 目录之前、与 ver 管理的命令同名的可执行文件
+    pub shadows: Vec<String>,
+}
+
+/// 当前用户与 ver 主目录所有者不一致的情况
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OwnershipMismatch {
+    /// 以 root 运行，但主目录属于普通用户（例如 sudo 保留了 HOME），新建的文件会属于 root
+    RootInUserDir { owner: u32 },
+    /// 以普通用户运行，但主目录属于 root（例如第一次运行时用了 sudo），无法写入
+    UserInRootDir,
+}
+
+/// `ver doctor` 对主目录中文件所有者的检查结果
+#[derive(Debug)]
+pub struct OwnershipCheck {
+    /// 主目录应属于的用户，取自主目录的上级目录（通常是用户主目录）
+    pub uid: u32,
+    /// 主目录应属于的组
+    pub gid: u32,
+    /// 所有者不是该用户的文件和目录
+    pub foreign: Vec<PathBuf>,
+}
+
+/// `ver doctor` 对一个工具的检查结果
+#[derive(Debug)]
+pub struct DoctorCheck {
+    pub version_type: VersionType,
+    /// 是否由 ver 管理（配置项 `manage`）
+    pub managed: bool,
+    /// 在 PATH 中第一个找到的命令及其归属
+    pub resolved: Option<(PathBuf, PathOwner)>,
+    /// 发现的问题
+    pub problems: Vec<String>,
+    /// ver 的 bin 目录中指向不由 ver 管理的工具的链接，`ver doctor --fix` 会删除
+    pub stale_links: Vec<PathBuf>,
+}
+
+/// 根据目录中的路径特征识别的其他版本管理器
+const PATH_MANAGERS: &[(&str, &[&str])] = &[
+    ("nvm", &[".nvm"]),
+    ("fnm", &["fnm", ".fnm", "fnm_multishells"]),
+    ("volta", &[".volta"]),
+    ("n", &["n/versions"]),
+    ("rustup", &[".cargo/bin", ".rustup"]),
+    ("pyenv", &[".pyenv"]),
+    ("gvm", &[".gvm"]),
+    ("goenv", &[".goenv"]),
+    ("asdf", &[".asdf"]),
+    ("mise", &["mise"]),
+];
+
+/// 缓存目录中的条目
+#[derive(Debug)]
+pub struct CacheEntry {
+    /// 文件名
+    pub name: String,
+    /// 条目类型：archive（下载的压缩包）或 index（版本索引缓存）
+    pub kind: &'static str,
+    /// 对应的版本号或工具
+    pub version: Option<String>,
+    /// 大小（字节）
+    pub size: u64,
+    /// 最后修改时间
+    pub modified: Option<std::time::SystemTime>,
+}
+
+/// 两个版本之间的变化
+#[derive(Debug, Serialize)]
+pub struct VersionDiff {
+    /// 工具标识
+    pub tool: &'static str,
+    pub from: String,
+    pub to: String,
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+    /// 两个版本之间（不含两端）的发布版本，从旧到新
+    pub skipped: Vec<String>,
+    /// 升级范围内（不含起始版本）的安全更新版本及其发布说明地址，索引不提供安全标记时为None
+    pub security_fixes: Option<Vec<(String, String)>>,
+    /// 目标版本的发布说明地址
+    pub release_notes: String,
+    /// 两个版本都已安装时的占用空间
+    pub sizes: Option<(u64, u64)>,
+}
+
+/// 项目需要的工具链的下载文件，由 `ver freeze` 输出
+#[derive(Debug, Serialize)]
+pub struct FrozenToolchain {
+    /// 工具标识
+    pub tool: &'static str,
+    pub version: String,
+    /// 平台后缀，例如 `linux-x64`
+    pub platform: String,
+    pub url: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Aliases {
+    /// 文件格式版本，见 [`state::SCHEMA_VERSION`]
+    #[serde(default)]
+    schema: u32,
+    aliases: HashMap<String, String>,
+}
+
+// 自定义错误类型
+#[derive(Debug)]
+pub enum VersionError {
+    NotInstalled(String, VersionType),
+    NotFound(String, VersionType),
+    CurrentlyActive(String, VersionType),
+    Locked(String, VersionType),
+    /// 版本仍被全局版本、别名或项目版本文件引用
+    Referenced {
+        version: String,
+        version_type: VersionType,
+        references: Vec<String>,
+    },
+    NoVersionConfigured(VersionType),
+    UnsupportedPlatform {
+        version_type: VersionType,
+        platform: String,
+        supported: Vec<String>,
+    },
+    DownloadFailed {
+        version: String,
+        version_type: VersionType,
+        url: String,
+        status: Option<u16>,
+        reason: String,
+    },
+    LibcMismatch {
+        version: String,
+        version_type: VersionType,
+        binary: PathBuf,
+    },
+    /// 版本目录中安装的构建变体与版本号要求的不同，例如 `3.13.0t` 目录中是普通构建
+    FlavorMismatch {
+        version: String,
+        version_type: VersionType,
+        installed: BuildFlavor,
+    },
+    Cancelled,
+    /// 版本目录由管理员统一提供，当前用户只能使用
+    ReadOnly(PathBuf),
+    /// 没有可用的用户主目录（容器、systemd 服务等），附带可以代替主目录指定路径的环境变量
+    NoHomeDir(Option<&'static str>),
+    IoError(io::Error),
+}
+
+impl VersionError {
+    /// 机器可读的错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            VersionError::NotInstalled(..) => "not_installed",
+            VersionError::NotFound(..) => "not_found",
+            VersionError::CurrentlyActive(..) => "currently_active",
+            VersionError::Locked(..) => "locked",
+            VersionError::Referenced { .. } => "referenced",
+            VersionError::NoVersionConfigured(_) => "no_version_configured",
+            VersionError::UnsupportedPlatform { .. } => "unsupported_platform",
+            VersionError::DownloadFailed { .. } => "download_failed",
+            VersionError::LibcMismatch { .. } => "libc_mismatch",
+            VersionError::FlavorMismatch { .. } => "flavor_mismatch",
+            VersionError::Cancelled => "cancelled",
+            VersionError::ReadOnly(_) => "read_only",
+            VersionError::NoHomeDir(_) => "no_home_dir",
+            VersionError::IoError(_) => "io_error",
+        }
+    }
+
+    /// 错误关联的工具类型
+    pub fn version_type(&self) -> Option<VersionType> {
+        match self {
+            VersionError::NotInstalled(_, version_type)
+            | VersionError::NotFound(_, version_type)
+            | VersionError::CurrentlyActive(_, version_type)
+            | VersionError::Locked(_, version_type)
+            | VersionError::Referenced { version_type, .. }
+            | VersionError::NoVersionConfigured(version_type)
+            | VersionError::UnsupportedPlatform { version_type, .. }
+            | VersionError::DownloadFailed { version_type, .. }
+            | VersionError::LibcMismatch { version_type, .. }
+            | VersionError::FlavorMismatch { version_type, .. } => Some(*version_type),
+            VersionError::Cancelled | VersionError::ReadOnly(_) | VersionError::NoHomeDir(_) | VersionError::IoError(_) => None,
+        }
+    }
+
+    /// 错误关联的版本号
+    pub fn version(&self) -> Option<&str> {
+        match self {
+            VersionError::NotInstalled(version, _)
+            | VersionError::NotFound(version, _)
+            | VersionError::CurrentlyActive(version, _)
+            | VersionError::Locked(version, _)
+            | VersionError::Referenced { version, .. }
+            | VersionError::DownloadFailed { version, .. }
+            | VersionError::LibcMismatch { version, .. }
+            | VersionError::FlavorMismatch { version, .. } => Some(version),
+            VersionError::NoVersionConfigured(_)
+            | VersionError::UnsupportedPlatform { .. }
+            | VersionError::Cancelled
+            | VersionError::ReadOnly(_)
+            | VersionError::NoHomeDir(_)
+            | VersionError::IoError(_) => None,
+        }
+    }
+
+    /// 错误关联的HTTP状态码
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            VersionError::DownloadFailed { status, .. } => *status,
+            _ => None,
+        }
+    }
+
+    /// 错误关联的下载地址
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            VersionError::DownloadFailed { url, .. } => Some(url),
+            VersionError::UnsupportedPlatform { version_type, .. } => Some(version_type.source_build_url()),
+            _ => None,
+        }
+    }
+}
+
+impl VersionError {
+    /// 针对错误给出的修复建议
+    ///
+    /// 建议只包含用户可以直接执行的操作，错误信息本身已经说明的内容不再重复。
+    pub fn suggestions(&self) -> Vec<String> {
+        match self {
+            VersionError::NotInstalled(version, version_type) => vec![
+                format!("运行 `ver install {} -t {}` 安装该版本", version, version_type.id()),
+            ],
+            VersionError::NotFound(_, version_type) => vec![
+                format!("运行 `ver installed -t {}` 查看已安装的版本", version_type.id()),
+            ],
+            VersionError::CurrentlyActive(_, version_type) => vec![
+                format!("先用 `ver use <其他版本> -t {}` 切换，再删除该版本", version_type.id()),
+            ],
+            VersionError::Referenced { version, version_type, .. } => vec![
+                format!("确认不再需要后运行 `ver remove {} -t {} --force` 删除", version, version_type.id()),
+            ],
+            VersionError::NoVersionConfigured(version_type) => vec![
+                format!("运行 `ver local <版本> -t {}` 为当前项目指定版本，或用 `ver use <版本> -t {}` 设置全局版本",
+                    version_type.id(), version_type.id()),
+            ],
+            VersionError::DownloadFailed { version_type, status: Some(404), .. } => vec![
+                format!("该版本可能不存在，运行 `ver list -t {}` 查看可用版本", version_type.id()),
+            ],
+            VersionError::DownloadFailed { version_type, status: Some(_), .. } => vec![
+                format!("镜像可能暂时不可用，运行 `ver mirror test -t {} --select` 选择其他镜像", version_type.id()),
+            ],
+            VersionError::DownloadFailed { version_type, status: None, .. } => vec![
+                "检查网络连接和代理设置（HTTPS_PROXY）".to_string(),
+                format!("或者用 `ver config set mirrors.{} <地址>` 配置可访问的镜像", version_type.id()),
+            ],
+            VersionError::LibcMismatch { version, version_type, .. } => match version_type {
+                VersionType::Node => vec![
+                    format!("Node.js 在 https://unofficial-builds.nodejs.org 提供 musl 版本（linux-x64-musl），可以手动解压到 ~/.version-manager/versions/{}", version),
+                    "或者改用基于 glibc 的系统或容器镜像（例如 debian 而不是 alpine）".to_string(),
+                ],
+                VersionType::Rust => vec![
+                    "安装 x86_64-unknown-linux-musl 目标的工具链，或者改用基于 glibc 的系统".to_string(),
+                ],
+                _ => vec![
+                    "改用基于 glibc 的系统或容器镜像（例如 debian 而不是 alpine）".to_string(),
+                ],
+            },
+            VersionError::FlavorMismatch { version, version_type, .. } => vec![
+                format!("运行 `ver remove {} -t {} --force` 后重新安装，ver 会按版本号的后缀构建对应的变体", version, version_type.id()),
+            ],
+            VersionError::ReadOnly(_) => vec![
+                "请联系管理员安装或删除版本；管理员可以加上 `--admin` 运行同样的命令".to_string(),
+                "别名、全局版本和项目版本文件仍然可以修改".to_string(),
+            ],
+            VersionError::NoHomeDir(Some("VER_DIR")) => vec![
+                "在容器或 systemd 服务中运行时设置 VER_DIR，例如 `VER_DIR=/var/lib/ver`".to_string(),
+            ],
+            VersionError::NoHomeDir(Some(var)) => vec![
+                format!("设置环境变量 {} 指定目录", var),
+            ],
+            VersionError::IoError(err) => io_error_suggestions(err),
+            VersionError::NoHomeDir(None) => vec![
+                "设置 HOME 为一个存在的目录后重试".to_string(),
+            ],
+            VersionError::Locked(..)
+            | VersionError::UnsupportedPlatform { .. }
+            | VersionError::Cancelled => Vec::new(),
+        }
+    }
+}
+
+/// 获取用户主目录
+///
+/// HOME 未设置、用户没有主目录或主目录不存在（常见于容器和 systemd 服务）时返回 [`VersionError::NoHomeDir`]，
+/// 而不是在之后读写文件时才失败。
+///
+/// # 参数
+///
+/// * `alternative` - 可以代替主目录指定所需路径的环境变量，用于错误提示
+///
+/// # 返回
+///
+/// 成功时返回主目录，失败时返回错误。
+pub fn home_dir(alternative: Option<&'static str>) -> Result<PathBuf> {
+    dirs::home_dir()
+        .filter(|home| home.is_dir())
+        .ok_or_else(|| VersionError::NoHomeDir(alternative).into())
+}
+
+/// 针对IO错误给出的修复建议
+///
+/// 没有包装成 VersionError 的IO错误（例如写入版本目录失败）也通过这里给出建议。
+pub fn io_error_suggestions(err: &io::Error) -> Vec<String> {
+    match err.kind() {
+        io::ErrorKind::PermissionDenied => vec![
+            "ver 的数据目录不可写，可以用环境变量 VER_DIR 指定一个可写的目录，例如 `VER_DIR=$HOME/.ver`".to_string(),
+        ],
+        io::ErrorKind::StorageFull => vec![
+            "磁盘空间不足，运行 `ver gc --dry-run` 和 `ver cache list` 查看可以清理的内容".to_string(),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+impl std::fmt::Display for VersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionError::NotInstalled(version, version_type) => 
+                write!(f, "{} 版本 {} 未安装", version_type, version),
+            VersionError::NotFound(version, version_type) => 
+                write!(f, "找不到 {} 版本 {}", version_type, version),
+            VersionError::CurrentlyActive(version, version_type) => 
+                write!(f, "无法删除当前活动的 {} 版本 {}。请先切换到其他版本。", version_type, version),
+            VersionError::Locked(version, version_type) => 
+                write!(f, "{} 版本 {} 已锁定，不能修改或删除。请先运行 `ver unlock {} {}`", version_type, version, version_type.id(), version),
+            VersionError::Referenced { version, version_type, references } => {
+                write!(f, "{} 版本 {} 仍在使用中:", version_type, version)?;
+                for reference in references {
+                    write!(f, "\n  - {}", reference)?;
+                }
+                Ok(())
+            }
+            VersionError::NoVersionConfigured(version_type) => 
+                write!(f, "没有为当前目录配置 {} 版本", version_type),
+            VersionError::UnsupportedPlatform { version_type, platform, supported } => 
+                write!(f, "{} 没有提供 {} 平台的预编译包。支持的平台: {}。可以参考 {} 从源码构建",
+                    version_type, platform, supported.join(", "), version_type.source_build_url()),
+            VersionError::DownloadFailed { version, version_type, url, status, reason } => {
+                write!(f, "下载 {} 版本 {} 失败 ({}): {}", version_type, version, url, reason)?;
+                if status.is_some() {
+                    write!(f, "。请运行 `ver list -t {}` 确认该版本存在", version_type.id())?;
+                }
+                Ok(())
+            }
+            VersionError::LibcMismatch { version, version_type, binary } => 
+                write!(f, "无法运行 {}：{} 版本 {} 是为 glibc 构建的，而当前系统使用 musl libc", binary.display(), version_type, version),
+            VersionError::FlavorMismatch { version, version_type, installed } => {
+                let requested = BuildFlavor::split(VersionManager::strip_arch_suffix(version), *version_type).1;
+                write!(f, "{} 版本 {} 要求 {} 构建，但版本目录中安装的是 {} 构建", version_type, version, requested.describe(), installed.describe())
+            }
+            VersionError::Cancelled => 
+                write!(f, "操作已取消，未完成的文件已清理"),
+            VersionError::ReadOnly(dir) => 
+                write!(f, "版本目录 {} 由管理员统一提供，不能安装、删除或修改版本", dir.display()),
+            VersionError::NoHomeDir(Some(var)) => 
+                write!(f, "找不到用户主目录（HOME 未设置或指向不存在的目录），请用环境变量 {} 指定目录", var),
+            VersionError::NoHomeDir(None) => 
+                write!(f, "找不到用户主目录（HOME 未设置或指向不存在的目录）"),
+            VersionError::IoError(err) => 
+                write!(f, "IO错误: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+impl From<io::Error> for VersionError {
+    fn from(err: io::Error) -> Self {
+        VersionError::IoError(err)
+    }
+}
+
+/// 创建版本管理器时的选项
+///
+/// 默认值与 `VersionManager::new()` 相同。集成测试可以把数据目录指向临时目录、把下载地址指向本地的
+/// 模拟服务器，完整的安装、切换、删除流程都不会访问网络或用户主目录。
+#[derive(Debug, Default, Clone)]
+pub struct ManagerOptions {
+    /// 数据目录，为None时使用 ~/.version-manager
+    pub base_dir: Option<PathBuf>,
+    /// 每个工具的下载地址，设置后替代配置的镜像和官方下载源
+    pub endpoints: Vec<(VersionType, String)>,
+    /// 增量源地址，替代 `install.delta_url`
+    pub delta_endpoint: Option<String>,
+    /// 补充元数据的地址，替代 endoflife.date、GitHub API 和安全公告源
+    pub metadata_endpoint: Option<String>,
+    /// 发布元数据的地址，替代 `update.release_url`
+    pub release_endpoint: Option<String>,
+}
+
+impl ManagerOptions {
+    /// 是否设置了任何地址
+    ///
+    /// 设置后所有网络请求都只发往设置的地址，没有设置地址的来源（例如只设置了 Node.js
+    /// 的下载地址时的增量源和元数据来源）不再访问。
+    pub fn is_hermetic(&self) -> bool {
+        !self.endpoints.is_empty()
+            || self.delta_endpoint.is_some()
+            || self.metadata_endpoint.is_some()
+            || self.release_endpoint.is_some()
+    }
+}
+
+/// 版本管理器结构体，用于管理不同语言的版本
+///
+/// 支持管理Node.js和Rust版本，提供版本的安装、切换、删除等功能。
+pub struct VersionManager {
+    /// 基础目录，默认为~/.version-manager
+    base_dir: PathBuf,
+    /// 存放已安装版本的目录
+    versions_dir: PathBuf,
+    /// 别名配置文件路径
+    aliases_file: PathBuf,
+    /// 用户配置文件路径
+    config_file: PathBuf,
+    /// 用户配置
+    config: Config,
+    /// 下载缓存目录
+    cache_dir: PathBuf,
+    /// 可执行文件目录
+    bin_dir: PathBuf,
+    /// 当前使用的版本
+    current_version: Option<String>,
+    /// 当前使用的版本类型
+    current_version_type: VersionType,
+    /// 操作系统类型
+    os_type: OsType,
+    /// 系统架构类型
+    arch_type: ArchType,
+    /// 进度输出方式
+    progress_mode: ProgressMode,
+    /// 下一次安装使用的操作ID，由 `ver install --detach` 预先分配
+    operation_id: Option<String>,
+    /// 正在进行的操作
+    operation: std::sync::Mutex<Option<Operation>>,
+    /// 正在进行的安装的阶段计时
+    phases: std::sync::Mutex<Option<PhaseTimer>>,
+    /// 版本目录是否由管理员统一提供（只读）
+    managed: bool,
+    /// 是否以管理员身份修改统一提供的版本目录（`--admin`）
+    admin: bool,
+    /// 替代镜像和其他网络来源的地址，见 ManagerOptions
+    endpoints: ManagerOptions,
+}
+
+impl VersionManager {
+    /// 创建一个新的版本管理器实例
+    ///
+    /// 初始化必要的目录结构，检测系统环境，读取当前版本信息。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回VersionManager实例，失败时返回错误。
+    pub fn new() -> Result<Self> {
+        Self::with_options(ManagerOptions::default())
+    }
+
+    /// 使用指定的数据目录和下载地址创建版本管理器
+    ///
+    /// # 参数
+    ///
+    /// * `options` - 数据目录和下载地址
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回VersionManager实例，失败时返回错误。
+    pub fn with_options(options: ManagerOptions) -> Result<Self> {
+        // 设置了 VER_DIR 时只使用它，不再依赖用户主目录
+        let base_dir = match options.base_dir.clone().or_else(|| env::var_os("VER_DIR").filter(|dir| !dir.is_empty()).map(PathBuf::from)) {
+            Some(base_dir) => base_dir,
+            None => home_dir(Some("VER_DIR"))?.join(".version-manager"),
+        };
+        
+        let aliases_file = base_dir.join("aliases.json");
+        let config_file = base_dir.join("config.json");
+        let cache_dir = base_dir.join("cache");
+        let bin_dir = base_dir.join("bin");
+        
+        // 旧版本创建的主目录先升级到当前格式，之后读到的状态文件都是当前格式
+        state::migrate(&base_dir)?;
+        
+        // Create directories if they don't exist
+        fs::create_dir_all(&base_dir).with_context(|| format!("无法创建基础目录 {}，可以用环境变量 VER_DIR 指定其他目录", base_dir.display()))?;
+        fs::create_dir_all(&cache_dir).context("无法创建缓存目录")?;
+        fs::create_dir_all(&bin_dir).context("无法创建bin目录")?;
+
+        let config = Config::load(&config_file)?;
+        
+        // 统一提供的版本目录由管理员维护，别名、全局版本等用户状态仍然保存在主目录中
+        let managed_dir = config.managed_versions_dir();
+        let managed = managed_dir.is_some();
+        let versions_dir = managed_dir.unwrap_or_else(|| base_dir.join("versions"));
+        if !managed {
+            fs::create_dir_all(&versions_dir).context("无法创建版本目录")?;
+        }
+
+        // Try to read current version from file
+        let current_version = Self::read_current_version(&base_dir, VersionType::Node).ok();
+        
+        // Detect OS and architecture
+        let os_type = Self::detect_os()?;
+        let arch_type = Self::detect_arch()?;
+
+        Ok(Self {
+            base_dir,
+            versions_dir,
+            aliases_file,
+            config_file,
+            config,
+            cache_dir,
+            bin_dir,
+            current_version,
+            current_version_type: VersionType::Node,
+            os_type,
+            arch_type,
+            progress_mode: ProgressMode::default(),
+            operation_id: None,
+            operation: std::sync::Mutex::new(None),
+            phases: std::sync::Mutex::new(None),
+            managed,
+            admin: false,
+            endpoints: ManagerOptions { base_dir: None, ..options },
+        })
+    }
+
+    /// 允许修改统一提供的版本目录（`--admin`）
+    pub fn set_admin(&mut self, admin: bool) {
+        self.admin = admin;
+    }
+
+    /// 版本目录是否由管理员统一提供
+    pub fn is_managed(&self) -> bool {
+        self.managed
+    }
+
+    /// 检查是否可以安装、删除或修改版本
+    ///
+    /// 版本目录由管理员统一提供时，只有使用 `--admin` 才能修改。
+    fn ensure_versions_writable(&self) -> Result<()> {
+        if self.managed && !self.admin {
+            return Err(VersionError::ReadOnly(self.versions_dir.clone()).into());
+        }
+        Ok(())
+    }
+
+    /// 设置长时间操作的进度输出方式
+    pub fn set_progress_mode(&mut self, mode: ProgressMode) {
+        self.progress_mode = mode;
+    }
+
+    /// 输出一条JSON进度事件
+    ///
+    /// 只在 `--progress json` 时输出，每个事件一行，写入标准错误以免和命令的正常输出混在一起。
+    ///
+    /// # 参数
+    ///
+    /// * `event` - 事件名称，例如 download-started、progress、extracted、verified、installed
+    /// * `version_type` - 版本类型
+    /// * `version` - 版本号
+    /// * `fields` - 事件的其他字段
+    fn emit_event(&self, event: &str, version_type: VersionType, version: &str, fields: serde_json::Value) {
+        self.update_operation(&fields);
+        if self.progress_mode != ProgressMode::Json {
+            return;
+        }
+        let mut object = serde_json::json!({
+            "event": event,
+            "tool": version_type.id(),
+            "version": version,
+            "time": chrono::Utc::now().to_rfc3339(),
+        });
+        if let (Some(object), serde_json::Value::Object(fields)) = (object.as_object_mut(), fields) {
+            object.extend(fields);
+        }
+        output::eprintln(object);
+    }
+
+    /// 输出安装过程中的状态信息，`--quiet-install` 时不输出
+    fn report(&self, message: &str) {
+        if self.progress_mode != ProgressMode::Quiet {
+            output::println(message);
+        }
+    }
+
+    /// 创建不知道总量时使用的进度指示，显示已下载的字节数和速度
+    ///
+    /// 定时刷新，连接暂时没有数据时也能看出程序仍在运行。
+    fn progress_spinner(&self) -> indicatif::ProgressBar {
+        if self.progress_mode != ProgressMode::Bar {
+            return indicatif::ProgressBar::hidden();
+        }
+        let pb = output::add(indicatif::ProgressBar::new_spinner());
+        pb.set_style(indicatif::ProgressStyle::default_spinner()
+            .template("{spinner:.green} [{elapsed_precise}] {bytes} ({bytes_per_sec})")
+            .unwrap());
+        pb.enable_steady_tick(std::time::Duration::from_millis(100));
+        pb
+    }
+
+    /// 创建进度条，`--progress json` 和 `--quiet-install` 时返回隐藏的进度条
+    fn progress_bar(&self, total: u64, template: &str) -> indicatif::ProgressBar {
+        if self.progress_mode != ProgressMode::Bar {
+            return indicatif::ProgressBar::hidden();
+        }
+        let pb = output::add(indicatif::ProgressBar::new(total));
+        pb.set_style(indicatif::ProgressStyle::default_bar()
+            .template(template)
+            .unwrap()
+            .progress_chars("#>-"));
+        pb
+    }
+
+    /// 生成新的操作ID
+    pub fn new_operation_id() -> String {
+        format!("op-{}-{}", chrono::Utc::now().timestamp_millis(), std::process::id())
+    }
+
+    /// 指定下一次安装使用的操作ID
+    pub fn set_operation_id(&mut self, id: &str) {
+        self.operation_id = Some(id.to_string());
+    }
+
+    /// 操作记录文件
+    fn operation_file(&self, id: &str) -> PathBuf {
+        self.base_dir.join("operations").join(format!("{}.json", id))
+    }
+
+    /// 保存操作记录
+    fn write_operation(&self, operation: &Operation) -> Result<()> {
+        let path = self.operation_file(&operation.id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&path, serde_json::to_string_pretty(operation)?)?;
+        Ok(())
+    }
+
+    /// 登记一个等待后台进程执行的安装操作
+    ///
+    /// 后台进程已经开始执行并写入了操作记录时不再覆盖。
+    ///
+    /// # 参数
+    ///
+    /// * `id` - 操作ID
+    /// * `version_type` - 版本类型
+    /// * `version` - 版本号
+    /// * `pid` - 执行操作的后台进程号
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub fn queue_operation(&self, id: &str, version_type: VersionType, version: &str, pid: u32) -> Result<()> {
+        if self.operation_file(id).exists() {
+            return Ok(());
+        }
+        self.write_operation(&Operation {
+            id: id.to_string(),
+            kind: "install".to_string(),
+            tool: version_type.id().to_string(),
+            version: version.to_string(),
+            pid,
+            state: "queued".to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            downloaded_bytes: 0,
+            total_bytes: 0,
+            error: None,
+            phase: None,
+        })
+    }
+
+    /// 开始记录安装操作
+    ///
+    /// 操作记录只用于查询进度，写入失败不影响安装本身。
+    fn begin_operation(&self, version_type: VersionType, version: &str) {
+        let operation = Operation {
+            id: self.operation_id.clone().unwrap_or_else(Self::new_operation_id),
+            kind: "install".to_string(),
+            tool: version_type.id().to_string(),
+            version: version.to_string(),
+            pid: std::process::id(),
+            state: "running".to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            downloaded_bytes: 0,
+            total_bytes: 0,
+            error: None,
+            phase: None,
+        };
+        let _ = self.write_operation(&operation);
+        *self.operation.lock().unwrap() = Some(operation);
+    }
+
+    /// 用进度事件中的下载字节数更新正在进行的操作
+    fn update_operation(&self, fields: &serde_json::Value) {
+        let Some(downloaded) = fields.get("downloaded_bytes").and_then(|value| value.as_u64()) else {
+            return;
+        };
+        let mut guard = self.operation.lock().unwrap();
+        if let Some(operation) = guard.as_mut() {
+            operation.downloaded_bytes = downloaded;
+            operation.total_bytes = fields.get("total_bytes").and_then(|value| value.as_u64()).unwrap_or(0);
+            let _ = self.write_operation(operation);
+        }
+    }
+
+    /// 记录安装操作的结果
+    fn finish_operation(&self, result: &Result<()>) {
+        let Some(mut operation) = self.operation.lock().unwrap().take() else {
+            return;
+        };
+        operation.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        operation.state = match result {
+            Ok(()) => "succeeded",
+            Err(err) if matches!(err.downcast_ref::<VersionError>(), Some(VersionError::Cancelled)) => "cancelled",
+            Err(_) => "failed",
+        }.to_string();
+        operation.error = result.as_ref().err().map(|err| err.to_string());
+        // 失败时保留出错的阶段
+        if result.is_ok() {
+            operation.phase = None;
+        }
+        let _ = self.write_operation(&operation);
+    }
+
+    /// 开始为一次安装的各阶段计时
+    fn start_phases(&self, version_type: VersionType, version: &str) {
+        *self.phases.lock().unwrap() = Some(PhaseTimer {
+            version_type,
+            version: version.to_string(),
+            started: std::time::Instant::now(),
+            current: None,
+            elapsed: Vec::new(),
+        });
+    }
+
+    /// 正在进行的安装阶段，没有进行中的安装时为None
+    fn current_phase(&self) -> Option<InstallPhase> {
+        self.phases.lock().unwrap().as_ref()?.current.map(|(phase, _)| phase)
+    }
+
+    /// 进入安装的下一个阶段
+    ///
+    /// 结束当前阶段并输出 `[序号/总数] 说明`，`--progress json` 时输出 phase-finished 和 phase-started 事件。
+    /// 没有进行中的安装（例如单独下载头文件）时什么也不做。
+    ///
+    /// # 参数
+    ///
+    /// * `phase` - 新的阶段
+    /// * `message` - 显示给用户的说明
+    fn begin_phase(&self, phase: InstallPhase, message: &str) {
+        let (version_type, version, finished) = {
+            let mut guard = self.phases.lock().unwrap();
+            let Some(timer) = guard.as_mut() else {
+                return;
+            };
+            let finished = timer.close();
+            timer.current = Some((phase, std::time::Instant::now()));
+            (timer.version_type, timer.version.clone(), finished)
+        };
+        if let Some((finished, duration)) = finished {
+            self.emit_event("phase-finished", version_type, &version, serde_json::json!({ "phase": finished.name(), "duration_ms": duration.as_millis() as u64 }));
+        }
+        self.emit_event("phase-started", version_type, &version, serde_json::json!({ "phase": phase.name(), "step": phase.step(), "steps": InstallPhase::ALL.len() }));
+        if let Some(operation) = self.operation.lock().unwrap().as_mut() {
+            operation.phase = Some(phase.name().to_string());
+            let _ = self.write_operation(operation);
+        }
+        self.report(&format!("[{}/{}] {}", phase.step(), InstallPhase::ALL.len(), message));
+    }
+
+    /// 结束阶段计时
+    ///
+    /// # 返回
+    ///
+    /// 返回总耗时和各阶段的耗时，没有进行中的安装时返回None。
+    fn finish_phases(&self) -> Option<InstallTiming> {
+        let mut timer = self.phases.lock().unwrap().take()?;
+        if let Some((finished, duration)) = timer.close() {
+            self.emit_event("phase-finished", timer.version_type, &timer.version, serde_json::json!({ "phase": finished.name(), "duration_ms": duration.as_millis() as u64 }));
+        }
+        Some(InstallTiming { total: timer.started.elapsed(), phases: timer.elapsed })
+    }
+
+    /// 列出记录的操作，按开始时间排序
+    ///
+    /// 进程已经退出但状态仍未结束的操作标记为失败，结束超过7天的记录会被删除。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回操作列表，失败时返回错误。
+    pub fn list_operations(&self) -> Result<Vec<Operation>> {
+        let dir = self.base_dir.join("operations");
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let expiry = chrono::Utc::now() - chrono::Duration::days(7);
+        let mut operations = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let Ok(mut operation) = fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| serde_json::from_str::<Operation>(&content).map_err(anyhow::Error::from)) else {
+                continue;
+            };
+            if !operation.is_finished() && !Self::process_alive(operation.pid) {
+                operation.state = "failed".to_string();
+                operation.finished_at = Some(chrono::Utc::now().to_rfc3339());
+                operation.error = Some("执行操作的进程已意外退出".to_string());
+                self.write_operation(&operation)?;
+            }
+            let expired = operation.finished_at.as_deref()
+                .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+                .is_some_and(|time| time < expiry);
+            if expired {
+                fs::remove_file(&path)?;
+                continue;
+            }
+            operations.push(operation);
+        }
+        operations.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        Ok(operations)
+    }
+
+    /// 检查进程是否仍在运行，无法判断时视为仍在运行
+    fn process_alive(pid: u32) -> bool {
+        if cfg!(target_os = "linux") {
+            return Path::new("/proc").join(pid.to_string()).exists();
+        }
+        if cfg!(unix) {
+            return Command::new("kill").arg("-0").arg(pid.to_string())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(true);
+        }
+        if cfg!(windows) {
+            // 没有匹配的进程时 tasklist 只输出一行提示
+            return Command::new("tasklist")
+                .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+                .output()
+                .map(|output| String::from_utf8_lossy(&output.stdout).split_whitespace().any(|word| word == pid.to_string()))
+                .unwrap_or(true);
+        }
+        true
+    }
+
+    /// 检测操作系统类型
+    ///
+    /// 根据系统环境变量OS来检测操作系统类型。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回OsType枚举值，失败时返回错误。
+    fn detect_os() -> Result<OsType> {
+        let os = env::consts::OS;
+        match os {
+            "macos" | "darwin" => Ok(OsType::Darwin),
+            "linux" => Ok(OsType::Linux),
+            "windows" => Ok(OsType::Windows),
+            _ => Err(anyhow::anyhow!("不支持的操作系统: {}", os)),
+        }
+    }
+
+    /// 检测架构类型
+    ///
+    /// 根据系统环境变量ARCH来检测架构类型。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回ArchType枚举值，失败时返回错误。
+    fn detect_arch() -> Result<ArchType> {
+        let arch = env::consts::ARCH;
+        match arch {
+            "x86_64" => Ok(ArchType::X64),
+            "aarch64" => Ok(ArchType::Arm64),
+            "arm" => Ok(ArchType::Arm),
+            "x86" => Ok(ArchType::X86),
+            _ => Err(anyhow::anyhow!("不支持的架构: {}", arch)),
+        }
+    }
+
+    /// 获取操作系统和架构对应的下载 URL 后缀
+    ///
+    /// 根据操作系统类型和架构类型生成Node.js下载 URL 后缀，不支持时返回unknown。
+    ///
+    /// # 返回
+    ///
+    /// 返回URL后缀字符串。
+    fn get_os_arch_suffix(&self) -> String {
+        self.platform_suffix(VersionType::Node).unwrap_or_else(|_| "unknown".to_string())
+    }
+
+    /// 获取工具提供预编译包的平台列表
+    fn platform_targets(version_type: VersionType) -> &'static [(OsType, ArchType, &'static str)] {
+        match version_type {
+            VersionType::Node => NODE_TARGETS,
+            VersionType::Rust => RUST_TARGETS,
+            VersionType::Python => PYTHON_TARGETS,
+            VersionType::Go => GO_TARGETS,
+        }
+    }
+
+    /// 构造预编译包的下载地址
+    ///
+    /// # 参数
+    ///
+    /// * `mirror` - 镜像地址
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    /// * `os` - 目标操作系统，决定压缩包格式
+    /// * `suffix` - 平台后缀
+    ///
+    /// # 返回
+    ///
+    /// 返回下载地址。
+    fn download_url(mirror: &str, version: &str, version_type: VersionType, os: OsType, suffix: &str) -> String {
+        let extension = match os {
+            OsType::Windows => ".zip",
+            _ => ".tar.gz",
+        };
+        
+        match version_type {
+            VersionType::Node => format!(
+                "{}/v{}/node-v{}-{}{}",
+                mirror, version, version, suffix, extension
+            ),
+            VersionType::Rust => format!(
+                "{}/rust-{}-{}{}",
+                mirror, version, suffix, extension
+            ),
+            // python.org 只为 Unix 提供源码包，需要在本地编译
+            VersionType::Python if os != OsType::Windows => format!(
+                "{}/{}/Python-{}.tgz",
+                mirror, version, version
+            ),
+            VersionType::Python => format!(
+                "{}/{}/python-{}-embed-{}.zip",
+                mirror, version, version, suffix
+            ),
+            VersionType::Go => format!(
+                "{}/go{}.{}{}",
+                mirror, version, suffix, extension
+            ),
+        }
+    }
+
+    /// 为所有支持的平台构造下载文件列表
+    ///
+    /// 用于索引中没有提供逐平台文件信息的工具。
+    fn release_files(&self, version: &str, version_type: VersionType) -> Vec<ReleaseFile> {
+        let mirror = self.mirror_base(version_type);
+        Self::platform_targets(version_type).iter()
+            .map(|(os, _, suffix)| ReleaseFile {
+                platform: suffix.to_string(),
+                url: Self::download_url(&mirror, version, version_type, *os, suffix),
+                sha256: None,
+            })
+            .collect()
+    }
+
+    /// 获取当前平台对应的下载文件名后缀
+    ///
+    /// 在下载之前检查工具是否为当前操作系统和架构提供预编译包。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回后缀字符串，不支持当前平台时返回列出支持平台的错误。
+    pub fn platform_suffix(&self, version_type: VersionType) -> Result<String> {
+        self.platform_suffix_for(version_type, self.arch_type)
+    }
+
+    /// 获取当前操作系统上指定架构对应的下载文件名后缀
+    fn platform_suffix_for(&self, version_type: VersionType, arch_type: ArchType) -> Result<String> {
+        let targets = Self::platform_targets(version_type);
+        if let Some((_, _, suffix)) = targets.iter()
+            .find(|(os, arch, _)| *os == self.os_type && *arch == arch_type)
+        {
+            return Ok(suffix.to_string());
+        }
+        
+        Err(VersionError::UnsupportedPlatform {
+            version_type,
+            platform: format!("{}-{}", self.os_type.name(), arch_type.name()),
+            supported: targets.iter()
+                .map(|(os, arch, _)| format!("{}-{}", os.name(), arch.name()))
+                .collect(),
+        }.into())
+    }
+
+    /// 获取可执行文件的扩展名
+    ///
+    /// 根据操作系统类型获取可执行文件的扩展名。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回扩展名字符串，失败时返回错误。
+    fn get_exe_extension(&self) -> &str {
+        match self.os_type {
+            OsType::Windows => ".exe",
+            _ => "",
+        }
+    }
+
+    /// 读取当前版本从文件
+    ///
+    /// 从指定目录下的.current-node文件读取当前版本信息。
+    ///
+    /// # 参数
+    ///
+    /// * `base_dir` - 基础目录
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回当前版本字符串，失败时返回错误。
+    #[allow(clippy::ptr_arg)]
+    fn read_current_version(base_dir: &PathBuf, version_type: VersionType) -> Result<String> {
+        let version_file = base_dir.join(format!(".current-{}", version_type));
+        if version_file.exists() {
+            let version = fs::read_to_string(version_file)?;
+            let version = version.trim();
+            // 旧版本在写入时崩溃可能留下空文件，按未设置处理
+            anyhow::ensure!(!version.is_empty(), "当前版本文件为空");
+            Ok(version.to_string())
+        } else {
+            Err(anyhow::anyhow!("找不到当前版本文件"))
+        }
+    }
+
+    /// 保存当前版本到文件
+    ///
+    /// 将当前版本信息保存到指定目录下的.current-node文件。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 当前版本字符串
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn save_current_version(&self, version: &str, version_type: VersionType) -> Result<()> {
+        let version_file = self.base_dir.join(format!(".current-{}", version_type));
+        write_atomic(&version_file, version)?;
+        Ok(())
+    }
+
+    /// 获取当前版本
+    ///
+    /// 获取当前使用的版本信息。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 设置了当前版本时返回版本字符串，否则返回None。
+    pub fn get_current_version(&self, version_type: VersionType) -> Option<String> {
+        if self.current_version_type == version_type {
+            self.current_version.clone()
+        } else {
+            Self::read_current_version(&self.base_dir, version_type).ok()
+        }
+    }
+
+    /// 获取用户配置
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// 获取 ver 的主目录
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// `ver sync` 使用的 git 仓库的工作目录
+    pub fn sync_dir(&self) -> PathBuf {
+        self.base_dir.join("sync")
+    }
+
+    /// 用户的项目模板目录，每个模板是一个 `<名称>.ver.toml`，`ver new <名称>` 时复制到项目中
+    pub fn templates_dir(&self) -> PathBuf {
+        self.base_dir.join("templates")
+    }
+
+    /// 读取本地的别名、默认版本、渠道订阅和项目模板，用于 `ver sync`
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回本地的同步状态，失败时返回错误。
+    pub fn local_sync_state(&self) -> Result<SyncState> {
+        let mut sync_state = SyncState::default();
+        for version_type in VersionType::all() {
+            let tool = version_type.id().to_string();
+            sync_state.aliases.extend(self.read_aliases(version_type)?.aliases.into_iter()
+                .map(|(alias, version)| ((tool.clone(), alias), version)));
+            if let Some(version) = self.get_current_version(version_type) {
+                sync_state.defaults.insert(tool, version);
+            }
+        }
+        sync_state.subscriptions = self.read_subscriptions()?.subscriptions.into_iter()
+            .map(|subscription| ((subscription.tool.clone(), subscription.channel.clone()), subscription))
+            .collect();
+        sync_state.templates = crate::sync::read_files(&self.templates_dir())?;
+        Ok(sync_state)
+    }
+
+    /// 备份本地的同步状态
+    ///
+    /// 备份保存在 ~/.version-manager/sync-backups/<时间> 中，格式与同步仓库相同，只保留最近的
+    /// [`SYNC_BACKUPS`] 份。
+    ///
+    /// # 参数
+    ///
+    /// * `sync_state` - 本地的同步状态
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回备份目录，失败时返回错误。
+    pub fn backup_sync_state(&self, sync_state: &SyncState) -> Result<PathBuf> {
+        let backups_dir = self.base_dir.join("sync-backups");
+        let backup = backups_dir.join(chrono::Local::now().format("%Y%m%d-%H%M%S%.3f").to_string());
+        fs::create_dir_all(&backup).with_context(|| format!("无法创建备份目录 {}", backup.display()))?;
+        crate::sync::write_state(&backup, sync_state)?;
+        
+        let mut backups: Vec<PathBuf> = fs::read_dir(&backups_dir)?.flatten().map(|entry| entry.path()).collect();
+        backups.sort();
+        for old in backups.iter().rev().skip(SYNC_BACKUPS) {
+            let _ = fs::remove_dir_all(old);
+        }
+        Ok(backup)
+    }
+
+    /// 用合并后的同步状态替换本地的别名、渠道订阅和项目模板，并切换默认版本
+    ///
+    /// 有改动时先用 `backup_sync_state` 备份本地状态。默认版本只在已安装时切换，不会下载。
+    ///
+    /// # 参数
+    ///
+    /// * `merged` - 合并后的同步状态
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回备份目录和没有切换的默认版本，失败时返回错误。
+    pub fn import_sync_state(&mut self, merged: &SyncState) -> Result<SyncImport> {
+        let local = self.local_sync_state()?;
+        let mut missing = Vec::new();
+        let mut switch = Vec::new();
+        for (tool, version) in &merged.defaults {
+            let Some(version_type) = VersionType::from_id(tool) else {
+                continue;
+            };
+            if local.defaults.get(tool) == Some(version) {
+                continue;
+            }
+            if self.is_installed(version, version_type) {
+                switch.push((version_type, version.clone()));
+            } else {
+                missing.push((version_type, version.clone()));
+            }
+        }
+        if merged.aliases == local.aliases
+            && merged.subscriptions == local.subscriptions
+            && merged.templates == local.templates
+            && switch.is_empty()
+        {
+            return Ok(SyncImport { backup: None, missing });
+        }
+        
+        let backup = self.backup_sync_state(&local)?;
+        for version_type in VersionType::all() {
+            let aliases: HashMap<String, String> = merged.aliases.iter()
+                .filter(|((tool, _), _)| tool == version_type.id())
+                .map(|((_, alias), version)| (alias.clone(), version.clone()))
+                .collect();
+            self.save_aliases(&Aliases { schema: state::SCHEMA_VERSION, aliases }, version_type)?;
+        }
+        self.save_subscriptions(&Subscriptions { subscriptions: merged.subscriptions.values().cloned().collect() })?;
+        crate::sync::write_files(&self.templates_dir(), &merged.templates)?;
+        for (version_type, version) in switch {
+            if let Err(err) = self.use_version(&version, version_type) {
+                output::eprintln(format_args!("Warning: could not switch {} to {}: {}", version_type.id(), version, err));
+            }
+        }
+        Ok(SyncImport { backup: Some(backup), missing })
+    }
+
+    /// 获取放置当前版本命令的 bin 目录
+    pub fn bin_dir(&self) -> &Path {
+        &self.bin_dir
+    }
+
+    /// 为 ver 子命令安装系统定时任务
+    ///
+    /// 输出保存在 ~/.version-manager/<名称>.log 中。
+    ///
+    /// # 参数
+    ///
+    /// * `name` - 任务名称，例如 `maintain`
+    /// * `args` - 传给 ver 的参数
+    /// * `interval` - 执行间隔
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回已安装任务的说明，失败时返回错误。
+    pub fn install_schedule(&self, name: &str, args: &[&str], interval: schedule::Interval) -> Result<String> {
+        schedule::install(name, args, interval, &self.base_dir.join(format!("{}.log", name)))
+    }
+
+    /// 修改并保存配置项
+    ///
+    /// # 参数
+    ///
+    /// * `key` - 配置项名称
+    /// * `value` - 配置值，为None时清除该配置项
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub fn set_config(&mut self, key: &str, value: Option<String>) -> Result<()> {
+        self.config.set(key, value)?;
+        self.config.save(&self.config_file)?;
+        self.update_env_file()
+    }
+
+    /// 获取激活某个工具时需要导出的环境变量
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回环境变量名和值的列表。
+    pub fn tool_env(&self, version_type: VersionType) -> Vec<(String, String)> {
+        self.config.tool_env(version_type)
+    }
+
+    /// 更新环境变量文件
+    ///
+    /// 为已激活的工具生成 ~/.version-manager/env，shell配置文件会加载该文件。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn update_env_file(&self) -> Result<()> {
+        let mut content = String::from("# Generated by ver, do not edit\n");
+        for version_type in VersionType::all().into_iter().filter(|version_type| self.manages_tool(*version_type)) {
+            let Ok(version) = Self::read_current_version(&self.base_dir, version_type) else {
+                continue;
+            };
+            for (key, value) in self.version_env(&version, version_type) {
+                content.push_str(&format!("export {}={}\n", key, sh_quote(&value)));
+            }
+        }
+        
+        write_atomic(&self.base_dir.join("env"), content)?;
+        
+        // fish 无法加载 sh 语法的文件，单独生成一份
+        let mut fish_content = String::from("# Generated by ver, do not edit\n");
+        for version_type in VersionType::all().into_iter().filter(|version_type| self.manages_tool(*version_type)) {
+            let Ok(version) = Self::read_current_version(&self.base_dir, version_type) else {
+                continue;
+            };
+            for (key, value) in self.version_env(&version, version_type) {
+                fish_content.push_str(&format!("set -gx {} {}\n", key, fish_quote(&value)));
+            }
+        }
+        write_atomic(&self.base_dir.join("env.fish"), fish_content)?;
+        Ok(())
+    }
+
+    /// 获取某个工具的候选镜像
+    ///
+    /// 配置的镜像在前，官方下载源在最后。通过 ManagerOptions 指定了下载地址时只使用该地址。
+    pub fn mirror_candidates(&self, version_type: VersionType) -> Vec<String> {
+        if let Some((_, endpoint)) = self.endpoints.endpoints.iter().find(|(tool, _)| *tool == version_type) {
+            return vec![endpoint.trim_end_matches('/').to_string()];
+        }
+        
+        let mut candidates: Vec<String> = self.config.mirrors(version_type)
+            .iter()
+            .map(|url| url.trim_end_matches('/').to_string())
+            .collect();
+        let default = version_type.default_mirror().to_string();
+        if !candidates.contains(&default) {
+            candidates.push(default);
+        }
+        candidates
+    }
+
+    /// 获取当前应使用的下载源
+    ///
+    /// 优先使用未过期的自动选择结果，其次是第一个配置的镜像，最后是官方下载源。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回不带结尾斜杠的下载源地址。
+    pub fn mirror_base(&self, version_type: VersionType) -> String {
+        let candidates = self.mirror_candidates(version_type);
+        
+        if let Some(selection) = self.read_mirror_selections().selections.get(version_type.id())
+            && chrono::Utc::now().timestamp() - selection.selected_at < MIRROR_SELECTION_TTL_SECS
+            && candidates.contains(&selection.url)
+        {
+            return selection.url.clone();
+        }
+        
+        candidates[0].clone()
+    }
+
+    /// 读取自动选择的镜像记录，文件不存在或损坏时返回空记录
+    fn read_mirror_selections(&self) -> MirrorSelections {
+        fs::read_to_string(self.base_dir.join("mirror-selection.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 测试某个工具所有候选镜像的可用性和延迟
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回每个候选镜像的测速结果。
+    pub async fn probe_mirrors(&self, version_type: VersionType) -> Result<Vec<MirrorProbe>> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()?;
+        
+        let probes = self.mirror_candidates(version_type).into_iter().map(|url| {
+            let client = client.clone();
+            async move {
+                let started = std::time::Instant::now();
+                match client.get(format!("{}/", url)).send().await {
+                    Ok(response) if response.status().is_success() => MirrorProbe {
+                        url,
+                        latency_ms: Some(started.elapsed().as_millis()),
+                        error: None,
+                    },
+                    Ok(response) => MirrorProbe {
+                        url,
+                        latency_ms: None,
+                        error: Some(format!("HTTP {}", response.status())),
+                    },
+                    Err(err) => MirrorProbe {
+                        url,
+                        latency_ms: None,
+                        error: Some(err.to_string()),
+                    },
+                }
+            }
+        });
+        let probes = futures_util::future::join_all(probes).await;
+        
+        Ok(probes)
+    }
+
+    /// 保存自动选择的镜像
+    ///
+    /// 选择结果在 MIRROR_SELECTION_TTL_SECS 秒内有效，过期后回退到配置的默认镜像。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `url` - 选中的镜像地址
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub fn select_mirror(&self, version_type: VersionType, url: &str) -> Result<()> {
+        let mut selections = self.read_mirror_selections();
+        selections.selections.insert(version_type.id().to_string(), MirrorSelection {
+            url: url.to_string(),
+            selected_at: chrono::Utc::now().timestamp(),
+        });
+        write_atomic(&self.base_dir.join("mirror-selection.json"), serde_json::to_string_pretty(&selections)?)?;
+        Ok(())
+    }
+
+    /// 读取渠道订阅，文件不存在时返回空列表
+    fn read_subscriptions(&self) -> Result<Subscriptions> {
+        Ok(state::read_json(&self.base_dir.join("subscriptions.json"))?.unwrap_or_default())
+    }
+
+    /// 保存渠道订阅
+    fn save_subscriptions(&self, subscriptions: &Subscriptions) -> Result<()> {
+        write_atomic(&self.base_dir.join("subscriptions.json"), serde_json::to_string_pretty(subscriptions)?)?;
+        Ok(())
+    }
+
+    /// 订阅发布渠道
+    ///
+    /// 同一工具的同一渠道只保留一个订阅，重复订阅时更新别名。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `channel` - 渠道：latest、lts（Node.js）、stable 或主版本前缀
+    /// * `alias` - 安装新版本后重新指向的别名
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，渠道无效时返回错误。
+    pub fn subscribe(&self, version_type: VersionType, channel: &str, alias: Option<&str>) -> Result<()> {
+        let channel = channel.trim_start_matches('v').to_lowercase();
+        let valid = match channel.as_str() {
+            "latest" | "stable" => true,
+            "lts" => version_type == VersionType::Node,
+            prefix => !prefix.is_empty() && prefix.split('.').all(|part| part.parse::<u64>().is_ok()),
+        };
+        if !valid {
+            return Err(anyhow::anyhow!("{} 不支持渠道 {}（可选 latest、stable{}或主版本号，例如 20）",
+                version_type, channel, if version_type == VersionType::Node { "、lts " } else { " " }));
+        }
+        
+        let mut subscriptions = self.read_subscriptions()?;
+        subscriptions.subscriptions.retain(|subscription| !(subscription.tool == version_type.id() && subscription.channel == channel));
+        subscriptions.subscriptions.push(Subscription {
+            tool: version_type.id().to_string(),
+            channel,
+            alias: alias.map(str::to_string),
+            installed: None,
+        });
+        self.save_subscriptions(&subscriptions)
+    }
+
+    /// 取消订阅
+    ///
+    /// # 返回
+    ///
+    /// 存在该订阅时返回true。
+    pub fn unsubscribe(&self, version_type: VersionType, channel: &str) -> Result<bool> {
+        let channel = channel.trim_start_matches('v').to_lowercase();
+        let mut subscriptions = self.read_subscriptions()?;
+        let before = subscriptions.subscriptions.len();
+        subscriptions.subscriptions.retain(|subscription| !(subscription.tool == version_type.id() && subscription.channel == channel));
+        if subscriptions.subscriptions.len() == before {
+            return Ok(false);
+        }
+        self.save_subscriptions(&subscriptions)?;
+        Ok(true)
+    }
+
+    /// 列出所有渠道订阅
+    pub fn list_subscriptions(&self) -> Result<Vec<Subscription>> {
+        Ok(self.read_subscriptions()?.subscriptions)
+    }
+
+    /// 读取 Rust 目录覆盖，文件不存在时返回空映射
+    fn read_rust_overrides(&self) -> Result<RustOverrides> {
+        Ok(state::read_json(&self.base_dir.join("rust-overrides.json"))?.unwrap_or_default())
+    }
+
+    /// 保存 Rust 目录覆盖
+    fn save_rust_overrides(&self, overrides: &RustOverrides) -> Result<()> {
+        write_atomic(&self.base_dir.join("rust-overrides.json"), serde_json::to_string_pretty(overrides)?)?;
+        Ok(())
+    }
+
+    /// 为目录设置 Rust 工具链覆盖
+    ///
+    /// 覆盖对目录及其子目录生效，优先于项目中的 rust-toolchain 文件，与 `rustup override set` 相同。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 目录
+    /// * `toolchain` - 已安装的工具链版本或别名
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回规范化后的目录，工具链未安装时返回错误。
+    pub fn set_rust_override(&self, dir: &Path, toolchain: &str) -> Result<PathBuf> {
+        if !self.get_version_dir(toolchain, VersionType::Rust).exists()
+            && self.get_alias(toolchain, VersionType::Rust)?.is_none()
+        {
+            return Err(VersionError::NotInstalled(toolchain.to_string(), VersionType::Rust).into());
+        }
+        let dir = dir.canonicalize().with_context(|| format!("无法访问目录 {}", dir.display()))?;
+        
+        let mut overrides = self.read_rust_overrides()?;
+        overrides.overrides.insert(dir.clone(), toolchain.to_string());
+        self.save_rust_overrides(&overrides)?;
+        Ok(dir)
+    }
+
+    /// 删除目录的 Rust 工具链覆盖
+    ///
+    /// 目录已被删除时按原样匹配，这样也能清理失效的覆盖。
+    ///
+    /// # 返回
+    ///
+    /// 存在该覆盖时返回true。
+    pub fn unset_rust_override(&self, dir: &Path) -> Result<bool> {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let mut overrides = self.read_rust_overrides()?;
+        if overrides.overrides.remove(&dir).is_none() {
+            return Ok(false);
+        }
+        self.save_rust_overrides(&overrides)?;
+        Ok(true)
+    }
+
+    /// 列出所有 Rust 目录覆盖，按目录排序
+    pub fn list_rust_overrides(&self) -> Result<Vec<(PathBuf, String)>> {
+        Ok(self.read_rust_overrides()?.overrides.into_iter().collect())
+    }
+
+    /// 查找对目录生效的 Rust 工具链覆盖
+    ///
+    /// 取离目录最近的一层覆盖。其他工具或读取失败时返回None，不影响后续的版本文件解析。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `dir` - 要解析的目录
+    /// * `trace` - 追加解析步骤说明
+    ///
+    /// # 返回
+    ///
+    /// 找到覆盖时返回工具链，否则返回None。
+    fn rust_override_traced(&self, version_type: VersionType, dir: &Path, trace: &mut Vec<String>) -> Option<String> {
+        if version_type != VersionType::Rust {
+            return None;
+        }
+        let overrides = self.read_rust_overrides().ok()?.overrides;
+        if overrides.is_empty() {
+            return None;
+        }
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let (ancestor, toolchain) = dir.ancestors()
+            .find_map(|ancestor| overrides.get(ancestor).map(|toolchain| (ancestor, toolchain)))?;
+        trace.push(format!("directory override for {}: {}", ancestor.display(), toolchain));
+        Some(toolchain.clone())
+    }
+
+    /// 获取渠道中的最新版本
+    ///
+    /// lts 和 stable 使用推荐版本列表，latest 使用所有版本，主版本前缀匹配该系列的最新版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `channel` - 渠道
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回最新版本，渠道中没有版本时返回None。
+    pub async fn channel_latest(&self, version_type: VersionType, channel: &str) -> Result<Option<String>> {
+        let recommended_only = matches!(channel, "lts" | "stable");
+        let versions = self.list_available_versions(recommended_only, version_type).await?;
+        let prefix = format!("{}.", channel);
+        Ok(versions.iter()
+            .map(|release| release.version.trim_start_matches('v'))
+            .find(|version| matches!(channel, "latest" | "lts" | "stable") || version.starts_with(&prefix))
+            .map(str::to_string))
+    }
+
+    /// 安装所有订阅渠道中的新版本
+    ///
+    /// 新版本安装后把订阅的别名指向它，并记录在订阅中。单个订阅失败时打印错误并继续。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回新安装的版本（版本类型和版本号），读取订阅失败时返回错误。
+    pub async fn upgrade_subscribed(&self) -> Result<Vec<(VersionType, String)>> {
+        let mut subscriptions = self.read_subscriptions()?;
+        // 先同时查询所有频道的最新版本，再依次安装
+        let lookups = subscriptions.subscriptions.iter().map(|subscription| async move {
+            match VersionType::from_id(&subscription.tool) {
+                Some(version_type) => Some(self.channel_latest(version_type, &subscription.channel).await),
+                None => None,
+            }
+        });
+        let lookups = futures_util::future::join_all(lookups).await;
+        let mut upgraded = Vec::new();
+        for (subscription, lookup) in subscriptions.subscriptions.iter_mut().zip(lookups) {
+            let (Some(version_type), Some(lookup)) = (VersionType::from_id(&subscription.tool), lookup) else {
+                continue;
+            };
+            let latest = match lookup {
+                Ok(Some(latest)) => latest,
+                Ok(None) => {
+                    output::eprintln(format_args!("{} {}: no releases found", subscription.tool, subscription.channel));
+                    continue;
+                }
+                Err(err) => {
+                    output::eprintln(format_args!("{} {}: failed to fetch remote versions: {}", subscription.tool, subscription.channel, err));
+                    continue;
+                }
+            };
+            
+            if !self.is_installed(&latest, version_type) {
+                if let Err(err) = self.install_version(&latest, version_type).await {
+                    output::eprintln(format_args!("{} {}: failed to install {}: {}", subscription.tool, subscription.channel, latest, err));
+                    continue;
+                }
+                upgraded.push((version_type, latest.clone()));
+            }
+            if let Some(alias) = &subscription.alias
+                && self.get_alias(alias, version_type)?.as_deref() != Some(latest.as_str())
+            {
+                self.create_alias(alias, &latest, version_type)?;
+                output::println(format_args!("Alias '{}' now points to {} version {}", alias, version_type, latest));
+            }
+            subscription.installed = Some(latest);
+        }
+        self.save_subscriptions(&subscriptions)?;
+        Ok(upgraded)
+    }
+
+    /// 读取别名配置
+    ///
+    /// 从指定目录下的aliases.json文件读取别名配置信息。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回别名配置信息，失败时返回错误。
+    fn read_aliases(&self, version_type: VersionType) -> Result<Aliases> {
+        let aliases_file = self.aliases_file.with_file_name(format!("aliases-{}.json", version_type));
+        let mut aliases = state::read_json(&aliases_file)?.unwrap_or(Aliases {
+            schema: state::SCHEMA_VERSION,
+            aliases: HashMap::new(),
+        });
+        // 启动时已经完成迁移，保存时按当前格式写回
+        aliases.schema = state::SCHEMA_VERSION;
+        Ok(aliases)
+    }
+
+    /// 保存别名配置
+    ///
+    /// 将别名配置信息保存到指定目录下的aliases.json文件。
+    ///
+    /// # 参数
+    ///
+    /// * `aliases` - 别名配置信息
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn save_aliases(&self, aliases: &Aliases, version_type: VersionType) -> Result<()> {
+        let aliases_file = self.aliases_file.with_file_name(format!("aliases-{}.json", version_type));
+        let content = serde_json::to_string_pretty(aliases)?;
+        write_atomic(&aliases_file, content)?;
+        Ok(())
+    }
+
+    /// 机器级别名文件
+    ///
+    /// 由管理员维护，为所有用户定义统一的别名，例如 `corp-lts`。
+    /// 目录由环境变量 VER_SYSTEM_DIR 指定，默认 Unix 为 /etc/ver，Windows 为 %ProgramData%\ver。
+    fn system_aliases_file(&self, version_type: VersionType) -> PathBuf {
+        let dir = env::var_os("VER_SYSTEM_DIR")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| match self.os_type {
+                OsType::Windows => PathBuf::from(env::var_os("ProgramData").unwrap_or_else(|| r"C:\ProgramData".into())).join("ver"),
+                _ => PathBuf::from("/etc/ver"),
+            });
+        dir.join(format!("aliases-{}.json", version_type.id()))
+    }
+
+    /// 读取机器级别名
+    fn read_system_aliases(&self, version_type: VersionType) -> Result<Aliases> {
+        Ok(state::read_json(&self.system_aliases_file(version_type))?.unwrap_or(Aliases {
+            schema: state::SCHEMA_VERSION,
+            aliases: HashMap::new(),
+        }))
+    }
+
+    /// 列出机器级别名
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回按名称排序的别名列表，失败时返回错误。
+    pub fn list_system_aliases(&self, version_type: VersionType) -> Result<Vec<(String, String)>> {
+        let mut result: Vec<_> = self.read_system_aliases(version_type)?.aliases.into_iter().collect();
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(result)
+    }
+
+    /// 创建机器级别名
+    ///
+    /// 写入所有用户共享的别名文件，通常需要管理员权限。版本不要求在本机已安装，
+    /// 用户使用时按需安装。
+    ///
+    /// # 参数
+    ///
+    /// * `alias` - 别名名称
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回别名文件路径，失败时返回错误。
+    pub fn create_system_alias(&self, alias: &str, version: &str, version_type: VersionType) -> Result<PathBuf> {
+        let path = self.system_aliases_file(version_type);
+        let mut aliases = self.read_system_aliases(version_type)?;
+        aliases.schema = state::SCHEMA_VERSION;
+        aliases.aliases.insert(alias.to_string(), version.to_string());
+        
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建目录 {}，修改机器级别名需要管理员权限", parent.display()))?;
+        }
+        write_atomic(&path, serde_json::to_string_pretty(&aliases)?)
+            .with_context(|| format!("无法写入 {}，修改机器级别名需要管理员权限", path.display()))?;
+        Ok(path)
+    }
+
+    /// 创建版本别名
+    ///
+    /// 为指定版本创建一个别名。
+    ///
+    /// # 参数
+    ///
+    /// * `alias` - 别名名称
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub fn create_alias(&self, alias: &str, version: &str, version_type: VersionType) -> Result<()> {
+        // 检查版本是否已安装
+        let version_dir = self.get_version_dir(version, version_type);
+        if !version_dir.exists() {
+            return Err(VersionError::NotInstalled(version.to_string(), version_type).into());
+        }
+
+        let mut aliases = self.read_aliases(version_type)?;
+        aliases.aliases.insert(alias.to_string(), version.to_string());
+        self.save_aliases(&aliases, version_type)?;
+
+        Ok(())
+    }
+
+    /// 获取别名对应的版本
+    ///
+    /// 获取指定别名对应的版本号。
+    ///
+    /// # 参数
+    ///
+    /// * `alias` - 别名名称
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回版本号字符串，失败时返回错误。
+    pub fn get_alias(&self, alias: &str, version_type: VersionType) -> Result<Option<String>> {
+        let aliases = self.read_aliases(version_type)?;
+        if let Some(version) = aliases.aliases.get(alias) {
+            return Ok(Some(version.clone()));
+        }
+        Ok(self.read_system_aliases(version_type)?.aliases.get(alias).cloned())
+    }
+
+    /// 列出所有别名
+    ///
+    /// 列出所有已定义的别名。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回别名列表，失败时返回错误。
+    pub fn list_aliases(&self, version_type: VersionType) -> Result<Vec<(String, String)>> {
+        // 用户别名覆盖同名的机器级别名
+        let mut aliases = self.read_system_aliases(version_type)?.aliases;
+        aliases.extend(self.read_aliases(version_type)?.aliases);
+        let mut result = Vec::new();
+        
+        for (alias, version) in aliases {
+            result.push((alias, version));
+        }
+        
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(result)
+    }
+
+    /// 设置本地版本
+    ///
+    /// 在当前目录下创建一个文件指定使用的版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub fn set_local_version(&self, version: &str, version_type: VersionType) -> Result<()> {
+        // 检查版本是否已安装
+        let version_dir = self.get_version_dir(version, version_type);
+        if !version_dir.exists() {
+            return Err(VersionError::NotInstalled(version.to_string(), version_type).into());
+        }
+
+        let current_dir = env::current_dir()?;
+        let version_file = current_dir.join(version_type.version_file_name());
+        
+        write_atomic(&version_file, version)?;
+        
+        Ok(())
+    }
+
+    /// 获取本地项目要求的版本
+    ///
+    /// 按顺序读取指定目录下的内置和自定义版本文件（配置项 `version_files.<文件名>`）。
+    ///
+    /// # 参数
+    ///
+    /// * `current_dir` - 要查询的目录
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回版本号和声明它的文件，没有版本文件时返回None。
+    fn get_local_version(&self, current_dir: &Path, version_type: VersionType) -> Result<Option<(String, PathBuf)>> {
+        for format in version_file::formats(&self.config) {
+            let version_file = current_dir.join(format.file_name());
+            if !version_file.is_file() {
+                continue;
+            }
+            let content = fs::read_to_string(&version_file)?;
+            if let Some(version) = format.parse(&content, version_type) {
+                return Ok(Some((version, version_file)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 查找目录所在Go工作区要求的工具链
+    ///
+    /// 从指定目录向上查找，与 go 命令一样，工作区的 go.work 优先于最近的 go.mod。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 起始目录
+    ///
+    /// # 返回
+    ///
+    /// 找到声明了 `go` 或 `toolchain` 指令的文件时返回要求，否则返回None。
+    pub fn go_toolchain_requirement(&self, dir: &Path) -> Result<Option<GoToolchainRequirement>> {
+        let find = |name: &str| dir.ancestors().map(|ancestor| ancestor.join(name)).find(|file| file.is_file());
+        let Some(file) = find("go.work").or_else(|| find("go.mod")) else {
+            return Ok(None);
+        };
+        
+        let content = fs::read_to_string(&file)?;
+        let mut required: Option<String> = None;
+        for line in content.lines() {
+            let version = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["go", version] => version.to_string(),
+                ["toolchain", toolchain] => match toolchain.strip_prefix("go") {
+                    Some(version) => version.to_string(),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if required.as_ref().is_none_or(|current| compare_versions(&version, current) == std::cmp::Ordering::Greater) {
+                required = Some(version);
+            }
+        }
+        
+        Ok(required.map(|version| GoToolchainRequirement {
+            // Go 1.21 起 `go 1.22` 表示 1.22.0，发布包名也带 .0
+            version: if version.matches('.').count() == 1 && compare_versions(&version, "1.21") != std::cmp::Ordering::Less {
+                format!("{}.0", version)
+            } else {
+                version
+            },
+            file,
+        }))
+    }
+
+    /// 解析指定目录应使用的版本
+    ///
+    /// 优先使用该目录下的版本文件，其次使用全局当前版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `dir` - 要解析的目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回版本号及其来源，没有可用版本时返回None。
+    pub fn resolve_version(&self, version_type: VersionType, dir: &Path) -> Result<Option<(String, VersionSource)>> {
+        let mut trace = Vec::new();
+        let resolved = self.resolve_version_traced(version_type, dir, &mut trace);
+        for step in &trace {
+            Self::trace_resolution(step);
+        }
+        resolved
+    }
+
+    /// 解析指定目录应使用的版本，并记录每一步解析过程
+    ///
+    /// 供 `ver which --explain` 和 VER_DEBUG_RESOLUTION 使用，帮助排查为什么解析到了某个版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `dir` - 要解析的目录
+    /// * `trace` - 追加解析步骤说明
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回版本号及其来源，没有可用版本时返回None。
+    pub fn resolve_version_traced(&self, version_type: VersionType, dir: &Path, trace: &mut Vec<String>) -> Result<Option<(String, VersionSource)>> {
+        let session = env::var(version_type.session_env_var()).ok().filter(|version| !version.is_empty());
+        if let Some(version) = &session {
+            trace.push(format!("session version from {}: {}", version_type.session_env_var(), version));
+        }
+        let resolved = match session.map(|version| (version, VersionSource::Session))
+            .or_else(|| self.compat_env_version(version_type, trace).map(|version| (version, VersionSource::Environment)))
+            .or_else(|| self.rust_override_traced(version_type, dir, trace).map(|version| (version, VersionSource::Override)))
+        {
+            Some(resolved) => Some(resolved),
+            None => match self.find_local_version(dir, version_type, trace)? {
+                Some((version, _)) => Some((version, VersionSource::Local)),
+                None => {
+                    let current_file = self.base_dir.join(format!(".current-{}", version_type));
+                    match Self::read_current_version(&self.base_dir, version_type) {
+                        Ok(version) => {
+                            trace.push(format!("global version from {}: {}", current_file.display(), version));
+                            Some((version, VersionSource::Global))
+                        }
+                        Err(_) => {
+                            trace.push(format!("no global version ({} does not exist)", current_file.display()));
+                            None
+                        }
+                    }
+                }
+            },
+        };
+        
+        // 版本文件中也可以写别名
+        let Some((mut version, source)) = resolved else {
+            return Ok(None);
+        };
+        if !self.get_version_dir(&version, version_type).exists()
+            && let Some(target) = self.get_alias(&version, version_type)?
+        {
+            trace.push(format!("alias '{}' -> {}", version, target));
+            version = target;
+        } else {
+            trace.push(format!("resolved {} {} ({})", version_type, version, source));
+        }
+        // `3.13.0t` 不能落到普通构建上，反之亦然
+        if let Err(err) = self.check_build_flavor(&version, version_type) {
+            trace.push(format!("rejected {} {}: {}", version_type, version, err));
+            return Err(err);
+        }
+        Ok(Some((version, source)))
+    }
+
+    /// 读取兼容环境变量中的版本
+    ///
+    /// 只在开启 `resolve.compat_env` 时生效，优先级高于版本文件，与 pyenv 等工具的行为一致。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `trace` - 追加解析步骤说明
+    ///
+    /// # 返回
+    ///
+    /// 环境变量设置了版本时返回版本号，否则返回None。
+    fn compat_env_version(&self, version_type: VersionType, trace: &mut Vec<String>) -> Option<String> {
+        if !self.config.compat_env() {
+            return None;
+        }
+        let var = version_type.compat_env_var();
+        let value = env::var(var).ok()?;
+        match version_type.compat_env_version(&value) {
+            Some(version) => {
+                trace.push(format!("version from {}={}: {}", var, value, version));
+                Some(version)
+            }
+            None => {
+                trace.push(format!("ignored {}={} (no specific version)", var, value));
+                None
+            }
+        }
+    }
+
+    /// 从目录向上查找项目版本文件
+    ///
+    /// 位于 `resolve.ignore_dirs` 中目录（默认 node_modules、target、.git）内部的目录会被跳过，
+    /// 最多向上查找 `resolve.max_depth` 层。忽略的目录只在其上层目录有版本文件时生效。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 起始目录
+    /// * `version_type` - 版本类型
+    /// * `trace` - 追加查找步骤说明
+    ///
+    /// # 返回
+    ///
+    /// 找到版本文件时返回其中的版本号和文件路径，否则返回None。
+    fn find_local_version(&self, dir: &Path, version_type: VersionType, trace: &mut Vec<String>) -> Result<Option<(String, PathBuf)>> {
+        let ignore_dirs = self.config.resolve_ignore_dirs();
+        let max_depth = self.config.resolve.max_depth;
+        
+        // 目录和版本文件的修改时间都没有变化时直接使用上次的结果
+        let cache_file = self.cache_dir.join("resolve.bin");
+        let mut cache = ResolveCache::load(&cache_file, &format!("{:?} {:?} {:?}", ignore_dirs, max_depth, self.config.version_files));
+        if let Some((found, checked)) = cache.lookup(version_type.id(), dir) {
+            match &found {
+                Some((version, file)) => trace.push(format!("found {}: {} (cached, {} timestamps unchanged)", file.display(), version, checked)),
+                None => trace.push(format!("no {} version file above {} (cached, {} timestamps unchanged)", version_type.id(), dir.display(), checked)),
+            }
+            return Ok(found);
+        }
+        
+        let found = self.find_local_version_uncached(dir, version_type, trace)?;
+        // 缓存只是优化，写入失败不影响解析结果
+        let mut checked: Vec<PathBuf> = dir.ancestors()
+            .take_while(|ancestor| found.as_ref().is_none_or(|(_, file)| !file.starts_with(ancestor) || file.parent() == Some(*ancestor)))
+            .map(Path::to_path_buf)
+            .collect();
+        // 多个工具共用的版本文件被原地修改时目录的修改时间不变，需要单独记录
+        let formats = version_file::formats(&self.config);
+        let files: Vec<PathBuf> = checked.iter()
+            .flat_map(|ancestor| formats.iter().map(|format| ancestor.join(format.file_name())))
+            .filter(|file| file.is_file())
+            .collect();
+        checked.extend(files);
+        cache.insert(version_type.id(), dir, found.clone(), &checked);
+        let _ = cache.save(&cache_file);
+        Ok(found)
+    }
+
+    /// 从目录向上逐层读取版本文件，不使用缓存
+    fn find_local_version_uncached(&self, dir: &Path, version_type: VersionType, trace: &mut Vec<String>) -> Result<Option<(String, PathBuf)>> {
+        let ignore_dirs = self.config.resolve_ignore_dirs();
+        let max_depth = self.config.resolve.max_depth;
+        
+        for (depth, ancestor) in dir.ancestors().enumerate() {
+            if max_depth.is_some_and(|max_depth| depth > max_depth) {
+                trace.push(format!("stopped at {} (resolve.max_depth = {})", ancestor.display(), depth - 1));
+                break;
+            }
+            // 忽略的目录名只在项目内部生效：它的上层目录本身有版本文件时才跳过，
+            // 项目所在的路径中碰巧有同名目录（例如 ~/target/app）不影响查找
+            let mut ignored = None;
+            for prefix in ancestor.ancestors() {
+                let Some(name) = prefix.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                if ignore_dirs.iter().any(|dir| dir == name)
+                    && let Some(project) = prefix.parent()
+                    && self.get_local_version(project, version_type)?.is_some()
+                {
+                    ignored = Some(name);
+                    break;
+                }
+            }
+            if let Some(ignored) = ignored {
+                trace.push(format!("skipped {} (inside ignored directory {})", ancestor.display(), ignored));
+                continue;
+            }
+            if let Some((version, version_file)) = self.get_local_version(ancestor, version_type)? {
+                trace.push(format!("found {}: {}", version_file.display(), version));
+                return Ok(Some((version, version_file)));
+            }
+            trace.push(format!("no {} version file in {}", version_type.id(), ancestor.display()));
+        }
+        
+        Ok(None)
+    }
+
+    /// 查找对目录生效的项目版本文件
+    ///
+    /// 子目录中的版本文件优先于上层目录，因此 monorepo 中的子包可以覆盖根目录的版本。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 起始目录
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 找到时返回版本号和版本文件路径，否则返回None。
+    pub fn local_version_file(&self, dir: &Path, version_type: VersionType) -> Result<Option<(String, PathBuf)>> {
+        self.find_local_version(dir, version_type, &mut Vec::new())
+    }
+
+    /// 根据项目版本文件推断目录使用的工具
+    ///
+    /// 取离目录最近的版本文件对应的工具；最近的一层中有多个工具的版本文件时无法推断。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 起始目录
+    ///
+    /// # 返回
+    ///
+    /// 能唯一确定时返回工具和版本文件路径，否则返回None。
+    pub fn infer_version_type(&self, dir: &Path) -> Result<Option<(VersionType, PathBuf)>> {
+        let mut found = Vec::new();
+        for version_type in VersionType::all() {
+            if let Some((_, file)) = self.local_version_file(dir, version_type)? {
+                found.push((version_type, file));
+            }
+        }
+        
+        let nearest = found.iter().map(|(_, file)| file.components().count()).max();
+        let mut nearest: Vec<_> = found.into_iter()
+            .filter(|(_, file)| Some(file.components().count()) == nearest)
+            .collect();
+        Ok(if nearest.len() == 1 { nearest.pop() } else { None })
+    }
+
+    /// 设置 VER_DEBUG_RESOLUTION 时把解析步骤输出到标准错误
+    pub fn trace_resolution(step: &str) {
+        if env::var("VER_DEBUG_RESOLUTION").is_ok_and(|value| !value.is_empty() && value != "0") {
+            output::eprintln(format_args!("[ver] {}", step));
+        }
+    }
+
+    /// 记录一次版本使用
+    ///
+    /// 写入失败不影响命令本身，因此忽略所有错误。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `version` - 版本号
+    /// * `dir` - 运行命令时所在的目录
+    fn record_usage(&self, version_type: VersionType, version: &str, dir: &Path) {
+        let record = UsageRecord {
+            time: chrono::Utc::now().to_rfc3339(),
+            tool: version_type.id().to_string(),
+            version: version.to_string(),
+            dir: dir.to_path_buf(),
+        };
+        let _ = self.append_usage_record(&record);
+    }
+
+    /// 追加使用记录，文件过大时先清理过期记录
+    fn append_usage_record(&self, record: &UsageRecord) -> Result<()> {
+        let path = self.base_dir.join(USAGE_LOG_FILE);
+        if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > USAGE_LOG_MAX_BYTES) {
+            let cutoff = chrono::Utc::now() - chrono::Duration::days(USAGE_LOG_RETENTION_DAYS);
+            let kept: String = self.usage_records(cutoff)?
+                .iter()
+                .filter_map(|record| serde_json::to_string(record).ok())
+                .map(|line| line + "\n")
+                .collect();
+            write_atomic(&path, kept)?;
+        }
+        
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+        Ok(())
+    }
+
+    /// 读取指定时间之后的使用记录
+    ///
+    /// # 参数
+    ///
+    /// * `since` - 起始时间
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回按时间顺序排列的记录，无法解析的行会被跳过。
+    pub fn usage_records(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<UsageRecord>> {
+        let path = self.base_dir.join(USAGE_LOG_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let content = fs::read_to_string(&path)?;
+        Ok(content.lines()
+            .filter_map(|line| serde_json::from_str::<UsageRecord>(line).ok())
+            .filter(|record| chrono::DateTime::parse_from_rfc3339(&record.time)
+                .is_ok_and(|time| time >= since))
+            .collect())
+    }
+
+    /// 使用指定版本执行命令
+    ///
+    /// 使用指定版本的环境执行命令。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `command` - 命令名称
+    /// * `args` - 命令参数
+    /// * `version_type` - 版本类型
+    /// * `options` - 执行选项，包括工作目录以及是否分配伪终端
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub async fn exec_with_version(&self, version: &str, command: &str, args: &[String], version_type: VersionType, options: ExecOptions<'_>) -> Result<()> {
+        let cwd = match options.cwd {
+            Some(cwd) => cwd.to_path_buf(),
+            None => env::current_dir()?,
+        };
+        let pins = if options.hermetic {
+            self.hermetic_pins(version, version_type, &cwd)?
+        } else {
+            Vec::new()
+        };
+        
+        // 检查版本是否已安装，如果没有则安装
+        let version_dir = self.get_version_dir(version, version_type);
+        if !version_dir.exists() {
+            self.report(&format!("Version {} is not installed. Installing...", version));
+            self.install_version(version, version_type).await?;
+            // `--quiet-install` 时每个安装的版本只输出一行，写入标准错误以免混入命令的输出
+            if self.progress_mode == ProgressMode::Quiet {
+                output::eprintln(format_args!("installed {} {} {}", version_type.id(), version, version_dir.display()));
+            }
+        }
+        self.check_build_flavor(version, version_type)?;
+
+        if self.is_locked(version, version_type) {
+            self.verify_locked_version(version, version_type)?;
+        }
+        
+        self.record_usage(version_type, version, &cwd);
+        
+        // 获取对应版本的二进制目录
+        let bin_path = self.get_bin_dir(version, version_type);
+        
+        // 将该目录添加到 PATH 环境变量
+        let path_var = env::var("PATH").unwrap_or_default();
+        let new_path = if options.hermetic {
+            // 项目固定的其他工具排在系统路径之前，ver 的全局 shim 目录被移除
+            let mut dirs = vec![bin_path.clone()];
+            dirs.extend(pins.iter().map(|(pin_type, pin)| self.get_bin_dir(pin, *pin_type)));
+            dirs.extend(env::split_paths(&path_var).filter(|dir| *dir != self.bin_dir));
+            let new_path = env::join_paths(dirs)?.to_string_lossy().to_string();
+            if Path::new(command).components().count() == 1 && !bin_path.join(command).exists() {
+                let managed = pins.iter().any(|(pin_type, pin)| self.get_bin_dir(pin, *pin_type).join(command).exists());
+                if !managed && Self::find_in_path(command, &new_path).is_some_and(|found| self.is_managed_binary(command, &found)) {
+                    return Err(anyhow::anyhow!("hermetic 模式下拒绝运行 {}：项目没有固定提供它的工具版本", command));
+                }
+            }
+            new_path
+        } else {
+            format!("{}:{}", bin_path.to_string_lossy(), path_var)
+        };
+        
+        // 其他架构的版本通过 qemu-user 运行，命令本身作为 qemu 的第一个参数
+        let emulator = if options.emulate {
+            self.emulator_for(version, version_type)?
+        } else {
+            None
+        };
+        let (command, args) = match &emulator {
+            Some((qemu, arch)) => {
+                let program = Self::find_in_path(command, &new_path)
+                    .unwrap_or_else(|| PathBuf::from(command));
+                output::eprintln(format_args!("[emulated] running {} {} ({}) through {}", version_type, version, arch.name(), qemu.display()));
+                let mut qemu_args = vec![program.to_string_lossy().to_string()];
+                qemu_args.extend(args.iter().cloned());
+                (qemu.to_string_lossy().to_string(), qemu_args)
+            }
+            None => (command.to_string(), args.to_vec()),
+        };
+        let (command, args) = (command.as_str(), args.as_slice());
+        
+        // 执行命令，需要时通过script(1)分配伪终端
+        let mut cmd = if options.tty {
+            Self::tty_command(command, args)?
+        } else {
+            let mut cmd = Command::new(command);
+            cmd.args(args);
+            cmd
+        };
+        if let Some((_, arch)) = &emulator
+            && env::var_os("QEMU_LD_PREFIX").is_none()
+            && let Some(prefix) = Self::qemu_sysroot(*arch)
+        {
+            cmd.env("QEMU_LD_PREFIX", prefix);
+        }
+        let (_, vars) = self.injected_env(version, version_type);
+        if let Some((_, node_prefix)) = vars.iter().find(|(key, _)| version_type == VersionType::Node && key == "npm_config_prefix")
+            && let Some((prefix, source)) = self.find_npm_prefix_override()
+        {
+            output::println(format_args!("Overriding npm prefix {} (from {}) with {} for this command", prefix, source, node_prefix));
+            cmd.env_remove("NPM_CONFIG_PREFIX");
+        }
+        cmd.env("PATH", new_path)
+            .envs(vars)
+            .envs(options.env.iter().cloned());
+        if let Some(cwd) = options.cwd {
+            cmd.current_dir(cwd);
+        }
+        let status = cmd.status().map_err(|e| {
+            if options.tty && e.kind() == std::io::ErrorKind::NotFound {
+                anyhow::anyhow!("需要 script 命令来分配伪终端，请先安装 util-linux 或 bsdutils")
+            } else if e.kind() == std::io::ErrorKind::NotFound
+                && bin_path.join(command).is_file()
+                && self.is_musl()
+            {
+                // 文件存在却报告找不到，通常是缺少 glibc 的动态链接器
+                VersionError::LibcMismatch {
+                    version: version.to_string(),
+                    version_type,
+                    binary: bin_path.join(command),
+                }.into()
+            } else {
+                e.into()
+            }
+        })?;
+            
+        if !status.success() {
+            return Err(anyhow::anyhow!("命令执行失败，退出码: {}", status));
+        }
+        
+        Ok(())
+    }
+
+    /// 查找运行其他架构版本所需的 qemu-user 模拟器
+    ///
+    /// 版本的架构取自安装记录，没有记录时取自版本目录名中的架构后缀（`ver install --arch`）。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 版本是其他架构时返回模拟器路径和版本的架构，与本机架构相同时返回None，
+    /// 不在 Linux 上或找不到模拟器时返回错误。
+    fn emulator_for(&self, version: &str, version_type: VersionType) -> Result<Option<(PathBuf, ArchType)>> {
+        let arch = self.read_install_receipt(version, version_type)?
+            .and_then(|receipt| receipt.arch)
+            .and_then(|arch| ArchType::from_name(&arch))
+            .or_else(|| version.rsplit_once('-').and_then(|(_, arch)| ArchType::from_name(arch)))
+            .unwrap_or(self.arch_type);
+        if arch == self.arch_type {
+            return Ok(None);
+        }
+        if self.os_type != OsType::Linux {
+            return Err(anyhow::anyhow!("只有 Linux 支持通过 qemu-user 运行其他架构的版本"));
+        }
+        
+        let name = match arch {
+            ArchType::X64 => "x86_64",
+            ArchType::Arm64 => "aarch64",
+            ArchType::Arm => "arm",
+            ArchType::X86 => "i386",
+        };
+        let path = env::var("PATH").unwrap_or_default();
+        [format!("qemu-{}", name), format!("qemu-{}-static", name)].iter()
+            .find_map(|qemu| Self::find_in_path(qemu, &path))
+            .map(|qemu| Some((qemu, arch)))
+            .ok_or_else(|| anyhow::anyhow!(
+                "{} {} 是 {} 版本，需要 qemu-{} 才能在本机运行，请安装 qemu-user（例如 apt install qemu-user）",
+                version_type, version, arch.name(), name
+            ))
+    }
+
+    /// 查找其他架构的系统库目录，供 qemu-user 加载动态链接器
+    ///
+    /// Debian 和 Ubuntu 的交叉编译包把库安装在 `/usr/<三元组>` 下。
+    fn qemu_sysroot(arch: ArchType) -> Option<PathBuf> {
+        let triple = match arch {
+            ArchType::X64 => "x86_64-linux-gnu",
+            ArchType::Arm64 => "aarch64-linux-gnu",
+            ArchType::Arm => "arm-linux-gnueabihf",
+            ArchType::X86 => "i686-linux-gnu",
+        };
+        let dir = Path::new("/usr").join(triple);
+        dir.join("lib").is_dir().then_some(dir)
+    }
+
+    /// 在容器中使用指定版本执行命令
+    ///
+    /// 通过 docker 或 podman（环境变量 VER_CONTAINER_ENGINE 可以指定）运行镜像，把工具链以只读方式挂载到
+    /// `/opt/ver/<工具>-<版本>` 并加入 PATH，当前目录挂载到 `/work` 作为工作目录。
+    /// 在 Linux 上直接挂载已安装的版本；在其他系统上下载同一版本的 Linux 预编译包，
+    /// 目前只有 Node.js 和 Go 提供。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `command` - 命令名称
+    /// * `args` - 命令参数
+    /// * `version_type` - 版本类型
+    /// * `image` - 容器镜像
+    /// * `options` - 执行选项，使用其中的工作目录、是否分配伪终端和额外的环境变量
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，找不到容器引擎或命令执行失败时返回错误。
+    pub async fn exec_in_container(&self, version: &str, command: &str, args: &[String], version_type: VersionType, image: &str, options: ExecOptions<'_>) -> Result<()> {
+        let engine = env::var("VER_CONTAINER_ENGINE").ok()
+            .filter(|engine| !engine.is_empty())
+            .or_else(|| {
+                let path = env::var("PATH").unwrap_or_default();
+                ["docker", "podman"].into_iter()
+                    .find(|engine| Self::find_in_path(&format!("{}{}", engine, self.get_exe_extension()), &path).is_some())
+                    .map(str::to_string)
+            })
+            .ok_or_else(|| anyhow::anyhow!("找不到容器引擎，请安装 docker 或 podman，或通过 VER_CONTAINER_ENGINE 指定"))?;
+        let cwd = match options.cwd {
+            Some(cwd) => cwd.to_path_buf(),
+            None => env::current_dir()?,
+        };
+        
+        let (toolchain_dir, bin_dir) = self.container_toolchain(version, version_type).await?;
+        let mount_point = format!("/opt/ver/{}-{}", version_type.id(), version);
+        let relative_bin = bin_dir.strip_prefix(&toolchain_dir).unwrap_or(Path::new("bin"));
+        let container_bin = format!("{}/{}", mount_point, relative_bin.to_string_lossy().replace('\\', "/"));
+        let platform = match self.arch_type {
+            ArchType::X64 => "linux/amd64",
+            ArchType::Arm64 => "linux/arm64",
+            ArchType::X86 => "linux/386",
+            ArchType::Arm => "linux/arm/v7",
+        };
+        
+        let mut cmd = Command::new(&engine);
+        cmd.args(["run", "--rm", "-i", "--platform", platform]);
+        if options.tty {
+            cmd.arg("-t");
+        }
+        cmd.arg("-v").arg(format!("{}:{}:ro", toolchain_dir.display(), mount_point))
+            .arg("-v").arg(format!("{}:/work", cwd.display()))
+            .args(["-w", "/work"])
+            .arg("-e").arg(format!("PATH={}:/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin", container_bin));
+        if version_type == VersionType::Rust {
+            cmd.arg("-e").arg(format!("RUSTUP_TOOLCHAIN={}", version));
+        }
+        // 工具的默认环境变量中可能包含主机上的路径，容器中只传递命令行指定的变量
+        for (key, value) in options.env {
+            cmd.arg("-e").arg(format!("{}={}", key, value));
+        }
+        cmd.arg(image).arg(command).args(args);
+        
+        self.record_usage(version_type, version, &cwd);
+        let status = cmd.status().with_context(|| format!("无法运行 {}", engine))?;
+        if !status.success() {
+            return Err(anyhow::anyhow!("命令执行失败，退出码: {}", status));
+        }
+        Ok(())
+    }
+
+    /// 准备挂载到 Linux 容器中的工具链
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回工具链目录和其中的可执行文件目录，失败时返回错误。
+    async fn container_toolchain(&self, version: &str, version_type: VersionType) -> Result<(PathBuf, PathBuf)> {
+        if self.os_type == OsType::Linux {
+            let version_dir = self.get_version_dir(version, version_type);
+            if !version_dir.exists() {
+                self.report(&format!("Version {} is not installed. Installing...", version));
+                self.install_version(version, version_type).await?;
+            }
+            return Ok((version_dir, self.get_bin_dir(version, version_type)));
+        }
+        
+        // 其他系统上安装的版本无法在 Linux 容器中运行，单独下载 Linux 预编译包
+        if !matches!(version_type, VersionType::Node | VersionType::Go) {
+            return Err(anyhow::anyhow!(
+                "在 {} 上只能为容器准备 Node.js 和 Go 的 Linux 版本，{} 没有可以直接解压使用的 Linux 预编译包",
+                self.os_type.name(), version_type
+            ));
+        }
+        let executable = match version_type {
+            VersionType::Node => "node",
+            _ => "go",
+        };
+        let dir = self.cache_dir.join("container")
+            .join(format!("{}-{}-linux-{}", version_type.id(), version, self.arch_type.name()));
+        if !dir.exists() {
+            let (url, suffix) = self.platform_download_url(version_type, version, OsType::Linux, self.arch_type)?;
+            self.report(&format!("Downloading {} v{} for {} to use in the container...", version_type, version, suffix));
+            let mut staging = InstallStaging::default();
+            let temp_file = self.cache_dir.join(format!("{}-{}-{}.tar.gz", version_type.id(), version, suffix));
+            staging.track(&temp_file);
+            let archive = self.fetch_archive(&url, version, version_type, &temp_file).await?;
+            
+            let temp_dir = dir.with_extension("tmp");
+            staging.track(&temp_dir);
+            let _ = fs::remove_dir_all(&temp_dir);
+            Self::extract_archive_blocking(&archive, &temp_dir).await?;
+            fs::rename(&temp_dir, &dir)?;
+        }
+        
+        // 压缩包中的顶层目录因工具而异（node-v20.1.0-linux-x64/bin、go/bin）
+        let bin_dir = fs::read_dir(&dir)?
+            .flatten()
+            .map(|entry| entry.path().join("bin"))
+            .find(|bin| bin.join(executable).is_file())
+            .ok_or_else(|| anyhow::anyhow!("在 {} 中找不到 {}", dir.display(), executable))?;
+        Ok((dir, bin_dir))
+    }
+
+    /// 检查 hermetic 模式的前提并返回项目固定的其他工具版本
+    ///
+    /// 要执行的版本必须与项目版本文件中固定的版本一致，项目固定的其他工具必须已经安装。
+    /// 全局默认版本和兼容环境变量都不参与解析。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 要执行的版本
+    /// * `version_type` - 要执行的版本类型
+    /// * `dir` - 项目目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回其他工具固定的版本，不满足项目的固定版本时返回错误。
+    fn hermetic_pins(&self, version: &str, version_type: VersionType, dir: &Path) -> Result<Vec<(VersionType, String)>> {
+        let mut pins = Vec::new();
+        for pin_type in VersionType::all() {
+            let Some((pin, file)) = self.find_local_version(dir, pin_type, &mut Vec::new())? else {
+                if pin_type == version_type {
+                    return Err(VersionError::NoVersionConfigured(version_type).into());
+                }
+                continue;
+            };
+            let pin = self.resolve_alias_for_pin(&pin, pin_type)?;
+            if pin_type == version_type {
+                if pin != self.resolve_alias_for_pin(version, version_type)? {
+                    return Err(anyhow::anyhow!("hermetic 模式下只能使用 {} 中固定的 {} 版本 {}，而不是 {}", file.display(), version_type, pin, version));
+                }
+                continue;
+            }
+            if !self.get_version_dir(&pin, pin_type).exists() {
+                return Err(VersionError::NotInstalled(pin, pin_type).into());
+            }
+            pins.push((pin_type, pin));
+        }
+        Ok(pins)
+    }
+
+    /// 版本目录不存在时按别名解析
+    fn resolve_alias_for_pin(&self, version: &str, version_type: VersionType) -> Result<String> {
+        if !self.get_version_dir(version, version_type).exists()
+            && let Some(target) = self.get_alias(version, version_type)?
+        {
+            return Ok(target);
+        }
+        Ok(version.to_string())
+    }
+
+    /// 在 PATH 中查找命令
+    fn find_in_path(command: &str, path: &str) -> Option<PathBuf> {
+        env::split_paths(path)
+            .map(|dir| dir.join(command))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// 判断命令是否是 ver 管理的工具提供的可执行文件，例如系统中的 node 或 python3
+    fn is_managed_binary(&self, command: &str, found: &Path) -> bool {
+        if found.starts_with(&self.versions_dir) {
+            return true;
+        }
+        VersionType::all().into_iter().any(|version_type| {
+            self.list_installed_versions(version_type).unwrap_or_default().iter()
+                .any(|version| self.get_bin_dir(version.trim_end_matches(" (current)"), version_type).join(command).exists())
+        })
+    }
+
+    /// 为找不到版本的错误查找相近的版本
+    ///
+    /// 本地版本不存在时在已安装版本中查找，下载返回404时在缓存的远程版本列表中查找。
+    /// 只在出错时调用，不访问网络，以免离线或镜像不可用时报错前再等待超时。
+    ///
+    /// # 参数
+    ///
+    /// * `err` - 错误
+    ///
+    /// # 返回
+    ///
+    /// 返回“你是不是想要”形式的建议，没有相近版本或没有缓存的版本列表时返回空列表。
+    pub fn did_you_mean(&self, err: &VersionError) -> Vec<String> {
+        let candidates: Vec<String> = match err {
+            VersionError::NotInstalled(_, version_type) | VersionError::NotFound(_, version_type) => {
+                self.list_installed_versions(*version_type)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|version| version.trim_end_matches(" (current)").to_string())
+                    .collect()
+            }
+            VersionError::DownloadFailed { version_type, status: Some(404), .. } => {
+                fs::read_to_string(self.completion_cache_file(*version_type))
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<Vec<String>>(&content).ok())
+                    .unwrap_or_default()
+            }
+            _ => return Vec::new(),
+        };
+        let Some(requested) = err.version() else {
+            return Vec::new();
+        };
+        
+        let similar = similar_versions(requested, candidates.iter().map(String::as_str));
+        if similar.is_empty() {
+            return Vec::new();
+        }
+        vec![format!("你是不是想要: {}", similar.join(", "))]
+    }
+
+    /// 判断当前系统是否使用 musl libc（例如 Alpine）
+    fn is_musl(&self) -> bool {
+        self.os_type == OsType::Linux
+            && fs::read_dir("/lib")
+                .map(|entries| entries.flatten().any(|entry| entry.file_name().to_string_lossy().starts_with("ld-musl-")))
+                .unwrap_or(false)
+    }
+
+    /// 构建在伪终端中运行的命令
+    ///
+    /// 通过script(1)为子进程分配伪终端，使交互式REPL在ver被其他工具包装时
+    /// 依然能获得正常的信号处理和作业控制。
+    ///
+    /// # 参数
+    ///
+    /// * `command` - 命令名称
+    /// * `args` - 命令参数
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回待执行的命令，当前平台不支持时返回错误。
+    fn tty_command(command: &str, args: &[String]) -> Result<Command> {
+        if cfg!(windows) {
+            return Err(anyhow::anyhow!("Windows 不支持 --tty 选项"));
+        }
+        
+        let mut cmd = Command::new("script");
+        if cfg!(target_os = "macos") {
+            // BSD script: script [-q] file command ...
+            cmd.arg("-q").arg("/dev/null").arg(command).args(args);
+        } else {
+            // util-linux script只接受单个命令字符串，需要进行shell转义
+            let command_line = std::iter::once(command)
+                .chain(args.iter().map(|a| a.as_str()))
+                .map(|a| format!("'{}'", a.replace('\'', "'\\''")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            cmd.args(["-q", "-e", "-c", &command_line, "/dev/null"]);
+        }
+        
+        Ok(cmd)
+    }
+
+    /// 清理缓存和临时文件
+    ///
+    /// 清理下载缓存和临时文件。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    #[allow(clippy::collapsible_if)]
+    pub fn clean(&self) -> Result<()> {
+        // 清理下载缓存
+        if self.cache_dir.exists() {
+            fs::remove_dir_all(&self.cache_dir)?;
+            fs::create_dir(&self.cache_dir)?;
+        }
+        
+        // 查找并删除临时文件
+        for entry in fs::read_dir(&self.base_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if let Some(name) = path.file_name() {
+                if name.to_string_lossy().starts_with("temp-") {
+                    if path.is_file() {
+                        fs::remove_file(path)?;
+                    } else if path.is_dir() {
+                        fs::remove_dir_all(path)?;
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// 列出缓存条目
+    ///
+    /// 列出下载缓存目录中的压缩包和版本索引。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回按名称排序的缓存条目列表，失败时返回错误。
+    pub fn list_cache(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        if !self.cache_dir.exists() {
+            return Ok(entries);
+        }
+        
+        for entry in fs::read_dir(&self.cache_dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let size = if metadata.is_dir() {
+                Self::dir_size(&entry.path())?
+            } else {
+                metadata.len()
+            };
+            
+            let (kind, version) = if let Some(tool) = name.strip_prefix("completion-").and_then(|n| n.strip_suffix(".json")) {
+                ("index", Some(tool.to_string()))
+            } else {
+                // 安装包按 `<工具>-<版本>-<平台>-<地址摘要>@<sha256>` 命名
+                let (stem, extension) = split_archive_name(&name);
+                let stem = stem.split('@').next().unwrap_or(stem);
+                let version = (!extension.is_empty()).then(|| {
+                    VersionType::all().iter()
+                        .find_map(|tool| stem.strip_prefix(tool.id()).and_then(|rest| rest.strip_prefix('-')))
+                        .and_then(|rest| rest.split('-').next())
+                        .unwrap_or(stem)
+                        .trim_start_matches('v')
+                        .to_string()
+                });
+                ("archive", version)
+            };
+            
+            entries.push(CacheEntry {
+                name,
+                kind,
+                version,
+                size,
+                modified: metadata.modified().ok(),
+            });
+        }
+        
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    /// 删除指定的缓存条目
+    ///
+    /// # 参数
+    ///
+    /// * `targets` - 要删除的文件名或版本号
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回被删除的条目，失败时返回错误。
+    pub fn remove_cache_entries(&self, targets: &[String]) -> Result<Vec<CacheEntry>> {
+        let mut removed = Vec::new();
+        for entry in self.list_cache()? {
+            let matches = targets.iter().any(|target| {
+                *target == entry.name || entry.version.as_deref() == Some(target.trim_start_matches('v'))
+            });
+            if !matches {
+                continue;
+            }
+            
+            let path = self.cache_dir.join(&entry.name);
+            if path.is_dir() {
+                fs::remove_dir_all(&path)?;
+            } else {
+                fs::remove_file(&path)?;
+            }
+            removed.push(entry);
+        }
+        Ok(removed)
+    }
+
+    /// 计算目录占用的空间
+    fn dir_size(path: &Path) -> Result<u64> {
+        let mut size = 0;
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                size += Self::dir_size(&entry.path())?;
+            } else {
+                size += metadata.len();
+            }
+        }
+        Ok(size)
+    }
+
+    /// 获取 ver 的发布元数据
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号，为None时取最新版本
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回发布元数据，失败时返回错误。
+    pub async fn release_manifest(&self, version: Option<&str>) -> Result<ReleaseManifest> {
+        let base = match &self.endpoints.release_endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None if self.endpoints.is_hermetic() => return Err(anyhow::anyhow!("没有设置发布元数据的地址（--endpoint release=<url>）")),
+            None => self.config.release_url(),
+        };
+        let url = release::manifest_url(&base, version);
+        let response = reqwest::get(&url).await
+            .with_context(|| format!("无法获取发布信息 {}", url))?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("无法获取发布信息 {}: HTTP {}", url, response.status()));
+        }
+        response.json().await.with_context(|| format!("无法解析发布信息 {}", url))
+    }
+
+    /// 自身更新
+    ///
+    /// 从最新发布的元数据中找到当前平台的二进制文件，校验SHA-256后替换当前可执行文件。
+    ///
+    /// # 返回
+    ///
+    /// 已经是最新版本时返回None，更新成功时返回新版本号，失败时返回错误。
+    pub async fn self_update(&self) -> Result<Option<String>> {
+        use sha2::{Digest, Sha256};
+        
+        let exe = fs::canonicalize(env::current_exe()?)?;
+        if exe.components().any(|component| component.as_os_str() == "Cellar") {
+            return Err(anyhow::anyhow!("ver 是通过 Homebrew 安装的，请运行 `brew upgrade ver` 更新"));
+        }
+        
+        let manifest = self.release_manifest(None).await?;
+        let current = env!("CARGO_PKG_VERSION");
+        if compare_versions(&manifest.version, current) != std::cmp::Ordering::Greater {
+            return Ok(None);
+        }
+        let target = release::current_target()
+            .ok_or_else(|| anyhow::anyhow!("没有为当前平台发布的 ver 二进制文件"))?;
+        let asset = manifest.asset_for(target)
+            .ok_or_else(|| anyhow::anyhow!("ver {} 没有 {} 平台的二进制文件", manifest.version, target))?;
+        
+        self.report(&format!("Downloading ver {} for {}...", manifest.version, target));
+        let response = reqwest::get(&asset.url).await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("无法下载 {}: HTTP {}", asset.url, response.status()));
+        }
+        let bytes = response.bytes().await?;
+        let actual = format!("{:x}", Sha256::digest(&bytes));
+        if !actual.eq_ignore_ascii_case(&asset.sha256) {
+            return Err(anyhow::anyhow!("{} 的SHA-256不匹配：应为 {}，实际为 {}", asset.url, asset.sha256, actual));
+        }
+        
+        // 新文件写在同一目录中再重命名，替换过程中断不会留下损坏的 ver
+        let temp = exe.with_file_name(format!(".ver.{}.tmp", std::process::id()));
+        fs::write(&temp, &bytes)?;
+        #[cfg(unix)]
+        fs::set_permissions(&temp, fs::Permissions::from_mode(0o755))?;
+        // Windows 不能覆盖正在运行的可执行文件，但可以把它改名
+        if cfg!(windows) {
+            let old = exe.with_extension("old.exe");
+            let _ = fs::remove_file(&old);
+            fs::rename(&exe, &old)?;
+        }
+        if let Err(err) = fs::rename(&temp, &exe) {
+            let _ = fs::remove_file(&temp);
+            return Err(err).with_context(|| format!("无法替换 {}", exe.display()));
+        }
+        // 新版本的子命令和选项可能有变化，由新的 ver 重新生成 `ver completions --install` 安装的补全脚本
+        let _ = Command::new(&exe)
+            .args(["completions", "--update"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status();
+        Ok(Some(manifest.version))
+    }
+
+    /// 从其他版本管理器迁移
+    ///
+    /// 从其他版本管理器迁移已安装的版本。
+    ///
+    /// # 参数
+    ///
+    /// * `source` - 来源版本管理器名称
+    /// * `version_type` - 版本类型
+    /// * `options` - 迁移选项
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回迁移的版本数量，失败时返回错误。
+    #[allow(clippy::manual_strip)]
+    pub async fn migrate_from(&mut self, source: &str, version_type: VersionType, options: MigrateOptions<'_>) -> Result<usize> {
+        self.ensure_versions_writable()?;
+        let mut migrated_count = 0;
+        
+        match (source.to_lowercase().as_str(), version_type) {
+            ("nvm", VersionType::Node) => {
+                // 尝试找到 NVM 安装目录
+                let nvm_dir = if let Ok(dir) = env::var("NVM_DIR") {
+                    PathBuf::from_str(&dir)?
+                } else {
+                    home_dir(Some("NVM_DIR"))?.join(".nvm")
+                };
+                
+                let versions_dir = nvm_dir.join("versions").join("node");
+                
+                if !versions_dir.exists() {
+                    return Err(anyhow::anyhow!("找不到 NVM 版本目录"));
+                }
+                
+                for entry in fs::read_dir(versions_dir)? {
+                    let entry = entry?;
+                    if entry.file_type()?.is_dir() {
+                        let version = entry.file_name().to_string_lossy().to_string();
+                        // 跳过 "v" 前缀
+                        let version = if version.starts_with('v') {
+                            &version[1..]
+                        } else {
+                            &version
+                        };
+                        
+                        if self.migrate_version("nvm", version, version_type, &entry.path(), options)? {
+                            migrated_count += 1;
+                        }
+                    }
+                }
+                
+                // nvm 的别名保存在 alias 目录中，每个文件一个别名，default 是默认版本
+                let mut aliases = Vec::new();
+                let mut default = None;
+                if let Ok(entries) = fs::read_dir(nvm_dir.join("alias")) {
+                    for entry in entries.flatten() {
+                        if !entry.path().is_file() {
+                            continue;
+                        }
+                        let name = entry.file_name().to_string_lossy().to_string();
+                        let target = fs::read_to_string(entry.path())?.trim().to_string();
+                        if name == "default" {
+                            default = Some(target);
+                        } else {
+                            aliases.push((name, target));
+                        }
+                    }
+                }
+                aliases.sort();
+                self.import_migrated_aliases("nvm", version_type, &aliases, default.as_deref(), options)?;
+            },
+            ("n", VersionType::Node) => {
+                // 尝试找到 N 安装目录
+                let n_prefix = env::var("N_PREFIX").unwrap_or_else(|_| "/usr/local".to_string());
+                let n_versions_dir = PathBuf::from_str(&n_prefix)?.join("n").join("versions").join("node");
+                
+                if !n_versions_dir.exists() {
+                    return Err(anyhow::anyhow!("找不到 N 版本目录"));
+                }
+                
+                for entry in fs::read_dir(n_versions_dir)? {
+                    let entry = entry?;
+                    if entry.file_type()?.is_dir() {
+                        let version = entry.file_name().to_string_lossy().to_string();
+                        
+                        if self.migrate_version("n", &version, version_type, &entry.path(), options)? {
+                            migrated_count += 1;
+                        }
+                    }
+                }
+            },
+            ("rustup", VersionType::Rust) => {
+                // 尝试找到 rustup 安装目录
+                let rustup_home = if let Ok(dir) = env::var("RUSTUP_HOME") {
+                    PathBuf::from_str(&dir)?
+                } else {
+                    home_dir(Some("RUSTUP_HOME"))?.join(".rustup")
+                };
+                
+                let toolchains_dir = rustup_home.join("toolchains");
+                
+                if !toolchains_dir.exists() {
+                    return Err(anyhow::anyhow!("找不到 rustup 工具链目录"));
+                }
+                
+                for entry in fs::read_dir(toolchains_dir)? {
+                    let entry = entry?;
+                    if entry.file_type()?.is_dir() {
+                        let toolchain = entry.file_name().to_string_lossy().to_string();
+                        if toolchain.contains("stable") {
+                            // 提取版本号
+                            let version = if let Some(idx) = toolchain.find('-') {
+                                toolchain[..idx].to_string()
+                            } else {
+                                toolchain.to_string()
+                            };
+                            
+                            if self.migrate_version("rustup", &version, version_type, &entry.path(), options)? {
+                                migrated_count += 1;
+                            }
+                        }
+                    }
+                }
+                
+                // rustup 的默认工具链记录在 settings.toml 中，例如 default_toolchain = "stable-x86_64-unknown-linux-gnu"
+                let default = fs::read_to_string(rustup_home.join("settings.toml"))
+                    .ok()
+                    .and_then(|settings| settings.lines()
+                        .find_map(|line| line.trim().strip_prefix("default_toolchain")
+                            .and_then(|value| value.trim().strip_prefix('='))
+                            .map(|value| value.trim().trim_matches('"').to_string())))
+                    .map(|toolchain| toolchain.split('-').next().unwrap_or(&toolchain).to_string());
+                self.import_migrated_aliases("rustup", version_type, &[], default.as_deref(), options)?;
+            },
+            _ => return Err(anyhow::anyhow!("不支持的源版本管理器: {} for {}", source, version_type)),
+        }
+        
+        Ok(migrated_count)
+    }
+
+    /// 迁移单个版本
+    ///
+    /// 复制整个版本目录并写入安装记录。已安装或不在 `--only` 列表中的版本会被跳过，
+    /// `--dry-run` 时只打印将要迁移的版本和大小。
+    ///
+    /// # 参数
+    ///
+    /// * `manager` - 来源版本管理器（nvm、n、rustup、pyenv、gvm）
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    /// * `source_dir` - 来源版本目录
+    /// * `options` - 迁移选项
+    ///
+    /// # 返回
+    ///
+    /// 迁移（或 `--dry-run` 时将要迁移）该版本时返回true，跳过时返回false，失败时返回错误。
+    fn migrate_version(&self, manager: &str, version: &str, version_type: VersionType, source_dir: &Path, options: MigrateOptions<'_>) -> Result<bool> {
+        let target_dir = self.get_version_dir(version, version_type);
+        if !options.includes(version) {
+            return Ok(false);
+        }
+        if target_dir.exists() {
+            if options.dry_run {
+                output::println(format_args!("Skip {} version {} from {} (already installed)", version_type, version, manager));
+            }
+            return Ok(false);
+        }
+        
+        if options.dry_run {
+            let size = Self::dir_size(source_dir)?;
+            output::println(format_args!("Would migrate {} version {} from {} ({}, {})",
+                version_type, version, manager, source_dir.display(), indicatif::HumanBytes(size)));
+            return Ok(true);
+        }
+        
+        output::println(format_args!("Migrating {} version {} from {}...", version_type, version, manager));
+        let cloned = self.copy_dir_recursively(source_dir, &target_dir, options.exclude)?;
+        if cloned > 0 {
+            output::println(format_args!("Cloned {} files copy-on-write", cloned));
+        }
+        self.write_migration_receipt(&target_dir, version, version_type, manager, source_dir)?;
+        Ok(true)
+    }
+
+    /// 导入其他版本管理器的别名和默认版本
+    ///
+    /// 别名只在 ver 中还没有同名别名、且目标版本已安装时创建；默认版本只在 ver 还没有
+    /// 全局版本时设置，因此不会覆盖已有的配置。
+    ///
+    /// # 参数
+    ///
+    /// * `manager` - 来源版本管理器
+    /// * `version_type` - 版本类型
+    /// * `aliases` - 别名名称和来源版本管理器中的目标（版本号、版本前缀或其他别名）
+    /// * `default` - 来源版本管理器的默认版本
+    /// * `options` - 迁移选项
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn import_migrated_aliases(&mut self, manager: &str, version_type: VersionType, aliases: &[(String, String)], default: Option<&str>, options: MigrateOptions<'_>) -> Result<()> {
+        for (name, target) in aliases {
+            if self.get_alias(name, version_type)?.is_some() {
+                continue;
+            }
+            match self.resolve_migrated_version(target, version_type, aliases)? {
+                Some(version) if options.dry_run => output::println(format_args!("Would import alias '{}' -> {} from {}", name, version, manager)),
+                Some(version) => {
+                    self.create_alias(name, &version, version_type)?;
+                    output::println(format_args!("Imported alias '{}' -> {} from {}", name, version, manager));
+                }
+                None if options.dry_run => output::println(format_args!("Would import alias '{}' -> {} from {} if the version is migrated", name, target, manager)),
+                None => output::println(format_args!("Skipped alias '{}' -> {} from {} (does not match an installed version)", name, target, manager)),
+            }
+        }
+        
+        let Some(default) = default else {
+            return Ok(());
+        };
+        if Self::read_current_version(&self.base_dir, version_type).is_ok() {
+            return Ok(());
+        }
+        match self.resolve_migrated_version(default, version_type, aliases)? {
+            Some(version) if options.dry_run => output::println(format_args!("Would use {} version {} ({} default)", version_type, version, manager)),
+            Some(version) => self.use_version(&version, version_type)?,
+            None if options.dry_run => output::println(format_args!("Would use {} {} ({} default) if the version is migrated", version_type, default, manager)),
+            None => output::println(format_args!("Skipped {} default {} (does not match an installed version)", manager, default)),
+        }
+        Ok(())
+    }
+
+    /// 将其他版本管理器中的版本说明解析为已安装的版本
+    ///
+    /// 支持完整版本号、`18` 这样的版本前缀、nvm 的 `node`/`stable`（最新版本）以及指向其他别名的别名。
+    /// `lts/*` 等需要远程信息的写法无法解析。
+    fn resolve_migrated_version(&self, spec: &str, version_type: VersionType, aliases: &[(String, String)]) -> Result<Option<String>> {
+        let installed: Vec<String> = self.list_installed_versions(version_type)?
+            .iter()
+            .map(|version| version.trim_end_matches(" (current)").to_string())
+            .collect();
+        let latest = |prefix: Option<&str>| installed.iter()
+            .filter(|version| prefix.is_none_or(|prefix| version.starts_with(&format!("{}.", prefix))))
+            .max_by(|a, b| compare_versions(a, b))
+            .cloned();
+        
+        let mut spec = spec.to_string();
+        // 别名可以指向别名，限制深度避免循环
+        for _ in 0..8 {
+            let version = spec.trim_start_matches('v');
+            if installed.iter().any(|installed| installed == version) {
+                return Ok(Some(version.to_string()));
+            }
+            if matches!(version, "node" | "stable") && version_type == VersionType::Node {
+                return Ok(latest(None));
+            }
+            if !version.is_empty() && version.split('.').all(|part| part.parse::<u64>().is_ok()) {
+                return Ok(latest(Some(version)));
+            }
+            match aliases.iter().find(|(name, _)| *name == spec) {
+                Some((_, target)) => spec = target.clone(),
+                None => return Ok(None),
+            }
+        }
+        Ok(None)
+    }
+
+    /// 递归复制目录
+    ///
+    /// 先扫描源目录，创建目录和符号链接并统计文件大小，再用多个线程并行复制文件，
+    /// 同时显示字节和文件数进度。迁移的版本目录可能有几个GB，逐个复制很慢。
+    /// 文件系统支持时文件以写时复制的方式克隆。
+    ///
+    /// 先复制到目标旁边的临时目录，全部完成后再重命名，复制失败或中断时不会留下
+    /// 会被当作已安装版本的不完整目录。
+    ///
+    /// # 参数
+    ///
+    /// * `src` - 源目录
+    /// * `dst` - 目标目录
+    /// * `excludes` - 跳过的文件或目录的通配符
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回克隆的文件数，失败时返回错误。
+    fn copy_dir_recursively(&self, src: &Path, dst: &Path, excludes: &[String]) -> Result<usize> {
+        let name = dst.file_name().context("目标目录没有名称")?.to_string_lossy();
+        let temp = dst.with_file_name(format!(".{}.tmp-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&temp);
+        let mut staging = InstallStaging::default();
+        staging.track(&temp);
+        let cloned = self.copy_dir_contents(src, &temp, excludes)?;
+        fs::rename(&temp, dst)?;
+        staging.commit();
+        Ok(cloned)
+    }
+
+    /// 把目录内容并行复制到目标目录，供 `copy_dir_recursively` 使用
+    fn copy_dir_contents(&self, src: &Path, dst: &Path, excludes: &[String]) -> Result<usize> {
+        use std::sync::atomic::AtomicUsize;
+        
+        fs::create_dir_all(dst)?;
+        let mut files = Vec::new();
+        let mut pending = vec![PathBuf::new()];
+        while let Some(relative_dir) = pending.pop() {
+            for entry in fs::read_dir(long_path(&src.join(&relative_dir)))? {
+                let entry = entry?;
+                let relative = relative_dir.join(entry.file_name());
+                if Self::is_excluded(&relative, excludes) {
+                    continue;
+                }
+                
+                let file_type = entry.file_type()?;
+                let dst_path = dst.join(&relative);
+                if file_type.is_dir() {
+                    fs::create_dir_all(long_path(&dst_path))?;
+                    pending.push(relative);
+                } else if file_type.is_symlink() {
+                    let target = fs::read_link(entry.path())?;
+                    create_symlink(&target, &dst_path)?;
+                } else if file_type.is_file() {
+                    files.push((entry.path(), dst_path, entry.metadata()?.len()));
+                }
+            }
+        }
+        
+        let total_bytes = files.iter().map(|(_, _, size)| size).sum();
+        let pb = self.progress_bar(total_bytes, "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {msg}");
+        pb.set_message(format!("0/{} files", files.len()));
+        
+        let next = AtomicUsize::new(0);
+        let copied = AtomicUsize::new(0);
+        let cloned = AtomicUsize::new(0);
+        let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).clamp(1, 8);
+        std::thread::scope(|scope| -> Result<()> {
+            let handles: Vec<_> = (0..workers).map(|_| scope.spawn(|| -> Result<()> {
+                while let Some((from, to, size)) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    match clone_or_copy(&long_path(from), &long_path(to)) {
+                        Ok(true) => {
+                            cloned.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Ok(false) => {}
+                        Err(err) => {
+                            // 让其他线程尽快停止
+                            next.store(files.len(), Ordering::Relaxed);
+                            return Err(anyhow::anyhow!("无法复制 {}: {}", from.display(), err));
+                        }
+                    }
+                    pb.inc(*size);
+                    let done = copied.fetch_add(1, Ordering::Relaxed) + 1;
+                    pb.set_message(format!("{}/{} files", done, files.len()));
+                }
+                Ok(())
+            })).collect();
+            for handle in handles {
+                handle.join().map_err(|_| anyhow::anyhow!("复制线程异常退出"))??;
+            }
+            Ok(())
+        })?;
+        
+        pb.finish_and_clear();
+        Ok(cloned.into_inner())
+    }
+
+    /// 以新名称复制一个已安装的版本，例如用于测试补丁
+    ///
+    /// 文件系统支持时以写时复制的方式克隆文件，几乎不占用额外空间。复制后重新生成激活脚本，
+    /// 其中的路径指向新目录。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 已安装的版本
+    /// * `name` - 新的版本目录名
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回（克隆的文件数，文件总数），失败时返回错误。
+    pub fn clone_version(&self, version: &str, name: &str, version_type: VersionType) -> Result<(usize, usize)> {
+        self.ensure_versions_writable()?;
+        if !self.is_installed(version, version_type) {
+            return Err(VersionError::NotInstalled(version.to_string(), version_type).into());
+        }
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(anyhow::anyhow!("无效的版本名称: {}", name));
+        }
+        let target_dir = self.get_version_dir(name, version_type);
+        if target_dir.exists() {
+            return Err(anyhow::anyhow!("版本 {} 已存在", name));
+        }
+        
+        let source_dir = self.get_version_dir(version, version_type);
+        let mut staging = InstallStaging { paths: vec![target_dir.clone()], committed: false };
+        let cloned = self.copy_dir_recursively(&source_dir, &target_dir, &[])?;
+        self.write_activation_scripts(name, version_type)?;
+        staging.commit();
+        
+        Ok((cloned, Self::count_files(&target_dir)))
+    }
+
+    /// 从任意地址安装一个版本，用于不在任何已配置镜像中的一次性构建
+    ///
+    /// 必须提供安装包的SHA-256，下载后校验不通过时不解压。安装包应是预编译的发行包（tar.gz 或 zip），
+    /// 解压后只有一个顶层目录时把其中的内容移到版本目录，之后版本目录中应有 `bin/<可执行文件>`
+    /// （Node.js 也可以保留官方发行包的 `node-v*` 目录）。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号，用作版本目录名
+    /// * `version_type` - 版本类型
+    /// * `url` - 安装包地址
+    /// * `sha256` - 安装包的SHA-256（十六进制）
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回版本目录，下载、校验失败或安装包中没有可执行文件时返回错误。
+    pub async fn import_url(&self, version: &str, version_type: VersionType, url: &str, sha256: &str) -> Result<PathBuf> {
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow::anyhow!("SHA-256 应为64位十六进制字符串: {}", sha256));
+        }
+        if version.is_empty() || version.starts_with('.') || version.contains(['/', '\\']) {
+            return Err(anyhow::anyhow!("无效的版本名称: {}", version));
+        }
+        let file_name = url.split(['?', '#']).next().unwrap_or(url).rsplit('/').next().unwrap_or_default();
+        let extension = if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            ".tar.gz"
+        } else if file_name.ends_with(".zip") {
+            ".zip"
+        } else {
+            return Err(anyhow::anyhow!("只支持 tar.gz 和 zip 格式的安装包: {}", url));
+        };
+        let version_dir = self.get_version_dir(version, version_type);
+        if version_dir.exists() {
+            return Err(anyhow::anyhow!("版本 {} 已存在，请先运行 `ver remove {} -t {}`", version, version, version_type.id()));
+        }
+        self.ensure_versions_writable()?;
+        self.check_policy(version, version_type)?;
+        
+        self.begin_operation(version_type, version);
+        let mut staging = InstallStaging::default();
+        let result = tokio::select! {
+            result = self.import_url_staged(version, version_type, url, sha256, extension, &mut staging) => result,
+            _ = tokio::signal::ctrl_c() => Err(VersionError::Cancelled.into()),
+        };
+        match &result {
+            Ok(()) => {
+                staging.commit();
+                self.emit_event("installed", version_type, version, serde_json::json!({ "dir": version_dir }));
+                self.run_install_hooks(version, version_type);
+            }
+            Err(err) => self.emit_event("failed", version_type, version, serde_json::json!({ "error": err.to_string() })),
+        }
+        self.finish_operation(&result);
+        result.map(|()| version_dir)
+    }
+
+    /// 下载、校验并解压 `import_url` 的安装包，创建的文件记录在 `staging` 中
+    async fn import_url_staged(&self, version: &str, version_type: VersionType, url: &str, sha256: &str, extension: &str, staging: &mut InstallStaging) -> Result<()> {
+        let version_dir = &self.get_version_dir(version, version_type);
+        let dest = self.cache_dir.join(format!("{}-{}-import{}", version_type.id(), version, extension));
+        let archive = match Self::find_cached_archive(&dest, url, Some(sha256)) {
+            Some(cached) => {
+                self.report(&format!("Found {} in the download cache, skipping download", cached.display()));
+                cached
+            }
+            None => {
+                self.report(&format!("Downloading {} {} from {}...", version_type, version, url));
+                staging.track(&dest);
+                let sha256 = self.download_archive(url, version, version_type, &dest, Some(sha256)).await?;
+                let archive = dest.with_file_name(Self::cached_archive_name(&dest.file_name().unwrap_or_default().to_string_lossy(), url, &sha256));
+                fs::rename(&dest, &archive)?;
+                archive
+            }
+        };
+        let archive_bytes = fs::metadata(&archive)?.len();
+        
+        staging.track(version_dir);
+        self.report("Extracting...");
+        Self::extract_archive_blocking(&archive, version_dir).await?;
+        
+        // 发行包通常把所有文件放在一个顶层目录中，Node.js 官方发行包的 node-v* 目录保持原样
+        let entries: Vec<PathBuf> = fs::read_dir(version_dir)?.flatten().map(|entry| entry.path()).collect();
+        if let [top] = entries.as_slice()
+            && top.is_dir()
+            && !(version_type == VersionType::Node && top.file_name().is_some_and(|name| name.to_string_lossy().starts_with("node-v")))
+        {
+            for entry in fs::read_dir(top)?.flatten() {
+                fs::rename(entry.path(), version_dir.join(entry.file_name()))?;
+            }
+            fs::remove_dir(top)?;
+        }
+        let executable = self.get_executable_path(version, version_type);
+        if !executable.exists() {
+            return Err(anyhow::anyhow!("安装包中找不到 {}，安装包应是包含 bin 目录的预编译发行包", executable.display()));
+        }
+        self.emit_event("extracted", version_type, version, serde_json::json!({ "dir": version_dir }));
+        
+        self.clear_quarantine(version_dir);
+        let receipt = InstallReceipt {
+            schema: state::SCHEMA_VERSION,
+            tool: version_type.id().to_string(),
+            version: version.to_string(),
+            source: url.to_string(),
+            arch: Some(self.arch_type.name().to_string()),
+            installed_at: chrono::Utc::now().to_rfc3339(),
+            skipped_modules: Vec::new(),
+            flavor: Some("url".to_string()),
+            files: Self::hash_tree(version_dir)?,
+            archive_bytes: Some(archive_bytes),
+            build: Some(BuildFlavor::split(version, version_type).1),
+        };
+        write_atomic(&version_dir.join(INSTALL_RECEIPT_FILE), serde_json::to_string_pretty(&receipt)?)?;
+        self.write_activation_scripts(version, version_type)?;
+        self.report(&format!("Successfully installed {} version {}", version_type, version));
+        Ok(())
+    }
+
+    /// 统计目录中的文件数（不含目录和符号链接）
+    fn count_files(path: &Path) -> usize {
+        fs::read_dir(path).map(|entries| entries.flatten()
+            .map(|entry| match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => Self::count_files(&entry.path()),
+                Ok(file_type) if file_type.is_file() => 1,
+                _ => 0,
+            })
+            .sum())
+            .unwrap_or(0)
+    }
+
+    /// 判断相对路径是否匹配排除规则
+    ///
+    /// 规则匹配整个相对路径（`/` 分隔）或其中任意一级名称，例如 `share/doc`、`*.md`、`__pycache__`。
+    fn is_excluded(relative: &Path, excludes: &[String]) -> bool {
+        if excludes.is_empty() {
+            return false;
+        }
+        let components: Vec<String> = relative.components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+        let path = components.join("/");
+        excludes.iter().any(|pattern| {
+            let pattern = pattern.trim_matches('/');
+            Self::wildcard_match(pattern, &path)
+                || components.iter().any(|component| Self::wildcard_match(pattern, component))
+        })
+    }
+
+    /// 简单的通配符匹配，`*` 匹配任意字符，`?` 匹配单个字符
+    fn wildcard_match(pattern: &str, text: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let text: Vec<char> = text.chars().collect();
+        let (mut p, mut t) = (0, 0);
+        let mut backtrack = None;
+        while t < text.len() {
+            if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+                p += 1;
+                t += 1;
+            } else if p < pattern.len() && pattern[p] == '*' {
+                backtrack = Some((p, t));
+                p += 1;
+            } else if let Some((star, matched)) = backtrack {
+                p = star + 1;
+                t = matched + 1;
+                backtrack = Some((star, matched + 1));
+            } else {
+                return false;
+            }
+        }
+        pattern[p..].iter().all(|c| *c == '*')
+    }
+
+    /// 列出可用的版本
+    ///
+    /// 列出可用的版本信息，并顺便刷新补全用的版本缓存。
+    ///
+    /// # 参数
+    ///
+    /// * `lts_only` - 是否只列出LTS版本
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回版本信息列表，失败时返回错误。
+    pub async fn list_available_versions(&self, lts_only: bool, version_type: VersionType) -> Result<Vec<RemoteRelease>> {
+        let versions = self.fetch_available_versions(lts_only, version_type).await?;
+        
+        // 补全缓存只是优化，写入失败不影响命令本身
+        if !lts_only {
+            let _ = self.write_completion_cache(version_type, &versions);
+        }
+        
+        Ok(versions)
+    }
+
+    /// 逐个输出远程版本，不等待整个索引下载完成
+    ///
+    /// Node.js 的 index.json 边下载边解析，按索引中的顺序（从新到旧）输出，回调返回false时停止下载，
+    /// 只看第一页时不必下载和排序整个索引。其他工具的索引是网页，仍然完整获取后按版本号排序输出。
+    ///
+    /// # 参数
+    ///
+    /// * `lts_only` - 是否只输出LTS或稳定版本
+    /// * `version_type` - 版本类型
+    /// * `on_release` - 每个版本调用一次，返回false时停止
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub async fn stream_available_versions(&self, lts_only: bool, version_type: VersionType, mut on_release: impl FnMut(RemoteRelease) -> bool) -> Result<()> {
+        if version_type != VersionType::Node {
+            for release in self.list_available_versions(lts_only, version_type).await? {
+                if !on_release(release) {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+        
+        let mirror = self.mirror_base(version_type);
+        let response = reqwest::Client::new()
+            .get(format!("{}/index.json", mirror))
+            .send()
+            .await?
+            .error_for_status()?;
+        let mut stream = response.bytes_stream();
+        let mut items = JsonArrayItems::default();
+        let mut newest = Vec::new();
+        let mut completed = true;
+        'stream: while let Some(chunk) = stream.next().await {
+            for item in items.push(&chunk?) {
+                let node_version: NodeVersion = serde_json::from_slice(&item)?;
+                let lts = node_version.lts;
+                let release = Self::node_release(node_version, &mirror);
+                if newest.len() < COMPLETION_CACHE_SIZE {
+                    newest.push(release.clone());
+                }
+                if (!lts_only || lts) && !on_release(release) {
+                    completed = false;
+                    break 'stream;
+                }
+            }
+        }
+        
+        // 补全缓存只是优化，写入失败不影响命令本身
+        if completed || newest.len() == COMPLETION_CACHE_SIZE {
+            let _ = self.write_completion_cache(version_type, &newest);
+        }
+        Ok(())
+    }
+
+    /// 把 Node.js 索引中的一项转换为远程版本
+    ///
+    /// # 参数
+    ///
+    /// * `node_version` - index.json 中的一项
+    /// * `mirror` - 下载源地址
+    fn node_release(node_version: NodeVersion, mirror: &str) -> RemoteRelease {
+        let version = node_version.version.trim_start_matches('v');
+        // index.json 只列出实际提供的平台，据此过滤而不是假设所有平台都有
+        let files = NODE_TARGETS.iter()
+            .filter(|(_, _, suffix)| node_version.files.iter().any(|f| f == &Self::node_index_file_name(suffix)))
+            .map(|(os, _, suffix)| ReleaseFile {
+                platform: suffix.to_string(),
+                url: Self::download_url(mirror, version, VersionType::Node, *os, suffix),
+                sha256: None,
+            })
+            .collect();
+        RemoteRelease {
+            version: node_version.version,
+            channel: if node_version.lts { ReleaseChannel::Lts } else { ReleaseChannel::Release },
+            date: Some(node_version.date),
+            security: node_version.security,
+            files,
+        }
+    }
+
+    /// 比较两个版本
+    ///
+    /// 从远程索引获取两个版本的发布日期、中间跳过的版本和安全更新，两个版本都已安装时还会统计占用空间。
+    /// 版本顺序无关，结果总是从旧版本到新版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `a` - 第一个版本
+    /// * `b` - 第二个版本
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回版本差异，任一版本不在远程索引中时返回错误。
+    pub async fn version_diff(&self, version_type: VersionType, a: &str, b: &str) -> Result<VersionDiff> {
+        let mut releases = self.list_available_versions(false, version_type).await?;
+        releases.iter_mut().for_each(|release| release.version = release.version.trim_start_matches('v').to_string());
+        releases.sort_by(|x, y| compare_versions(&x.version, &y.version));
+        
+        let find = |version: &str| {
+            let version = version.trim_start_matches('v');
+            releases.iter()
+                .position(|release| release.version == version)
+                .ok_or_else(|| VersionError::NotFound(version.to_string(), version_type))
+        };
+        let (mut from, mut to) = (find(a)?, find(b)?);
+        if from > to {
+            std::mem::swap(&mut from, &mut to);
+        }
+        
+        let between = &releases[from + 1..to];
+        // 只有 Node.js 的索引标记了安全更新，其他工具无法判断
+        let security_fixes = (version_type == VersionType::Node).then(|| {
+            releases[from + 1..=to].iter()
+                .filter(|release| release.security)
+                .map(|release| (release.version.clone(), version_type.release_notes_url(&release.version)))
+                .collect()
+        });
+        
+        let (from, to) = (&releases[from], &releases[to]);
+        let size = |version: &str| {
+            self.is_installed(version, version_type)
+                .then(|| Self::dir_size(&self.versions_dir.join(version)).ok())
+                .flatten()
+        };
+        let sizes = size(&from.version).zip(size(&to.version));
+        
+        Ok(VersionDiff {
+            tool: version_type.id(),
+            from: from.version.clone(),
+            to: to.version.clone(),
+            from_date: from.date.clone(),
+            to_date: to.date.clone(),
+            skipped: between.iter().map(|release| release.version.clone()).collect(),
+            security_fixes,
+            release_notes: version_type.release_notes_url(&to.version),
+            sizes,
+        })
+    }
+
+    /// 获取一个工具的补充来源信息（停止维护日期、GitHub Releases、安全公告）
+    ///
+    /// 结果缓存在下载缓存目录中，[`metadata::CACHE_TTL_SECS`] 内不再访问网络。
+    /// 配置 `metadata.enabled` 为 false 时返回空的信息。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回补充来源的信息，来源不可用时其中对应的部分为空。
+    pub async fn metadata_sources(&self, version_type: VersionType) -> metadata::Sources {
+        if !self.config.metadata_enabled() {
+            return metadata::Sources::default();
+        }
+        let cache_file = self.cache_dir.join(format!("metadata-{}.json", version_type.id()));
+        if let Ok(Some(cached)) = state::read_json::<metadata::Sources>(&cache_file)
+            && chrono::Utc::now().timestamp() - cached.fetched_at < metadata::CACHE_TTL_SECS
+        {
+            return cached;
+        }
+        
+        let sources = match &self.endpoints.metadata_endpoint {
+            Some(endpoint) => {
+                let endpoint = endpoint.trim_end_matches('/');
+                let feed = format!("{}/security.json", endpoint);
+                metadata::fetch(version_type, endpoint, endpoint, Some(&feed)).await
+            }
+            None if self.endpoints.is_hermetic() => return metadata::Sources::default(),
+            None => metadata::fetch(version_type, self.config.eol_url(), metadata::GITHUB_API, self.config.metadata.security_feed.as_deref()).await,
+        };
+        // 一个来源都没有获取到时多半是离线，不缓存，下次重试
+        if !sources.available.is_empty()
+            && let Ok(content) = serde_json::to_string_pretty(&sources)
+        {
+            let _ = write_atomic(&cache_file, content);
+        }
+        sources
+    }
+
+    /// 获取一个版本合并了所有来源的信息
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回版本信息，版本不在远程索引中时返回 NotFound 错误。
+    pub async fn version_metadata(&self, version: &str, version_type: VersionType) -> Result<metadata::VersionMetadata> {
+        let releases = self.list_available_versions(false, version_type).await?;
+        let release = releases.iter()
+            .find(|release| release.version.trim_start_matches('v') == version.trim_start_matches('v'))
+            .ok_or_else(|| VersionError::NotFound(version.to_string(), version_type))?;
+        let sources = self.metadata_sources(version_type).await;
+        Ok(metadata::merge(version_type, release, &sources, &chrono::Utc::now().format("%Y-%m-%d").to_string()))
+    }
+
+    /// 根据项目文件推荐每个工具的固定版本
+    ///
+    /// 线索见 [`detect::scan`]。线索本身是确切版本时直接采用；否则依次在已安装的版本、
+    /// LTS/稳定版本和所有版本中，从新到旧找第一个满足所有线索的版本。线索互相矛盾时只满足最可信的一条。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 项目根目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回有线索的工具的推荐，获取远程版本列表失败时返回错误。
+    pub async fn detect_pins(&self, dir: &Path) -> Result<Vec<PinProposal>> {
+        let evidence = detect::scan(dir);
+        let mut proposals = Vec::new();
+        for version_type in VersionType::all() {
+            let evidence: Vec<Evidence> = evidence.iter().filter(|evidence| evidence.tool == version_type).cloned().collect();
+            let Some(first) = evidence.first() else {
+                continue;
+            };
+            let satisfies_all = |version: &str| evidence.iter().all(|evidence| Policy::range_matches(&evidence.range, version).unwrap_or(true));
+            let satisfies_first = |version: &str| Policy::range_matches(&first.range, version).unwrap_or(false);
+
+            let exact = (first.range.split('.').count() == 3 && first.range.split('.').all(|part| part.parse::<u64>().is_ok()))
+                .then(|| first.range.clone());
+            let mut conflict = exact.as_deref().is_some_and(|version| !satisfies_all(version));
+            let mut installed: Vec<String> = self.list_installed_versions(version_type)?.iter()
+                .map(|version| version.trim_end_matches(" (current)").to_string())
+                .filter(|version| version.split('.').all(|part| part.parse::<u64>().is_ok()))
+                .collect();
+            installed.sort_by(|a, b| compare_versions(b, a));
+
+            let mut version = exact.or_else(|| installed.iter().find(|version| satisfies_all(version)).cloned());
+            if version.is_none() {
+                let mut remote = Vec::new();
+                for recommended_only in [true, false] {
+                    remote = self.list_available_versions(recommended_only, version_type).await?.into_iter()
+                        .map(|release| release.version.trim_start_matches('v').to_string())
+                        .filter(|version| version.split('.').all(|part| part.parse::<u64>().is_ok()))
+                        .collect();
+                    version = remote.iter().find(|version| satisfies_all(version)).cloned();
+                    if version.is_some() {
+                        break;
+                    }
+                }
+                if version.is_none() {
+                    version = installed.iter().chain(&remote).find(|version| satisfies_first(version)).cloned();
+                    conflict = version.is_some();
+                }
+            }
+
+            proposals.push(PinProposal {
+                tool: version_type,
+                installed: version.as_ref().is_some_and(|version| self.is_installed(version, version_type)),
+                version,
+                conflict,
+                evidence,
+            });
+        }
+        Ok(proposals)
+    }
+
+    /// 列出项目固定的工具版本
+    ///
+    /// 只包含项目版本文件（或兼容环境变量）指定的工具，全局默认版本不属于项目。别名解析为实际版本。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 项目目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回（版本类型，版本号）列表，版本不是确切的版本号时返回错误。
+    pub fn project_pins(&self, dir: &Path) -> Result<Vec<(VersionType, String)>> {
+        let mut pins = Vec::new();
+        for version_type in VersionType::all() {
+            let Some((version, source)) = self.resolve_version(version_type, dir)? else {
+                continue;
+            };
+            // 全局版本和目录覆盖只在本机生效，不属于项目
+            if matches!(source, VersionSource::Global | VersionSource::Override) {
+                continue;
+            }
+            let version = self.resolve_alias_for_pin(&version, version_type)?;
+            let version = version.trim_start_matches('v').to_string();
+            if !version.starts_with(|c: char| c.is_ascii_digit()) {
+                return Err(anyhow::anyhow!("{} 版本 {} 不是确切的版本号，无法生成固定的下载地址", version_type, version));
+            }
+            pins.push((version_type, version));
+        }
+        Ok(pins)
+    }
+
+    /// 列出项目需要的工具链在 Linux 上的下载地址和SHA-256
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 项目目录
+    /// * `arch` - 目标架构，为None时使用本机架构
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回每个工具链的下载文件，版本不是确切的版本号或平台不受支持时返回错误。
+    pub async fn freeze(&self, dir: &Path, arch: Option<&str>) -> Result<Vec<FrozenToolchain>> {
+        let arch_type = match arch {
+            Some(arch) => Self::parse_arch(arch)?,
+            None => self.arch_type,
+        };
+        
+        // 各工具的校验和同时获取
+        let pins = self.project_pins(dir)?;
+        let requests = pins.iter().map(|(version_type, version)| self.frozen_toolchain(*version_type, version, OsType::Linux, arch_type));
+        futures_util::future::join_all(requests).await.into_iter().collect()
+    }
+
+    /// 获取某个平台上预编译包的下载地址和SHA-256
+    ///
+    /// 校验和优先使用发布方提供的文件（Node.js 的 SHASUMS256.txt、Rust 和 Go 的 .sha256），
+    /// 没有时下载文件自行计算。
+    async fn frozen_toolchain(&self, version_type: VersionType, version: &str, os_type: OsType, arch_type: ArchType) -> Result<FrozenToolchain> {
+        let (url, suffix) = self.platform_download_url(version_type, version, os_type, arch_type)?;
+        let sha256 = match self.published_sha256(version_type, version, &url).await {
+            Some(hash) => hash,
+            None => self.download_sha256(&url).await?,
+        };
+        Ok(FrozenToolchain {
+            tool: version_type.id(),
+            version: version.to_string(),
+            platform: suffix.to_string(),
+            url,
+            sha256,
+        })
+    }
+
+    /// 获取某个平台上预编译包的下载地址
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回下载地址和平台后缀，工具不支持该平台时返回错误。
+    fn platform_download_url(&self, version_type: VersionType, version: &str, os_type: OsType, arch_type: ArchType) -> Result<(String, &'static str)> {
+        let targets = Self::platform_targets(version_type);
+        let Some((_, _, suffix)) = targets.iter().find(|(os, arch, _)| *os == os_type && *arch == arch_type) else {
+            return Err(VersionError::UnsupportedPlatform {
+                version_type,
+                platform: format!("{}-{}", os_type.name(), arch_type.name()),
+                supported: targets.iter()
+                    .map(|(os, arch, _)| format!("{}-{}", os.name(), arch.name()))
+                    .collect(),
+            }.into());
+        };
+        Ok((Self::download_url(&self.mirror_base(version_type), version, version_type, os_type, suffix), suffix))
+    }
+
+    /// 生成固定项目工具版本的 Nix flake
+    ///
+    /// Node.js 和 Go 使用官方预编译包（按 Nix 系统列出下载地址和SHA-256），Rust 使用 rust-overlay
+    /// 中的确切版本，Python 使用 nixpkgs 中同一小版本的包。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 项目目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回 flake.nix 的内容，项目没有固定任何工具时返回错误。
+    pub async fn export_nix(&self, dir: &Path) -> Result<String> {
+        let pins = self.project_pins(dir)?;
+        if pins.is_empty() {
+            return Err(anyhow::anyhow!("当前项目没有通过版本文件指定任何工具的版本"));
+        }
+        
+        // 所有工具和平台的校验和同时获取
+        let requests = pins.into_iter().map(|(version_type, version)| async move {
+            let mut sources = Vec::new();
+            if matches!(version_type, VersionType::Node | VersionType::Go) {
+                let toolchains = futures_util::future::join_all(NIX_SYSTEMS.iter().map(|(_, os_type, arch_type)| {
+                    self.frozen_toolchain(version_type, &version, *os_type, *arch_type)
+                })).await;
+                for ((system, _, _), toolchain) in NIX_SYSTEMS.iter().zip(toolchains) {
+                    // 旧版本可能没有某些平台的预编译包，在 flake 中对该系统报错即可
+                    if let Ok(toolchain) = toolchain {
+                        sources.push((*system, toolchain));
+                    }
+                }
+            }
+            nix::NixPin { version_type, version, sources }
+        });
+        let nix_pins = futures_util::future::join_all(requests).await;
+        let systems: Vec<&str> = NIX_SYSTEMS.iter().map(|(system, _, _)| *system).collect();
+        Ok(nix::flake(&nix_pins, &systems))
+    }
+
+    /// 生成供 Makefile 或 justfile 使用的工具链变量
+    ///
+    /// 对当前目录解析出版本的每个工具输出 `<TOOL>_VERSION`、`<TOOL>_BIN`（bin目录）和主要命令的绝对路径，
+    /// 例如 `NODE`、`NPM`、`CARGO`、`GO`，构建系统直接调用这些路径，不经过 ~/.version-manager/bin 中的链接。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 项目目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回变量名和值的列表；没有解析出任何版本或解析出的版本未安装时返回错误。
+    pub fn make_variables(&self, dir: &Path) -> Result<Vec<(String, String)>> {
+        let mut variables = Vec::new();
+        for version_type in VersionType::all() {
+            let Some((version, _)) = self.resolve_version(version_type, dir)? else {
+                continue;
+            };
+            let version = self.resolve_alias_for_pin(&version, version_type)?;
+            if !self.is_installed(&version, version_type) {
+                return Err(VersionError::NotInstalled(version, version_type).into());
+            }
+            
+            let prefix = version_type.id().to_uppercase();
+            let bin_dir = self.get_bin_dir(&version, version_type);
+            let commands: &[(&str, &str)] = match version_type {
+                VersionType::Node => &[("NODE", "node"), ("NPM", "npm"), ("NPX", "npx")],
+                VersionType::Rust => &[("CARGO", "cargo"), ("RUSTC", "rustc")],
+                VersionType::Python => &[("PYTHON", "python3"), ("PIP", "pip3")],
+                VersionType::Go => &[("GO", "go"), ("GOFMT", "gofmt")],
+            };
+            variables.push((format!("{}_VERSION", prefix), version.trim_start_matches('v').to_string()));
+            variables.push((format!("{}_BIN", prefix), bin_dir.to_string_lossy().to_string()));
+            for (name, command) in commands {
+                let path = bin_dir.join(command);
+                if path.exists() {
+                    variables.push((name.to_string(), path.to_string_lossy().to_string()));
+                }
+            }
+        }
+        if variables.is_empty() {
+            return Err(anyhow::anyhow!("当前目录没有为任何工具配置版本"));
+        }
+        Ok(variables)
+    }
+
+    /// 获取发布方提供的SHA-256，没有提供或获取失败时返回None
+    async fn published_sha256(&self, version_type: VersionType, version: &str, url: &str) -> Option<String> {
+        let checksum_url = match version_type {
+            VersionType::Node => format!("{}/v{}/SHASUMS256.txt", self.mirror_base(version_type), version),
+            VersionType::Rust | VersionType::Go => format!("{}.sha256", url),
+            VersionType::Python => return None,
+        };
+        let response = reqwest::get(&checksum_url).await.ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        let content = response.text().await.ok()?;
+        
+        // 每行是 `<hash>  <文件名>`，Go 的 .sha256 文件只有哈希值
+        let file_name = url.rsplit('/').next().unwrap_or_default();
+        content.lines().find_map(|line| {
+            let mut parts = line.split_whitespace();
+            let hash = parts.next()?;
+            let name = parts.next().map(|name| name.trim_start_matches('*'));
+            (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) && name.is_none_or(|name| name == file_name))
+                .then(|| hash.to_ascii_lowercase())
+        })
+    }
+
+    /// 下载文件并计算SHA-256，不保存文件
+    async fn download_sha256(&self, url: &str) -> Result<String> {
+        use sha2::{Digest, Sha256};
+        
+        output::eprintln(format_args!("Computing checksum of {}...", url));
+        let response = reqwest::get(url).await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("无法下载 {}: HTTP {}", url, response.status()));
+        }
+        let mut hasher = Sha256::new();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            hasher.update(&chunk?);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// 补全缓存文件路径
+    fn completion_cache_file(&self, version_type: VersionType) -> PathBuf {
+        self.cache_dir.join(format!("completion-{}.json", version_type.id()))
+    }
+
+    /// 写入补全用的远程版本缓存
+    ///
+    /// 只保存最新的 COMPLETION_CACHE_SIZE 个版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `versions` - 从远程获取的版本列表（从新到旧）
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn write_completion_cache(&self, version_type: VersionType, versions: &[RemoteRelease]) -> Result<()> {
+        let top: Vec<&str> = versions.iter()
+            .take(COMPLETION_CACHE_SIZE)
+            .map(|v| v.version.trim_start_matches('v'))
+            .collect();
+        fs::create_dir_all(&self.cache_dir)?;
+        write_atomic(&self.completion_cache_file(version_type), serde_json::to_string(&top)?)?;
+        Ok(())
+    }
+
+    /// 安装或删除版本后顺便刷新补全缓存
+    ///
+    /// 最多等待几秒，离线或下载源不可用时保留原来的缓存，不影响命令本身。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    pub async fn refresh_completion_cache(&self, version_type: VersionType) {
+        let _ = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            self.list_available_versions(false, version_type),
+        ).await;
+    }
+
+    /// 获取用于shell补全的版本列表
+    ///
+    /// 只读取本地数据（已安装版本、别名和补全缓存），不访问网络。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回去重后的版本列表，失败时返回错误。
+    pub fn completion_versions(&self, version_type: VersionType) -> Result<Vec<String>> {
+        let mut result: Vec<String> = Vec::new();
+        let mut push = |value: String| {
+            if !result.contains(&value) {
+                result.push(value);
+            }
+        };
+        
+        for version in self.list_installed_versions(version_type)? {
+            push(version.trim_end_matches(" (current)").to_string());
+        }
+        for (alias, _) in self.list_aliases(version_type)? {
+            push(alias);
+        }
+        
+        let cache_file = self.completion_cache_file(version_type);
+        if cache_file.exists() {
+            let content = fs::read_to_string(cache_file)?;
+            // 缓存损坏时忽略即可，下次list时会重新生成
+            let cached: Vec<String> = serde_json::from_str(&content).unwrap_or_default();
+            for version in cached {
+                push(version);
+            }
+        }
+        
+        Ok(result)
+    }
+
+    /// 从远程获取可用的版本
+    #[allow(clippy::collapsible_if, clippy::is_digit_ascii_radix)]
+    async fn fetch_available_versions(&self, lts_only: bool, version_type: VersionType) -> Result<Vec<RemoteRelease>> {
+        match version_type {
+            VersionType::Node => {
+                let client = reqwest::Client::new();
+                let mirror = self.mirror_base(version_type);
+                let response = client
+                    .get(format!("{}/index.json", mirror))
+                    .send()
+                    .await?
+                    .json::<Vec<NodeVersion>>()
+                    .await?;
+
+                let mut versions: Vec<RemoteRelease> = response.into_iter()
+                    .filter(|v| !lts_only || v.lts)
+                    .map(|v| Self::node_release(v, &mirror))
+                    .collect();
+                
+                // 按版本号排序（从新到旧）
+                versions.sort_by(|a, b| compare_versions(&b.version, &a.version)); // 从新到旧排序
+
+                Ok(versions)
+            },
+            VersionType::Rust => {
+                // 获取Rust版本列表
+                let client = reqwest::Client::new();
+                let response = client
+                    .get(format!("{}/channel-rust-stable.toml", self.mirror_base(version_type)))
+                    .send()
+                    .await?
+                    .text()
+                    .await?;
+                
+                let mut versions = Vec::new();
+                if let Some(release) = self.parse_rust_channel_manifest(&response) {
+                    versions.push(release);
+                }
+                
+                // 获取其他版本
+                if !lts_only {
+                    let response = client
+                        .get(format!("{}/", self.mirror_base(version_type)))
+                        .send()
+                        .await?
+                        .text()
+                        .await?;
+                    
+                    // 简单解析HTML获取版本号
+                    for line in response.lines() {
+                        if line.contains("rust-") && line.contains(".tar.gz") && !line.contains("beta") && !line.contains("nightly") {
+                            if let Some(start) = line.find("rust-") {
+                                if let Some(end) = line[start..].find(".tar.gz") {
+                                    let v = &line[start + 5..start + end];
+                                    if v.contains('-') {
+                                        continue; // 跳过带有平台信息的文件
+                                    }
+                                    
+                                    if !versions.iter().any(|existing: &RemoteRelease| existing.version == v) {
+                                        versions.push(RemoteRelease {
+                                            version: v.to_string(),
+                                            channel: ReleaseChannel::Release,
+                                            date: None,
+                                            security: false,
+                                            files: self.release_files(v, version_type),
+                                        });
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                
+                // 按版本号排序
+                versions.sort_by(|a, b| compare_versions(&b.version, &a.version)); // 从新到旧排序
+                
+                Ok(versions)
+            },
+            VersionType::Python => {
+                // 获取Python版本列表
+                let client = reqwest::Client::new();
+                let response = client
+                    .get(format!("{}/", self.mirror_base(version_type)))
+                    .send()
+                    .await?
+                    .text()
+                    .await?;
+                
+                // 简单解析HTML获取版本号
+                let mut versions = Vec::new();
+                for line in response.lines() {
+                    if line.contains("href=\"") && line.contains("/\"") {
+                        if let Some(start) = line.find("href=\"") {
+                            if let Some(end) = line[start + 6..].find("\"") {
+                                let version = &line[start + 6..start + 6 + end];
+                                if version.ends_with('/') && version.chars().any(|c| c.is_digit(10)) {
+                                    let version = version.trim_end_matches('/');
+                                    // 只列出稳定版本时跳过 alpha、beta、rc
+                                    if lts_only && ["alpha", "beta", "rc"].iter().any(|tag| version.contains(tag)) {
+                                        continue;
+                                    }
+                                    if !versions.iter().any(|existing: &RemoteRelease| existing.version == version) {
+                                        versions.push(RemoteRelease {
+                                            version: version.to_string(),
+                                            channel: ReleaseChannel::Release,
+                                            date: None,
+                                            security: false,
+                                            files: self.release_files(version, version_type),
+                                        });
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                
+                // 按版本号排序
+                versions.sort_by(|a, b| compare_versions(&b.version, &a.version)); // 从新到旧排序
+                
+                Ok(versions)
+            },
+            VersionType::Go => {
+                // 获取Go版本列表
+                let client = reqwest::Client::new();
+                let response = client
+                    .get(format!("{}/", self.mirror_base(version_type)))
+                    .send()
+                    .await?
+                    .text()
+                    .await?;
+                
+                // 简单解析HTML获取版本号
+                let mut versions = Vec::new();
+                for line in response.lines() {
+                    if line.contains("go") && line.contains("toggleVisible") {
+                        if let Some(start) = line.find("go") {
+                            if let Some(end) = line[start..].find(" ") {
+                                let version = &line[start + 2..start + end];
+                                if version.chars().any(|c| c.is_digit(10)) && !version.contains("beta") && !version.contains("rc") {
+                                    if !versions.iter().any(|existing: &RemoteRelease| existing.version == version) {
+                                        versions.push(RemoteRelease {
+                                            version: version.to_string(),
+                                            channel: ReleaseChannel::Release,
+                                            date: None,
+                                            security: false,
+                                            files: self.release_files(version, version_type),
+                                        });
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                
+                // 按版本号排序
+                versions.sort_by(|a, b| compare_versions(&b.version, &a.version)); // 从新到旧排序
+                
+                Ok(versions)
+            }
+        }
+    }
+
+    /// Node.js index.json 中 `files` 字段使用的平台名称
+    fn node_index_file_name(suffix: &str) -> String {
+        if let Some(arch) = suffix.strip_prefix("darwin-") {
+            format!("osx-{}-tar", arch)
+        } else if suffix.starts_with("win-") {
+            format!("{}-zip", suffix)
+        } else {
+            suffix.to_string()
+        }
+    }
+
+    /// 解析Rust稳定渠道清单
+    ///
+    /// 从 channel-rust-stable.toml 中读取版本号、发布日期以及各平台 `rust` 包的SHA-256。
+    ///
+    /// # 参数
+    ///
+    /// * `manifest` - 清单文件内容
+    ///
+    /// # 返回
+    ///
+    /// 解析到版本号时返回远程版本信息，否则返回None。
+    fn parse_rust_channel_manifest(&self, manifest: &str) -> Option<RemoteRelease> {
+        let RustManifest { date, version, packages } = RustManifest::parse(manifest);
+        
+        let version = version?;
+        let mut files = self.release_files(&version, VersionType::Rust);
+        for file in &mut files {
+            // 只有文件名一致时校验和才适用（Windows 下载的是zip，清单中是tar.gz）
+            if let Some((url, hash)) = packages.get(&("rust".to_string(), file.platform.clone()))
+                && url.rsplit('/').next() == file.url.rsplit('/').next()
+            {
+                file.sha256 = Some(hash.clone());
+            }
+        }
+        
+        Some(RemoteRelease {
+            version,
+            channel: ReleaseChannel::Stable,
+            date,
+            security: false,
+            files,
+        })
+    }
+
+    /// 并发获取多个工具的最新版本
+    ///
+    /// 同时请求各工具的远程索引，而不是依次请求。
+    ///
+    /// # 参数
+    ///
+    /// * `version_types` - 要查询的版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回每个版本类型对应的查询结果，单个工具失败不会影响其他工具。
+    pub async fn fetch_latest_versions(&self, version_types: &[VersionType]) -> Vec<(VersionType, Result<Option<String>>)> {
+        let requests = version_types.iter().map(|&version_type| async move {
+            let latest = self.list_available_versions(false, version_type).await
+                .map(|versions| versions.into_iter().next().map(|v| v.version.trim_start_matches('v').to_string()));
+            (version_type, latest)
+        });
+        futures_util::future::join_all(requests).await
+    }
+
+    /// 安装最新版本
+    ///
+    /// 安装最新版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub async fn install_latest(&mut self, version_type: VersionType) -> Result<()> {
+        let versions = self.list_available_versions(false, version_type).await?;
+        
+        if let Some(latest) = versions.first() {
+            output::println(format_args!("Latest {} version: {}", version_type, latest.version));
+            self.install_version(&latest.version, version_type).await?;
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("找不到最新的 {} 版本", version_type))
+        }
+    }
+
+    /// 安装最新的LTS版本
+    ///
+    /// 安装最新的LTS版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub async fn install_latest_lts(&mut self, version_type: VersionType) -> Result<()> {
+        let versions = self.list_available_versions(true, version_type).await?;
+        
+        if let Some(latest_lts) = versions.first() {
+            output::println(format_args!("Latest LTS {} version: {}", version_type, latest_lts.version));
+            self.install_version(&latest_lts.version, version_type).await?;
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("找不到最新的 LTS {} 版本", version_type))
+        }
+    }
+
+    /// 下载压缩包到指定文件
+    ///
+    /// 在写入文件之前检查HTTP状态码和Content-Type，并显示下载进度。
+    /// SHA-256在写入的同时计算，校验不需要再读一遍文件。
+    ///
+    /// # 参数
+    ///
+    /// * `url` - 下载地址
+    /// * `version` - 版本号（用于错误信息）
+    /// * `version_type` - 版本类型
+    /// * `dest` - 保存的文件路径
+    /// * `expected_sha256` - 发布方提供的SHA-256，为None时不校验
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回文件的SHA-256，下载失败或校验不通过时返回错误。
+    async fn download_archive(&self, url: &str, version: &str, version_type: VersionType, dest: &Path, expected_sha256: Option<&str>) -> Result<String> {
+        use sha2::{Digest, Sha256};
+        
+        // Create a progress bar for download
+        let client = reqwest::Client::new();
+        let response = client.get(url).send().await.map_err(|err| VersionError::DownloadFailed {
+            version: version.to_string(),
+            version_type,
+            url: url.to_string(),
+            status: None,
+            reason: err.to_string(),
+        })?;
+        
+        // 在写入任何文件之前检查响应，避免把错误页面当作压缩包保存
+        let status = response.status();
+        if !status.is_success() {
+            return Err(VersionError::DownloadFailed {
+                version: version.to_string(),
+                version_type,
+                url: url.to_string(),
+                status: Some(status.as_u16()),
+                reason: format!("HTTP {}", status),
+            }.into());
+        }
+        
+        let content_type = response.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+        if content_type.starts_with("text/html") {
+            return Err(VersionError::DownloadFailed {
+                version: version.to_string(),
+                version_type,
+                url: url.to_string(),
+                status: Some(status.as_u16()),
+                reason: format!("服务器返回了网页而不是压缩包 (Content-Type: {})", content_type),
+            }.into());
+        }
+        
+        // 部分镜像不返回 Content-Length，此时无法显示进度条，改为显示已下载的字节数
+        let total_size = response.content_length().unwrap_or(0);
+        let pb = if total_size > 0 {
+            self.progress_bar(total_size, "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+        } else {
+            self.progress_spinner()
+        };
+        self.emit_event("download-started", version_type, version, serde_json::json!({ "url": url, "total_bytes": total_size }));
+        
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::File::create(dest)?;
+        let mut hasher = Sha256::new();
+        let mut downloaded: u64 = 0;
+        let mut last_event = std::time::Instant::now();
+        let mut stream = response.bytes_stream();
+        
+        while let Some(item) = stream.next().await {
+            let chunk = item?;
+            file.write_all(&chunk)?;
+            hasher.update(&chunk);
+            downloaded += chunk.len() as u64;
+            pb.set_position(if total_size > 0 { std::cmp::min(downloaded, total_size) } else { downloaded });
+            if last_event.elapsed().as_millis() >= PROGRESS_EVENT_INTERVAL_MS {
+                last_event = std::time::Instant::now();
+                self.emit_event("progress", version_type, version, serde_json::json!({ "downloaded_bytes": downloaded, "total_bytes": total_size }));
+            }
+        }
+        self.emit_event("progress", version_type, version, serde_json::json!({ "downloaded_bytes": downloaded, "total_bytes": total_size }));
+        // 安装过程中下载的头文件等不算作单独的校验阶段
+        if self.current_phase() == Some(InstallPhase::Download) {
+            self.begin_phase(InstallPhase::Verify, "Verifying checksum...");
+        }
+        
+        // 连接中断时流可能提前结束，不能把截断的文件当作完整的压缩包
+        if total_size > 0 && downloaded != total_size {
+            return Err(VersionError::DownloadFailed {
+                version: version.to_string(),
+                version_type,
+                url: url.to_string(),
+                status: None,
+                reason: format!("下载不完整: 收到 {} 字节，应为 {} 字节", downloaded, total_size),
+            }.into());
+        }
+        let sha256 = format!("{:x}", hasher.finalize());
+        if let Some(expected) = expected_sha256
+            && !expected.eq_ignore_ascii_case(&sha256)
+        {
+            drop(file);
+            let _ = fs::remove_file(dest);
+            return Err(VersionError::DownloadFailed {
+                version: version.to_string(),
+                version_type,
+                url: url.to_string(),
+                status: None,
+                reason: format!("SHA-256不匹配: 应为 {}，实际为 {}", expected, sha256),
+            }.into());
+        }
+        self.emit_event("verified", version_type, version, serde_json::json!({ "url": url, "bytes": downloaded, "sha256": sha256 }));
+        
+        pb.finish_with_message(format!("Downloaded {} v{}", version_type, version));
+        if self.progress_mode == ProgressMode::Plain {
+            output::println(format_args!("Downloaded {} v{} ({})", version_type, version, format::size(downloaded)));
+        }
+        Ok(sha256)
+    }
+
+    /// 缓存中的安装包文件名
+    ///
+    /// 在下载时的文件名（包含工具、版本和平台）后加上下载地址的摘要和内容的SHA-256，
+    /// 例如 `node-20.1.0-linux-x64-<地址摘要>@<sha256>.tar.gz`。地址摘要区分不同的镜像和安装来源。
+    fn cached_archive_name(file_name: &str, url: &str, sha256: &str) -> String {
+        let (stem, extension) = split_archive_name(file_name);
+        format!("{}-{}@{}{}", stem, url_digest(url), sha256, extension)
+    }
+
+    /// 在缓存目录中查找安装包
+    ///
+    /// 知道SHA-256时查找内容相同的任意安装包，其他版本或镜像下载的相同文件也可以复用；
+    /// 否则只使用从同一地址下载的安装包，切换镜像或安装来源后不会用到其他来源的文件。
+    /// 找到的文件会重新计算SHA-256，与文件名中的不一致（下载被截断或文件损坏）时删除，之后重新下载。
+    ///
+    /// # 参数
+    ///
+    /// * `dest` - 下载时保存的文件路径，在它所在的目录中查找
+    /// * `url` - 下载地址
+    /// * `sha256` - 发布方提供的SHA-256
+    ///
+    /// # 返回
+    ///
+    /// 找到内容完好的文件时返回文件路径，否则返回None。
+    fn find_cached_archive(dest: &Path, url: &str, sha256: Option<&str>) -> Option<PathBuf> {
+        for path in Self::cached_archive_candidates(dest, url, sha256) {
+            if Self::cached_archive_intact(&path) {
+                return Some(path);
+            }
+            let _ = fs::remove_file(&path);
+        }
+        None
+    }
+
+    /// 在共享缓存目录中查找安装包
+    ///
+    /// 共享缓存可以被其他用户写入，只在发布方提供了SHA-256时使用，并且重新计算文件的SHA-256，
+    /// 与文件名和发布的校验和都一致才使用。不一致的文件不属于当前用户，只跳过不删除。
+    ///
+    /// # 参数
+    ///
+    /// * `shared` - 共享缓存中的文件路径，在它所在的目录中查找
+    /// * `url` - 下载地址
+    /// * `sha256` - 发布方提供的SHA-256
+    ///
+    /// # 返回
+    ///
+    /// 找到内容与发布的校验和一致的文件时返回文件路径，否则返回None。
+    fn find_shared_archive(shared: &Path, url: &str, sha256: &str) -> Option<PathBuf> {
+        Self::cached_archive_candidates(shared, url, Some(sha256))
+            .into_iter()
+            .find(|path| Self::cached_archive_intact(path))
+    }
+
+    /// 缓存目录中文件名与下载地址或SHA-256匹配的安装包，尚未校验内容
+    fn cached_archive_candidates(dest: &Path, url: &str, sha256: Option<&str>) -> Vec<PathBuf> {
+        let (Some(dir), Some(file_name)) = (dest.parent(), dest.file_name()) else {
+            return Vec::new();
+        };
+        let file_name = file_name.to_string_lossy();
+        let (stem, extension) = split_archive_name(&file_name);
+        let key = format!("{}-{}", stem, url_digest(url));
+        let matches = |name: &str| {
+            let Some((name_key, digest)) = name.strip_suffix(extension).and_then(|name| name.rsplit_once('@')) else {
+                return false;
+            };
+            match sha256 {
+                Some(sha256) => digest.eq_ignore_ascii_case(sha256),
+                None => name_key == key,
+            }
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        entries.flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.file_name().is_some_and(|name| matches(&name.to_string_lossy())))
+            .collect()
+    }
+
+    /// 缓存的安装包内容的SHA-256是否与文件名中 `@` 之后的一致
+    fn cached_archive_intact(path: &Path) -> bool {
+        let Some(name) = path.file_name().map(|name| name.to_string_lossy().into_owned()) else {
+            return false;
+        };
+        let (stem, _) = split_archive_name(&name);
+        let Some((_, expected)) = stem.rsplit_once('@') else {
+            return false;
+        };
+        let Ok(metadata) = fs::metadata(path) else {
+            return false;
+        };
+        matches!(Self::file_digest(path, metadata.file_type()), Ok(Some(digest)) if digest.eq_ignore_ascii_case(expected))
+    }
+
+    /// 共享缓存中某个下载文件的路径，未配置共享缓存时返回None
+    ///
+    /// 文件按工具分目录保存，文件名取自下载地址；地址最后一段不是压缩包文件名时（例如 nuget 的包地址）
+    /// 与上一段拼接，避免不同的包重名。实际保存的文件名由 `cached_archive_name` 加上地址摘要和SHA-256。
+    fn shared_cache_file(&self, version_type: VersionType, url: &str) -> Option<PathBuf> {
+        let dir = self.config.shared_cache_dir()?;
+        let mut segments = url.trim_end_matches('/').rsplit('/');
+        let last = segments.next().filter(|name| !name.is_empty())?;
+        let name = if last.ends_with(".tar.gz") || last.ends_with(".zip") {
+            last.to_string()
+        } else {
+            format!("{}-{}", segments.next()?, last)
+        };
+        Some(dir.join(version_type.id()).join(name))
+    }
+
+    /// 获取安装包
+    ///
+    /// 共享缓存或下载缓存中已有该文件时直接使用，不再下载；否则下载到 `dest`，按SHA-256重命名后
+    /// 保留在下载缓存中，并在共享缓存目录可写时放入共享缓存，供同一台机器上的其他用户使用。
+    ///
+    /// # 参数
+    ///
+    /// * `url` - 下载地址
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    /// * `dest` - 下载时保存的文件路径
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回用于解压的安装包路径（文件名中带有SHA-256），失败时返回错误。
+    async fn fetch_archive(&self, url: &str, version: &str, version_type: VersionType, dest: &Path) -> Result<PathBuf> {
+        // 发布方提供了校验和时，内容相同的安装包可以直接使用
+        let expected = self.published_sha256(version_type, version, url).await;
+        if let Some(cached) = Self::find_cached_archive(dest, url, expected.as_deref()) {
+            self.report(&format!("Found {} in the download cache, skipping download", cached.display()));
+            self.emit_event("cache-hit", version_type, version, serde_json::json!({ "path": cached }));
+            return Ok(cached);
+        }
+        let shared = self.shared_cache_file(version_type, url);
+        if let (Some(shared), Some(expected)) = (&shared, &expected)
+            && let Some(cached) = Self::find_shared_archive(shared, url, expected)
+        {
+            self.report(&format!("Found {} in the shared cache, skipping download", cached.display()));
+            self.emit_event("cache-hit", version_type, version, serde_json::json!({ "path": cached }));
+            return Ok(cached);
+        }
+        
+        let sha256 = self.download_archive(url, version, version_type, dest, expected.as_deref()).await?;
+        let file_name = dest.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let archive = dest.with_file_name(Self::cached_archive_name(&file_name, url, &sha256));
+        fs::rename(dest, &archive)?;
+        if let Some(shared) = &shared
+            && let Some(name) = shared.file_name()
+        {
+            Self::publish_shared_archive(&archive, &shared.with_file_name(Self::cached_archive_name(&name.to_string_lossy(), url, &sha256)));
+        }
+        Ok(archive)
+    }
+
+    /// 把下载的安装包复制到共享缓存
+    ///
+    /// 共享缓存对普通用户通常是只读的，无法写入时直接跳过。先复制到临时文件再重命名，
+    /// 其他用户不会读到复制了一半的文件。
+    fn publish_shared_archive(archive: &Path, shared: &Path) {
+        let (Some(parent), Some(name)) = (shared.parent(), shared.file_name()) else {
+            return;
+        };
+        if fs::create_dir_all(parent).is_err() {
+            return;
+        }
+        let temp = parent.join(format!(".{}.{}.tmp", name.to_string_lossy(), std::process::id()));
+        if clone_or_copy(archive, &temp).is_err() {
+            let _ = fs::remove_file(&temp);
+            return;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&temp, fs::Permissions::from_mode(0o644));
+        }
+        if fs::rename(&temp, shared).is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+    }
+
+    /// 解压压缩包
+    ///
+    /// 根据文件扩展名选择 tar.gz 或 zip 格式。
+    ///
+    /// # 参数
+    ///
+    /// * `archive_path` - 压缩包路径
+    /// * `dest` - 解压目标目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn extract_archive(archive_path: &Path, dest: &Path) -> Result<()> {
+        Self::extract_archive_cancellable(archive_path, dest, &CancelToken::default())
+    }
+
+    /// 在阻塞线程中解压压缩包，解压过程中可以用 Ctrl-C 取消安装
+    ///
+    /// # 参数
+    ///
+    /// * `archive_path` - 压缩包路径
+    /// * `dest` - 解压目标目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败或被取消时返回错误。
+    async fn extract_archive_blocking(archive_path: &Path, dest: &Path) -> Result<()> {
+        let (archive_path, dest) = (archive_path.to_path_buf(), dest.to_path_buf());
+        run_blocking(move |cancel| Self::extract_archive_cancellable(&archive_path, &dest, cancel)).await
+    }
+
+    /// 解压压缩包，每个条目写入前检查是否已取消
+    #[allow(clippy::collapsible_if)]
+    fn extract_archive_cancellable(archive_path: &Path, dest: &Path, cancel: &CancelToken) -> Result<()> {
+        let file_name = archive_path.to_string_lossy();
+        // npm 自带的依赖目录很深，在 Windows 上容易超过 MAX_PATH
+        let dest = long_path(dest);
+        let dest = dest.as_path();
+        
+        // Extract based on the file type
+        // 压缩包来自第三方镜像，解压前逐项检查路径，拒绝写到目标目录之外的条目
+        if file_name.ends_with(".tar.gz") {
+            let file = fs::File::open(archive_path)?;
+            let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+            fs::create_dir_all(dest)?;
+            for entry in archive.entries()? {
+                cancel.check()?;
+                let mut entry = entry?;
+                let path = entry.path()?.into_owned();
+                let link = entry.link_name()?.map(|link| link.into_owned());
+                Self::check_archive_entry(&path, link.as_deref(), entry.header().entry_type().is_hard_link())?;
+                entry.unpack_in(dest)?;
+            }
+        } else if file_name.ends_with(".zip") {
+            let file = fs::File::open(archive_path)?;
+            let mut archive = zip::ZipArchive::new(file)?;
+            let mut links = Vec::new();
+            for i in 0..archive.len() {
+                cancel.check()?;
+                let mut file = archive.by_index(i)?;
+                let Some(name) = file.enclosed_name().map(|name| name.to_path_buf()) else {
+                    return Err(anyhow::anyhow!("压缩包包含不安全的路径: {}", file.name()));
+                };
+                // 逐个组件拼接，保证扩展长度路径中使用系统的分隔符
+                let mut outpath = dest.to_path_buf();
+                for component in name.components() {
+                    outpath.push(component);
+                }
+                
+                if file.name().ends_with('/') {
+                    fs::create_dir_all(&outpath)?;
+                } else {
+                    if let Some(p) = outpath.parent() {
+                        if !p.exists() {
+                            fs::create_dir_all(p)?;
+                        }
+                    }
+                    
+                    // Unix 上打包的 zip 会在外部属性中记录文件类型和权限，符号链接的内容是链接目标
+                    let mode = file.unix_mode();
+                    if mode.is_some_and(|mode| mode & 0o170000 == 0o120000) {
+                        let mut target = String::new();
+                        io::Read::read_to_string(&mut file, &mut target)?;
+                        Self::check_archive_entry(&name, Some(Path::new(&target)), false)?;
+                        links.push((outpath, PathBuf::from(target)));
+                        continue;
+                    }
+                    
+                    let mut outfile = fs::File::create(&outpath)?;
+                    io::copy(&mut file, &mut outfile)?;
+                    #[cfg(unix)]
+                    if let Some(mode) = mode {
+                        fs::set_permissions(&outpath, fs::Permissions::from_mode(mode & 0o7777))?;
+                    }
+                }
+            }
+            
+            // 链接目标可能排在链接之后，全部文件写完再创建
+            for (link, target) in links {
+                cancel.check()?;
+                create_symlink(&target, &link)?;
+            }
+        } else {
+            return Err(anyhow::anyhow!("不支持的压缩文件格式: {}", file_name));
+        }
+        
+        Ok(())
+    }
+
+    /// 复制目录中的文件，保留符号链接
+    ///
+    /// # 参数
+    ///
+    /// * `src` - 源目录
+    /// * `dest` - 目标目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn copy_dir_entries(src: &Path, dest: &Path) -> Result<()> {
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let target = dest.join(entry.file_name());
+            if file_type.is_symlink() {
+                create_symlink(&fs::read_link(entry.path())?, &target)?;
+            } else if file_type.is_file() {
+                // 克隆和复制都会保留权限
+                clone_or_copy(&entry.path(), &target)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查压缩包条目是否会写到解压目录之外
+    ///
+    /// 拒绝绝对路径、包含 `..` 的路径，以及指向解压目录之外的符号链接和硬链接。
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 条目在压缩包中的路径
+    /// * `link` - 链接条目的目标
+    /// * `hard_link` - 是否为硬链接（目标相对于解压目录而不是条目所在目录）
+    ///
+    /// # 返回
+    ///
+    /// 安全时返回Ok(()，否则返回错误。
+    fn check_archive_entry(path: &Path, link: Option<&Path>, hard_link: bool) -> Result<()> {
+        let escapes = |path: &Path| {
+            let mut depth = 0usize;
+            for component in path.components() {
+                match component {
+                    std::path::Component::Normal(_) => depth += 1,
+                    std::path::Component::CurDir => {}
+                    std::path::Component::ParentDir if depth > 0 => depth -= 1,
+                    _ => return true,
+                }
+            }
+            false
+        };
+        
+        if path.components().any(|c| !matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir)) {
+            return Err(anyhow::anyhow!("压缩包包含不安全的路径: {}", path.display()));
+        }
+        
+        if let Some(link) = link {
+            // 符号链接的目标相对于链接所在目录解析
+            let resolved = if hard_link {
+                link.to_path_buf()
+            } else {
+                path.parent().unwrap_or(Path::new("")).join(link)
+            };
+            if escapes(&resolved) {
+                return Err(anyhow::anyhow!("压缩包中的链接 {} 指向解压目录之外: {}", path.display(), link.display()));
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// 按组件安装Rust
+    ///
+    /// 从 channel-rust-<version>.toml 清单中找到当前平台各组件的安装包，
+    /// 逐个下载并运行组件自带的 install.sh 安装到版本目录。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `target` - 当前平台的目标三元组
+    /// * `name` - 版本目录名（指定 `--arch` 时带架构后缀）
+    /// * `version_dir` - 版本目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    async fn install_rust_components(&self, version: &str, target: &str, name: &str, version_dir: &Path, staging: &mut InstallStaging) -> Result<()> {
+        if self.os_type == OsType::Windows {
+            return Err(anyhow::anyhow!("Windows 暂不支持按组件安装Rust，请将 rust.source 设置为 standalone"));
+        }
+        
+        let mirror = self.mirror_base(VersionType::Rust);
+        let manifest_url = format!("{}/channel-rust-{}.toml", mirror, version);
+        let client = reqwest::Client::new();
+        let response = client.get(&manifest_url).send().await.map_err(|err| VersionError::DownloadFailed {
+            version: version.to_string(),
+            version_type: VersionType::Rust,
+            url: manifest_url.clone(),
+            status: None,
+            reason: err.to_string(),
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(VersionError::DownloadFailed {
+                version: version.to_string(),
+                version_type: VersionType::Rust,
+                url: manifest_url,
+                status: Some(status.as_u16()),
+                reason: format!("HTTP {}", status),
+            }.into());
+        }
+        let manifest = RustManifest::parse(&response.text().await?);
+        
+        // 先确认所有组件都可用，避免下载到一半才失败
+        let mut files = Vec::new();
+        for component in self.config.rust_components() {
+            let Some((url, _)) = manifest.packages.get(&(component.clone(), target.to_string())) else {
+                return Err(anyhow::anyhow!("Rust {} 的组件 {} 不支持平台 {}", version, component, target));
+            };
+            // 清单中的地址指向官方源，配置了镜像时改为从镜像下载
+            let url = match url.strip_prefix(VersionType::Rust.default_mirror()) {
+                Some(path) => format!("{}{}", mirror, path),
+                None => url.clone(),
+            };
+            files.push((component, url));
+        }
+        
+        staging.track(version_dir);
+        fs::create_dir_all(version_dir)?;
+        let staging_dir = self.cache_dir.join(format!("rust-components-{}", version));
+        staging.track(&staging_dir);
+        
+        let installed = async {
+            let mut archive_bytes = 0;
+            for (component, url) in files {
+                let Some(file_name) = url.rsplit('/').next().filter(|name| name.ends_with(".tar.gz")) else {
+                    return Err(anyhow::anyhow!("无法识别组件 {} 的安装包格式: {}", component, url));
+                };
+                self.begin_phase(InstallPhase::Download, &format!("Downloading Rust component {}...", component));
+                let archive = self.cache_dir.join(file_name);
+                staging.track(&archive);
+                let archive = self.fetch_archive(&url, version, VersionType::Rust, &archive).await?;
+                archive_bytes += fs::metadata(&archive)?.len();
+                self.begin_phase(InstallPhase::Extract, &format!("Extracting Rust component {}...", component));
+                Self::extract_archive_blocking(&archive, &staging_dir).await?;
+                self.emit_event("extracted", VersionType::Rust, version, serde_json::json!({ "component": component }));
+                self.begin_phase(InstallPhase::PostInstall, &format!("Installing Rust component {}...", component));
+                let install_script = staging_dir.join(file_name.trim_end_matches(".tar.gz")).join("install.sh");
+                let status = Command::new("sh")
+                    .arg(&install_script)
+                    .arg("--prefix")
+                    .arg(version_dir)
+                    .arg("--disable-ldconfig")
+                    .status()?;
+                if !status.success() {
+                    return Err(anyhow::anyhow!("Rust组件 {} 安装失败，退出码: {}", component, status));
+                }
+            }
+            Ok(archive_bytes)
+        }.await;
+        let archive_bytes = match installed {
+            Ok(archive_bytes) => archive_bytes,
+            Err(err) => {
+                // 已经装入的组件不完整，留下版本目录会被当作已安装的版本
+                let _ = fs::remove_dir_all(version_dir);
+                let _ = fs::remove_dir_all(&staging_dir);
+                return Err(err);
+            }
+        };
+        
+        fs::remove_dir_all(&staging_dir)?;
+        self.clear_quarantine(version_dir);
+        self.write_install_receipt(version_dir, version, VersionType::Rust, &manifest_url, self.arch_type, InstallDetails { archive_bytes: Some(archive_bytes), ..Default::default() })?;
+        self.write_staged_activation_scripts(name, VersionType::Rust, version_dir)?;
+        self.report(&format!("Successfully installed Rust v{}", version));
+        Ok(())
+    }
+
+    /// 安装指定版本
+    ///
+    /// 安装指定版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub async fn install_version(&self, version: &str, version_type: VersionType) -> Result<()> {
+        self.install_version_for_arch(version, version_type, None).await
+    }
+
+    /// 安装指定架构的版本
+    ///
+    /// 架构与当前机器不同时，版本目录名为 `<版本>-<架构>`（例如 `20.1.0-x64`），
+    /// 可以与本机架构的同一版本并存，之后用这个名称切换或执行。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    /// * `arch` - 架构名称（x64、arm64、x86、arm），为None时使用本机架构
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub async fn install_version_for_arch(&self, version: &str, version_type: VersionType, arch: Option<&str>) -> Result<()> {
+        let name = self.installed_version_name(version, arch)?;
+        self.begin_operation(version_type, &name);
+        let result = self.install_version_checked(version, version_type, arch, &name).await;
+        self.finish_operation(&result);
+        result
+    }
+
+    /// 检查版本是否已安装和是否符合策略，然后执行安装
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    /// * `arch` - 架构名称，为None时使用本机架构
+    /// * `name` - 版本目录名
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    async fn install_version_checked(&self, version: &str, version_type: VersionType, arch: Option<&str>, name: &str) -> Result<()> {
+        let version_dir = self.get_version_dir(name, version_type);
+        if version_dir.exists() {
+            self.report(&format!("Version {} is already installed", name));
+            return Ok(());
+        }
+        self.ensure_versions_writable()?;
+
+        self.check_policy(BuildFlavor::split(version, version_type).0, version_type)?;
+        
+        // 同一版本不能同时由两个进程安装；锁在函数返回时释放，包括被 Ctrl-C 取消时
+        let _install_lock = self.lock_install(name)?;
+        if version_dir.exists() {
+            self.report(&format!("Version {} is already installed", name));
+            return Ok(());
+        }
+        
+        // Ctrl-C 时丢弃安装过程，由 InstallStaging 清理已下载和解压的文件
+        let mut staging = InstallStaging::default();
+        self.start_phases(version_type, name);
+        let result = tokio::select! {
+            result = self.install_version_staged(version, version_type, arch, name, &version_dir, &mut staging) => result,
+            _ = tokio::signal::ctrl_c() => Err(VersionError::Cancelled.into()),
+        };
+        let timing = self.finish_phases();
+        match &result {
+            Ok(()) => {
+                staging.commit();
+                let mut fields = serde_json::json!({ "dir": version_dir });
+                if let Some(timing) = &timing {
+                    if let (Some(fields), serde_json::Value::Object(timing)) = (fields.as_object_mut(), timing.to_json()) {
+                        fields.extend(timing);
+                    }
+                    self.report(&timing.summary());
+                }
+                self.emit_event("installed", version_type, name, fields);
+                self.run_install_hooks(name, version_type);
+            }
+            Err(err) => self.emit_event("failed", version_type, name, serde_json::json!({ "error": err.to_string() })),
+        }
+        result
+    }
+
+    /// 获取安装某个版本目录的锁
+    ///
+    /// 锁文件中记录持有者的进程号，持有者已退出（例如被强制结束）时视为过期。
+    ///
+    /// # 参数
+    ///
+    /// * `name` - 版本目录名
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回锁，其他进程正在安装该版本时返回错误。
+    fn lock_install(&self, name: &str) -> Result<state::FileLock> {
+        let path = self.versions_dir.join(format!(".{}.installing", name));
+        if let Some(pid) = fs::read_to_string(&path).ok().and_then(|pid| pid.trim().parse::<u32>().ok()) {
+            if pid != std::process::id() && Self::process_alive(pid) {
+                return Err(anyhow::anyhow!("另一个 ver 进程（pid {}）正在安装 {}，请等待它完成", pid, name));
+            }
+            let _ = fs::remove_file(&path);
+        }
+        state::FileLock::acquire(&path, std::time::Duration::from_secs(10))
+    }
+
+    /// 在新安装的版本环境中运行 `on_install.<tool>` 配置的命令
+    ///
+    /// 命令通过 shell 运行，PATH 中该版本的 bin 目录在最前面，Node.js 的全局包安装到该版本的前缀中。
+    /// 版本已经安装完成，命令失败时只打印警告，不影响安装结果。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    fn run_install_hooks(&self, version: &str, version_type: VersionType) {
+        let commands = self.config.on_install(version_type);
+        if commands.is_empty() {
+            return;
+        }
+        
+        let bin_path = self.get_bin_dir(version, version_type);
+        let path = env::join_paths(std::iter::once(bin_path.clone()).chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())))
+            .unwrap_or_default();
+        for command in commands {
+            self.report(&format!("Running on-install hook: {}", command));
+            let mut cmd = match self.os_type {
+                OsType::Windows => {
+                    let mut cmd = Command::new("cmd");
+                    cmd.arg("/C").arg(command);
+                    cmd
+                }
+                _ => {
+                    let mut cmd = Command::new("sh");
+                    cmd.arg("-c").arg(command);
+                    cmd
+                }
+            };
+            cmd.env("PATH", &path).envs(self.injected_env(version, version_type).1);
+            if version_type == VersionType::Node
+                && let Some(node_prefix) = bin_path.parent()
+            {
+                cmd.env("npm_config_prefix", node_prefix).env_remove("NPM_CONFIG_PREFIX");
+            }
+            if self.progress_mode == ProgressMode::Quiet {
+                cmd.stdout(std::process::Stdio::null());
+            }
+            // 钩子的输出直接写到终端，运行期间暂时清除进度条
+            match output::suspend(|| cmd.status()) {
+                Ok(status) if status.success() => {}
+                Ok(status) => output::eprintln(format_args!("Warning: on-install hook `{}` failed with {}", command, status)),
+                Err(err) => output::eprintln(format_args!("Warning: failed to run on-install hook `{}`: {}", command, err)),
+            }
+        }
+    }
+
+    /// 下载、解压并配置版本，创建的文件记录在 `staging` 中
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    /// * `arch` - 架构名称，为None时使用本机架构
+    /// * `name` - 版本目录名
+    /// * `version_dir` - 版本目录
+    /// * `staging` - 失败或取消时需要删除的文件
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    async fn install_version_staged(&self, version: &str, version_type: VersionType, arch: Option<&str>, name: &str, version_dir: &Path, staging: &mut InstallStaging) -> Result<()> {
+        // 变体后缀（例如 `3.13.0t`）只影响编译选项，下载的是同一个版本的源码包
+        let requested = version;
+        let (version, build) = BuildFlavor::split(version, version_type);
+        if build.free_threaded && compare_versions(version, "3.13") == std::cmp::Ordering::Less {
+            return Err(anyhow::anyhow!("free-threaded 构建需要 Python 3.13 或更高版本，{} 不支持", version));
+        }
+        if (build.free_threaded || build.debug) && self.os_type == OsType::Windows {
+            return Err(anyhow::anyhow!("Windows 上只能安装 Python 的默认构建，不支持 {}", requested));
+        }
+        if version_type == VersionType::Python && self.os_type != OsType::Windows {
+            // python.org 只为 Unix 提供源码包，编译前明确告知用户
+            output::println(format_args!("Note: python.org publishes only source archives for Linux and macOS; Python {} will be compiled locally.", requested));
+            self.preflight_source_build(version_type)?;
+        }
+        
+        // Determine appropriate URL based on OS and architecture
+        let arch_type = match arch {
+            Some(arch) => Self::parse_arch(arch)?,
+            None => self.install_arch(version, version_type).await?,
+        };
+        let os_arch_suffix = self.platform_suffix_for(version_type, arch_type)?;
+        
+        if version_type == VersionType::Rust && self.config.rust_source() == RustSource::Components {
+            return self.install_rust_components(version, &os_arch_suffix, name, version_dir, staging).await;
+        }
+        
+        let extension = match self.os_type {
+            OsType::Windows => ".zip",
+            _ => ".tar.gz",
+        };
+
+        let url = if version_type == VersionType::Python
+            && self.os_type == OsType::Windows
+            && self.config.python_windows_source() == PythonWindowsSource::Nuget
+        {
+            let package = if self.arch_type == ArchType::X86 { "pythonx86" } else { "python" };
+            // 设置了 Python 的下载地址时 NuGet 包也从那里下载
+            let nuget = match self.endpoints.endpoints.iter().find(|(tool, _)| *tool == VersionType::Python) {
+                Some((_, endpoint)) => endpoint.trim_end_matches('/').to_string(),
+                None => self.config.python_nuget_url().to_string(),
+            };
+            format!("{}/{}/{}", nuget, package, version)
+        } else {
+            Self::download_url(&self.mirror_base(version_type), version, version_type, self.os_type, &os_arch_suffix)
+        };
+
+        // 失败时不留下半解压的目录，否则下次会被当作已安装
+        staging.track(version_dir);
+        
+        // 配置了增量源且有已安装的相邻版本时，只下载内容变化的文件
+        let delta = match self.install_from_delta(version, version_type, &os_arch_suffix, &url, version_dir).await {
+            Ok(delta) => delta,
+            Err(err) => {
+                self.report(&format!("Delta install failed ({}), downloading the full archive", err));
+                staging.discard(version_dir);
+                None
+            }
+        };
+        let archive_bytes = match delta {
+            Some(stats) => {
+                self.report(&format!("Reused {} file(s) from installed versions, downloaded {} file(s) ({} bytes)",
+                    stats.reused, stats.downloaded, stats.downloaded_bytes));
+                self.emit_event("extracted", version_type, version, serde_json::json!({ "dir": version_dir, "delta": { "reused": stats.reused, "downloaded": stats.downloaded, "downloaded_bytes": stats.downloaded_bytes } }));
+                None
+            }
+            None => {
+                self.begin_phase(InstallPhase::Download, &format!("Downloading {} v{} for {}...", version_type, version, os_arch_suffix));
+                
+                // Download to a temporary file
+                let temp_file = self.cache_dir.join(format!("{}-{}-{}{}", version_type.id(), version, os_arch_suffix, extension));
+                staging.track(&temp_file);
+                let archive = self.fetch_archive(&url, version, version_type, &temp_file).await?;
+                let archive_bytes = fs::metadata(&archive)?.len();
+                
+                fs::create_dir_all(version_dir)?;
+                self.begin_phase(InstallPhase::Extract, "Extracting...");
+                Self::extract_archive_blocking(&archive, version_dir).await?;
+                self.emit_event("extracted", version_type, version, serde_json::json!({ "dir": version_dir }));
+                Some(archive_bytes)
+            }
+        };
+        
+        self.begin_phase(InstallPhase::PostInstall, &format!("Setting up {} v{}...", version_type, requested));
+        
+        // 特殊处理Rust安装
+        if version_type == VersionType::Rust {
+            // 运行安装脚本
+            let install_script = match self.os_type {
+                OsType::Windows => version_dir.join(format!("rust-{}-{}/install.bat", version, os_arch_suffix)),
+                _ => version_dir.join(format!("rust-{}-{}/install.sh", version, os_arch_suffix)),
+            };
+            
+            if install_script.exists() {
+                self.report("Running Rust installation script...");
+                
+                let status = match self.os_type {
+                    OsType::Windows => {
+                        Command::new("cmd")
+                            .arg("/C")
+                            .arg(&install_script)
+                            .arg("--prefix")
+                            .arg(version_dir)
+                            .arg("--without=rust-docs")
+                            .status()?
+                    },
+                    _ => {
+                        Command::new("sh")
+                            .arg(&install_script)
+                            .arg("--prefix")
+                            .arg(version_dir)
+                            .arg("--without=rust-docs")
+                            .status()?
+                    }
+                };
+                
+                if !status.success() {
+                    return Err(anyhow::anyhow!("Rust安装脚本执行失败，退出码: {}", status));
+                }
+            } else {
+                self.report("No installation script found, trying to set up manually...");
+                // 手动设置bin目录
+                let bin_dir = version_dir.join("bin");
+                fs::create_dir_all(&bin_dir)?;
+                
+                // 查找并移动可执行文件
+                let rust_bin_dir = match self.os_type {
+                    OsType::Windows => version_dir.join(format!("rust-{}-{}/rustc/bin", version, os_arch_suffix)),
+                    _ => version_dir.join(format!("rust-{}-{}/rustc/bin", version, os_arch_suffix)),
+                };
+                
+                if rust_bin_dir.exists() {
+                    Self::copy_dir_entries(&rust_bin_dir, &bin_dir)?;
+                }
+                
+                // 复制cargo可执行文件
+                let cargo_bin_dir = match self.os_type {
+                    OsType::Windows => version_dir.join(format!("rust-{}-{}/cargo/bin", version, os_arch_suffix)),
+                    _ => version_dir.join(format!("rust-{}-{}/cargo/bin", version, os_arch_suffix)),
+                };
+                
+                if cargo_bin_dir.exists() {
+                    Self::copy_dir_entries(&cargo_bin_dir, &bin_dir)?;
+                }
+            }
+        }
+        
+        // 特殊处理Python安装
+        let mut skipped_modules = Vec::new();
+        if version_type == VersionType::Python && self.os_type != OsType::Windows {
+            self.report(&format!("Building Python {} from source (this may take a few minutes)...", version));
+            let prefix = self.get_version_dir(name, version_type);
+            let (version, build, version_dir, quiet) = (version.to_string(), build.clone(), version_dir.to_path_buf(), self.progress_mode == ProgressMode::Quiet);
+            skipped_modules = run_blocking(move |cancel| Self::build_python_from_source(&version, &build, &version_dir, &prefix, quiet, cancel)).await?;
+        } else if version_type == VersionType::Python {
+            self.setup_windows_python(version, version_dir)?;
+        }
+        
+        // 特殊处理Go安装
+        if version_type == VersionType::Go {
+            // 手动设置bin目录
+            let bin_dir = version_dir.join("bin");
+            fs::create_dir_all(&bin_dir)?;
+            
+            // 查找并移动可执行文件
+            let go_bin_dir = match self.os_type {
+                OsType::Windows => version_dir.join(format!("go{}-{}.exe", version, os_arch_suffix)),
+                _ => version_dir.join(format!("go{}-{}{}", version, os_arch_suffix, self.get_exe_extension())),
+            };
+            
+            if go_bin_dir.exists() {
+                let target_bin = bin_dir.join("go");
+                fs::copy(go_bin_dir, &target_bin)?;
+                
+                // 设置执行权限
+                #[cfg(unix)]
+                if let OsType::Darwin | OsType::Linux = self.os_type {
+                    let mut perms = fs::metadata(&target_bin)?.permissions();
+                    perms.set_mode(0o755); // rwxr-xr-x
+                    fs::set_permissions(&target_bin, perms)?;
+                }
+            }
+        }
+        
+        // 预先下载头文件，离线时 node-gyp 也能编译原生模块
+        if version_type == VersionType::Node
+            && let Err(err) = self.fetch_node_headers(version, version_dir).await
+        {
+            output::println(format_args!("Warning: failed to download Node.js {} headers: {}", version, err));
+            output::println(format_args!("Native addons will download them on demand; run `ver node headers {}` to retry", version));
+        }
+        
+        self.clear_quarantine(version_dir);
+        self.write_install_receipt(version_dir, requested, version_type, &url, arch_type, InstallDetails { skipped_modules, archive_bytes })?;
+        self.write_staged_activation_scripts(name, version_type, version_dir)?;
+        self.report(&format!("Successfully installed {} version {}", version_type, requested));
+        Ok(())
     }
 
-    /// 列出所有别名
+    /// 安装到版本目录本身时生成激活脚本
+    ///
+    /// 激活脚本中是版本目录的绝对路径。修复时安装到暂存目录，由 `swap_repaired` 换到版本目录后再生成。
+    fn write_staged_activation_scripts(&self, name: &str, version_type: VersionType, version_dir: &Path) -> Result<()> {
+        if version_dir != self.get_version_dir(name, version_type) {
+            return Ok(());
+        }
+        self.begin_phase(InstallPhase::Shim, "Writing activation scripts...");
+        self.write_activation_scripts(name, version_type)
+    }
+
+    /// 从增量源安装版本的文件
+    ///
+    /// 下载增量源中该版本的文件清单，内容与已安装版本（按安装记录）相同的文件直接复制，
+    /// 其余文件从增量源下载并校验SHA-256。相邻的补丁版本通常只有少数文件不同，可以大幅减少下载量。
+    ///
+    /// 清单必须记录生成它的官方安装包的SHA-256，并与发布方公布的校验和一致；
+    /// 发布方没有公布校验和（例如 Python）时不使用增量源。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    /// * `platform` - 平台后缀，例如 `linux-x64`
+    /// * `url` - 完整安装包的下载地址，用于获取发布方公布的校验和
+    /// * `version_dir` - 版本目录
+    ///
+    /// # 返回
+    ///
+    /// 未配置增量源、发布方没有公布校验和、没有可复用的已安装版本或增量源中没有该版本时返回None，
+    /// 成功时返回统计，下载或校验失败时返回错误（版本目录可能已写入部分文件）。
+    async fn install_from_delta(&self, version: &str, version_type: VersionType, platform: &str, url: &str, version_dir: &Path) -> Result<Option<DeltaStats>> {
+        let base = match &self.endpoints.delta_endpoint {
+            Some(endpoint) => Some(endpoint.trim_end_matches('/').to_string()),
+            None if self.endpoints.is_hermetic() => None,
+            None => self.config.delta_url(),
+        };
+        let Some(base) = base else {
+            return Ok(None);
+        };
+        
+        // 已安装版本中的文件，按内容摘要索引
+        let mut reusable: HashMap<String, PathBuf> = HashMap::new();
+        for installed in self.list_installed_versions(version_type)? {
+            let installed = installed.trim_end_matches(" (current)");
+            let Ok(Some(receipt)) = self.read_install_receipt(installed, version_type) else {
+                continue;
+            };
+            let installed_dir = self.get_version_dir(installed, version_type);
+            for (relative, digest) in receipt.files {
+                if !digest.starts_with("symlink:") {
+                    reusable.entry(digest).or_insert_with(|| installed_dir.join(relative));
+                }
+            }
+        }
+        if reusable.is_empty() {
+            return Ok(None);
+        }
+        
+        // 增量文件只按清单校验，清单必须能对应到官方发布的安装包
+        let Some(published) = self.published_sha256(version_type, version, url).await else {
+            self.report(&format!("No published checksum for {} v{}, downloading the full archive instead of using the delta source", version_type, version));
+            return Ok(None);
+        };
+        
+        self.begin_phase(InstallPhase::Download, &format!("Downloading changed files of {} v{} from the delta source...", version_type, version));
+        let url = format!("{}/{}/{}/{}.json", base, version_type.id(), version, platform);
+        let client = reqwest::Client::new();
+        let response = client.get(&url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            self.report(&format!("No delta published for {} v{} ({}), downloading the full archive", version_type, version, platform));
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("获取增量清单失败: {} 返回 HTTP {}", url, response.status()));
+        }
+        let manifest: DeltaManifest = response.json().await
+            .with_context(|| format!("无法解析增量清单: {}", url))?;
+        match &manifest.archive_sha256 {
+            Some(archive_sha256) if archive_sha256.eq_ignore_ascii_case(&published) => {}
+            Some(archive_sha256) => return Err(anyhow::anyhow!(
+                "增量清单 {} 对应的安装包SHA-256为 {}，与发布方公布的 {} 不一致", url, archive_sha256, published
+            )),
+            None => return Err(anyhow::anyhow!("增量清单 {} 没有记录对应的安装包SHA-256，请用新版本的 ver mirror delta 重新生成", url)),
+        }
+        
+        self.report(&format!("Installing {} v{} from the delta source {}...", version_type, version, base));
+        fs::create_dir_all(version_dir)?;
+        let mut stats = DeltaStats { reused: 0, downloaded: 0, downloaded_bytes: 0 };
+        let mut missing = Vec::new();
+        for (relative, digest) in &manifest.files {
+            let link = digest.strip_prefix("symlink:").map(Path::new);
+            Self::check_archive_entry(Path::new(relative), link, false)?;
+            let dest = version_dir.join(relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            if let Some(link) = link {
+                create_symlink(link, &dest)?;
+                continue;
+            }
+            // 已安装版本的文件可能被修改过，复制后按摘要再校验一次
+            if let Some(source) = reusable.get(digest)
+                && clone_or_copy(source, &dest).is_ok()
+                && Self::file_digest(&dest, fs::symlink_metadata(&dest)?.file_type())?.as_deref() == Some(digest.as_str())
+            {
+                stats.reused += 1;
+                continue;
+            }
+            missing.push((dest, digest.clone()));
+        }
+        
+        let pb = self.progress_bar(missing.len() as u64, "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files");
+        let mut downloads = futures_util::stream::iter(missing.into_iter().map(|(dest, digest)| {
+            let client = client.clone();
+            let url = format!("{}/blobs/{}", base, digest);
+            async move {
+                use sha2::{Digest, Sha256};
+                
+                let response = client.get(&url).send().await?.error_for_status()?;
+                let bytes = response.bytes().await?;
+                let actual = format!("{:x}", Sha256::digest(&bytes));
+                if actual != digest {
+                    return Err(anyhow::anyhow!("增量文件 {} 的SHA-256不匹配: 实际为 {}", url, actual));
+                }
+                fs::write(&dest, &bytes)?;
+                Ok::<u64, anyhow::Error>(bytes.len() as u64)
+            }
+        })).buffer_unordered(8);
+        while let Some(result) = downloads.next().await {
+            stats.downloaded_bytes += result?;
+            stats.downloaded += 1;
+            pb.inc(1);
+        }
+        pb.finish_and_clear();
+        
+        #[cfg(unix)]
+        for relative in &manifest.executables {
+            let path = version_dir.join(relative);
+            if path.is_file() {
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+            }
+        }
+        Ok(Some(stats))
+    }
+
+    /// 为解压后的安装包生成增量清单，并把文件内容写入增量源目录
+    ///
+    /// 生成的目录结构与 `install.delta_url` 期望的一致，可以直接用静态文件服务器发布。
+    /// 已存在的文件内容不会重复写入，多个版本共享同一个 blobs 目录。
+    ///
+    /// # 参数
+    ///
+    /// * `archive` - 安装包路径
+    /// * `version_type` - 版本类型
+    /// * `version` - 版本号
+    /// * `platform` - 平台后缀，例如 `linux-x64`
+    /// * `out_dir` - 增量源目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回清单中的文件数和新写入的文件内容数，失败时返回错误。
+    pub fn publish_delta(&self, archive: &Path, version_type: VersionType, version: &str, platform: &str, out_dir: &Path) -> Result<(usize, usize)> {
+        let temp_dir = self.cache_dir.join(format!(".delta.{}.tmp", std::process::id()));
+        let _ = fs::remove_dir_all(&temp_dir);
+        let result = Self::extract_archive(archive, &temp_dir).and_then(|_| {
+            let files = Self::hash_tree(&temp_dir)?;
+            let blobs = out_dir.join("blobs");
+            fs::create_dir_all(&blobs)?;
+            let mut written = 0;
+            let mut executables = Vec::new();
+            for (relative, digest) in &files {
+                if digest.starts_with("symlink:") {
+                    continue;
+                }
+                let path = temp_dir.join(relative);
+                // Windows 上没有可执行位，清单中不记录可执行文件
+                #[cfg(unix)]
+                let executable = fs::metadata(&path)?.permissions().mode() & 0o111 != 0;
+                #[cfg(not(unix))]
+                let executable = false;
+                if executable {
+                    executables.push(relative.clone());
+                }
+                let blob = blobs.join(digest);
+                if !blob.exists() {
+                    fs::copy(&path, &blob)?;
+                    written += 1;
+                }
+            }
+            
+            let archive_sha256 = Self::file_digest(archive, fs::metadata(archive)?.file_type())?;
+            let manifest = DeltaManifest { archive_sha256, files, executables };
+            let manifest_file = out_dir.join(version_type.id()).join(version).join(format!("{}.json", platform));
+            if let Some(parent) = manifest_file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_atomic(&manifest_file, serde_json::to_string_pretty(&manifest)?)?;
+            Ok((manifest.files.len(), written))
+        });
+        let _ = fs::remove_dir_all(&temp_dir);
+        result
+    }
+
+    /// Node.js 头文件的目录，作为 npm_config_nodedir 传给 node-gyp
+    ///
+    /// # 参数
+    ///
+    /// * `version_dir` - Node.js 版本目录
+    /// * `version` - 版本号
+    fn node_headers_dir(version_dir: &Path, version: &str) -> PathBuf {
+        version_dir.join("headers").join(format!("node-v{}", Self::strip_arch_suffix(version)))
+    }
+
+    /// 下载并解压 Node.js 头文件
+    ///
+    /// 头文件压缩包保存在下载缓存中，解压到版本目录的 headers 目录。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_dir` - Node.js 版本目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回头文件目录，失败时返回错误。
+    async fn fetch_node_headers(&self, version: &str, version_dir: &Path) -> Result<PathBuf> {
+        let version = Self::strip_arch_suffix(version);
+        let file_name = format!("node-v{}-headers.tar.gz", version);
+        let dest = self.cache_dir.join(&file_name);
+        let url = format!("{}/v{}/{}", self.mirror_base(VersionType::Node), version, file_name);
+        let archive = match Self::find_cached_archive(&dest, &url, None) {
+            Some(archive) => archive,
+            None => {
+                self.report(&format!("Downloading Node.js v{} headers...", version));
+                match self.fetch_archive(&url, version, VersionType::Node, &dest).await {
+                    Ok(path) => path,
+                    Err(err) => {
+                        let _ = fs::remove_file(&dest);
+                        return Err(err);
+                    }
+                }
+            }
+        };
+        
+        let headers_dir = version_dir.join("headers");
+        if headers_dir.exists() {
+            fs::remove_dir_all(&headers_dir)?;
+        }
+        fs::create_dir_all(&headers_dir)?;
+        Self::extract_archive_blocking(&archive, &headers_dir).await?;
+        Ok(Self::node_headers_dir(version_dir, version))
+    }
+
+    /// 读取已安装 Node.js 版本的 NODE_MODULE_VERSION
+    ///
+    /// 优先读取已下载头文件中的 node_version.h，没有头文件时运行 `node -p process.versions.modules`。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    ///
+    /// # 返回
+    ///
+    /// 无法确定时返回None。
+    pub fn installed_node_abi(&self, version: &str) -> Option<u32> {
+        let version_dir = self.get_version_dir(version, VersionType::Node);
+        let header = Self::node_headers_dir(&version_dir, version)
+            .join("include")
+            .join("node")
+            .join("node_version.h");
+        if let Ok(content) = fs::read_to_string(header)
+            && let Some(abi) = content.lines()
+                .filter_map(|line| line.trim().strip_prefix("#define NODE_MODULE_VERSION"))
+                .find_map(|value| value.trim().parse().ok())
+        {
+            return Some(abi);
+        }
+        
+        let output = Command::new(self.get_executable_path(version, VersionType::Node))
+            .args(["-p", "process.versions.modules"])
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    }
+
+    /// 从 Node.js 的 index.json 获取每个版本的 NODE_MODULE_VERSION
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回从新到旧排序的版本号和ABI版本，失败时返回错误。
+    pub async fn node_abi_table(&self) -> Result<Vec<(String, u32)>> {
+        let response = reqwest::Client::new()
+            .get(format!("{}/index.json", self.mirror_base(VersionType::Node)))
+            .send()
+            .await?
+            .json::<Vec<NodeVersion>>()
+            .await?;
+        let mut table: Vec<(String, u32)> = response.into_iter()
+            .filter_map(|v| {
+                let abi = v.modules.as_deref()?.parse().ok()?;
+                Some((v.version.trim_start_matches('v').to_string(), abi))
+            })
+            .collect();
+        table.sort_by(|a, b| compare_versions(&b.0, &a.0));
+        Ok(table)
+    }
+
+    /// 为已安装的 Node.js 版本下载头文件
+    ///
+    /// 安装时下载失败，或者版本是迁移、收养而来时使用。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回头文件目录，失败时返回错误。
+    pub async fn install_node_headers(&self, version: &str) -> Result<PathBuf> {
+        self.ensure_versions_writable()?;
+        let version_dir = self.get_version_dir(version, VersionType::Node);
+        if !version_dir.exists() {
+            return Err(VersionError::NotInstalled(version.to_string(), VersionType::Node).into());
+        }
+        let headers = self.fetch_node_headers(version, &version_dir).await?;
+        // 重新生成激活脚本和环境变量文件，带上 npm_config_nodedir
+        self.write_activation_scripts(version, VersionType::Node)?;
+        self.update_env_file()?;
+        Ok(headers)
+    }
+
+    /// 获取某个版本的环境变量
+    ///
+    /// 在工具的默认环境变量之外，Node.js 版本下载了头文件时设置 npm_config_nodedir，
+    /// 让 node-gyp 使用与该版本 ABI 一致的本地头文件。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回环境变量列表。
+    pub fn version_env(&self, version: &str, version_type: VersionType) -> Vec<(String, String)> {
+        let mut vars = self.tool_env(version_type);
+        if version_type == VersionType::Node {
+            let headers = Self::node_headers_dir(&self.get_version_dir(version, version_type), version);
+            if headers.join("include").join("node").exists()
+                && !vars.iter().any(|(key, _)| key.eq_ignore_ascii_case("npm_config_nodedir"))
+            {
+                vars.push(("npm_config_nodedir".to_string(), headers.to_string_lossy().to_string()));
+            }
+        }
+        vars
+    }
+
+    /// 在某个版本中运行命令时注入的环境
+    ///
+    /// `ver exec` 把返回的目录加到 PATH 前面并设置返回的环境变量，`ver <tool> env` 打印同一份结果。
+    /// 除了 `version_env` 之外，Rust 设置 RUSTUP_TOOLCHAIN，Go 设置 GOROOT，
+    /// Node.js 在 npm 前缀指向其他位置时把 npm_config_prefix 改为该版本的目录。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回加到 PATH 前面的目录和环境变量列表。
+    pub fn injected_env(&self, version: &str, version_type: VersionType) -> (Vec<PathBuf>, Vec<(String, String)>) {
+        let bin_path = self.get_bin_dir(version, version_type);
+        let mut vars = self.version_env(version, version_type);
+        let prefix = bin_path.parent().map(Path::to_path_buf).unwrap_or_default();
+        match version_type {
+            // 让rustup风格的代理脚本在子进程中使用同一工具链
+            VersionType::Rust => vars.push(("RUSTUP_TOOLCHAIN".to_string(), version.to_string())),
+            // 覆盖外部设置的 GOROOT，否则 go 会使用其中的标准库
+            VersionType::Go if !vars.iter().any(|(key, _)| key == "GOROOT") => {
+                vars.push(("GOROOT".to_string(), prefix.to_string_lossy().to_string()));
+            }
+            // 避免全局npm包安装到其他版本的前缀中
+            VersionType::Node => {
+                if let Some((npm_prefix, _)) = self.find_npm_prefix_override()
+                    && !Path::new(&npm_prefix).starts_with(&prefix)
+                {
+                    vars.push(("npm_config_prefix".to_string(), prefix.to_string_lossy().to_string()));
+                }
+            }
+            _ => {}
+        }
+        (vec![bin_path], vars)
+    }
+
+    /// 只对当前终端生效的版本切换
+    ///
+    /// 返回需要在 shell 中执行的环境变量修改：设置 `VER_<TOOL>_VERSION`，把版本的bin目录放到 PATH 最前面，
+    /// 并设置 `injected_env` 中的变量。不修改全局的符号链接，多个终端可以同时使用不同的版本。
+    /// 之前 `ver shell` 加入 PATH 的同一工具的目录会被移除。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 要使用的版本，为None时撤销当前终端的版本
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回环境变量名和新值的列表（None表示删除该变量），版本未安装或不符合策略时返回错误。
+    pub fn session_activation(&self, version: Option<&str>, version_type: VersionType) -> Result<Vec<(String, Option<String>)>> {
+        let var = version_type.session_env_var();
+        let mut changes = Vec::new();
+        
+        // 撤销上一次 `ver shell` 设置的变量
+        let previous = env::var(var).ok().filter(|version| !version.is_empty());
+        if let Some(previous) = &previous {
+            for (key, _) in self.injected_env(previous, version_type).1 {
+                changes.push((key, None));
+            }
+        }
+        
+        let installed_bin_dirs: Vec<PathBuf> = self.list_installed_versions(version_type)?
+            .iter()
+            .map(|installed| self.get_bin_dir(installed.trim_end_matches(" (current)"), version_type))
+            .collect();
+        let mut path: Vec<PathBuf> = env::split_paths(&env::var_os("PATH").unwrap_or_default())
+            .filter(|dir| previous.is_none() || !installed_bin_dirs.contains(dir))
+            .collect();
+        
+        match version {
+            Some(version) => {
+                if !self.get_version_dir(version, version_type).exists() {
+                    return Err(VersionError::NotInstalled(version.to_string(), version_type).into());
+                }
+                self.check_policy(Self::strip_arch_suffix(version), version_type)?;
+                
+                let (dirs, vars) = self.injected_env(version, version_type);
+                path.retain(|dir| !dirs.contains(dir));
+                path.splice(0..0, dirs);
+                changes.retain(|(key, _)| !vars.iter().any(|(var, _)| var == key));
+                changes.extend(vars.into_iter().map(|(key, value)| (key, Some(value))));
+                changes.push((var.to_string(), Some(version.to_string())));
+            }
+            None => changes.push((var.to_string(), None)),
+        }
+        changes.push(("PATH".to_string(), Some(env::join_paths(path)?.to_string_lossy().to_string())));
+        Ok(changes)
+    }
+
+    /// 解析用户指定的架构名称
+    fn parse_arch(arch: &str) -> Result<ArchType> {
+        ArchType::from_name(arch).ok_or_else(|| anyhow::anyhow!("不支持的架构: {}（可选 x64、arm64、x86、arm）", arch))
+    }
+
+    /// 获取指定架构的版本在本地使用的名称
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `arch` - 架构名称，为None或与本机相同时不加后缀
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回版本目录名，例如 `20.1.0` 或 `20.1.0-x64`，架构无效时返回错误。
+    pub fn installed_version_name(&self, version: &str, arch: Option<&str>) -> Result<String> {
+        match arch {
+            Some(arch) => {
+                let arch_type = Self::parse_arch(arch)?;
+                if arch_type == self.arch_type {
+                    Ok(version.to_string())
+                } else {
+                    Ok(format!("{}-{}", version, arch_type.name()))
+                }
+            }
+            None => Ok(version.to_string()),
+        }
+    }
+
+    /// 去掉版本目录名中的架构后缀，例如 `20.1.0-x64` -> `20.1.0`
+    fn strip_arch_suffix(version: &str) -> &str {
+        if let Some((base, arch)) = version.rsplit_once('-')
+            && ArchType::from_name(arch).is_some_and(|arch_type| arch == arch_type.name())
+        {
+            return base;
+        }
+        version
+    }
+
+    /// 确定安装使用的架构
+    ///
+    /// 在 Apple Silicon 上，如果Node.js索引显示某个版本只有 x64 构建（例如旧的大版本），
+    /// 并且启用了 install.rosetta_fallback，则改为安装 x64 版本通过 Rosetta 2 运行。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回要安装的架构，缺少 arm64 版本且未启用回退时返回错误。
+    async fn install_arch(&self, version: &str, version_type: VersionType) -> Result<ArchType> {
+        if self.os_type != OsType::Darwin || self.arch_type != ArchType::Arm64 || version_type != VersionType::Node {
+            return Ok(self.arch_type);
+        }
+        
+        // 离线或索引不可用时按原架构尝试下载
+        let Ok(releases) = self.list_available_versions(false, version_type).await else {
+            return Ok(self.arch_type);
+        };
+        let Some(release) = releases.iter().find(|r| r.version.trim_start_matches('v') == version.trim_start_matches('v')) else {
+            return Ok(self.arch_type);
+        };
+        
+        if release.file_for("darwin-arm64").is_some() || release.file_for("darwin-x64").is_none() {
+            return Ok(self.arch_type);
+        }
+        
+        if !self.config.rosetta_fallback() {
+            return Err(anyhow::anyhow!(
+                "Node.js {} 没有 arm64 版本，运行 `ver config set install.rosetta_fallback true` 可以改为安装 x64 版本并通过 Rosetta 2 运行",
+                version
+            ));
+        }
+        
+        output::println(format_args!("Node.js {} has no arm64 build; installing the x64 build to run under Rosetta 2", version));
+        Ok(ArchType::X64)
+    }
+
+    /// 获取已安装版本的架构说明
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回架构说明，例如 `arm64` 或 `x64, Rosetta`，没有安装记录时为None。
+    pub fn installed_arch_label(&self, version: &str, version_type: VersionType) -> Result<Option<String>> {
+        let Some(arch) = self.read_install_receipt(version, version_type)?.and_then(|receipt| receipt.arch) else {
+            return Ok(None);
+        };
+        
+        if self.os_type == OsType::Darwin && self.arch_type == ArchType::Arm64 && arch == ArchType::X64.name() {
+            Ok(Some(format!("{}, Rosetta", arch)))
+        } else {
+            Ok(Some(arch))
+        }
+    }
+
+    /// 移除macOS的隔离属性
+    ///
+    /// 带有 com.apple.quarantine 属性的可执行文件首次运行时会被Gatekeeper拦截，
+    /// 弹出“无法打开”的对话框。失败时只打印警告，不影响安装。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 安装目录
+    fn clear_quarantine(&self, dir: &Path) {
+        if self.os_type != OsType::Darwin {
+            return;
+        }
+        
+        // 没有该属性的文件会让 xattr 返回非零退出码，因此只在无法运行时警告
+        let result = Command::new("xattr")
+            .args(["-dr", "com.apple.quarantine"])
+            .arg(dir)
+            .stderr(std::process::Stdio::null())
+            .status();
+        if result.is_err() {
+            output::println(format_args!("Warning: failed to remove the quarantine attribute from {}", dir.display()));
+            output::println(format_args!("If macOS refuses to run the binaries, run: xattr -dr com.apple.quarantine \"{}\"", dir.display()));
+        }
+    }
+
+    /// 写入安装记录
+    ///
+    /// 记录保存在版本目录的 INSTALL_RECEIPT_FILE 中，说明版本的来源和安装结果。
+    ///
+    /// # 参数
+    ///
+    /// * `version_dir` - 版本目录
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    /// * `source` - 下载地址
+    /// * `arch_type` - 安装的架构
+    /// * `details` - 跳过的模块、安装包大小等安装结果
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn write_install_receipt(&self, version_dir: &Path, version: &str, version_type: VersionType, source: &str, arch_type: ArchType, details: InstallDetails) -> Result<()> {
+        let (_, mut build) = BuildFlavor::split(version, version_type);
+        build.source_built = version_type == VersionType::Python && self.os_type != OsType::Windows;
+        // 官方的 Linux 预编译包都是为 glibc 构建的，Go 的工具链是静态链接的
+        build.libc = match (self.os_type, version_type) {
+            (OsType::Linux, VersionType::Go) => None,
+            (OsType::Linux, _) if build.source_built && self.is_musl() => Some("musl".to_string()),
+            (OsType::Linux, _) => Some("glibc".to_string()),
+            _ => None,
+        };
+        let receipt = InstallReceipt {
+            schema: state::SCHEMA_VERSION,
+            tool: version_type.id().to_string(),
+            version: version.to_string(),
+            source: source.to_string(),
+            arch: Some(arch_type.name().to_string()),
+            installed_at: chrono::Utc::now().to_rfc3339(),
+            skipped_modules: details.skipped_modules,
+            flavor: None,
+            files: Self::hash_tree(version_dir)?,
+            archive_bytes: details.archive_bytes,
+            build: Some(build),
+        };
+        write_atomic(&version_dir.join(INSTALL_RECEIPT_FILE), serde_json::to_string_pretty(&receipt)?)?;
+        Ok(())
+    }
+
+    /// 为迁移来的版本写入安装记录
     ///
-    /// 列出所有已定义的别名。
+    /// 与 ver 安装的版本一样记录每个文件的SHA-256，迁移的版本因此也可以用 `ver verify` 校验。
     ///
     /// # 参数
     ///
+    /// * `version_dir` - 版本目录
+    /// * `version` - 版本号
     /// * `version_type` - 版本类型
+    /// * `manager` - 来源版本管理器（nvm、n、rustup、pyenv、gvm）
+    /// * `source_dir` - 复制来源目录
     ///
     /// # 返回
     ///
-    /// 成功时返回别名列表，失败时返回错误。
-    pub fn list_aliases(&self, version_type: VersionType) -> Result<Vec<(String, String)>> {
-        let aliases = self.read_aliases(version_type)?;
-        let mut result = Vec::new();
-        
-        for (alias, version) in aliases.aliases {
-            result.push((alias, version));
-        }
-        
-        result.sort_by(|a, b| a.0.cmp(&b.0));
-        Ok(result)
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn write_migration_receipt(&self, version_dir: &Path, version: &str, version_type: VersionType, manager: &str, source_dir: &Path) -> Result<()> {
+        let receipt = InstallReceipt {
+            schema: state::SCHEMA_VERSION,
+            tool: version_type.id().to_string(),
+            version: version.to_string(),
+            source: source_dir.display().to_string(),
+            arch: None,
+            installed_at: chrono::Utc::now().to_rfc3339(),
+            skipped_modules: Vec::new(),
+            flavor: Some(manager.to_string()),
+            files: Self::hash_tree(version_dir)?,
+            archive_bytes: None,
+            // pyenv 从源码编译，其他版本管理器下载官方的预编译包
+            build: Some(BuildFlavor { source_built: manager == "pyenv", ..BuildFlavor::split(version, version_type).1 }),
+        };
+        write_atomic(&version_dir.join(INSTALL_RECEIPT_FILE), serde_json::to_string_pretty(&receipt)?)?;
+        Ok(())
     }
 
-    /// 设置本地版本
+    /// 按安装记录校验版本目录
     ///
-    /// 在当前目录下创建一个文件指定使用的版本。
+    /// 只检查记录中的文件是否被修改或删除；安装后新增的文件（例如全局安装的 npm 包）不算失败。
     ///
     /// # 参数
     ///
@@ -438,1069 +7366,1012 @@ impl VersionManager {
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub fn set_local_version(&self, version: &str, version_type: VersionType) -> Result<()> {
-        // 检查版本是否已安装
+    /// 成功时返回校验结果，安装记录中没有文件摘要时返回None，失败时返回错误。
+    pub fn verify_version(&self, version: &str, version_type: VersionType) -> Result<Option<VerifyResult>> {
         let version_dir = self.get_version_dir(version, version_type);
         if !version_dir.exists() {
-            return Err(anyhow::anyhow!("{}", VersionError::NotInstalled(version.to_string(), version_type)));
+            return Err(VersionError::NotInstalled(version.to_string(), version_type).into());
         }
+        Self::verify_dir(&version_dir)
+    }
 
-        let current_dir = env::current_dir()?;
-        let version_file = match version_type {
-            VersionType::Node => current_dir.join(".node-version"),
-            VersionType::Rust => current_dir.join(".rust-version"),
-            VersionType::Python => current_dir.join(".python-version"),
-            VersionType::Go => current_dir.join(".go-version"),
+    /// 按目录中的安装记录校验目录，见 `verify_version`
+    fn verify_dir(version_dir: &Path) -> Result<Option<VerifyResult>> {
+        let Some(receipt) = state::read_json::<InstallReceipt>(&version_dir.join(INSTALL_RECEIPT_FILE))? else {
+            return Ok(None);
         };
+        if receipt.files.is_empty() {
+            return Ok(None);
+        }
         
-        fs::write(version_file, version)?;
-        
-        Ok(())
+        let mut result = VerifyResult::default();
+        for (path, expected) in &receipt.files {
+            let file = version_dir.join(path);
+            match fs::symlink_metadata(long_path(&file)) {
+                Ok(metadata) => {
+                    if Self::file_digest(&file, metadata.file_type())?.as_ref() != Some(expected) {
+                        result.modified.push(path.clone());
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => result.missing.push(path.clone()),
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(Some(result))
     }
 
-    /// 获取本地项目要求的版本
+    /// 重新下载校验失败的版本
     ///
-    /// 获取当前目录下指定的版本号。
+    /// 从配置的镜像重新安装到暂存目录并校验，通过后才与原目录交换；安装后新增的文件（例如全局安装的
+    /// npm 包）从原目录搬到新目录。重新安装期间原目录保持可用，失败或被取消时只删除暂存目录。
+    /// 锁定的版本和非 ver 下载的版本（adopt、迁移、`ver import-url`）无法重新下载。
     ///
     /// # 参数
     ///
+    /// * `version` - 版本目录名
     /// * `version_type` - 版本类型
     ///
     /// # 返回
     ///
-    /// 成功时返回版本号字符串，失败时返回错误。
-    #[allow(dead_code)]  // 标记为允许未使用
-    pub fn get_local_version(version_type: VersionType) -> Result<Option<String>> {
-        let current_dir = env::current_dir()?;
-        let version_file = match version_type {
-            VersionType::Node => current_dir.join(".node-version"),
-            VersionType::Rust => current_dir.join(".rust-version"),
-            VersionType::Python => current_dir.join(".python-version"),
-            VersionType::Go => current_dir.join(".go-version"),
+    /// 修复后校验通过时返回Ok(())，失败时返回错误。
+    pub async fn repair_version(&self, version: &str, version_type: VersionType) -> Result<()> {
+        let version_dir = self.get_version_dir(version, version_type);
+        let Some(receipt) = self.read_install_receipt(version, version_type)? else {
+            return Err(anyhow::anyhow!("{} 版本 {} 没有安装记录，无法重新下载", version_type, version));
         };
-        
-        if version_file.exists() {
-            let version = fs::read_to_string(version_file)?;
-            Ok(Some(version.trim().to_string()))
-        } else {
-            Ok(None)
+        if let Some(flavor) = &receipt.flavor {
+            return Err(anyhow::anyhow!("{} 版本 {} 来自 {}，无法重新下载", version_type, version, flavor));
+        }
+        if self.is_locked(version, version_type) {
+            return Err(VersionError::Locked(version.to_string(), version_type).into());
+        }
+        self.ensure_versions_writable()?;
+        let _install_lock = self.lock_install(version)?;
+
+        // 目录名带架构后缀时按记录中的架构安装，目录名不变
+        let base = Self::strip_arch_suffix(version);
+        let arch = if base != version { receipt.arch.as_deref() } else { None };
+        let staged = version_dir.with_file_name(format!(".{}.repair-{}", version, std::process::id()));
+
+        // Ctrl-C 时丢弃暂存目录，原目录不受影响
+        let mut staging = InstallStaging::default();
+        self.begin_operation(version_type, version);
+        self.start_phases(version_type, version);
+        let result = tokio::select! {
+            result = self.install_version_staged(base, version_type, arch, version, &staged, &mut staging) => result,
+            _ = tokio::signal::ctrl_c() => Err(VersionError::Cancelled.into()),
+        };
+        self.finish_phases();
+        let result = result
+            .and_then(|()| match Self::verify_dir(&staged)? {
+                Some(result) if !result.modified.is_empty() || !result.missing.is_empty() => {
+                    Err(anyhow::anyhow!("重新下载的 {} 版本 {} 仍然校验失败", version_type, version))
+                }
+                _ => Ok(()),
+            })
+            .and_then(|()| self.swap_repaired(&staged, &version_dir, version, version_type));
+        if result.is_ok() {
+            staging.commit();
         }
+        self.finish_operation(&result);
+        result
     }
 
-    /// 使用指定版本执行命令
+    /// 用重新安装的暂存目录替换原版本目录
     ///
-    /// 使用指定版本的环境执行命令。
+    /// 原目录先移到一旁，暂存目录移入后再把原目录中新增的文件搬过来，最后删除原目录。
     ///
     /// # 参数
     ///
-    /// * `version` - 版本号
-    /// * `command` - 命令名称
-    /// * `args` - 命令参数
+    /// * `staged` - 重新安装的暂存目录
+    /// * `version_dir` - 原版本目录
+    /// * `version` - 版本目录名
     /// * `version_type` - 版本类型
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub fn exec_with_version(&self, version: &str, command: &str, args: &[String], version_type: VersionType) -> Result<()> {
-        // 检查版本是否已安装，如果没有则安装
-        let version_dir = self.get_version_dir(version, version_type);
-        if !version_dir.exists() {
-            println!("Version {} is not installed. Installing...", version);
-            // 创建一个块作用域以避免 `?` 运算符立即返回
-            {
-                let rt = tokio::runtime::Runtime::new()?;
-                rt.block_on(self.install_version(version, version_type))?;
+    /// 成功时返回Ok(())，失败时返回错误（暂存目录未能移入时原目录已恢复）。
+    fn swap_repaired(&self, staged: &Path, version_dir: &Path, version: &str, version_type: VersionType) -> Result<()> {
+        let aside = version_dir.with_file_name(format!(".{}.corrupt-{}", version, std::process::id()));
+        fs::rename(long_path(version_dir), long_path(&aside))
+            .context(format!("移动 {} 版本 {} 的目录失败", version_type, version))?;
+        if let Err(err) = fs::rename(long_path(staged), long_path(version_dir)) {
+            fs::rename(long_path(&aside), long_path(version_dir))
+                .context(format!("恢复 {} 版本 {} 的目录失败，原目录在 {}", version_type, version, aside.display()))?;
+            return Err(anyhow::Error::from(err).context(format!("替换 {} 版本 {} 的目录失败", version_type, version)));
+        }
+        Self::carry_over(&aside, version_dir)
+            .context(format!("搬移安装后新增的文件失败，原目录在 {}", aside.display()))?;
+        fs::remove_dir_all(long_path(&aside))?;
+        self.write_activation_scripts(version, version_type)
+    }
+
+    /// 把旧目录中新目录没有的文件和目录移到新目录中
+    fn carry_over(from: &Path, to: &Path) -> Result<()> {
+        for entry in fs::read_dir(long_path(from))? {
+            let entry = entry?;
+            let target = to.join(entry.file_name());
+            match fs::symlink_metadata(long_path(&target)) {
+                Err(err) if err.kind() == io::ErrorKind::NotFound => fs::rename(long_path(&entry.path()), long_path(&target))?,
+                Ok(metadata) if metadata.is_dir() && entry.file_type()?.is_dir() => Self::carry_over(&entry.path(), &target)?,
+                Ok(_) => {}
+                Err(err) => return Err(err.into()),
             }
         }
+        Ok(())
+    }
 
-        // 获取对应版本的二进制目录
-        let bin_path = match version_type {
-            VersionType::Node => version_dir.join(format!("node-v{}-{}/bin", version, self.get_os_arch_suffix())),
-            VersionType::Rust => version_dir.join("bin"),
-            VersionType::Python => version_dir.join("bin"),
-            VersionType::Go => version_dir.join("bin"),
+    /// 读取安装记录
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回安装记录（旧版本安装的没有记录时为None），失败时返回错误。
+    pub fn read_install_receipt(&self, version: &str, version_type: VersionType) -> Result<Option<InstallReceipt>> {
+        state::read_json(&self.get_version_dir(version, version_type).join(INSTALL_RECEIPT_FILE))
+    }
+
+    /// 检查版本目录中安装的构建变体是否符合版本号的后缀
+    ///
+    /// 例如 `3.13.0t` 的目录中必须是 free-threaded 构建。没有安装记录或旧记录中没有变体时不检查。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本目录名
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 变体一致时返回Ok(()，不一致时返回 [`VersionError::FlavorMismatch`]。
+    pub fn check_build_flavor(&self, version: &str, version_type: VersionType) -> Result<()> {
+        let Some(installed) = self.read_install_receipt(version, version_type)?.and_then(|receipt| receipt.build) else {
+            return Ok(());
         };
-        
-        // 将该目录添加到 PATH 环境变量
-        let path_var = env::var("PATH").unwrap_or_default();
-        let new_path = format!("{}:{}", bin_path.to_string_lossy(), path_var);
-        
-        // 执行命令
-        let status = Command::new(command)
-            .args(args)
-            .env("PATH", new_path)
-            .status()?;
-            
-        if !status.success() {
-            return Err(anyhow::anyhow!("命令执行失败，退出码: {}", status));
+        let (_, requested) = BuildFlavor::split(Self::strip_arch_suffix(version), version_type);
+        if installed.satisfies(&requested) {
+            return Ok(());
         }
-        
-        Ok(())
+        Err(VersionError::FlavorMismatch {
+            version: version.to_string(),
+            version_type,
+            installed,
+        }.into())
     }
 
-    /// 清理缓存和临时文件
+    /// 检查版本是否符合团队策略
     ///
-    /// 清理下载缓存和临时文件。
+    /// 未配置策略文件时直接通过。策略为 warn 时只打印警告，为 deny 时返回错误。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub fn clean(&self) -> Result<()> {
-        // 清理下载缓存
-        if self.cache_dir.exists() {
-            fs::remove_dir_all(&self.cache_dir)?;
-            fs::create_dir(&self.cache_dir)?;
+    /// 允许使用时返回Ok(()，被策略拒绝或策略文件无效时返回错误。
+    pub fn check_policy(&self, version: &str, version_type: VersionType) -> Result<()> {
+        let Some(path) = self.config.policy_file() else {
+            return Ok(());
+        };
+        let policy = Policy::load(&path)?;
+        if policy.allows(version_type, version) {
+            return Ok(());
         }
         
-        // 查找并删除临时文件
-        for entry in fs::read_dir(&self.base_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if let Some(name) = path.file_name() {
-                if name.to_string_lossy().starts_with("temp-") {
-                    if path.is_file() {
-                        fs::remove_file(path)?;
-                    } else if path.is_dir() {
-                        fs::remove_dir_all(path)?;
-                    }
-                }
+        let range = policy.range(version_type).unwrap_or_default();
+        match policy.mode {
+            PolicyMode::Deny => Err(anyhow::anyhow!(
+                "{} 版本 {} 不符合团队策略 {} 允许的范围 {}", version_type, version, path.display(), range
+            )),
+            PolicyMode::Warn => {
+                output::println(format_args!("Warning: {} version {} is outside the range allowed by {} ({})", version_type, version, path.display(), range));
+                Ok(())
             }
         }
-        
-        Ok(())
     }
 
-    /// 自身更新
+    /// 锁定版本
     ///
-    /// 更新版本管理器自身。
+    /// 锁定后 remove 会拒绝删除该版本，切换到该版本时会校验bin目录中的文件。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
     ///
     /// # 返回
     ///
     /// 成功时返回Ok(()，失败时返回错误。
-    pub async fn self_update(&self) -> Result<()> {
-        // 这个功能的实现可能需要与特定的发布渠道集成
-        // 这里简单地打印一条消息，实际应用中可以替换为真正的更新逻辑
-        println!("Self-update functionality not yet implemented.");
-        println!("Please manually update using cargo install --path .");
+    pub fn lock_version(&self, version: &str, version_type: VersionType) -> Result<()> {
+        self.ensure_versions_writable()?;
+        let version_dir = self.get_version_dir(version, version_type);
+        if !version_dir.exists() {
+            return Err(VersionError::NotInstalled(version.to_string(), version_type).into());
+        }
+        
+        let lock = VersionLock {
+            locked_at: chrono::Utc::now().to_rfc3339(),
+            files: Self::hash_bin_dir(&self.get_bin_dir(version, version_type))?,
+        };
+        write_atomic(&version_dir.join(LOCK_FILE), serde_json::to_string_pretty(&lock)?)?;
         Ok(())
     }
 
-    /// 从其他版本管理器迁移
+    /// 解除版本锁定
     ///
-    /// 从其他版本管理器迁移已安装的版本。
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回该版本之前是否处于锁定状态，失败时返回错误。
+    pub fn unlock_version(&self, version: &str, version_type: VersionType) -> Result<bool> {
+        self.ensure_versions_writable()?;
+        let lock_file = self.get_version_dir(version, version_type).join(LOCK_FILE);
+        if !lock_file.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(lock_file)?;
+        Ok(true)
+    }
+
+    /// 检查版本是否已安装
+    pub fn is_installed(&self, version: &str, version_type: VersionType) -> bool {
+        self.get_version_dir(version, version_type).exists()
+    }
+
+    /// 检查版本是否已锁定
+    pub fn is_locked(&self, version: &str, version_type: VersionType) -> bool {
+        self.get_version_dir(version, version_type).join(LOCK_FILE).exists()
+    }
+
+    /// 校验锁定版本的文件
+    ///
+    /// 与锁定时记录的SHA-256比较，任何文件被修改、删除或新增都视为校验失败；
+    /// Node.js 全局安装的包（lib/node_modules 和bin目录中新增的入口）除外。
     ///
     /// # 参数
     ///
-    /// * `source` - 来源版本管理器名称
+    /// * `version` - 版本号
     /// * `version_type` - 版本类型
     ///
     /// # 返回
     ///
-    /// 成功时返回迁移的版本数量，失败时返回错误。
-    pub async fn migrate_from(&self, source: &str, version_type: VersionType) -> Result<usize> {
-        let mut migrated_count = 0;
+    /// 校验通过时返回Ok(()，文件不一致或无法读取时返回错误。
+    pub fn verify_locked_version(&self, version: &str, version_type: VersionType) -> Result<()> {
+        let lock_file = self.get_version_dir(version, version_type).join(LOCK_FILE);
+        let content = fs::read_to_string(&lock_file)?;
+        let lock: VersionLock = serde_json::from_str(&content)
+            .with_context(|| format!("无法解析锁定记录 {}", lock_file.display()))?;
+        let current = Self::hash_bin_dir(&self.get_bin_dir(version, version_type))?;
         
-        match (source.to_lowercase().as_str(), version_type) {
-            ("nvm", VersionType::Node) => {
-                // 尝试找到 NVM 安装目录
-                let nvm_dir = if let Ok(dir) = env::var("NVM_DIR") {
-                    PathBuf::from_str(&dir)?
-                } else {
-                    dirs::home_dir()
-                        .context("Could not find home directory")?
-                        .join(".nvm")
-                };
-                
-                let versions_dir = nvm_dir.join("versions").join("node");
-                
-                if !versions_dir.exists() {
-                    return Err(anyhow::anyhow!("找不到 NVM 版本目录"));
-                }
-                
-                for entry in fs::read_dir(versions_dir)? {
-                    let entry = entry?;
-                    if entry.file_type()?.is_dir() {
-                        let version = entry.file_name().to_string_lossy().to_string();
-                        // 跳过 "v" 前缀
-                        let version = if version.starts_with('v') {
-                            &version[1..]
-                        } else {
-                            &version
-                        };
-                        
-                        // 检查是否已经安装
-                        let target_dir = self.get_version_dir(version, version_type);
-                        if !target_dir.exists() {
-                            println!("Migrating Node.js version {} from NVM...", version);
-                            // 复制文件
-                            let source_dir = entry.path();
-                            self.copy_dir_recursively(&source_dir, &target_dir)?;
-                            migrated_count += 1;
-                        }
-                    }
-                }
-            },
-            ("n", VersionType::Node) => {
-                // 尝试找到 N 安装目录
-                let n_prefix = env::var("N_PREFIX").unwrap_or_else(|_| "/usr/local".to_string());
-                let n_versions_dir = PathBuf::from_str(&n_prefix)?.join("n").join("versions").join("node");
-                
-                if !n_versions_dir.exists() {
-                    return Err(anyhow::anyhow!("找不到 N 版本目录"));
-                }
-                
-                for entry in fs::read_dir(n_versions_dir)? {
-                    let entry = entry?;
-                    if entry.file_type()?.is_dir() {
-                        let version = entry.file_name().to_string_lossy().to_string();
-                        
-                        // 检查是否已经安装
-                        let target_dir = self.get_version_dir(&version, version_type);
-                        if !target_dir.exists() {
-                            println!("Migrating Node.js version {} from N...", version);
-                            // 复制文件
-                            let source_dir = entry.path();
-                            self.copy_dir_recursively(&source_dir, &target_dir)?;
-                            migrated_count += 1;
-                        }
-                    }
+        let mut changed: Vec<&str> = lock.files.iter()
+            .filter(|(name, hash)| current.get(*name) != Some(*hash))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        // 与安装记录的校验一致，全局安装的 npm 包会在bin目录中新增入口，不算被改动
+        if version_type != VersionType::Node {
+            changed.extend(current.keys().filter(|name| !lock.files.contains_key(*name)).map(|name| name.as_str()));
+        }
+        if !changed.is_empty() {
+            return Err(anyhow::anyhow!(
+                "锁定的 {} 版本 {} 校验失败，以下文件自 {} 锁定后发生了变化: {}",
+                version_type, version, lock.locked_at, changed.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// 计算bin目录中文件的SHA-256
+    ///
+    /// 符号链接记录其指向的路径，而不跟随链接。
+    fn hash_bin_dir(bin_dir: &Path) -> Result<std::collections::BTreeMap<String, String>> {
+        let mut files = std::collections::BTreeMap::new();
+        if !bin_dir.exists() {
+            return Ok(files);
+        }
+        for entry in fs::read_dir(long_path(bin_dir))? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(digest) = Self::file_digest(&entry.path(), entry.file_type()?)? {
+                files.insert(name, digest);
+            }
+        }
+        Ok(files)
+    }
+
+    /// 递归计算版本目录中所有文件的SHA-256
+    ///
+    /// 键为使用 `/` 分隔的相对路径，ver 自己写入的记录和激活脚本不计入。
+    fn hash_tree(version_dir: &Path) -> Result<std::collections::BTreeMap<String, String>> {
+        let mut files = std::collections::BTreeMap::new();
+        let mut pending = vec![version_dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(long_path(&dir))? {
+                let entry = entry?;
+                let path = dir.join(entry.file_name());
+                let file_type = entry.file_type()?;
+                if file_type.is_dir() {
+                    pending.push(path);
+                    continue;
                 }
-            },
-            ("rustup", VersionType::Rust) => {
-                // 尝试找到 rustup 安装目录
-                let rustup_home = if let Ok(dir) = env::var("RUSTUP_HOME") {
-                    PathBuf::from_str(&dir)?
-                } else {
-                    dirs::home_dir()
-                        .context("Could not find home directory")?
-                        .join(".rustup")
-                };
-                
-                let toolchains_dir = rustup_home.join("toolchains");
-                
-                if !toolchains_dir.exists() {
-                    return Err(anyhow::anyhow!("找不到 rustup 工具链目录"));
+                let relative = path.strip_prefix(version_dir)?
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if VER_METADATA_FILES.contains(&relative.as_str()) {
+                    continue;
                 }
-                
-                for entry in fs::read_dir(toolchains_dir)? {
-                    let entry = entry?;
-                    if entry.file_type()?.is_dir() {
-                        let toolchain = entry.file_name().to_string_lossy().to_string();
-                        if toolchain.contains("stable") {
-                            // 提取版本号
-                            let version = if let Some(idx) = toolchain.find('-') {
-                                toolchain[..idx].to_string()
-                            } else {
-                                toolchain.to_string()
-                            };
-                            
-                            // 检查是否已经安装
-                            let target_dir = self.get_version_dir(&version, version_type);
-                            if !target_dir.exists() {
-                                println!("Migrating Rust version {} from rustup...", version);
-                                // 复制文件
-                                let source_dir = entry.path();
-                                self.copy_dir_recursively(&source_dir, &target_dir)?;
-                                
-                                // 创建bin目录
-                                let bin_dir = target_dir.join("bin");
-                                fs::create_dir_all(&bin_dir)?;
-                                
-                                // 复制可执行文件
-                                let source_bin_dir = source_dir.join("bin");
-                                if source_bin_dir.exists() {
-                                    for bin_entry in fs::read_dir(&source_bin_dir)? {
-                                        let bin_entry = bin_entry?;
-                                        if bin_entry.file_type()?.is_file() {
-                                            let file_name = bin_entry.file_name();
-                                            let target_bin = bin_dir.join(&file_name);
-                                            fs::copy(bin_entry.path(), &target_bin)?;
-                                            
-                                            // 设置执行权限
-                                            if let OsType::Darwin | OsType::Linux = self.os_type {
-                                                let mut perms = fs::metadata(&target_bin)?.permissions();
-                                                perms.set_mode(0o755); // rwxr-xr-x
-                                                fs::set_permissions(&target_bin, perms)?;
-                                            }
-                                        }
-                                    }
-                                }
-                                
-                                migrated_count += 1;
-                            }
-                        }
-                    }
+                if let Some(digest) = Self::file_digest(&path, file_type)? {
+                    files.insert(relative, digest);
                 }
-            },
-            _ => return Err(anyhow::anyhow!("不支持的源版本管理器: {} for {}", source, version_type)),
+            }
         }
+        Ok(files)
+    }
+
+    /// 计算单个文件的摘要
+    ///
+    /// 符号链接记录为 `symlink:<目标>`，不跟随链接；目录等其他类型返回None。
+    fn file_digest(path: &Path, file_type: fs::FileType) -> Result<Option<String>> {
+        use sha2::{Digest, Sha256};
         
-        Ok(migrated_count)
+        if file_type.is_symlink() {
+            Ok(Some(format!("symlink:{}", fs::read_link(long_path(path))?.display())))
+        } else if file_type.is_file() {
+            let mut hasher = Sha256::new();
+            io::copy(&mut fs::File::open(long_path(path))?, &mut hasher)?;
+            Ok(Some(format!("{:x}", hasher.finalize())))
+        } else {
+            Ok(None)
+        }
     }
 
-    /// 递归复制目录
+    /// 生成版本的激活脚本
     ///
-    /// 递归复制源目录到目标目录。
+    /// 与 venv 类似，在版本目录中生成 activate、activate.fish 和 Activate.ps1，
+    /// 加载后将该版本的bin目录加入 PATH，调用 deactivate 恢复原来的环境。
+    /// 脚本只使用绝对路径，不依赖 ver 本身在 PATH 中。
     ///
     /// # 参数
     ///
-    /// * `src` - 源目录
-    /// * `dst` - 目标目录
+    /// * `version` - 版本目录名
+    /// * `version_type` - 版本类型
     ///
     /// # 返回
     ///
     /// 成功时返回Ok(()，失败时返回错误。
-    fn copy_dir_recursively(&self, src: &Path, dst: &Path) -> Result<()> {
-        if !dst.exists() {
-            fs::create_dir_all(dst)?;
+    fn write_activation_scripts(&self, version: &str, version_type: VersionType) -> Result<()> {
+        let version_dir = self.get_version_dir(version, version_type);
+        let bin_dir = self.get_bin_dir(version, version_type).to_string_lossy().to_string();
+        let tool_env = self.version_env(version, version_type);
+        let header = format!("Generated by ver for {} {}", version_type, version);
+        
+        let mut sh = format!("# {}\n# Usage: . \"{}\"\n\n", header, version_dir.join("activate").display());
+        sh.push_str("if [ -n \"${_VER_OLD_PATH:-}\" ]; then\n    PATH=\"$_VER_OLD_PATH\"\nfi\n");
+        sh.push_str("_VER_OLD_PATH=\"$PATH\"\n");
+        sh.push_str(&format!("PATH={}:\"$PATH\"\nexport PATH\n", sh_quote(&bin_dir)));
+        for (key, value) in &tool_env {
+            sh.push_str(&format!("export {}={}\n", key, sh_quote(value)));
         }
+        sh.push_str("\ndeactivate () {\n    PATH=\"$_VER_OLD_PATH\"\n    export PATH\n    unset _VER_OLD_PATH\n");
+        for (key, _) in &tool_env {
+            sh.push_str(&format!("    unset {}\n", key));
+        }
+        sh.push_str("    unset -f deactivate\n    hash -r 2>/dev/null\n}\n\nhash -r 2>/dev/null\n");
         
-        for entry in fs::read_dir(src)? {
-            let entry = entry?;
-            let file_type = entry.file_type()?;
-            let src_path = entry.path();
-            let dst_path = dst.join(entry.file_name());
+        let mut fish = format!("# {}\n# Usage: source \"{}\"\n\n", header, version_dir.join("activate.fish").display());
+        fish.push_str("if set -q _VER_OLD_PATH\n    set -gx PATH $_VER_OLD_PATH\nend\n");
+        fish.push_str("set -gx _VER_OLD_PATH $PATH\n");
+        fish.push_str(&format!("set -gx PATH {} $PATH\n", fish_quote(&bin_dir)));
+        for (key, value) in &tool_env {
+            fish.push_str(&format!("set -gx {} {}\n", key, fish_quote(value)));
+        }
+        fish.push_str("\nfunction deactivate\n    set -gx PATH $_VER_OLD_PATH\n    set -e _VER_OLD_PATH\n");
+        for (key, _) in &tool_env {
+            fish.push_str(&format!("    set -e {}\n", key));
+        }
+        fish.push_str("    functions -e deactivate\nend\n");
+        
+        let ps_quote = |value: &str| format!("'{}'", value.replace('\'', "''"));
+        let mut ps = format!("# {}\n# Usage: . \"{}\"\n\n", header, version_dir.join("Activate.ps1").display());
+        ps.push_str("if (Test-Path env:_VER_OLD_PATH) {\n    $env:PATH = $env:_VER_OLD_PATH\n}\n");
+        ps.push_str("$env:_VER_OLD_PATH = $env:PATH\n");
+        ps.push_str(&format!("$env:PATH = {} + [IO.Path]::PathSeparator + $env:PATH\n", ps_quote(&bin_dir)));
+        for (key, value) in &tool_env {
+            ps.push_str(&format!("$env:{} = {}\n", key, ps_quote(value)));
+        }
+        ps.push_str("\nfunction global:deactivate {\n    $env:PATH = $env:_VER_OLD_PATH\n    Remove-Item env:_VER_OLD_PATH\n");
+        for (key, _) in &tool_env {
+            ps.push_str(&format!("    Remove-Item env:{} -ErrorAction SilentlyContinue\n", key));
+        }
+        ps.push_str("    Remove-Item function:deactivate\n}\n");
+        
+        fs::write(long_path(&version_dir.join("activate")), sh)?;
+        fs::write(long_path(&version_dir.join("activate.fish")), fish)?;
+        fs::write(long_path(&version_dir.join("Activate.ps1")), ps)?;
+        Ok(())
+    }
+
+    /// 整理 Windows 上解压后的Python目录
+    ///
+    /// nuget 包的解释器位于 tools 目录，嵌入式包位于根目录，统一移动到 bin 目录，
+    /// 并为嵌入式包启用 site，使 pip 安装的包可以被导入。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_dir` - 版本目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn setup_windows_python(&self, version: &str, version_dir: &Path) -> Result<()> {
+        let bin_dir = version_dir.join("bin");
+        let tools_dir = version_dir.join("tools");
+        
+        if tools_dir.join("python.exe").exists() {
+            fs::rename(&tools_dir, &bin_dir)?;
+        } else if version_dir.join("python.exe").exists() {
+            fs::create_dir_all(&bin_dir)?;
+            for entry in fs::read_dir(version_dir)? {
+                let entry = entry?;
+                if entry.path() != bin_dir && entry.file_name() != INSTALL_RECEIPT_FILE {
+                    fs::rename(entry.path(), bin_dir.join(entry.file_name()))?;
+                }
+            }
             
-            if file_type.is_dir() {
-                self.copy_dir_recursively(&src_path, &dst_path)?;
-            } else if file_type.is_file() {
-                fs::copy(&src_path, &dst_path)?;
-            } else if file_type.is_symlink() {
-                let target = fs::read_link(&src_path)?;
-                std::os::unix::fs::symlink(target, &dst_path)?;
+            // 嵌入式包的 pythonXY._pth 默认注释掉了 import site
+            for entry in fs::read_dir(&bin_dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "_pth") {
+                    let content = fs::read_to_string(&path)?.replace("#import site", "import site");
+                    fs::write(&path, content)?;
+                }
             }
+            output::println("Note: the embeddable Python package does not include pip; install it with get-pip.py if needed.");
+        } else {
+            return Err(anyhow::anyhow!("在下载的Python {} 包中找不到 python.exe", version));
         }
         
         Ok(())
     }
 
-    /// 列出可用的版本
+    /// 从源码编译前检查系统依赖
     ///
-    /// 列出可用的版本信息。
+    /// 缺少编译器或 make 时 configure 会在很深的地方失败，在下载源码之前直接返回错误；
+    /// 只缺少可选依赖时列出受影响的功能和安装命令，继续安装。
     ///
     /// # 参数
     ///
-    /// * `lts_only` - 是否只列出LTS版本
     /// * `version_type` - 版本类型
     ///
     /// # 返回
     ///
-    /// 成功时返回版本信息列表，失败时返回错误。
-    pub async fn list_available_versions(&self, lts_only: bool, version_type: VersionType) -> Result<Vec<NodeVersion>> {
-        match version_type {
-            VersionType::Node => {
-                let client = reqwest::Client::new();
-                let response = client
-                    .get("https://nodejs.org/dist/index.json")
-                    .send()
-                    .await?
-                    .json::<Vec<NodeVersion>>()
-                    .await?;
-
-                let mut versions = if lts_only {
-                    response.into_iter().filter(|v| v.lts).collect::<Vec<_>>()
-                } else {
-                    response
-                };
-                
-                // 按版本号排序（从新到旧）
-                versions.sort_by(|a, b| {
-                    let a_parts: Vec<&str> = a.version.trim_start_matches('v').split('.').collect();
-                    let b_parts: Vec<&str> = b.version.trim_start_matches('v').split('.').collect();
-                    
-                    for i in 0..std::cmp::min(a_parts.len(), b_parts.len()) {
-                        let a_num = a_parts[i].parse::<i32>().unwrap_or(0);
-                        let b_num = b_parts[i].parse::<i32>().unwrap_or(0);
-                        
-                        if a_num != b_num {
-                            return b_num.cmp(&a_num); // 从新到旧排序
-                        }
-                    }
-                    
-                    b_parts.len().cmp(&a_parts.len())
-                });
+    /// 必需的依赖都存在时返回Ok(()，否则返回错误。
+    fn preflight_source_build(&self, version_type: VersionType) -> Result<()> {
+        let missing: Vec<&prereqs::Prereq> = prereqs::check(version_type).into_iter()
+            .filter(|check| check.found.is_none())
+            .map(|check| check.prereq)
+            .collect();
+        let required: Vec<&str> = missing.iter().filter(|prereq| prereq.required).map(|prereq| prereq.name).collect();
+        if !required.is_empty() {
+            return Err(anyhow::anyhow!("缺少从源码编译{}所需的 {}，运行 `ver prereqs {}` 查看安装方法", version_type, required.join(", "), version_type.id()));
+        }
+        if !missing.is_empty() {
+            output::println("Missing optional build dependencies:");
+            for prereq in &missing {
+                output::println(format_args!("  {} ({})", prereq.name, prereq.purpose));
+            }
+            let commands = prereqs::package_manager()
+                .map(|manager| prereqs::install_commands(manager, &missing))
+                .unwrap_or_default();
+            if commands.is_empty() {
+                output::println("Install the development packages for them with your package manager.");
+            } else {
+                output::println(format_args!("Install them with:\n  {}", commands.join("\n  ")));
+            }
+            output::println("Continuing without them.");
+        }
+        Ok(())
+    }
 
-                Ok(versions)
-            },
-            VersionType::Rust => {
-                // 获取Rust版本列表
-                let client = reqwest::Client::new();
-                let response = client
-                    .get("https://static.rust-lang.org/dist/channel-rust-stable.toml")
-                    .send()
-                    .await?
-                    .text()
-                    .await?;
-                
-                // 简单解析TOML获取版本号
-                let mut versions = Vec::new();
-                let mut version = String::new();
-                
-                for line in response.lines() {
-                    if line.starts_with("version = ") {
-                        if let Some(v) = line.split('"').nth(1) {
-                            version = v.to_string();
-                            break;
-                        }
-                    }
-                }
-                
-                if !version.is_empty() {
-                    versions.push(NodeVersion {
-                        version: version.clone(),
-                        lts: true,
-                        date: chrono::Utc::now().format("%Y-%m-%d").to_string(),
-                        files: vec![],
-                    });
-                }
-                
-                // 获取其他版本
-                if !lts_only {
-                    let response = client
-                        .get("https://static.rust-lang.org/dist/")
-                        .send()
-                        .await?
-                        .text()
-                        .await?;
-                    
-                    // 简单解析HTML获取版本号
-                    for line in response.lines() {
-                        if line.contains("rust-") && line.contains(".tar.gz") && !line.contains("beta") && !line.contains("nightly") {
-                            if let Some(start) = line.find("rust-") {
-                                if let Some(end) = line[start..].find(".tar.gz") {
-                                    let v = &line[start + 5..start + end];
-                                    if v.contains('-') {
-                                        continue; // 跳过带有平台信息的文件
-                                    }
-                                    
-                                    if !versions.iter().any(|existing: &NodeVersion| existing.version == v) {
-                                        versions.push(NodeVersion {
-                                            version: v.to_string(),
-                                            lts: false,
-                                            date: "".to_string(),
-                                            files: vec![],
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
+    /// 从源码编译安装Python
+    ///
+    /// 编译依赖在下载前由 `preflight_source_build` 检查，编译后检查实际缺失的模块。
+    /// 在阻塞线程中执行，取消时终止正在运行的编译命令。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号（不带变体后缀）
+    /// * `build` - 要编译的变体
+    /// * `version_dir` - 版本目录（源码已解压到其中）
+    /// * `prefix` - 最终的版本目录，修复时与暂存的 `version_dir` 不同
+    /// * `quiet` - 不显示编译输出
+    /// * `cancel` - 取消标记
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回被跳过的可选模块列表，失败或被取消时返回错误。
+    fn build_python_from_source(version: &str, build: &BuildFlavor, version_dir: &Path, prefix: &Path, quiet: bool, cancel: &CancelToken) -> Result<Vec<String>> {
+        let source_dir = version_dir.join(format!("Python-{}", version));
+        if !source_dir.join("configure").exists() {
+            return Err(anyhow::anyhow!("在 {} 中找不到Python源码", source_dir.display()));
+        }
+        
+        let jobs = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        // 脚本的 shebang 等路径按最终目录生成，安装到暂存目录时通过 DESTDIR 写入
+        let destdir = (prefix != version_dir).then(|| version_dir.join(".destdir"));
+        let mut install_args = vec!["install".to_string()];
+        if let Some(destdir) = &destdir {
+            install_args.push(format!("DESTDIR={}", destdir.display()));
+        }
+        let mut configure_args = vec![format!("--prefix={}", prefix.display())];
+        if build.free_threaded {
+            configure_args.push("--disable-gil".to_string());
+        }
+        if build.debug {
+            configure_args.push("--with-pydebug".to_string());
+        }
+        let steps: [(&str, Vec<String>); 3] = [
+            ("./configure", configure_args),
+            ("make", vec![format!("-j{}", jobs)]),
+            ("make", install_args),
+        ];
+        for (program, args) in steps {
+            cancel.check()?;
+            let mut command = Command::new(program);
+            command.args(&args).current_dir(&source_dir);
+            if quiet {
+                command.stdout(std::process::Stdio::null());
+            }
+            let mut child = command
+                .spawn()
+                .with_context(|| format!("无法运行 {}", program))?;
+            let status = loop {
+                if let Some(status) = child.try_wait()? {
+                    break status;
                 }
-                
-                // 按版本号排序
-                versions.sort_by(|a, b| {
-                    let a_parts: Vec<&str> = a.version.split('.').collect();
-                    let b_parts: Vec<&str> = b.version.split('.').collect();
-                    
-                    for i in 0..std::cmp::min(a_parts.len(), b_parts.len()) {
-                        let a_num = a_parts[i].parse::<i32>().unwrap_or(0);
-                        let b_num = b_parts[i].parse::<i32>().unwrap_or(0);
-                        
-                        if a_num != b_num {
-                            return b_num.cmp(&a_num); // 从新到旧排序
-                        }
-                    }
-                    
-                    b_parts.len().cmp(&a_parts.len())
-                });
-                
-                Ok(versions)
-            },
-            VersionType::Python => {
-                // 获取Python版本列表
-                let client = reqwest::Client::new();
-                let response = client
-                    .get("https://www.python.org/ftp/python/")
-                    .send()
-                    .await?
-                    .text()
-                    .await?;
-                
-                // 简单解析HTML获取版本号
-                let mut versions = Vec::new();
-                for line in response.lines() {
-                    if line.contains("href=\"") && line.contains("/\"") {
-                        if let Some(start) = line.find("href=\"") {
-                            if let Some(end) = line[start + 6..].find("\"") {
-                                let version = &line[start + 6..start + 6 + end];
-                                if version.ends_with('/') && version.chars().any(|c| c.is_digit(10)) {
-                                    let version = version.trim_end_matches('/');
-                                    if !versions.iter().any(|existing: &NodeVersion| existing.version == version) {
-                                        versions.push(NodeVersion {
-                                            version: version.to_string(),
-                                            lts: false,
-                                            date: "".to_string(),
-                                            files: vec![],
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
+                if cancel.check().is_err() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(VersionError::Cancelled.into());
                 }
-                
-                // 按版本号排序
-                versions.sort_by(|a, b| {
-                    let a_parts: Vec<&str> = a.version.split('.').collect();
-                    let b_parts: Vec<&str> = b.version.split('.').collect();
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            };
+            if !status.success() {
+                return Err(anyhow::anyhow!("Python编译失败（{} {}），退出码: {}", program, args.join(" "), status));
+            }
+        }
+        
+        if let Some(destdir) = &destdir {
+            let installed = destdir.join(prefix.strip_prefix("/").unwrap_or(prefix));
+            for entry in fs::read_dir(&installed)? {
+                let entry = entry?;
+                fs::rename(entry.path(), version_dir.join(entry.file_name()))?;
+            }
+            fs::remove_dir_all(destdir)?;
+        }
+        
+        // make install 只生成 python3，补一个 python 入口
+        let bin_dir = version_dir.join("bin");
+        // free-threaded 构建的可执行文件名带 t 后缀，例如 python3t
+        if build.free_threaded && !bin_dir.join("python3").exists() && bin_dir.join("python3t").exists() {
+            create_symlink(Path::new("python3t"), &bin_dir.join("python3"))?;
+        }
+        let python = bin_dir.join("python");
+        if !python.exists() {
+            create_symlink(Path::new("python3"), &python)?;
+        }
+        fs::remove_dir_all(&source_dir)?;
+        
+        // 以实际能否导入为准，头文件检查可能漏掉或误报
+        let skipped: Vec<String> = prereqs::prereqs(VersionType::Python).iter()
+            .filter_map(|prereq| prereq.module)
+            .filter(|module| !Command::new(bin_dir.join("python3"))
+                .args(["-c", &format!("import {}", module)])
+                .stderr(std::process::Stdio::null())
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false))
+            .map(|module| module.to_string())
+            .collect();
+        if !skipped.is_empty() {
+            output::println(format_args!("Python {} was built without: {}", version, skipped.join(", ")));
+        }
+        
+        Ok(skipped)
+    }
+
+    /// 使用指定版本
+    ///
+    /// 切换到指定版本。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    pub fn use_version(&mut self, version: &str, version_type: VersionType) -> Result<()> {
+        if !self.manages_tool(version_type) {
+            return Err(anyhow::anyhow!(
+                "配置项 manage 中没有 {}，ver 不为它创建全局链接；用 `ver {} exec` 或 `ver shell` 临时使用，或把它加入 manage",
+                version_type.id(), version_type.id()
+            ));
+        }
+        let version_dir = self.get_version_dir(version, version_type);
+        if !version_dir.exists() {
+            return Err(VersionError::NotInstalled(version.to_string(), version_type).into());
+        }
+
+        self.check_policy(BuildFlavor::split(Self::strip_arch_suffix(version), version_type).0, version_type)?;
+        self.check_build_flavor(version, version_type)?;
+        
+        // 锁定的版本在切换前校验文件是否被改动
+        if self.is_locked(version, version_type) {
+            self.verify_locked_version(version, version_type)?;
+        }
+        
+        // 旧版本ver安装的目录中没有激活脚本，切换时补上
+        if !version_dir.join("activate").exists() {
+            self.write_activation_scripts(version, version_type)?;
+        }
+        
+        // Update symlinks
+        fs::create_dir_all(&self.bin_dir)?;
+
+        // Remove existing symlinks and shims
+        for entry in fs::read_dir(&self.bin_dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_symlink() || Self::is_rust_shim(&entry.path()) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+
+        // Determine the bin directory based on OS and architecture
+        let os_arch_suffix = self.platform_suffix(version_type)?;
+        
+        let bin_dir = self.get_bin_dir(version, version_type);
+        
+        // Create symlinks for all binaries in that directory
+        // npm/npx 以及 corepack 生成的 yarn/pnpm 本身就是符号链接，也需要链接
+        if bin_dir.exists() {
+            for entry in fs::read_dir(&bin_dir)? {
+                let entry = entry?;
+                let file_type = entry.file_type()?;
+                if file_type.is_file() || file_type.is_symlink() {
+                    let file_name = entry.file_name();
+                    let target_path = self.bin_dir.join(&file_name);
                     
-                    for i in 0..std::cmp::min(a_parts.len(), b_parts.len()) {
-                        let a_num = a_parts[i].parse::<i32>().unwrap_or(0);
-                        let b_num = b_parts[i].parse::<i32>().unwrap_or(0);
-                        
-                        if a_num != b_num {
-                            return b_num.cmp(&a_num); // 从新到旧排序
-                        }
+                    // Windows 上Python的bin目录中还有DLL和标准库，只为可执行文件生成启动脚本
+                    if self.os_type == OsType::Windows
+                        && version_type == VersionType::Python
+                        && !file_name.to_string_lossy().ends_with(".exe")
+                    {
+                        continue;
                     }
                     
-                    b_parts.len().cmp(&a_parts.len())
-                });
-                
-                Ok(versions)
-            },
-            VersionType::Go => {
-                // 获取Go版本列表
-                let client = reqwest::Client::new();
-                let response = client
-                    .get("https://golang.org/dl/")
-                    .send()
-                    .await?
-                    .text()
-                    .await?;
-                
-                // 简单解析HTML获取版本号
-                let mut versions = Vec::new();
-                for line in response.lines() {
-                    if line.contains("go") && line.contains("toggleVisible") {
-                        if let Some(start) = line.find("go") {
-                            if let Some(end) = line[start..].find(" ") {
-                                let version = &line[start + 2..start + end];
-                                if version.chars().any(|c| c.is_digit(10)) && !version.contains("beta") && !version.contains("rc") {
-                                    if !versions.iter().any(|existing: &NodeVersion| existing.version == version) {
-                                        versions.push(NodeVersion {
-                                            version: version.to_string(),
-                                            lts: false,
-                                            date: "".to_string(),
-                                            files: vec![],
-                                        });
-                                    }
-                                }
-                            }
+                    match self.os_type {
+                        OsType::Windows => {
+                            // 在 Windows 上，创建一个 .cmd 文件来启动相应的程序
+                            let cmd_content = match version_type {
+                                VersionType::Node => format!(
+                                    "@echo off\r\n\"%~dp0\\..\\versions\\{}\\node-v{}-{}\\bin\\{}{}\" %*\r\n",
+                                    version, version, os_arch_suffix, file_name.to_string_lossy(), self.get_exe_extension()
+                                ),
+                                VersionType::Rust => format!(
+                                    "@echo off\r\n\"%~dp0\\..\\versions\\{}\\bin\\{}{}\" %*\r\n",
+                                    version, file_name.to_string_lossy(), self.get_exe_extension()
+                                ),
+                                VersionType::Python => format!(
+                                    "@echo off\r\n\"%~dp0\\..\\versions\\{}\\bin\\{}\" %*\r\n",
+                                    version, file_name.to_string_lossy()
+                                ),
+                                VersionType::Go => format!(
+                                    "@echo off\r\n\"%~dp0\\..\\versions\\{}\\bin\\{}{}\" %*\r\n",
+                                    version, file_name.to_string_lossy(), self.get_exe_extension()
+                                ),
+                            };
+                            fs::write(long_path(&target_path.with_extension("cmd")), cmd_content)?;
+                        },
+                        _ if version_type == VersionType::Rust => {
+                            // Rust 使用代理脚本，以支持 `cargo +1.78 build` 和 RUSTUP_TOOLCHAIN
+                            self.write_rust_shim(&target_path, &file_name.to_string_lossy())?;
                         }
-                    }
-                }
-                
-                // 按版本号排序
-                versions.sort_by(|a, b| {
-                    let a_parts: Vec<&str> = a.version.split('.').collect();
-                    let b_parts: Vec<&str> = b.version.split('.').collect();
-                    
-                    for i in 0..std::cmp::min(a_parts.len(), b_parts.len()) {
-                        let a_num = a_parts[i].parse::<i32>().unwrap_or(0);
-                        let b_num = b_parts[i].parse::<i32>().unwrap_or(0);
-                        
-                        if a_num != b_num {
-                            return b_num.cmp(&a_num); // 从新到旧排序
+                        _ => {
+                            // 在 Unix 系统上创建符号链接
+                            create_symlink(&entry.path(), &target_path)?;
                         }
                     }
-                    
-                    b_parts.len().cmp(&a_parts.len())
-                });
-                
-                Ok(versions)
+                }
+            }
+        } else {
+            return Err(anyhow::anyhow!("找不到二进制目录"));
+        }
+
+        // Update PATH in shell config
+        self.update_shell_config()?;
+
+        // Save and update current version
+        self.save_current_version(version, version_type)?;
+        self.current_version = Some(version.to_string());
+        self.current_version_type = version_type;
+        self.update_env_file()?;
+
+        output::println(format_args!("Switched to {} version {}", version_type, version));
+        
+        if version_type == VersionType::Node {
+            let shims = self.corepack_shims(version)?;
+            if !shims.is_empty() {
+                output::println(format_args!("Using corepack-managed {} from Node.js {}", shims.join(", "), version));
             }
+            self.warn_npm_prefix_override(version);
         }
+        if version_type == VersionType::Python && self.os_type == OsType::Windows {
+            self.warn_py_launcher(version);
+        }
+        Ok(())
     }
 
-    /// 安装最新版本
+    /// 提示Windows的py启动器不会使用 ver 管理的Python
     ///
-    /// 安装最新版本。
+    /// py.exe 通过注册表发现Python安装，ver 不写注册表，因此二者互不影响。
     ///
     /// # 参数
     ///
-    /// * `version_type` - 版本类型
-    ///
-    /// # 返回
-    ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub async fn install_latest(&mut self, version_type: VersionType) -> Result<()> {
-        let versions = self.list_available_versions(false, version_type).await?;
-        
-        if let Some(latest) = versions.first() {
-            println!("Latest {} version: {}", version_type, latest.version);
-            self.install_version(&latest.version, version_type).await?;
-            Ok(())
-        } else {
-            return Err(anyhow::anyhow!("找不到最新的 {} 版本", version_type));
+    /// * `version` - 当前切换到的Python版本
+    fn warn_py_launcher(&self, version: &str) {
+        let path_var = env::var("PATH").unwrap_or_default();
+        if env::split_paths(&path_var).any(|dir| dir.join("py.exe").exists()) {
+            output::println(format_args!("Note: the Windows `py` launcher only sees registry-installed Pythons and will not use Python {}.", version));
+            output::println(format_args!("Use `python` or `ver python exec {} -- ...` to run the ver-managed version.", version));
         }
     }
 
-    /// 安装最新的LTS版本
+    /// 判断文件是否为 ver 生成的Rust代理脚本
+    fn is_rust_shim(path: &Path) -> bool {
+        fs::read_to_string(path)
+            .map(|content| content.lines().nth(1) == Some(RUST_SHIM_MARKER))
+            .unwrap_or(false)
+    }
+
+    /// 生成Rust代理脚本
     ///
-    /// 安装最新的LTS版本。
+    /// 脚本把调用转发给 `ver rust proxy`，由它解析 `+toolchain` 参数和 RUSTUP_TOOLCHAIN。
     ///
     /// # 参数
     ///
-    /// * `version_type` - 版本类型
+    /// * `path` - 脚本路径
+    /// * `binary` - 被代理的可执行文件名，例如 `cargo`
     ///
     /// # 返回
     ///
     /// 成功时返回Ok(()，失败时返回错误。
-    pub async fn install_latest_lts(&mut self, version_type: VersionType) -> Result<()> {
-        let versions = self.list_available_versions(true, version_type).await?;
+    fn write_rust_shim(&self, path: &Path, binary: &str) -> Result<()> {
+        let ver = env::current_exe().context("无法获取 ver 可执行文件路径")?;
+        let content = format!(
+            "#!/bin/sh\n{}\nexec \"{}\" rust proxy {} \"$@\"\n",
+            RUST_SHIM_MARKER, ver.display(), binary
+        );
+        fs::write(path, content)?;
         
-        if let Some(latest_lts) = versions.first() {
-            println!("Latest LTS {} version: {}", version_type, latest_lts.version);
-            self.install_version(&latest_lts.version, version_type).await?;
-            Ok(())
-        } else {
-            return Err(anyhow::anyhow!("找不到最新的 LTS {} 版本", version_type));
+        #[cfg(unix)]
+        {
+            let mut perms = fs::metadata(path)?.permissions();
+            perms.set_mode(0o755); // rwxr-xr-x
+            fs::set_permissions(path, perms)?;
         }
+        Ok(())
     }
 
-    /// 安装指定版本
+    /// 以rustup兼容的方式运行Rust工具
     ///
-    /// 安装指定版本。
+    /// 工具链按以下顺序确定：第一个参数中的 `+toolchain`、RUSTUP_TOOLCHAIN 环境变量、
+    /// 当前目录的 .rust-version 文件、全局版本。工具链可以是版本号或别名。
+    /// 子进程会继承 RUSTUP_TOOLCHAIN，保证 cargo 调用的 rustc 使用同一工具链。
     ///
     /// # 参数
     ///
-    /// * `version` - 版本号
-    /// * `version_type` - 版本类型
+    /// * `binary` - 要运行的可执行文件名，例如 `cargo`
+    /// * `args` - 传给可执行文件的参数
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub async fn install_version(&self, version: &str, version_type: VersionType) -> Result<()> {
-        let version_dir = self.get_version_dir(version, version_type);
-        if version_dir.exists() {
-            println!("Version {} is already installed", version);
-            return Ok(());
-        }
-
-        // Create version directory
-        fs::create_dir_all(&version_dir)?;
-
-        // Determine appropriate URL based on OS and architecture
-        let os_arch_suffix = match version_type {
-            VersionType::Node => self.get_os_arch_suffix(),
-            VersionType::Rust => {
-                match (&self.os_type, &self.arch_type) {
-                    (OsType::Darwin, ArchType::X64) => "x86_64-apple-darwin",
-                    (OsType::Darwin, ArchType::Arm64) => "aarch64-apple-darwin",
-                    (OsType::Linux, ArchType::X64) => "x86_64-unknown-linux-gnu",
-                    (OsType::Linux, ArchType::Arm64) => "aarch64-unknown-linux-gnu",
-                    (OsType::Linux, ArchType::Arm) => "linux-armv7l",
-                    (OsType::Windows, ArchType::X64) => "x86_64-pc-windows-msvc",
-                    (OsType::Windows, ArchType::X86) => "i686-pc-windows-msvc",
-                    _ => "unknown",
-                }.to_string()
-            },
-            VersionType::Python => {
-                match (&self.os_type, &self.arch_type) {
-                    (OsType::Darwin, ArchType::X64) => "macosx10.9.x86_64",
-                    (OsType::Darwin, ArchType::Arm64) => "macos11.0.arm64",
-                    (OsType::Linux, ArchType::X64) => "x86_64",
-                    (OsType::Linux, ArchType::Arm64) => "aarch64",
-                    (OsType::Linux, ArchType::Arm) => "armv7l",
-                    (OsType::Windows, ArchType::X64) => "amd64",
-                    (OsType::Windows, ArchType::X86) => "win32",
-                    _ => "unknown",
-                }.to_string()
-            },
-            VersionType::Go => {
-                match (&self.os_type, &self.arch_type) {
-                    (OsType::Darwin, ArchType::X64) => "darwin-amd64",
-                    (OsType::Darwin, ArchType::Arm64) => "darwin-arm64",
-                    (OsType::Linux, ArchType::X64) => "linux-amd64",
-                    (OsType::Linux, ArchType::Arm64) => "linux-arm64",
-                    (OsType::Linux, ArchType::Arm) => "linux-armv6l",
-                    (OsType::Windows, ArchType::X64) => "windows-amd64",
-                    (OsType::Windows, ArchType::X86) => "windows-386",
-                    _ => "unknown",
-                }.to_string()
+    /// 成功时返回子进程的退出码，失败时返回错误。
+    pub fn run_rust_proxy(&self, binary: &str, args: &[String]) -> Result<i32> {
+        let (toolchain, args) = match args.first().and_then(|arg| arg.strip_prefix('+')) {
+            Some(toolchain) => {
+                Self::trace_resolution(&format!("toolchain from command line: +{}", toolchain));
+                (toolchain.to_string(), &args[1..])
+            }
+            None => {
+                let toolchain = match env::var("RUSTUP_TOOLCHAIN") {
+                    Ok(toolchain) if !toolchain.is_empty() => {
+                        Self::trace_resolution(&format!("toolchain from RUSTUP_TOOLCHAIN: {}", toolchain));
+                        toolchain
+                    }
+                    _ => match self.resolve_version(VersionType::Rust, &env::current_dir()?)? {
+                        Some((version, _)) => version,
+                        None => return Err(VersionError::NoVersionConfigured(VersionType::Rust).into()),
+                    },
+                };
+                (toolchain, args)
             }
         };
         
-        let extension = match self.os_type {
-            OsType::Windows => ".zip",
-            _ => ".tar.gz",
-        };
-
-        let url = match version_type {
-            VersionType::Node => format!(
-                "https://nodejs.org/dist/v{}/node-v{}-{}{}",
-                version, version, os_arch_suffix, extension
-            ),
-            VersionType::Rust => format!(
-                "https://static.rust-lang.org/dist/rust-{}-{}{}",
-                version, os_arch_suffix, extension
-            ),
-            VersionType::Python => format!(
-                "https://www.python.org/ftp/python/{}/Python-{}-{}.tar.xz",
-                version, version, os_arch_suffix
-            ),
-            VersionType::Go => format!(
-                "https://golang.org/dl/go{}.{}",
-                version, os_arch_suffix
-            ),
+        let version = if self.get_version_dir(&toolchain, VersionType::Rust).exists() {
+            toolchain
+        } else {
+            self.get_alias(&toolchain, VersionType::Rust)?
+                .ok_or(VersionError::NotInstalled(toolchain, VersionType::Rust))?
         };
-
-        println!("Downloading {} v{} for {}...", version_type, version, os_arch_suffix);
-        
-        // Create a progress bar for download
-        let client = reqwest::Client::new();
-        let response = client.get(&url).send().await?;
-        let total_size = response.content_length().unwrap_or(0);
-        
-        let pb = indicatif::ProgressBar::new(total_size);
-        pb.set_style(indicatif::ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-            .unwrap()
-            .progress_chars("#>-"));
-        
-        // Download to a temporary file
-        let temp_file = self.cache_dir.join(format!("{}{}", version, extension));
-        let mut file = fs::File::create(&temp_file)?;
-        let mut downloaded: u64 = 0;
-        let mut stream = response.bytes_stream();
-        
-        while let Some(item) = stream.next().await {
-            let chunk = item?;
-            file.write_all(&chunk)?;
-            let new = std::cmp::min(downloaded + (chunk.len() as u64), total_size);
-            downloaded = new;
-            pb.set_position(new);
+        if !self.get_version_dir(&version, VersionType::Rust).exists() {
+            return Err(VersionError::NotInstalled(version, VersionType::Rust).into());
         }
         
-        pb.finish_with_message(format!("Downloaded {} v{}", version_type, version));
+        self.record_usage(VersionType::Rust, &version, &env::current_dir()?);
         
-        println!("Extracting...");
+        let bin_path = self.get_bin_dir(&version, VersionType::Rust);
+        let path_var = env::var("PATH").unwrap_or_default();
+        let new_path = format!("{}:{}", bin_path.to_string_lossy(), path_var);
         
-        // Extract based on the file type
-        match extension {
-            ".tar.gz" => {
-                let file = fs::File::open(&temp_file)?;
-                let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
-                archive.unpack(&version_dir)?;
-            },
-            ".zip" => {
-                let file = fs::File::open(&temp_file)?;
-                let mut archive = zip::ZipArchive::new(file)?;
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i)?;
-                    let outpath = version_dir.join(file.name());
-                    
-                    if file.name().ends_with('/') {
-                        fs::create_dir_all(&outpath)?;
-                    } else {
-                        if let Some(p) = outpath.parent() {
-                            if !p.exists() {
-                                fs::create_dir_all(p)?;
-                            }
-                        }
-                        let mut outfile = fs::File::create(&outpath)?;
-                        io::copy(&mut file, &mut outfile)?;
-                    }
-                }
-            },
-            _ => return Err(anyhow::anyhow!("不支持的压缩文件格式: {}", extension)),
-        }
+        let status = Command::new(bin_path.join(format!("{}{}", binary, self.get_exe_extension())))
+            .args(args)
+            .env("PATH", new_path)
+            .env("RUSTUP_TOOLCHAIN", &version)
+            .envs(self.tool_env(VersionType::Rust))
+            .status()
+            .with_context(|| format!("无法运行 {} (Rust {})", binary, version))?;
         
-        // 特殊处理Rust安装
-        if version_type == VersionType::Rust {
-            // 运行安装脚本
-            let install_script = match self.os_type {
-                OsType::Windows => version_dir.join(format!("rust-{}-{}/install.bat", version, os_arch_suffix)),
-                _ => version_dir.join(format!("rust-{}-{}/install.sh", version, os_arch_suffix)),
-            };
-            
-            if install_script.exists() {
-                println!("Running Rust installation script...");
-                
-                let status = match self.os_type {
-                    OsType::Windows => {
-                        Command::new("cmd")
-                            .arg("/C")
-                            .arg(&install_script)
-                            .arg("--prefix")
-                            .arg(&version_dir)
-                            .arg("--without=rust-docs")
-                            .status()?
-                    },
-                    _ => {
-                        Command::new("sh")
-                            .arg(&install_script)
-                            .arg("--prefix")
-                            .arg(&version_dir)
-                            .arg("--without=rust-docs")
-                            .status()?
-                    }
-                };
-                
-                if !status.success() {
-                    return Err(anyhow::anyhow!("Rust安装脚本执行失败，退出码: {}", status));
-                }
-            } else {
-                println!("No installation script found, trying to set up manually...");
-                // 手动设置bin目录
-                let bin_dir = version_dir.join("bin");
-                fs::create_dir_all(&bin_dir)?;
-                
-                // 查找并移动可执行文件
-                let rust_bin_dir = match self.os_type {
-                    OsType::Windows => version_dir.join(format!("rust-{}-{}/rustc/bin", version, os_arch_suffix)),
-                    _ => version_dir.join(format!("rust-{}-{}/rustc/bin", version, os_arch_suffix)),
-                };
-                
-                if rust_bin_dir.exists() {
-                    for entry in fs::read_dir(&rust_bin_dir)? {
-                        let entry = entry?;
-                        if entry.file_type()?.is_file() {
-                            let file_name = entry.file_name();
-                            let target_bin = bin_dir.join(&file_name);
-                            fs::copy(entry.path(), &target_bin)?;
-                            
-                            // 设置执行权限
-                            if let OsType::Darwin | OsType::Linux = self.os_type {
-                                let mut perms = fs::metadata(&target_bin)?.permissions();
-                                perms.set_mode(0o755); // rwxr-xr-x
-                                fs::set_permissions(&target_bin, perms)?;
-                            }
-                        }
-                    }
-                }
-                
-                // 复制cargo可执行文件
-                let cargo_bin_dir = match self.os_type {
-                    OsType::Windows => version_dir.join(format!("rust-{}-{}/cargo/bin", version, os_arch_suffix)),
-                    _ => version_dir.join(format!("rust-{}-{}/cargo/bin", version, os_arch_suffix)),
-                };
-                
-                if cargo_bin_dir.exists() {
-                    for entry in fs::read_dir(&cargo_bin_dir)? {
-                        let entry = entry?;
-                        if entry.file_type()?.is_file() {
-                            let file_name = entry.file_name();
-                            let target_bin = bin_dir.join(&file_name);
-                            fs::copy(entry.path(), &target_bin)?;
-                            
-                            // 设置执行权限
-                            if let OsType::Darwin | OsType::Linux = self.os_type {
-                                let mut perms = fs::metadata(&target_bin)?.permissions();
-                                perms.set_mode(0o755); // rwxr-xr-x
-                                fs::set_permissions(&target_bin, perms)?;
-                            }
-                        }
-                    }
-                }
-            }
+        Ok(status.code().unwrap_or(1))
+    }
+
+    /// 列出Node.js版本中由corepack管理的包管理器入口
+    ///
+    /// # 参数
+    ///
+    /// * `version` - Node.js版本
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回入口名称列表（例如 yarn、pnpm），失败时返回错误。
+    pub fn corepack_shims(&self, version: &str) -> Result<Vec<String>> {
+        let bin_dir = self.get_bin_dir(version, VersionType::Node);
+        let mut shims = Vec::new();
+        if !bin_dir.exists() {
+            return Ok(shims);
         }
         
-        // 特殊处理Python安装
-        if version_type == VersionType::Python {
-            // 手动设置bin目录
-            let bin_dir = version_dir.join("bin");
-            fs::create_dir_all(&bin_dir)?;
-            
-            // 查找并移动可执行文件
-            let python_bin_dir = match self.os_type {
-                OsType::Windows => version_dir.join(format!("Python-{}-{}/python.exe", version, os_arch_suffix)),
-                _ => version_dir.join(format!("Python-{}-{}/bin/python{}", version, os_arch_suffix, self.get_exe_extension())),
-            };
-            
-            if python_bin_dir.exists() {
-                let target_bin = bin_dir.join("python");
-                fs::copy(python_bin_dir, &target_bin)?;
-                
-                // 设置执行权限
-                if let OsType::Darwin | OsType::Linux = self.os_type {
-                    let mut perms = fs::metadata(&target_bin)?.permissions();
-                    perms.set_mode(0o755); // rwxr-xr-x
-                    fs::set_permissions(&target_bin, perms)?;
-                }
+        for entry in fs::read_dir(&bin_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("corepack") {
+                continue;
             }
-        }
-        
-        // 特殊处理Go安装
-        if version_type == VersionType::Go {
-            // 手动设置bin目录
-            let bin_dir = version_dir.join("bin");
-            fs::create_dir_all(&bin_dir)?;
             
-            // 查找并移动可执行文件
-            let go_bin_dir = match self.os_type {
-                OsType::Windows => version_dir.join(format!("go{}-{}.exe", version, os_arch_suffix)),
-                _ => version_dir.join(format!("go{}-{}{}", version, os_arch_suffix, self.get_exe_extension())),
-            };
-            
-            if go_bin_dir.exists() {
-                let target_bin = bin_dir.join("go");
-                fs::copy(go_bin_dir, &target_bin)?;
-                
-                // 设置执行权限
-                if let OsType::Darwin | OsType::Linux = self.os_type {
-                    let mut perms = fs::metadata(&target_bin)?.permissions();
-                    perms.set_mode(0o755); // rwxr-xr-x
-                    fs::set_permissions(&target_bin, perms)?;
-                }
-            }
-        }
-        
-        // Set executable permissions for binaries on Unix-like systems
-        if let OsType::Darwin | OsType::Linux = self.os_type {
-            let bin_dir = match version_type {
-                VersionType::Node => version_dir.join(format!("node-v{}-{}/bin", version, os_arch_suffix)),
-                VersionType::Rust => version_dir.join("bin"),
-                VersionType::Python => version_dir.join("bin"),
-                VersionType::Go => version_dir.join("bin"),
-            };
-            if bin_dir.exists() {
-                for entry in fs::read_dir(bin_dir)? {
-                    let entry = entry?;
-                    let path = entry.path();
-                    if path.is_file() {
-                        let mut perms = fs::metadata(&path)?.permissions();
-                        perms.set_mode(0o755); // rwxr-xr-x
-                        fs::set_permissions(&path, perms)?;
-                    }
-                }
+            // Unix 上是指向 corepack/dist 的符号链接，Windows 上是引用它的 .cmd 脚本
+            let managed = match fs::read_link(entry.path()) {
+                Ok(target) => target.to_string_lossy().contains("corepack"),
+                Err(_) => fs::read_to_string(entry.path())
+                    .map(|content| content.contains("corepack"))
+                    .unwrap_or(false),
+            };
+            if managed {
+                shims.push(name);
             }
         }
-
-        println!("Successfully installed {} version {}", version_type, version);
-        Ok(())
+        
+        shims.sort();
+        Ok(shims)
     }
 
-    /// 使用指定版本
+    /// 为指定Node.js版本启用或禁用corepack
     ///
-    /// 切换到指定版本。
+    /// 把 corepack 的入口安装到该版本自己的bin目录中，
+    /// 如果该版本正在使用，会重新链接以便新入口立即生效。
     ///
     /// # 参数
     ///
-    /// * `version` - 版本号
-    /// * `version_type` - 版本类型
+    /// * `version` - Node.js版本
+    /// * `enable` - true 为启用，false 为禁用
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub fn use_version(&mut self, version: &str, version_type: VersionType) -> Result<()> {
-        let version_dir = self.get_version_dir(version, version_type);
-        if !version_dir.exists() {
-            return Err(anyhow::anyhow!("{}", VersionError::NotInstalled(version.to_string(), version_type)));
+    /// 成功时返回操作后由corepack管理的入口列表，失败时返回错误。
+    pub fn set_corepack_enabled(&mut self, version: &str, enable: bool) -> Result<Vec<String>> {
+        self.ensure_versions_writable()?;
+        if !self.get_version_dir(version, VersionType::Node).exists() {
+            return Err(VersionError::NotInstalled(version.to_string(), VersionType::Node).into());
         }
-
-        // Update symlinks
-        fs::create_dir_all(&self.bin_dir)?;
-
-        // Remove existing symlinks
-        for entry in fs::read_dir(&self.bin_dir)? {
-            let entry = entry?;
-            if entry.file_type()?.is_symlink() {
-                fs::remove_file(entry.path())?;
-            }
+        
+        let bin_dir = self.get_bin_dir(version, VersionType::Node);
+        let corepack = bin_dir.join(match self.os_type {
+            OsType::Windows => "corepack.cmd",
+            _ => "corepack",
+        });
+        if !corepack.exists() {
+            return Err(anyhow::anyhow!("Node.js {} 没有附带 corepack（需要 16.9 及以上版本）", version));
+        }
+        
+        let path_var = env::var("PATH").unwrap_or_default();
+        let status = Command::new(&corepack)
+            .arg(if enable { "enable" } else { "disable" })
+            .arg("--install-directory")
+            .arg(&bin_dir)
+            .env("PATH", format!("{}:{}", bin_dir.to_string_lossy(), path_var))
+            .status()?;
+        if !status.success() {
+            return Err(anyhow::anyhow!("corepack 执行失败，退出码: {}", status));
+        }
+        
+        if self.current_version_type == VersionType::Node && self.current_version.as_deref() == Some(version) {
+            self.use_version(version, VersionType::Node)?;
         }
+        
+        self.corepack_shims(version)
+    }
 
-        // Determine the bin directory based on OS and architecture
-        let os_arch_suffix = match version_type {
-            VersionType::Node => self.get_os_arch_suffix(),
-            VersionType::Rust => {
-                match (&self.os_type, &self.arch_type) {
-                    (OsType::Darwin, ArchType::X64) => "x86_64-apple-darwin",
-                    (OsType::Darwin, ArchType::Arm64) => "aarch64-apple-darwin",
-                    (OsType::Linux, ArchType::X64) => "x86_64-unknown-linux-gnu",
-                    (OsType::Linux, ArchType::Arm64) => "aarch64-unknown-linux-gnu",
-                    (OsType::Linux, ArchType::Arm) => "linux-armv7l",
-                    (OsType::Windows, ArchType::X64) => "x86_64-pc-windows-msvc",
-                    (OsType::Windows, ArchType::X86) => "i686-pc-windows-msvc",
-                    _ => "unknown",
-                }.to_string()
-            },
-            VersionType::Python => {
-                match (&self.os_type, &self.arch_type) {
-                    (OsType::Darwin, ArchType::X64) => "macosx10.9.x86_64",
-                    (OsType::Darwin, ArchType::Arm64) => "macos11.0.arm64",
-                    (OsType::Linux, ArchType::X64) => "x86_64",
-                    (OsType::Linux, ArchType::Arm64) => "aarch64",
-                    (OsType::Linux, ArchType::Arm) => "armv7l",
-                    (OsType::Windows, ArchType::X64) => "amd64",
-                    (OsType::Windows, ArchType::X86) => "win32",
-                    _ => "unknown",
-                }.to_string()
-            },
-            VersionType::Go => {
-                match (&self.os_type, &self.arch_type) {
-                    (OsType::Darwin, ArchType::X64) => "darwin-amd64",
-                    (OsType::Darwin, ArchType::Arm64) => "darwin-arm64",
-                    (OsType::Linux, ArchType::X64) => "linux-amd64",
-                    (OsType::Linux, ArchType::Arm64) => "linux-arm64",
-                    (OsType::Linux, ArchType::Arm) => "linux-armv6l",
-                    (OsType::Windows, ArchType::X64) => "windows-amd64",
-                    (OsType::Windows, ArchType::X86) => "windows-386",
-                    _ => "unknown",
-                }.to_string()
+    /// 查找npm全局前缀覆盖
+    ///
+    /// 依次检查环境变量和用户npmrc中的prefix设置。
+    ///
+    /// # 返回
+    ///
+    /// 找到时返回前缀路径及其来源，否则返回None。
+    fn find_npm_prefix_override(&self) -> Option<(String, String)> {
+        for var in ["npm_config_prefix", "NPM_CONFIG_PREFIX"] {
+            if let Ok(prefix) = env::var(var)
+                && !prefix.is_empty()
+            {
+                return Some((prefix, format!("${}", var)));
             }
+        }
+        
+        let npmrc = match env::var("NPM_CONFIG_USERCONFIG").or_else(|_| env::var("npm_config_userconfig")) {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => home_dir(Some("NPM_CONFIG_USERCONFIG")).ok()?.join(".npmrc"),
         };
+        let content = fs::read_to_string(&npmrc).ok()?;
         
-        let bin_dir = match version_type {
-            VersionType::Node => version_dir.join(format!("node-v{}-{}/bin", version, os_arch_suffix)),
-            VersionType::Rust => version_dir.join("bin"),
-            VersionType::Python => version_dir.join("bin"),
-            VersionType::Go => version_dir.join("bin"),
+        content.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.starts_with('#') && !line.starts_with(';'))
+            .filter_map(|line| line.split_once('='))
+            .find(|(key, _)| key.trim() == "prefix")
+            .map(|(_, value)| (value.trim().to_string(), npmrc.display().to_string()))
+    }
+
+    /// 检查npm全局前缀是否会逃逸出受管理的Node.js版本
+    ///
+    /// 如果prefix被覆盖到版本目录之外，打印警告说明全局包不会随版本切换。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 当前切换到的Node.js版本
+    fn warn_npm_prefix_override(&self, version: &str) {
+        let bin_dir = self.get_bin_dir(version, VersionType::Node);
+        let Some(node_prefix) = bin_dir.parent() else {
+            return;
         };
         
-        // Create symlinks for all binaries in that directory
-        if bin_dir.exists() {
-            for entry in fs::read_dir(&bin_dir)? {
-                let entry = entry?;
-                if entry.file_type()?.is_file() {
-                    let file_name = entry.file_name();
-                    let target_path = self.bin_dir.join(&file_name);
-                    
-                    match self.os_type {
-                        OsType::Windows => {
-                            // 在 Windows 上，创建一个 .cmd 文件来启动相应的程序
-                            let cmd_content = match version_type {
-                                VersionType::Node => format!(
-                                    "@echo off\r\n\"%~dp0\\..\\versions\\{}\\node-v{}-{}\\bin\\{}{}\" %*\r\n",
-                                    version, version, os_arch_suffix, file_name.to_string_lossy(), self.get_exe_extension()
-                                ),
-                                VersionType::Rust => format!(
-                                    "@echo off\r\n\"%~dp0\\..\\versions\\{}\\bin\\{}{}\" %*\r\n",
-                                    version, file_name.to_string_lossy(), self.get_exe_extension()
-                                ),
-                                VersionType::Python => format!(
-                                    "@echo off\r\n\"%~dp0\\..\\versions\\{}\\bin\\{}{}\" %*\r\n",
-                                    version, file_name.to_string_lossy(), self.get_exe_extension()
-                                ),
-                                VersionType::Go => format!(
-                                    "@echo off\r\n\"%~dp0\\..\\versions\\{}\\bin\\{}{}\" %*\r\n",
-                                    version, file_name.to_string_lossy(), self.get_exe_extension()
-                                ),
-                            };
-                            fs::write(target_path.with_extension("cmd"), cmd_content)?;
-                        },
-                        _ => {
-                            // 在 Unix 系统上创建符号链接
-                            std::os::unix::fs::symlink(entry.path(), target_path)?;
-                        }
-                    }
-                }
-            }
-        } else {
-            return Err(anyhow::anyhow!("找不到二进制目录"));
+        if let Some((prefix, source)) = self.find_npm_prefix_override()
+            && !Path::new(&prefix).starts_with(node_prefix)
+        {
+            output::println(format_args!("Warning: npm global prefix is set to {} (from {})", prefix, source));
+            output::println(format_args!("Global packages will be installed outside Node.js {} and shared across versions.", version));
+            output::println("Remove the prefix setting to keep global packages per version, or use `ver exec` which overrides it.");
         }
-
-        // Update PATH in shell config
-        self.update_shell_config()?;
-
-        // Save and update current version
-        self.save_current_version(version, version_type)?;
-        self.current_version = Some(version.to_string());
-        self.current_version_type = version_type;
-
-        println!("Switched to {} version {}", version_type, version);
-        Ok(())
     }
 
     /// 列出已安装的版本
@@ -1514,14 +8385,21 @@ impl VersionManager {
     /// # 返回
     ///
     /// 成功时返回已安装版本列表，失败时返回错误。
-    pub fn list_installed_versions(&self, _version_type: VersionType) -> Result<Vec<String>> {
+    #[allow(clippy::needless_range_loop)]
+    pub fn list_installed_versions(&self, version_type: VersionType) -> Result<Vec<String>> {
         let mut versions = Vec::new();
         for entry in fs::read_dir(&self.versions_dir)? {
             let entry = entry?;
-            if entry.path().is_dir() {
-                if let Some(name) = entry.file_name().to_str() {
-                    versions.push(name.to_string());
+            // 以点开头的是复制、修复过程中的临时目录
+            if entry.path().is_dir()
+                && let Some(name) = entry.file_name().to_str()
+                && !name.starts_with('.')
+            {
+                // 所有工具共用版本目录，按安装记录或目录结构过滤
+                if self.version_dir_tool(name).is_some_and(|tool| tool != version_type) {
+                    continue;
                 }
+                versions.push(name.to_string());
             }
         }
         
@@ -1538,33 +8416,233 @@ impl VersionManager {
         Ok(versions)
     }
 
+    /// 判断版本目录属于哪个工具
+    ///
+    /// 优先使用安装记录，没有记录的目录（旧版本安装或迁移的）根据目录结构推断。
+    ///
+    /// # 参数
+    ///
+    /// * `name` - 版本目录名
+    ///
+    /// # 返回
+    ///
+    /// 能够判断时返回工具类型，否则返回None。
+    fn version_dir_tool(&self, name: &str) -> Option<VersionType> {
+        let version_dir = self.versions_dir.join(name);
+        if let Ok(content) = fs::read_to_string(version_dir.join(INSTALL_RECEIPT_FILE))
+            && let Ok(receipt) = serde_json::from_str::<InstallReceipt>(&content)
+        {
+            return VersionType::from_id(&receipt.tool);
+        }
+        
+        let has_bin = |binary: &str| version_dir.join("bin").join(format!("{}{}", binary, self.get_exe_extension())).exists();
+        let has_node_dist = fs::read_dir(&version_dir).ok()?
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("node-v"));
+        if has_node_dist || has_bin("node") {
+            Some(VersionType::Node)
+        } else if has_bin("rustc") {
+            Some(VersionType::Rust)
+        } else if has_bin("go") {
+            Some(VersionType::Go)
+        } else if has_bin("python") || has_bin("python3") {
+            Some(VersionType::Python)
+        } else {
+            None
+        }
+    }
+
+    /// 计算可以回收的已安装版本
+    ///
+    /// 统计每个版本占用的空间、最后使用时间（来自本地使用记录），以及引用它的全局版本、
+    /// 别名、锁定和项目版本文件。项目版本文件只检查使用记录中出现过的目录，
+    /// 因此既没有安装记录也没有使用记录的版本视为使用情况未知，不算可回收。
+    ///
+    /// # 参数
+    ///
+    /// * `unused_days` - 超过该天数未使用且没有引用的版本视为可回收
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回所有已安装版本的回收信息，失败时返回错误。
+    pub fn gc_report(&self, unused_days: i64) -> Result<Vec<GcCandidate>> {
+        let records = self.usage_records(chrono::DateTime::<chrono::Utc>::MIN_UTC)?;
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(unused_days);
+        let project_dirs = Self::project_dirs(&records);
+        
+        let mut candidates = Vec::new();
+        for version_type in VersionType::all() {
+            let index = self.reference_index(version_type, &project_dirs)?;
+            
+            for name in self.list_installed_versions(version_type)? {
+                let version = name.trim_end_matches(" (current)").to_string();
+                // 无法判断工具的目录会出现在每个工具的列表中，只统计一次
+                if self.version_dir_tool(&version).is_none() && candidates.iter().any(|c: &GcCandidate| c.version == version) {
+                    continue;
+                }
+                
+                let mut references = index.references_to(&version);
+                if self.is_locked(&version, version_type) {
+                    references.push("locked".to_string());
+                }
+                
+                let last_used = records.iter()
+                    .filter(|record| record.tool == version_type.id() && record.version == version)
+                    .map(|record| record.time.clone())
+                    .max();
+                // 刚安装还没来得及使用的版本也不回收
+                let installed_at = self.read_install_receipt(&version, version_type).ok().flatten()
+                    .map(|receipt| receipt.installed_at);
+                let recently_used = [last_used.as_ref(), installed_at.as_ref()].into_iter()
+                    .flatten()
+                    .filter_map(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+                    .any(|time| time >= cutoff);
+                
+                let usage_known = last_used.is_some() || installed_at.is_some();
+                
+                candidates.push(GcCandidate {
+                    version_type,
+                    size: Self::dir_size(&self.get_version_dir(&version, version_type))?,
+                    reclaimable: references.is_empty() && usage_known && !recently_used,
+                    version,
+                    last_used,
+                    installed_at,
+                    references,
+                });
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// 使用记录中出现过的项目目录，去掉重复项
+    fn project_dirs(records: &[UsageRecord]) -> Vec<&Path> {
+        let mut project_dirs: Vec<&Path> = records.iter().map(|record| record.dir.as_path()).collect();
+        project_dirs.sort();
+        project_dirs.dedup();
+        project_dirs
+    }
+
+    /// 收集某个工具中引用已安装版本的全局版本、别名和项目版本文件
+    ///
+    /// # 参数
+    ///
+    /// * `version_type` - 版本类型
+    /// * `project_dirs` - 要检查版本文件的项目目录
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回引用索引，失败时返回错误。
+    fn reference_index(&self, version_type: VersionType, project_dirs: &[&Path]) -> Result<ReferenceIndex> {
+        let mut project_files = Vec::new();
+        for dir in project_dirs {
+            if let Some((version, file)) = self.local_version_file(dir, version_type)? {
+                let version = self.get_alias(&version, version_type)?.unwrap_or(version);
+                if !project_files.iter().any(|(_, f)| f == &file) {
+                    project_files.push((version, file));
+                }
+            }
+        }
+        Ok(ReferenceIndex {
+            current: Self::read_current_version(&self.base_dir, version_type).ok(),
+            aliases: self.list_aliases(version_type)?,
+            project_files,
+        })
+    }
+
     /// 删除版本
     ///
-    /// 删除指定版本。
+    /// 删除指定版本。版本仍被全局版本、别名或最近使用过的项目中的版本文件引用时，
+    /// 除非指定 `force`，否则列出这些引用并返回错误。
     ///
     /// # 参数
     ///
     /// * `version` - 版本号
     /// * `version_type` - 版本类型
+    /// * `force` - 忽略引用直接删除
     ///
     /// # 返回
     ///
     /// 成功时返回Ok(()，失败时返回错误。
-    pub fn remove_version(&self, version: &str, version_type: VersionType) -> Result<()> {
+    #[allow(clippy::collapsible_if)]
+    pub fn remove_version(&self, version: &str, version_type: VersionType, force: bool) -> Result<()> {
+        self.ensure_versions_writable()?;
         // Don't allow removing the current version
         if let Some(current) = &self.current_version {
             if current == version && self.current_version_type == version_type {
-                return Err(anyhow::anyhow!("{}", VersionError::CurrentlyActive(version.to_string(), version_type)));
+                return Err(VersionError::CurrentlyActive(version.to_string(), version_type).into());
             }
         }
 
         let version_dir = self.get_version_dir(version, version_type);
         if !version_dir.exists() {
-            return Err(anyhow::anyhow!("{}", VersionError::NotFound(version.to_string(), version_type)));
+            return Err(VersionError::NotFound(version.to_string(), version_type).into());
+        }
+        if self.is_locked(version, version_type) {
+            return Err(VersionError::Locked(version.to_string(), version_type).into());
+        }
+        if !force {
+            let records = self.usage_records(chrono::DateTime::<chrono::Utc>::MIN_UTC)?;
+            let references = self.reference_index(version_type, &Self::project_dirs(&records))?
+                .references_to(version);
+            if !references.is_empty() {
+                return Err(VersionError::Referenced {
+                    version: version.to_string(),
+                    version_type,
+                    references,
+                }.into());
+            }
         }
 
-        fs::remove_dir_all(version_dir).context(format!("删除 {} 版本 {} 失败", version_type, version))?;
-        println!("成功删除 {} 版本 {}", version_type, version);
+        fs::remove_dir_all(long_path(&version_dir)).context(format!("删除 {} 版本 {} 失败", version_type, version))?;
+        output::println(format_args!("成功删除 {} 版本 {}", version_type, version));
+        self.clean_up_removed_version(version, version_type)
+    }
+
+    /// 删除版本后清理仍指向它的全局状态
+    ///
+    /// 删除的是全局当前版本，或者该工具已经没有其他版本时，删除 .current 文件和 bin 目录中失效的链接，
+    /// 并重新生成环境变量文件，去掉该工具的环境变量（例如 GOPROXY、npm_config_nodedir），
+    /// 避免 shell 中留下指向已删除目录的变量。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 已删除的版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回Ok(()，失败时返回错误。
+    fn clean_up_removed_version(&self, version: &str, version_type: VersionType) -> Result<()> {
+        let current = Self::read_current_version(&self.base_dir, version_type).ok();
+        let last = self.list_installed_versions(version_type)?.is_empty();
+        if current.as_deref() != Some(version) && !last {
+            return Ok(());
+        }
+        
+        if current.is_some() {
+            fs::remove_file(self.base_dir.join(format!(".current-{}", version_type)))?;
+        }
+        if self.bin_dir.exists() {
+            let windows_marker = format!("\\versions\\{}\\", version);
+            for entry in fs::read_dir(&self.bin_dir)? {
+                let path = entry?.path();
+                let dangling = fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+                    && !path.exists();
+                let stale_cmd = path.extension().is_some_and(|ext| ext == "cmd")
+                    && fs::read_to_string(&path).is_ok_and(|content| content.contains(&windows_marker));
+                let stale_shim = last && version_type == VersionType::Rust && Self::is_rust_shim(&path);
+                if dangling || stale_cmd || stale_shim {
+                    fs::remove_file(&path)?;
+                }
+            }
+        }
+        self.update_env_file()?;
+        
+        if last {
+            output::println(format_args!("No {} versions left; removed its shims and environment exports", version_type));
+        } else {
+            output::println(format_args!("{} {} was the global version; run `ver use <version> -t {}` to pick another", version_type, version, version_type.id()));
+        }
         Ok(())
     }
 
@@ -1589,6 +8667,77 @@ impl VersionManager {
         }
     }
 
+    /// 获取版本的二进制目录
+    ///
+    /// 获取指定版本存放可执行文件的目录。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回二进制目录路径。
+    pub fn get_bin_dir(&self, version: &str, version_type: VersionType) -> PathBuf {
+        let version_dir = self.get_version_dir(version, version_type);
+        match version_type {
+            VersionType::Node => {
+                // 通过 Rosetta 或 --arch 安装的版本目录名中的平台与当前平台不同
+                let version = Self::strip_arch_suffix(version);
+                let dist_dir = version_dir.join(format!("node-v{}-{}", version, self.get_os_arch_suffix()));
+                // 用 ver clone 复制的版本目录名与其中的发行包目录名不同，退回到任意 node-v* 目录
+                let dist_dir = if dist_dir.exists() {
+                    dist_dir
+                } else {
+                    let candidates: Vec<PathBuf> = fs::read_dir(&version_dir).ok()
+                        .map(|entries| entries.flatten()
+                            .map(|entry| entry.path())
+                            .filter(|path| path.is_dir() && path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("node-v")))
+                            .collect())
+                        .unwrap_or_default();
+                    let prefix = format!("node-v{}-", version);
+                    candidates.iter()
+                        .find(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with(&prefix)))
+                        .or(candidates.first())
+                        .cloned()
+                        .unwrap_or(dist_dir)
+                };
+                // ver adopt 登记的版本和从 nvm 迁移的版本直接在版本目录下有bin目录
+                if !dist_dir.exists() && version_dir.join("bin").exists() {
+                    return version_dir.join("bin");
+                }
+                dist_dir.join("bin")
+            }
+            VersionType::Rust => version_dir.join("bin"),
+            VersionType::Python => version_dir.join("bin"),
+            VersionType::Go => version_dir.join("bin"),
+        }
+    }
+
+    /// 获取版本的主可执行文件路径
+    ///
+    /// 获取指定版本的解释器或编译器路径，例如node、rustc、python、go。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - 版本号
+    /// * `version_type` - 版本类型
+    ///
+    /// # 返回
+    ///
+    /// 返回可执行文件路径。
+    pub fn get_executable_path(&self, version: &str, version_type: VersionType) -> PathBuf {
+        let name = match version_type {
+            VersionType::Node => "node",
+            VersionType::Rust => "rustc",
+            VersionType::Python => "python",
+            VersionType::Go => "go",
+        };
+        self.get_bin_dir(version, version_type)
+            .join(format!("{}{}", name, self.get_exe_extension()))
+    }
+
     /// 更新shell配置
     ///
     /// 更新shell配置文件中的PATH环境变量。
@@ -1602,218 +8751,703 @@ impl VersionManager {
         match self.os_type {
             OsType::Windows => {
                 // 在 Windows 上修改用户环境变量
-                println!("请将以下目录添加到 PATH 环境变量中:");
-                println!("{}", bin_path);
-                println!("可以通过打开系统属性 -> 高级 -> 环境变量来实现。");
+                output::println("请将以下目录添加到 PATH 环境变量中:");
+                output::println(bin_path);
+                output::println("可以通过打开系统属性 -> 高级 -> 环境变量来实现。");
             },
             _ => {
                 // 在 Unix 系统上修改 shell 配置文件
                 let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
-                let config_file = if shell.ends_with("zsh") {
-                    dirs::home_dir()
-                        .context("无法找到用户主目录")?
-                        .join(".zshrc")
+                let home = home_dir(None)?;
+                let config = if shell.ends_with("zsh") {
+                    ShellConfig { shell: "zsh", file: home.join(".zshrc") }
+                } else if shell.ends_with("fish") {
+                    ShellConfig { shell: "fish", file: home.join(".config").join("fish").join("config.fish") }
                 } else {
-                    dirs::home_dir()
-                        .context("无法找到用户主目录")?
-                        .join(".bashrc")
+                    ShellConfig { shell: "bash", file: home.join(".bashrc") }
                 };
+                self.install_shell_hook(&config)?;
+            }
+        }
+
+        Ok(())
+    }
 
-                let export_line = format!("\nexport PATH=\"{}:$PATH\"\n", bin_path);
+    /// 查找通过 Homebrew 或 apt 等系统包管理器安装的工具链
+    ///
+    /// 只检查 Node.js、Python 和 Go，Rust 一般由 rustup 管理，可以通过 `ver rust migrate` 导入。
+    ///
+    /// # 返回
+    ///
+    /// 返回检测到的工具链，同一工具的同一版本只出现一次。
+    pub fn detect_system_toolchains(&self) -> Vec<SystemToolchain> {
+        let dirs = ["/opt/homebrew/bin", "/home/linuxbrew/.linuxbrew/bin", "/usr/local/bin", "/usr/bin"];
+        let tools = [
+            (VersionType::Node, "node", &["--version"][..]),
+            (VersionType::Python, "python3", &["--version"][..]),
+            (VersionType::Go, "go", &["version"][..]),
+        ];
+        
+        let mut found: Vec<SystemToolchain> = Vec::new();
+        for (version_type, name, args) in tools {
+            for dir in dirs {
+                let executable = Path::new(dir).join(name);
+                if !executable.is_file() {
+                    continue;
+                }
+                let Ok(output) = Command::new(&executable).args(args).output() else {
+                    continue;
+                };
+                // python 3.3 之前把版本号输出到标准错误
+                let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+                let Some(version) = Self::parse_system_version(version_type, &text) else {
+                    continue;
+                };
+                if found.iter().any(|t| t.version_type == version_type && t.version == version) {
+                    continue;
+                }
                 
-                if !config_file.exists() {
-                    fs::write(&config_file, export_line)?;
+                let real_path = fs::canonicalize(&executable).unwrap_or_else(|_| executable.clone());
+                let flavor = if dir.contains("brew") || real_path.to_string_lossy().contains("/Cellar/") {
+                    "homebrew"
                 } else {
-                    let content = fs::read_to_string(&config_file)?;
-                    if !content.contains(&*bin_path) {
-                        fs::write(&config_file, format!("{}{}", content, export_line))?;
-                    }
-                }
+                    // /usr/bin 中的文件不一定来自 apt，向当前系统的包管理器查询文件归属
+                    prereqs::owning_package_manager(&real_path).unwrap_or("system")
+                };
+                found.push(SystemToolchain { version_type, version, executable, flavor });
             }
         }
+        found
+    }
+
+    /// 从 `node --version`、`python3 --version`、`go version` 的输出中解析版本号
+    fn parse_system_version(version_type: VersionType, output: &str) -> Option<String> {
+        let word = match version_type {
+            VersionType::Node => output.split_whitespace().next()?.strip_prefix('v')?,
+            VersionType::Python => output.split_whitespace().nth(1)?,
+            VersionType::Go => output.split_whitespace().nth(2)?.strip_prefix("go")?,
+            VersionType::Rust => return None,
+        };
+        word.chars().next().is_some_and(|c| c.is_ascii_digit()).then(|| word.to_string())
+    }
+
+    /// 登记系统工具链
+    ///
+    /// 在 `system-<版本>` 版本目录的bin目录中创建指向系统可执行文件的符号链接，不复制任何文件，
+    /// 并在该工具还没有 `system` 别名时创建它。删除该版本只会删除符号链接。
+    ///
+    /// # 参数
+    ///
+    /// * `toolchain` - 检测到的系统工具链
+    ///
+    /// # 返回
+    ///
+    /// 新登记时返回版本名，已经登记过时返回None，失败时返回错误。
+    pub fn adopt_system_toolchain(&self, toolchain: &SystemToolchain) -> Result<Option<String>> {
+        self.ensure_versions_writable()?;
+        let name = format!("system-{}", toolchain.version);
+        let version_dir = self.get_version_dir(&name, toolchain.version_type);
+        if version_dir.exists() {
+            return Ok(None);
+        }
+        
+        let source_dir = toolchain.executable.parent().context("无法确定可执行文件所在目录")?;
+        // 只链接该工具自己的命令，避免把 /usr/bin 中的其他程序带进 ~/.version-manager/bin
+        let links: &[(&str, &str)] = match toolchain.version_type {
+            VersionType::Node => &[("node", "node"), ("npm", "npm"), ("npx", "npx"), ("corepack", "corepack")],
+            VersionType::Python => &[("python", "python3"), ("python3", "python3"), ("pip", "pip3"), ("pip3", "pip3")],
+            VersionType::Go => &[("go", "go"), ("gofmt", "gofmt")],
+            VersionType::Rust => &[],
+        };
+        
+        // apt 的 /usr/bin/go 是指向 /usr/lib/go-X/bin/go 的链接，gofmt 可能只在真实目录中
+        let real_dir = fs::canonicalize(&toolchain.executable)?
+            .parent()
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(|| source_dir.to_path_buf());
+        
+        let bin_dir = version_dir.join("bin");
+        fs::create_dir_all(&bin_dir)?;
+        for (link, target) in links {
+            let target = [source_dir, real_dir.as_path()]
+                .iter()
+                .map(|dir| dir.join(target))
+                .find(|path| path.exists());
+            if let Some(target) = target {
+                create_symlink(&target, &bin_dir.join(link))?;
+            }
+        }
+        
+        let receipt = InstallReceipt {
+            schema: state::SCHEMA_VERSION,
+            tool: toolchain.version_type.id().to_string(),
+            version: toolchain.version.clone(),
+            source: toolchain.executable.display().to_string(),
+            arch: None,
+            installed_at: chrono::Utc::now().to_rfc3339(),
+            skipped_modules: Vec::new(),
+            flavor: Some(toolchain.flavor.to_string()),
+            files: Self::hash_tree(&version_dir)?,
+            archive_bytes: None,
+            // 系统包管理器的构建方式未知
+            build: None,
+        };
+        write_atomic(&version_dir.join(INSTALL_RECEIPT_FILE), serde_json::to_string_pretty(&receipt)?)?;
+        
+        if self.get_alias("system", toolchain.version_type)?.is_none() {
+            self.create_alias("system", &name, toolchain.version_type)?;
+        }
+        Ok(Some(name))
+    }
+
+    /// 查找本机已安装的shell及其配置文件
+    ///
+    /// 配置文件已存在或 $SHELL 指向该shell时视为已安装。
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回检测到的shell配置，失败时返回错误。
+    pub fn detect_shells(&self) -> Result<Vec<ShellConfig>> {
+        let home = home_dir(None)?;
+        let login_shell = env::var("SHELL").unwrap_or_default();
+        let candidates = [
+            ShellConfig { shell: "bash", file: home.join(".bashrc") },
+            ShellConfig { shell: "zsh", file: home.join(".zshrc") },
+            ShellConfig { shell: "fish", file: home.join(".config").join("fish").join("config.fish") },
+        ];
+        
+        Ok(candidates.into_iter()
+            .filter(|config| config.file.exists() || login_shell.ends_with(config.shell))
+            .collect())
+    }
+
+    /// 生成加入shell配置文件的内容
+    ///
+    /// 返回将 ~/.version-manager/bin 加入 PATH 的行和加载环境变量文件的行。
+    fn shell_hook_lines(&self, shell: &str) -> (String, String) {
+        let bin_path = self.bin_dir.to_string_lossy();
+        if shell == "fish" {
+            let env_file = self.base_dir.join("env.fish");
+            (
+                format!("contains \"{0}\" $PATH; or set -gx PATH \"{0}\" $PATH\n", bin_path),
+                format!("test -f \"{0}\"; and source \"{0}\"\n", env_file.display()),
+            )
+        } else {
+            let env_file = self.base_dir.join("env");
+            (
+                format!("export PATH=\"{}:$PATH\"\n", bin_path),
+                format!("[ -f \"{0}\" ] && . \"{0}\"\n", env_file.display()),
+            )
+        }
+    }
 
-        Ok(())
+    /// 检查shell配置文件中是否已经加入了 ver
+    pub fn shell_hook_installed(&self, config: &ShellConfig) -> bool {
+        let (_, source_line) = self.shell_hook_lines(config.shell);
+        fs::read_to_string(&config.file)
+            .is_ok_and(|content| content.contains(&*self.bin_dir.to_string_lossy()) && content.contains(&source_line))
     }
 
-    /// 获取当前Rust版本
+    /// 在shell配置文件中加入 ver
+    ///
+    /// # 参数
     ///
-    /// 获取当前使用的Rust版本。
+    /// * `config` - shell配置
     ///
     /// # 返回
     ///
-    /// 成功时返回当前Rust版本字符串，失败时返回错误。
-    pub fn get_current_rust_version(&self) -> Option<&String> {
-        if self.current_version_type == VersionType::Rust {
-            self.current_version.as_ref()
-        } else {
-            None
+    /// 成功时返回是否修改了配置文件，失败时返回错误。
+    pub fn install_shell_hook(&self, config: &ShellConfig) -> Result<bool> {
+        let (path_line, source_line) = self.shell_hook_lines(config.shell);
+        let bin_path = self.bin_dir.to_string_lossy();
+        // 同时运行的多个 ver 进程依次修改，后面的进程能看到前面加入的内容，不会重复添加
+        let _lock = state::FileLock::acquire(&self.base_dir.join("shell-config.lock"), std::time::Duration::from_secs(10))?;
+        
+        for _ in 0..SHELL_CONFIG_ATTEMPTS {
+            let original = match fs::read_to_string(&config.file) {
+                Ok(content) => content,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+                Err(err) => return Err(err.into()),
+            };
+            let mut content = dedup_line(&dedup_line(&original, &path_line), &source_line);
+            if !content.contains(&*bin_path) {
+                content.push('\n');
+                content.push_str(&path_line);
+            }
+            if !content.contains(&source_line) {
+                content.push_str(&source_line);
+            }
+            if content == original {
+                return Ok(false);
+            }
+            
+            if let Some(parent) = config.file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // 其他工具（编辑器、dotfiles 管理器等）不遵守上面的锁，写入前确认文件没有在读取后被改写，
+            // 否则基于新内容重新修改，避免覆盖用户的修改
+            let current = fs::read(&config.file).unwrap_or_default();
+            if current != original.as_bytes() {
+                continue;
+            }
+            write_atomic(&config.file, content)?;
+            return Ok(true);
         }
+        
+        anyhow::bail!("{} 在修改过程中不断被其他程序改写，请稍后重试", config.file.display())
     }
-    
-    /// 列出可用的Rust版本
+
+    /// 查找本机上其他版本管理器安装的版本
     ///
-    /// 列出可用的Rust版本。
+    /// # 返回
+    ///
+    /// 返回检测到的版本管理器，只包含至少有一个已安装版本的管理器。
+    pub fn detect_version_managers(&self) -> Vec<DetectedManager> {
+        let Ok(home) = home_dir(None) else {
+            return Vec::new();
+        };
+        let root = |var: &str, default: &str| env::var_os(var)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(default));
+        let candidates = [
+            ("nvm", VersionType::Node, root("NVM_DIR", ".nvm").join("versions").join("node")),
+            ("rustup", VersionType::Rust, root("RUSTUP_HOME", ".rustup").join("toolchains")),
+            ("pyenv", VersionType::Python, root("PYENV_ROOT", ".pyenv").join("versions")),
+            ("gvm", VersionType::Go, root("GVM_ROOT", ".gvm").join("gos")),
+        ];
+        
+        candidates.into_iter()
+            .filter_map(|(name, version_type, dir)| {
+                let versions = fs::read_dir(&dir).ok()?
+                    .flatten()
+                    .filter(|entry| entry.path().is_dir() && !entry.file_name().to_string_lossy().starts_with('.'))
+                    .count();
+                (versions > 0).then_some(DetectedManager { name, version_type, dir, versions })
+            })
+            .collect()
+    }
+
+    /// 检查 PATH 中每个目录的归属
+    ///
+    /// 排在 ver 的 bin 目录之前、提供同名命令的目录会让 ver 切换的版本不生效。
     ///
     /// # 参数
     ///
-    /// * `stable_only` - 是否只列出稳定版本
+    /// * `path` - PATH 环境变量的值
     ///
     /// # 返回
     ///
-    /// 成功时返回Rust版本列表，失败时返回错误。
-    pub async fn list_available_rust_versions(&self, stable_only: bool) -> Result<Vec<String>> {
-        let versions = self.list_available_versions(stable_only, VersionType::Rust).await?;
-        let mut result = Vec::new();
+    /// 按 PATH 顺序返回每个目录的归属和遮盖的命令。
+    pub fn audit_path(&self, path: &std::ffi::OsStr) -> Vec<PathEntry> {
+        let managed: Vec<String> = fs::read_dir(&self.bin_dir)
+            .map(|entries| entries.flatten().map(|entry| entry.file_name().to_string_lossy().into_owned()).collect())
+            .unwrap_or_default();
+        // ver 的 bin 目录不在 PATH 中时没有遮盖的问题，只需要运行 ver setup
+        let mut before_ver = env::split_paths(path).any(|dir| dir == self.bin_dir);
         
-        for version in versions {
-            result.push(version.version);
+        env::split_paths(path)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| {
+                let owner = self.path_owner(&dir);
+                if dir == self.bin_dir {
+                    before_ver = false;
+                }
+                let shadows = if before_ver && owner != PathOwner::Ver {
+                    managed.iter().filter(|name| dir.join(name).is_file()).cloned().collect()
+                } else {
+                    Vec::new()
+                };
+                PathEntry { dir, owner, shadows }
+            })
+            .collect()
+    }
+
+    /// 判断 PATH 中的目录属于 ver、其他版本管理器还是系统
+    fn path_owner(&self, dir: &Path) -> PathOwner {
+        if dir.starts_with(&self.base_dir) || dir.starts_with(&self.versions_dir) {
+            return PathOwner::Ver;
         }
-        
-        Ok(result)
+        // 按路径中连续的几级目录名匹配，例如 `.cargo/bin`
+        let parts: Vec<String> = dir.components().map(|part| part.as_os_str().to_string_lossy().into_owned()).collect();
+        let matches = |pattern: &str| {
+            let pattern: Vec<&str> = pattern.split('/').collect();
+            parts.windows(pattern.len()).any(|window| window.iter().zip(&pattern).all(|(part, name)| part == name))
+        };
+        PATH_MANAGERS.iter()
+            .find(|(_, patterns)| patterns.iter().any(|pattern| matches(pattern)))
+            .map_or(PathOwner::Other, |(name, _)| PathOwner::Manager(name))
     }
-    
-    /// 安装Rust版本
+
+    /// ver 是否管理某个工具，见配置项 `manage`
+    pub fn manages_tool(&self, version_type: VersionType) -> bool {
+        self.config.manages(version_type)
+    }
+
+    /// 检查 ver 与其他版本管理器的分工
     ///
-    /// 安装指定的Rust版本。
+    /// 由 ver 管理的工具，PATH 中第一个找到的命令应来自 ver；不由 ver 管理的工具，
+    /// ver 的 bin 目录中不应有指向它的链接，命令也不应解析到 ver 安装的版本。
     ///
     /// # 参数
     ///
-    /// * `version` - 版本号
+    /// * `path` - 要检查的 PATH
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub async fn install_rust_version(&self, version: &str) -> Result<()> {
-        if version == "latest" {
-            println!("安装最新的 Rust 版本...");
-            let versions = self.list_available_rust_versions(true).await?;
-            if let Some(latest) = versions.first() {
-                self.install_version(latest, VersionType::Rust).await?;
+    /// 成功时返回每个工具的检查结果，失败时返回错误。
+    pub fn doctor(&self, path: &std::ffi::OsStr) -> Result<Vec<DoctorCheck>> {
+        let path = path.to_string_lossy();
+        let mut checks = Vec::new();
+        for version_type in VersionType::all() {
+            let managed = self.manages_tool(version_type);
+            let command = match version_type {
+                VersionType::Node => "node",
+                VersionType::Rust => "cargo",
+                VersionType::Python => "python3",
+                VersionType::Go => "go",
+            };
+            let resolved = Self::find_in_path(command, &path)
+                .map(|found| {
+                    let owner = found.parent().map_or(PathOwner::Other, |dir| self.path_owner(dir));
+                    (found, owner)
+                });
+            
+            // bin 目录中指向该工具已安装版本的链接和Rust代理脚本
+            let version_dirs: Vec<PathBuf> = self.list_installed_versions(version_type)?
+                .iter()
+                .map(|version| self.get_version_dir(version.trim_end_matches(" (current)"), version_type))
+                .collect();
+            let links: Vec<PathBuf> = fs::read_dir(&self.bin_dir)
+                .map(|entries| entries.flatten().map(|entry| entry.path()).collect::<Vec<_>>())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|link| match fs::read_link(link) {
+                    Ok(target) => version_dirs.iter().any(|dir| target.starts_with(dir)),
+                    Err(_) => version_type == VersionType::Rust && Self::is_rust_shim(link),
+                })
+                .collect();
+            
+            let mut problems = Vec::new();
+            let mut stale_links = Vec::new();
+            if managed {
+                match &resolved {
+                    Some((found, PathOwner::Manager(name))) => problems.push(format!("{} resolves to {} from {}", command, found.display(), name)),
+                    Some((found, PathOwner::Other)) if !links.is_empty() => problems.push(format!("{} resolves to {} instead of ver", command, found.display())),
+                    _ => {}
+                }
             } else {
-                return Err(anyhow::anyhow!("找不到最新的 Rust 版本"));
+                if !links.is_empty() {
+                    problems.push(format!("ver still links {} command(s) for {}", links.len(), version_type));
+                    stale_links = links;
+                }
+                if let Some((found, PathOwner::Ver)) = &resolved {
+                    problems.push(format!("{} resolves to {} from ver", command, found.display()));
+                }
             }
-        } else {
-            self.install_version(version, VersionType::Rust).await?;
+            checks.push(DoctorCheck { version_type, managed, resolved, problems, stale_links });
         }
-        
-        Ok(())
+        Ok(checks)
     }
-    
-    /// 使用指定的Rust版本
-    ///
-    /// 切换到指定的Rust版本。
-    ///
-    /// # 参数
-    ///
-    /// * `version` - 版本号
+
+    /// 当前进程的有效用户ID，不支持的平台上为None
+    fn effective_uid() -> Option<u32> {
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        {
+            // SAFETY: geteuid 没有参数，也不会失败
+            Some(unsafe { libc::geteuid() })
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        {
+            None
+        }
+    }
+
+    /// 检查当前用户是否与 ver 主目录的所有者一致
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub fn use_rust_version(&mut self, version: &str) -> Result<()> {
-        self.use_version(version, VersionType::Rust)
+    /// 以 root 使用普通用户的主目录，或以普通用户使用属于 root 的主目录时返回对应的情况，否则返回None。
+    /// 非 Unix 平台总是返回None。
+    pub fn ownership_mismatch(&self) -> Option<OwnershipMismatch> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let uid = Self::effective_uid()?;
+            let owner = fs::metadata(&self.base_dir).ok()?.uid();
+            match (uid, owner) {
+                (0, 0) => None,
+                (0, owner) => Some(OwnershipMismatch::RootInUserDir { owner }),
+                (_, 0) => Some(OwnershipMismatch::UserInRootDir),
+                _ => None,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
     }
-    
-    /// 列出已安装的Rust版本
+
+    /// 是否已通过配置项 `allow_root` 或环境变量 VER_ALLOW_ROOT 确认以 root 运行
+    pub fn allows_root(&self) -> bool {
+        self.config.allow_root()
+    }
+
+    /// 查找主目录中所有者不正确的文件
     ///
-    /// 列出已安装的Rust版本。
+    /// 主目录应与它的上级目录属于同一个用户。以 root 运行过 ver 后，新建的版本、缓存和状态文件属于 root，
+    /// 之后以该用户运行时会因为没有权限而失败。统一提供的版本目录不在主目录中，不检查。
     ///
     /// # 返回
     ///
-    /// 成功时返回已安装Rust版本列表，失败时返回错误。
-    pub fn list_installed_rust_versions(&self) -> Result<Vec<String>> {
-        self.list_installed_versions(VersionType::Rust)
+    /// 成功时返回检查结果，无法读取上级目录时返回错误。非 Unix 平台总是返回空的检查结果。
+    pub fn check_ownership(&self) -> Result<OwnershipCheck> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let parent = self.base_dir.parent().unwrap_or(&self.base_dir);
+            let metadata = fs::metadata(parent).with_context(|| format!("无法读取 {}", parent.display()))?;
+            let (uid, gid) = (metadata.uid(), metadata.gid());
+            let mut foreign = Vec::new();
+            let mut pending = vec![self.base_dir.clone()];
+            while let Some(path) = pending.pop() {
+                let Ok(metadata) = fs::symlink_metadata(&path) else {
+                    continue;
+                };
+                if metadata.uid() != uid {
+                    foreign.push(path.clone());
+                }
+                if metadata.is_dir()
+                    && let Ok(entries) = fs::read_dir(&path)
+                {
+                    pending.extend(entries.flatten().map(|entry| entry.path()));
+                }
+            }
+            foreign.sort();
+            Ok(OwnershipCheck { uid, gid, foreign })
+        }
+        #[cfg(not(unix))]
+        {
+            Ok(OwnershipCheck { uid: 0, gid: 0, foreign: Vec::new() })
+        }
     }
-    
-    /// 删除Rust版本
+
+    /// 把主目录中所有者不正确的文件改回应有的用户和组
     ///
-    /// 删除指定的Rust版本。
+    /// 修改其他用户的文件需要 root 权限，否则返回错误并给出可以手动运行的命令。
     ///
     /// # 参数
     ///
-    /// * `version` - 版本号
+    /// * `check` - `check_ownership` 的结果
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub fn remove_rust_version(&self, version: &str) -> Result<()> {
-        self.remove_version(version, VersionType::Rust)
+    /// 成功时返回修改的文件数量，失败时返回错误。
+    pub fn repair_ownership(&self, check: &OwnershipCheck) -> Result<usize> {
+        if check.foreign.is_empty() {
+            return Ok(0);
+        }
+        if Self::effective_uid() != Some(0) {
+            return Err(anyhow::anyhow!(
+                "修改文件所有者需要 root 权限，请运行 `sudo chown -R {}:{} {}`",
+                check.uid, check.gid, self.base_dir.display()
+            ));
+        }
+        #[cfg(unix)]
+        for path in &check.foreign {
+            std::os::unix::fs::lchown(path, Some(check.uid), Some(check.gid))
+                .with_context(|| format!("无法修改 {} 的所有者", path.display()))?;
+        }
+        Ok(check.foreign.len())
     }
-    
-    /// 创建Rust版本别名
-    ///
-    /// 为指定的Rust版本创建一个别名。
+
+    /// 删除 ver 的 bin 目录中指向不由 ver 管理的工具的链接，并重新生成环境变量文件
     ///
     /// # 参数
     ///
-    /// * `alias` - 别名名称
-    /// * `version` - 版本号
+    /// * `checks` - `doctor` 的结果
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub fn create_rust_alias(&self, alias: &str, version: &str) -> Result<()> {
-        self.create_alias(alias, version, VersionType::Rust)
+    /// 成功时返回删除的链接，失败时返回错误。
+    pub fn remove_stale_links(&self, checks: &[DoctorCheck]) -> Result<Vec<PathBuf>> {
+        let mut removed = Vec::new();
+        for link in checks.iter().flat_map(|check| &check.stale_links) {
+            fs::remove_file(link)?;
+            removed.push(link.clone());
+        }
+        self.update_env_file()?;
+        Ok(removed)
     }
-    
-    /// 获取Rust别名对应的版本
+
+    /// 生成修正后的 PATH 顺序
     ///
-    /// 获取指定Rust别名对应的版本。
+    /// ver 的 bin 目录和 ver 安装的版本排在最前面，其他目录保持原来的相对顺序，重复的目录只保留第一个。
     ///
     /// # 参数
     ///
-    /// * `alias` - 别名名称
+    /// * `entries` - `audit_path` 的结果
     ///
     /// # 返回
     ///
-    /// 成功时返回Rust版本字符串，失败时返回错误。
-    pub fn get_rust_alias(&self, alias: &str) -> Result<Option<String>> {
-        self.get_alias(alias, VersionType::Rust)
+    /// 返回修正后的目录列表。
+    pub fn fixed_path(&self, entries: &[PathEntry]) -> Vec<PathBuf> {
+        let mut dirs = vec![self.bin_dir.clone()];
+        let ver = entries.iter().filter(|entry| entry.owner == PathOwner::Ver);
+        let others = entries.iter().filter(|entry| entry.owner != PathOwner::Ver);
+        for entry in ver.chain(others) {
+            if !dirs.contains(&entry.dir) {
+                dirs.push(entry.dir.clone());
+            }
+        }
+        dirs
     }
-    
-    /// 列出所有Rust别名
+
+    /// 从检测到的版本管理器导入版本
+    ///
+    /// # 参数
     ///
-    /// 列出所有已定义的Rust别名。
+    /// * `name` - 版本管理器名称（nvm、rustup、pyenv 或 gvm）
     ///
     /// # 返回
     ///
-    /// 成功时返回Rust别名列表，失败时返回错误。
-    pub fn list_rust_aliases(&self) -> Result<Vec<(String, String)>> {
-        self.list_aliases(VersionType::Rust)
+    /// 成功时返回导入的版本数量，失败时返回错误。
+    pub async fn import_from_manager(&mut self, name: &str) -> Result<usize> {
+        match name {
+            "nvm" => self.migrate_from("nvm", VersionType::Node, MigrateOptions::default()).await,
+            "rustup" => self.migrate_from_rustup().await,
+            "pyenv" => self.migrate_from_pyenv(MigrateOptions::default()).await,
+            "gvm" => self.migrate_from_gvm(MigrateOptions::default()).await,
+            _ => Err(anyhow::anyhow!("不支持的版本管理器: {}", name)),
+        }
     }
-    
-    /// 设置本地Rust版本
+
+    /// 按组件统计每个Rust工具链的占用空间
     ///
-    /// 在当前目录下创建一个文件指定使用的Rust版本。
+    /// 文档、rust-src、各平台标准库和安装包解压目录单独列出，其余计入 other。
     ///
-    /// # 参数
+    /// # 返回
     ///
-    /// * `version` - 版本号
+    /// 成功时返回每个工具链的占用空间，失败时返回错误。
+    pub fn rust_toolchain_usage(&self) -> Result<Vec<RustToolchainUsage>> {
+        let host = self.platform_suffix_for(VersionType::Rust, self.arch_type).ok();
+        let mut toolchains = Vec::new();
+        for version in self.list_installed_versions(VersionType::Rust)? {
+            let version = version.trim_end_matches(" (current)").to_string();
+            let version_dir = self.get_version_dir(&version, VersionType::Rust);
+            let size = Self::dir_size(&version_dir)?;
+            let mut entries = Vec::new();
+            let mut push = |label: String, path: PathBuf, part: Option<RustPart>| -> Result<()> {
+                if path.is_dir() {
+                    let size = Self::dir_size(&path)?;
+                    entries.push(RustUsageEntry { label, path, size, part });
+                }
+                Ok(())
+            };
+            
+            push("docs".to_string(), version_dir.join("share").join("doc").join("rust").join("html"), Some(RustPart::Docs))?;
+            let rustlib = version_dir.join("lib").join("rustlib");
+            push("src".to_string(), rustlib.join("src"), Some(RustPart::Source))?;
+            if rustlib.is_dir() {
+                let mut targets: Vec<_> = fs::read_dir(&rustlib)?
+                    .flatten()
+                    .filter(|entry| entry.path().join("lib").is_dir())
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect();
+                targets.sort();
+                for target in targets {
+                    let part = (host.as_deref() != Some(target.as_str())).then_some(RustPart::Targets);
+                    push(format!("std {}", target), rustlib.join(&target), part)?;
+                }
+                
+                // 通过安装脚本安装后，解压出来的安装包目录不再被使用
+                for entry in fs::read_dir(&version_dir)?.flatten() {
+                    if entry.file_name().to_string_lossy().starts_with("rust-") {
+                        push("installer".to_string(), entry.path(), Some(RustPart::Installer))?;
+                    }
+                }
+            }
+            
+            let listed: u64 = entries.iter().map(|entry| entry.size).sum();
+            entries.push(RustUsageEntry {
+                label: "other".to_string(),
+                path: version_dir.clone(),
+                size: size.saturating_sub(listed),
+                part: None,
+            });
+            toolchains.push(RustToolchainUsage {
+                locked: self.is_locked(&version, VersionType::Rust),
+                version,
+                size,
+                entries,
+            });
+        }
+        toolchains.sort_by(|a, b| compare_versions(&b.version, &a.version));
+        Ok(toolchains)
+    }
+
+    /// 统计 cargo 目录（CARGO_HOME，默认 ~/.cargo）中下载的依赖
     ///
     /// # 返回
     ///
-    /// 成功时返回Ok(()，失败时返回错误。
-    pub fn set_local_rust_version(&self, version: &str) -> Result<()> {
-        self.set_local_version(version, VersionType::Rust)
+    /// 成功时返回 cargo 目录和其中各部分的占用空间，找不到 cargo 目录时返回None。
+    pub fn cargo_home_usage(&self) -> Result<Option<(PathBuf, Vec<RustUsageEntry>)>> {
+        let cargo_home = match env::var_os("CARGO_HOME").filter(|value| !value.is_empty()) {
+            Some(path) => PathBuf::from(path),
+            None => home_dir(Some("CARGO_HOME"))?.join(".cargo"),
+        };
+        if !cargo_home.is_dir() {
+            return Ok(None);
+        }
+        
+        let mut entries = Vec::new();
+        for (label, part) in [
+            ("registry/cache", None),
+            ("registry/index", None),
+            ("registry/src", Some(RustPart::Registry)),
+            ("git/db", None),
+            ("git/checkouts", Some(RustPart::Registry)),
+        ] {
+            let path = label.split('/').fold(cargo_home.clone(), |path, component| path.join(component));
+            if path.is_dir() {
+                entries.push(RustUsageEntry { label: label.to_string(), size: Self::dir_size(&path)?, path, part });
+            }
+        }
+        Ok(Some((cargo_home, entries)))
     }
-    
-    /// 使用指定Rust版本执行命令
+
+    /// 删除Rust工具链中的一部分文件
     ///
-    /// 使用指定的Rust版本执行命令。
+    /// 同时从安装记录中去掉被删除的文件，避免 `ver verify` 把它们报告为缺失。
     ///
     /// # 参数
     ///
-    /// * `version` - 版本号
-    /// * `command` - 命令名称
-    /// * `args` - 命令参数
+    /// * `version` - 工具链版本，cargo 目录中的文件为None
+    /// * `entry` - 要删除的部分
     ///
     /// # 返回
     ///
     /// 成功时返回Ok(()，失败时返回错误。
-    pub fn exec_with_rust_version(&self, version: &str, command: &str, args: &[String]) -> Result<()> {
-        self.exec_with_version(version, command, args, VersionType::Rust)
+    pub fn remove_rust_part(&self, version: Option<&str>, entry: &RustUsageEntry) -> Result<()> {
+        self.ensure_versions_writable()?;
+        fs::remove_dir_all(&entry.path)?;
+        let Some(version) = version else {
+            return Ok(());
+        };
+        let version_dir = self.get_version_dir(version, VersionType::Rust);
+        if let Some(mut receipt) = self.read_install_receipt(version, VersionType::Rust)?
+            && let Ok(relative) = entry.path.strip_prefix(&version_dir)
+        {
+            let prefix = format!("{}/", relative.components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"));
+            receipt.files.retain(|path, _| !path.starts_with(&prefix));
+            write_atomic(&version_dir.join(INSTALL_RECEIPT_FILE), serde_json::to_string_pretty(&receipt)?)?;
+        }
+        Ok(())
     }
-    
+
     /// 从rustup迁移
     ///
     /// 从rustup迁移已安装的Rust版本。
@@ -1821,94 +9455,26 @@ impl VersionManager {
     /// # 返回
     ///
     /// 成功时返回迁移的版本数量，失败时返回错误。
-    #[allow(dead_code)]
-    pub async fn migrate_from_rustup(&self) -> Result<usize> {
-        self.migrate_from("rustup", VersionType::Rust).await
+    pub async fn migrate_from_rustup(&mut self) -> Result<usize> {
+        self.migrate_from("rustup", VersionType::Rust, MigrateOptions::default()).await
     }
 
-    /// 获取可用的 Python 版本列表
-    pub async fn list_available_python_versions(&self, stable_only: bool) -> Result<Vec<String>> {
-        let versions = self.list_available_versions(false, VersionType::Python).await?;
-        let mut result = Vec::new();
-        
-        for version in versions {
-            // 如果只需要稳定版本，则跳过包含 alpha、beta、rc 的版本
-            if stable_only && (version.version.contains("alpha") || 
-                              version.version.contains("beta") || 
-                              version.version.contains("rc")) {
-                continue;
-            }
-            result.push(version.version);
-        }
-        
-        Ok(result)
-    }
-    
-    /// 安装指定的 Python 版本
-    pub async fn install_python_version(&self, version: &str) -> Result<()> {
-        // 直接使用版本字符串，不需要解析
-        self.install_version(version, VersionType::Python).await?;
-        Ok(())
-    }
-    
-    /// 使用指定的 Python 版本
-    pub fn use_python_version(&mut self, version: &str) -> Result<()> {
-        self.use_version(version, VersionType::Python)
-    }
-    
-    /// 获取当前使用的 Python 版本
-    pub fn get_current_python_version(&self) -> Option<String> {
-        self.get_current_version(VersionType::Python).cloned()
-    }
-    
-    /// 列出已安装的 Python 版本
-    pub fn list_installed_python_versions(&self) -> Result<Vec<String>> {
-        self.list_installed_versions(VersionType::Python)
-    }
-    
-    /// 删除指定的 Python 版本
-    pub fn remove_python_version(&self, version: &str) -> Result<()> {
-        self.remove_version(version, VersionType::Python)
-    }
-    
-    /// 创建 Python 版本别名
-    pub fn create_python_alias(&self, name: &str, version: &str) -> Result<()> {
-        self.create_alias(name, version, VersionType::Python)
-    }
-    
-    /// 获取 Python 版本别名对应的实际版本
-    pub fn get_python_alias(&self, alias: &str) -> Result<Option<String>> {
-        self.get_alias(alias, VersionType::Python)
-    }
-    
-    /// 列出所有 Python 版本别名
-    pub fn list_python_aliases(&self) -> Result<Vec<(String, String)>> {
-        self.list_aliases(VersionType::Python)
-    }
-    
-    /// 设置当前目录的 Python 版本
-    pub fn set_local_python_version(&self, version: &str) -> Result<()> {
-        self.set_local_version(version, VersionType::Python)
-    }
-    
-    /// 使用指定的 Python 版本执行命令
-    pub fn exec_with_python_version(&self, version: &str, command: &str, args: &[String]) -> Result<()> {
-        self.exec_with_version(version, command, args, VersionType::Python)
-    }
-    
     /// 从 pyenv 迁移 Python 版本
-    pub async fn migrate_from_pyenv(&self) -> Result<usize> {
-        let pyenv_versions_dir = dirs::home_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            .join(".pyenv")
-            .join("versions");
+    #[allow(clippy::collapsible_if)]
+    pub async fn migrate_from_pyenv(&mut self, options: MigrateOptions<'_>) -> Result<usize> {
+        self.ensure_versions_writable()?;
+        let pyenv_root = match env::var_os("PYENV_ROOT").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => home_dir(Some("PYENV_ROOT"))?.join(".pyenv"),
+        };
+        let pyenv_versions_dir = pyenv_root.join("versions");
         
         if !pyenv_versions_dir.exists() {
             return Ok(0);
         }
         
         let mut count = 0;
-        for entry in fs::read_dir(pyenv_versions_dir)? {
+        for entry in fs::read_dir(&pyenv_versions_dir)? {
             let entry = entry?;
             let path = entry.path();
             
@@ -1919,120 +9485,80 @@ impl VersionManager {
                         continue;
                     }
                     
-                    // 复制版本目录
-                    let target_dir = self.versions_dir.join(version_str);
-                    if !target_dir.exists() {
-                        fs::create_dir_all(&target_dir)?;
-                        
-                        // 复制 bin 目录
-                        let bin_dir = path.join("bin");
-                        if bin_dir.exists() {
-                            let target_bin_dir = target_dir.join("bin");
-                            fs::create_dir_all(&target_bin_dir)?;
-                            
-                            for bin_entry in fs::read_dir(bin_dir)? {
-                                let bin_entry = bin_entry?;
-                                let bin_path = bin_entry.path();
-                                
-                                if bin_path.is_file() {
-                                    let file_name = bin_path.file_name().unwrap();
-                                    let target_bin_path = target_bin_dir.join(file_name);
-                                    fs::copy(&bin_path, &target_bin_path)?;
-                                    
-                                    // 设置执行权限
-                                    if let OsType::Darwin | OsType::Linux = self.os_type {
-                                        let mut perms = fs::metadata(&target_bin_path)?.permissions();
-                                        perms.set_mode(0o755); // rwxr-xr-x
-                                        fs::set_permissions(&target_bin_path, perms)?;
-                                    }
-                                }
-                            }
-                            
-                            count += 1;
-                        }
+                    if path.join("bin").exists() && self.migrate_version("pyenv", version_str, VersionType::Python, &path, options)? {
+                        count += 1;
                     }
                 }
             }
         }
         
+        // pyenv 的全局版本记录在 version 文件中（旧版本为 global），system 表示系统自带的Python
+        let pyenv_root = pyenv_versions_dir.parent().map(Path::to_path_buf).unwrap_or_default();
+        let default = ["version", "global"].iter()
+            .find_map(|name| fs::read_to_string(pyenv_root.join(name)).ok())
+            .and_then(|content| content.split_whitespace().next().map(str::to_string))
+            .filter(|version| version != "system");
+        self.import_migrated_aliases("pyenv", VersionType::Python, &[], default.as_deref(), options)?;
+        
         Ok(count)
     }
     
-    /// 获取可用的 Go 版本列表
-    pub async fn list_available_go_versions(&self, stable_only: bool) -> Result<Vec<String>> {
-        let versions = self.list_available_versions(false, VersionType::Go).await?;
-        let mut result = Vec::new();
-        
-        for version in versions {
-            // 如果只需要稳定版本，则跳过包含 beta、rc 的版本
-            if stable_only && (version.version.contains("beta") || 
-                              version.version.contains("rc")) {
-                continue;
+    /// 生成 Go 交叉编译的环境变量
+    ///
+    /// 设置 GOOS/GOARCH；目标平台与本机不同且没有要求 cgo 时设置 CGO_ENABLED=0，
+    /// 因为交叉编译 cgo 需要目标平台的C编译器。目标平台通过 `go tool dist list` 校验。
+    ///
+    /// # 参数
+    ///
+    /// * `version` - Go版本
+    /// * `goos` - 目标操作系统，例如 linux
+    /// * `goarch` - 目标架构，例如 arm64
+    /// * `cgo` - 是否启用 cgo
+    /// * `cc` - 启用 cgo 时使用的C编译器
+    ///
+    /// # 返回
+    ///
+    /// 成功时返回环境变量列表，目标平台不受支持时返回错误。
+    pub fn go_cross_env(&self, version: &str, goos: &str, goarch: &str, cgo: bool, cc: Option<&str>) -> Result<Vec<(String, String)>> {
+        let target = format!("{}/{}", goos, goarch);
+        // 版本尚未安装时跳过校验，由 go 自己报错
+        if let Ok(output) = Command::new(self.get_executable_path(version, VersionType::Go))
+            .args(["tool", "dist", "list"])
+            .output()
+            && output.status.success()
+        {
+            let targets = String::from_utf8_lossy(&output.stdout);
+            if !targets.lines().any(|line| line.trim() == target) {
+                return Err(anyhow::anyhow!("Go {} 不支持目标平台 {}，运行 `go tool dist list` 查看支持的平台", version, target));
             }
-            result.push(version.version);
         }
         
-        Ok(result)
-    }
-    
-    /// 安装指定的 Go 版本
-    pub async fn install_go_version(&self, version: &str) -> Result<()> {
-        // 直接使用版本字符串，不需要解析
-        self.install_version(version, VersionType::Go).await?;
-        Ok(())
-    }
-    
-    /// 使用指定的 Go 版本
-    pub fn use_go_version(&mut self, version: &str) -> Result<()> {
-        self.use_version(version, VersionType::Go)
-    }
-    
-    /// 获取当前使用的 Go 版本
-    pub fn get_current_go_version(&self) -> Option<String> {
-        self.get_current_version(VersionType::Go).cloned()
-    }
-    
-    /// 列出已安装的 Go 版本
-    pub fn list_installed_go_versions(&self) -> Result<Vec<String>> {
-        self.list_installed_versions(VersionType::Go)
-    }
-    
-    /// 删除指定的 Go 版本
-    pub fn remove_go_version(&self, version: &str) -> Result<()> {
-        self.remove_version(version, VersionType::Go)
-    }
-    
-    /// 创建 Go 版本别名
-    pub fn create_go_alias(&self, name: &str, version: &str) -> Result<()> {
-        self.create_alias(name, version, VersionType::Go)
-    }
-    
-    /// 获取 Go 版本别名对应的实际版本
-    pub fn get_go_alias(&self, alias: &str) -> Result<Option<String>> {
-        self.get_alias(alias, VersionType::Go)
-    }
-    
-    /// 列出所有 Go 版本别名
-    pub fn list_go_aliases(&self) -> Result<Vec<(String, String)>> {
-        self.list_aliases(VersionType::Go)
-    }
-    
-    /// 设置当前目录的 Go 版本
-    pub fn set_local_go_version(&self, version: &str) -> Result<()> {
-        self.set_local_version(version, VersionType::Go)
-    }
-    
-    /// 使用指定的 Go 版本执行命令
-    pub fn exec_with_go_version(&self, version: &str, command: &str, args: &[String]) -> Result<()> {
-        self.exec_with_version(version, command, args, VersionType::Go)
+        let host = self.platform_suffix_for(VersionType::Go, self.arch_type).ok()
+            .map(|suffix| suffix.replacen('-', "/", 1));
+        let mut env = vec![
+            ("GOOS".to_string(), goos.to_string()),
+            ("GOARCH".to_string(), goarch.to_string()),
+        ];
+        if cgo {
+            env.push(("CGO_ENABLED".to_string(), "1".to_string()));
+            if let Some(cc) = cc {
+                env.push(("CC".to_string(), cc.to_string()));
+            }
+        } else if host.as_deref() != Some(target.as_str()) {
+            env.push(("CGO_ENABLED".to_string(), "0".to_string()));
+        }
+        Ok(env)
     }
     
     /// 从 gvm 迁移 Go 版本
-    pub async fn migrate_from_gvm(&self) -> Result<usize> {
-        let gvm_versions_dir = dirs::home_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            .join(".gvm")
-            .join("gos");
+    #[allow(clippy::collapsible_if)]
+    pub async fn migrate_from_gvm(&self, options: MigrateOptions<'_>) -> Result<usize> {
+        self.ensure_versions_writable()?;
+        let gvm_root = match env::var_os("GVM_ROOT").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => home_dir(Some("GVM_ROOT"))?.join(".gvm"),
+        };
+        let gvm_versions_dir = gvm_root.join("gos");
         
         if !gvm_versions_dir.exists() {
             return Ok(0);
@@ -2053,37 +9579,8 @@ impl VersionManager {
                     // 提取版本号
                     let version = &version_str[2..]; // 去掉 "go" 前缀
                     
-                    // 复制版本目录
-                    let target_dir = self.versions_dir.join(version);
-                    if !target_dir.exists() {
-                        fs::create_dir_all(&target_dir)?;
-                        
-                        // 复制 bin 目录
-                        let bin_dir = path.join("bin");
-                        if bin_dir.exists() {
-                            let target_bin_dir = target_dir.join("bin");
-                            fs::create_dir_all(&target_bin_dir)?;
-                            
-                            for bin_entry in fs::read_dir(bin_dir)? {
-                                let bin_entry = bin_entry?;
-                                let bin_path = bin_entry.path();
-                                
-                                if bin_path.is_file() {
-                                    let file_name = bin_path.file_name().unwrap();
-                                    let target_bin_path = target_bin_dir.join(file_name);
-                                    fs::copy(&bin_path, &target_bin_path)?;
-                                    
-                                    // 设置执行权限
-                                    if let OsType::Darwin | OsType::Linux = self.os_type {
-                                        let mut perms = fs::metadata(&target_bin_path)?.permissions();
-                                        perms.set_mode(0o755); // rwxr-xr-x
-                                        fs::set_permissions(&target_bin_path, perms)?;
-                                    }
-                                }
-                            }
-                            
-                            count += 1;
-                        }
+                    if path.join("bin").exists() && self.migrate_version("gvm", version, VersionType::Go, &path, options)? {
+                        count += 1;
                     }
                 }
             }
@@ -2092,3 +9589,134 @@ impl VersionManager {
         Ok(count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sh_quote_keeps_special_characters_literal() {
+        assert_eq!(sh_quote("/opt/ver"), "'/opt/ver'");
+        assert_eq!(sh_quote("a\"$(b)`c`"), "'a\"$(b)`c`'");
+        assert_eq!(sh_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn fish_quote_escapes_backslashes_and_quotes() {
+        assert_eq!(fish_quote("$HOME/(x)"), "'$HOME/(x)'");
+        assert_eq!(fish_quote("a\\b'c"), "'a\\\\b\\'c'");
+    }
+
+    #[test]
+    fn wildcard_match_handles_stars_and_question_marks() {
+        assert!(VersionManager::wildcard_match("*.md", "README.md"));
+        assert!(VersionManager::wildcard_match("*", ""));
+        assert!(VersionManager::wildcard_match("lib?.so", "lib1.so"));
+        assert!(VersionManager::wildcard_match("a*b*c", "aXXbYYc"));
+        assert!(VersionManager::wildcard_match("*test*", "unittest_x"));
+        assert!(!VersionManager::wildcard_match("*.md", "README.txt"));
+        assert!(!VersionManager::wildcard_match("lib?.so", "lib.so"));
+        assert!(!VersionManager::wildcard_match("abc", "abcd"));
+    }
+
+    #[test]
+    fn is_excluded_matches_paths_and_components() {
+        let excludes = vec!["share/doc".to_string(), "__pycache__".to_string(), "*.md".to_string()];
+        assert!(VersionManager::is_excluded(Path::new("share/doc"), &excludes));
+        assert!(VersionManager::is_excluded(Path::new("lib/python3.12/__pycache__/x.pyc"), &excludes));
+        assert!(VersionManager::is_excluded(Path::new("include/README.md"), &excludes));
+        assert!(!VersionManager::is_excluded(Path::new("share/man"), &excludes));
+        assert!(!VersionManager::is_excluded(Path::new("bin/node"), &excludes));
+        assert!(!VersionManager::is_excluded(Path::new("bin/node"), &[]));
+    }
+
+    #[test]
+    fn check_archive_entry_rejects_absolute_and_parent_paths() {
+        assert!(VersionManager::check_archive_entry(Path::new("/etc/passwd"), None, false).is_err());
+        assert!(VersionManager::check_archive_entry(Path::new("../evil"), None, false).is_err());
+        assert!(VersionManager::check_archive_entry(Path::new("node/../../evil"), None, false).is_err());
+        // 即使最终仍在目录内，也不接受 `..`
+        assert!(VersionManager::check_archive_entry(Path::new("node/../bin/node"), None, false).is_err());
+        assert!(VersionManager::check_archive_entry(Path::new("node-v20/bin/node"), None, false).is_ok());
+        assert!(VersionManager::check_archive_entry(Path::new("./node-v20/bin/node"), None, false).is_ok());
+    }
+
+    #[test]
+    fn check_archive_entry_rejects_links_out_of_the_root() {
+        let entry = Path::new("node-v20/bin/npm");
+        assert!(VersionManager::check_archive_entry(entry, Some(Path::new("../../..")), false).is_err());
+        assert!(VersionManager::check_archive_entry(entry, Some(Path::new("../../../etc/passwd")), false).is_err());
+        assert!(VersionManager::check_archive_entry(entry, Some(Path::new("/etc")), false).is_err());
+        // 硬链接的目标相对于解压目录
+        assert!(VersionManager::check_archive_entry(entry, Some(Path::new("../outside")), true).is_err());
+        assert!(VersionManager::check_archive_entry(entry, Some(Path::new("/etc/passwd")), true).is_err());
+    }
+
+    #[test]
+    fn check_archive_entry_allows_links_inside_the_root() {
+        let entry = Path::new("node-v20/bin/npm");
+        assert!(VersionManager::check_archive_entry(entry, Some(Path::new("../lib/node_modules/npm/bin/npm-cli.js")), false).is_ok());
+        assert!(VersionManager::check_archive_entry(entry, Some(Path::new("node")), false).is_ok());
+        assert!(VersionManager::check_archive_entry(entry, Some(Path::new("node-v20/bin/node")), true).is_ok());
+    }
+
+    #[test]
+    fn extract_archive_rejects_zip_entries_outside_the_destination() {
+        let dir = std::env::temp_dir().join(format!("ver-zip-slip-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (case, name) in ["../evil.txt", "/tmp/evil.txt"].iter().enumerate() {
+            let archive = dir.join(format!("{}.zip", case));
+            let mut writer = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+            writer.start_file("bin/node", zip::write::FileOptions::default()).unwrap();
+            writer.write_all(b"node").unwrap();
+            writer.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            writer.write_all(b"evil").unwrap();
+            writer.finish().unwrap();
+
+            let dest = dir.join(format!("out{}", case)).join("version");
+            let err = VersionManager::extract_archive(&archive, &dest).unwrap_err();
+            assert!(err.to_string().contains("不安全的路径"), "{}: {}", name, err);
+            assert!(!dir.join(format!("out{}", case)).join("evil.txt").exists());
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("20.1.0", "20.1.0"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("20.1.0", "20.10.0"), 1);
+        assert_eq!(edit_distance("18.2.0", "20.2.0"), 2);
+        assert_eq!(edit_distance("pyhton", "python"), 2);
+        // 按字符而不是字节计算
+        assert_eq!(edit_distance("nöde", "node"), 1);
+    }
+
+    #[test]
+    fn similar_versions_prefers_the_same_major() {
+        let candidates = ["20.11.0", "20.10.0", "21.1.0", "18.1.0", "2.1.0"];
+        assert_eq!(similar_versions("20.1.0", candidates), vec!["20.11.0", "20.10.0", "21.1.0"]);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_path_uses_extended_length_prefixes() {
+        assert_eq!(long_path(Path::new(r"C:\Users\me\.version-manager")), PathBuf::from(r"\\?\C:\Users\me\.version-manager"));
+        // 扩展长度路径中只能使用反斜杠
+        assert_eq!(long_path(Path::new("C:/ver/versions/20.11.1")), PathBuf::from(r"\\?\C:\ver\versions\20.11.1"));
+        assert_eq!(long_path(Path::new(r"\\server\share\ver")), PathBuf::from(r"\\?\UNC\server\share\ver"));
+        assert_eq!(long_path(Path::new(r"\\?\C:\ver")), PathBuf::from(r"\\?\C:\ver"));
+        assert_eq!(long_path(Path::new(r"versions\20.11.1")), PathBuf::from(r"versions\20.11.1"));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn long_path_leaves_paths_unchanged_outside_windows() {
+        assert_eq!(long_path(Path::new("/opt/ver/versions")), PathBuf::from("/opt/ver/versions"));
+        assert_eq!(long_path(Path::new("versions/20.11.1")), PathBuf::from("versions/20.11.1"));
+    }
+}
@@ -0,0 +1,353 @@
+//! 针对本地模拟服务器的安装、切换、删除流程测试
+//!
+//! 数据目录使用临时目录，下载地址指向本地服务器，不访问网络或用户主目录。
+
+#![cfg(unix)]
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use ver::version_manager::{ManagerOptions, ProgressMode, VersionManager, VersionType};
+
+/// 模拟服务器提供的平台
+const PLATFORMS: &[&str] = &["linux-x64", "linux-arm64", "darwin-x64", "darwin-arm64"];
+
+/// 在后台线程中运行的静态文件服务器，路径不存在时返回404
+fn serve(files: HashMap<String, Vec<u8>>) -> String {
+    serve_logged(files).0
+}
+
+/// 同 `serve`，并返回收到的请求路径
+fn serve_logged(files: HashMap<String, Vec<u8>>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let files = Arc::new(files);
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let log = requests.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let files = files.clone();
+            let log = log.clone();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).is_err() {
+                    return;
+                }
+                // 读完请求头
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                    line.clear();
+                }
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let path = path.split('?').next().unwrap_or(path);
+                log.lock().unwrap().push(path.to_string());
+                let (status, body) = match files.get(path) {
+                    Some(body) => ("200 OK", body.as_slice()),
+                    None => ("404 Not Found", &b""[..]),
+                };
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len());
+                let _ = stream.write_all(body);
+            });
+        }
+    });
+    (url, requests)
+}
+
+/// 生成只含 `bin/node` 脚本的 Node.js 发行包
+fn node_tarball(version: &str, platform: &str) -> Vec<u8> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    let mut builder = tar::Builder::new(encoder);
+    let script = format!("#!/bin/sh\necho v{}\n", version);
+    let mut header = tar::Header::new_gnu();
+    header.set_size(script.len() as u64);
+    header.set_mode(0o755);
+    header.set_cksum();
+    builder.append_data(&mut header, format!("node-v{}-{}/bin/node", version, platform), script.as_bytes()).unwrap();
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+/// 生成一组 Node.js 版本的下载文件；`corrupt` 为true时 SHASUMS256.txt 中的哈希与文件不符
+fn node_release_files(versions: &[&str], corrupt: bool) -> HashMap<String, Vec<u8>> {
+    let mut files = HashMap::new();
+    let mut index = Vec::new();
+    for version in versions {
+        let mut shasums = String::new();
+        for platform in PLATFORMS {
+            let name = format!("node-v{}-{}.tar.gz", version, platform);
+            let tarball = node_tarball(version, platform);
+            let hash = if corrupt { "0".repeat(64) } else { format!("{:x}", Sha256::digest(&tarball)) };
+            shasums.push_str(&format!("{}  {}\n", hash, name));
+            files.insert(format!("/v{}/{}", version, name), tarball);
+        }
+        files.insert(format!("/v{}/SHASUMS256.txt", version), shasums.into_bytes());
+        index.push(serde_json::json!({
+            "version": format!("v{}", version),
+            "date": "2024-01-01",
+            "files": PLATFORMS,
+            "lts": false,
+        }));
+    }
+    files.insert("/index.json".to_string(), serde_json::to_vec(&index).unwrap());
+    files
+}
+
+/// 使用临时数据目录和模拟服务器创建版本管理器
+fn manager(base_dir: &Path, url: &str) -> VersionManager {
+    manager_with_delta(base_dir, url, None)
+}
+
+/// 同 `manager`，并使用增量源
+fn manager_with_delta(base_dir: &Path, url: &str, delta: Option<&str>) -> VersionManager {
+    let mut manager = VersionManager::with_options(ManagerOptions {
+        base_dir: Some(base_dir.to_path_buf()),
+        endpoints: vec![(VersionType::Node, url.to_string())],
+        delta_endpoint: delta.map(str::to_string),
+        ..ManagerOptions::default()
+    }).unwrap();
+    manager.set_progress_mode(ProgressMode::Quiet);
+    manager
+}
+
+/// 每个测试独立的临时数据目录
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ver-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn install_use_remove() {
+    let url = serve(node_release_files(&["20.1.0", "20.2.0"], false));
+    let base_dir = temp_dir("install-use-remove");
+    let mut manager = manager(&base_dir, &url);
+
+    manager.install_version("20.1.0", VersionType::Node).await.unwrap();
+    manager.install_version("20.2.0", VersionType::Node).await.unwrap();
+    assert!(manager.is_installed("20.1.0", VersionType::Node));
+    assert!(manager.get_bin_dir("20.1.0", VersionType::Node).join("node").exists());
+    assert_eq!(manager.list_installed_versions(VersionType::Node).unwrap().len(), 2);
+
+    manager.use_version("20.1.0", VersionType::Node).unwrap();
+    assert_eq!(manager.get_current_version(VersionType::Node).as_deref(), Some("20.1.0"));
+    let output = std::process::Command::new(base_dir.join("bin").join("node")).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "v20.1.0");
+
+    // 当前版本不能删除
+    assert!(manager.remove_version("20.1.0", VersionType::Node, false).is_err());
+    manager.remove_version("20.2.0", VersionType::Node, false).unwrap();
+    assert!(!manager.is_installed("20.2.0", VersionType::Node));
+    assert!(manager.is_installed("20.1.0", VersionType::Node));
+
+    let _ = std::fs::remove_dir_all(&base_dir);
+}
+
+#[tokio::test]
+async fn checksum_mismatch_leaves_nothing_installed() {
+    let url = serve(node_release_files(&["20.1.0"], true));
+    let base_dir = temp_dir("checksum-mismatch");
+    let manager = manager(&base_dir, &url);
+
+    assert!(manager.install_version("20.1.0", VersionType::Node).await.is_err());
+    assert!(!manager.is_installed("20.1.0", VersionType::Node));
+    assert!(manager.list_installed_versions(VersionType::Node).unwrap().is_empty());
+
+    let _ = std::fs::remove_dir_all(&base_dir);
+}
+
+/// 把 `dir` 中的文件按相对路径加入模拟服务器的文件表
+fn add_tree(files: &mut HashMap<String, Vec<u8>>, root: &Path, dir: &Path) {
+    for entry in std::fs::read_dir(dir).unwrap().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            add_tree(files, root, &path);
+        } else {
+            let relative = path.strip_prefix(root).unwrap().to_string_lossy().to_string();
+            files.insert(format!("/delta/{}", relative), std::fs::read(&path).unwrap());
+        }
+    }
+}
+
+/// 安装 20.1.0 后发布 20.2.0 的增量清单，`tamper` 修改清单中记录的安装包SHA-256
+async fn delta_install(name: &str, tamper: bool) -> Vec<String> {
+    let base_dir = temp_dir(name);
+    let mut files = node_release_files(&["20.1.0", "20.2.0"], false);
+    let platform = manager(&base_dir, "http://127.0.0.1:9").platform_suffix(VersionType::Node).unwrap();
+    let archive = base_dir.join("node-v20.2.0.tar.gz");
+    std::fs::write(&archive, &files[&format!("/v20.2.0/node-v20.2.0-{}.tar.gz", platform)]).unwrap();
+    let out = base_dir.join("delta");
+    manager(&base_dir, "http://127.0.0.1:9").publish_delta(&archive, VersionType::Node, "20.2.0", &platform, &out).unwrap();
+    if tamper {
+        let manifest = out.join("node").join("20.2.0").join(format!("{}.json", platform));
+        let mut value: serde_json::Value = serde_json::from_slice(&std::fs::read(&manifest).unwrap()).unwrap();
+        value["archive_sha256"] = serde_json::json!("0".repeat(64));
+        std::fs::write(&manifest, serde_json::to_vec(&value).unwrap()).unwrap();
+    }
+    add_tree(&mut files, &out, &out);
+    let (url, requests) = serve_logged(files);
+    let manager = manager_with_delta(&base_dir, &url, Some(&format!("{}/delta", url)));
+
+    manager.install_version("20.1.0", VersionType::Node).await.unwrap();
+    requests.lock().unwrap().clear();
+    manager.install_version("20.2.0", VersionType::Node).await.unwrap();
+    let output = std::process::Command::new(manager.get_bin_dir("20.2.0", VersionType::Node).join("node")).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "v20.2.0");
+
+    let _ = std::fs::remove_dir_all(&base_dir);
+    requests.lock().unwrap().clone()
+}
+
+/// 请求的是否为 20.2.0 的完整安装包（头文件包除外）
+fn is_full_archive(path: &str) -> bool {
+    path.starts_with("/v20.2.0/node-v20.2.0-") && !path.contains("headers")
+}
+
+#[tokio::test]
+async fn delta_install_checks_the_published_checksum() {
+    let requests = delta_install("delta", false).await;
+    assert!(requests.iter().any(|path| path.starts_with("/delta/blobs/")));
+    assert!(!requests.iter().any(|path| is_full_archive(path)));
+}
+
+#[tokio::test]
+async fn delta_manifest_for_another_archive_falls_back_to_the_full_archive() {
+    let requests = delta_install("delta-tampered", true).await;
+    assert!(!requests.iter().any(|path| path.starts_with("/delta/blobs/")));
+    assert!(requests.iter().any(|path| is_full_archive(path)));
+}
+
+#[tokio::test]
+async fn repair_reinstalls_beside_the_version_and_keeps_added_files() {
+    let url = serve(node_release_files(&["20.1.0"], false));
+    let base_dir = temp_dir("repair");
+    let manager = manager(&base_dir, &url);
+    manager.install_version("20.1.0", VersionType::Node).await.unwrap();
+
+    let bin_dir = manager.get_bin_dir("20.1.0", VersionType::Node);
+    std::fs::write(bin_dir.join("node"), "#!/bin/sh\necho tampered\n").unwrap();
+    std::fs::write(bin_dir.join("added"), "global package").unwrap();
+    let result = manager.verify_version("20.1.0", VersionType::Node).unwrap().unwrap();
+    assert_eq!(result.modified.len(), 1);
+
+    manager.repair_version("20.1.0", VersionType::Node).await.unwrap();
+    let result = manager.verify_version("20.1.0", VersionType::Node).unwrap().unwrap();
+    assert!(result.modified.is_empty() && result.missing.is_empty());
+    assert_eq!(std::fs::read_to_string(bin_dir.join("added")).unwrap(), "global package");
+    let version_dir = bin_dir.parent().unwrap().parent().unwrap();
+    assert!(version_dir.join("activate").exists());
+    let leftovers: Vec<_> = std::fs::read_dir(version_dir.parent().unwrap()).unwrap()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(".20.1.0"))
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+
+    let _ = std::fs::remove_dir_all(&base_dir);
+}
+
+/// 目录中缓存的 20.1.0 完整安装包（头文件包除外）
+fn cached_archives(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            name.starts_with("node-20.1.0-") && name.contains('@') && !name.contains("headers")
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn truncated_cached_archive_is_downloaded_again() {
+    let (url, requests) = serve_logged(node_release_files(&["20.1.0"], false));
+    let base_dir = temp_dir("truncated-cache");
+    let manager = manager(&base_dir, &url);
+    manager.install_version("20.1.0", VersionType::Node).await.unwrap();
+
+    let cached = cached_archives(&base_dir.join("cache"));
+    assert_eq!(cached.len(), 1);
+    let content = std::fs::read(&cached[0]).unwrap();
+    std::fs::write(&cached[0], &content[..content.len() / 2]).unwrap();
+    manager.remove_version("20.1.0", VersionType::Node, false).unwrap();
+    requests.lock().unwrap().clear();
+
+    manager.install_version("20.1.0", VersionType::Node).await.unwrap();
+    assert!(requests.lock().unwrap().iter().any(|path| path.starts_with("/v20.1.0/node-v20.1.0-") && !path.contains("headers")));
+    let output = std::process::Command::new(manager.get_bin_dir("20.1.0", VersionType::Node).join("node")).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "v20.1.0");
+    assert_eq!(std::fs::read(&cached_archives(&base_dir.join("cache"))[0]).unwrap(), content);
+
+    let _ = std::fs::remove_dir_all(&base_dir);
+}
+
+#[tokio::test]
+async fn tampered_archive_in_the_shared_cache_is_not_used() {
+    let files = node_release_files(&["20.1.0"], false);
+    let (url, requests) = serve_logged(files.clone());
+    let base_dir = temp_dir("shared-cache");
+    let shared = base_dir.join("shared");
+    let home = base_dir.join("home");
+    std::fs::create_dir_all(&home).unwrap();
+    std::fs::write(home.join("config.json"), serde_json::json!({ "cache": { "shared_dir": shared } }).to_string()).unwrap();
+    let first = manager(&home, &url);
+
+    // 文件名带有发布的SHA-256，内容却是另一个安装包
+    let platform = first.platform_suffix(VersionType::Node).unwrap();
+    let real = &files[&format!("/v20.1.0/node-v20.1.0-{}.tar.gz", platform)];
+    let planted = shared.join("node").join(format!("node-v20.1.0-{}-000000000000@{:x}.tar.gz", platform, Sha256::digest(real)));
+    std::fs::create_dir_all(planted.parent().unwrap()).unwrap();
+    std::fs::write(&planted, node_tarball("6.6.6", &platform)).unwrap();
+
+    first.install_version("20.1.0", VersionType::Node).await.unwrap();
+    assert!(requests.lock().unwrap().iter().any(|path| path == &format!("/v20.1.0/node-v20.1.0-{}.tar.gz", platform)));
+    let output = std::process::Command::new(first.get_bin_dir("20.1.0", VersionType::Node).join("node")).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "v20.1.0");
+    // 其他用户放入的文件不删除
+    assert!(planted.exists());
+
+    // 下载后放入共享缓存的安装包内容完好，另一个用户可以直接使用
+    let other = base_dir.join("other");
+    std::fs::create_dir_all(&other).unwrap();
+    std::fs::copy(home.join("config.json"), other.join("config.json")).unwrap();
+    std::fs::remove_file(&planted).unwrap();
+    requests.lock().unwrap().clear();
+    manager(&other, &url).install_version("20.1.0", VersionType::Node).await.unwrap();
+    assert!(!requests.lock().unwrap().iter().any(|path| path == &format!("/v20.1.0/node-v20.1.0-{}.tar.gz", platform)));
+
+    let _ = std::fs::remove_dir_all(&base_dir);
+}
+
+#[tokio::test]
+async fn shared_cache_is_not_used_without_a_published_checksum() {
+    let mut files = node_release_files(&["20.1.0"], false);
+    files.remove("/v20.1.0/SHASUMS256.txt");
+    let (url, requests) = serve_logged(files);
+    let base_dir = temp_dir("shared-cache-unverified");
+    let shared = base_dir.join("shared");
+    let home = base_dir.join("home");
+    std::fs::create_dir_all(&home).unwrap();
+    std::fs::write(home.join("config.json"), serde_json::json!({ "cache": { "shared_dir": shared } }).to_string()).unwrap();
+    let manager = manager(&home, &url);
+
+    // 文件名和内容一致，但没有发布的校验和可以确认它就是官方的安装包
+    let platform = manager.platform_suffix(VersionType::Node).unwrap();
+    let archive_url = format!("{}/v20.1.0/node-v20.1.0-{}.tar.gz", url, platform);
+    let planted_content = node_tarball("6.6.6", &platform);
+    let url_digest = &format!("{:x}", Sha256::digest(archive_url.as_bytes()))[..12];
+    let planted = shared.join("node").join(format!("node-v20.1.0-{}-{}@{:x}.tar.gz", platform, url_digest, Sha256::digest(&planted_content)));
+    std::fs::create_dir_all(planted.parent().unwrap()).unwrap();
+    std::fs::write(&planted, &planted_content).unwrap();
+
+    manager.install_version("20.1.0", VersionType::Node).await.unwrap();
+    assert!(requests.lock().unwrap().iter().any(|path| path == &format!("/v20.1.0/node-v20.1.0-{}.tar.gz", platform)));
+    let output = std::process::Command::new(manager.get_bin_dir("20.1.0", VersionType::Node).join("node")).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "v20.1.0");
+
+    let _ = std::fs::remove_dir_all(&base_dir);
+}